@@ -0,0 +1,224 @@
+#![no_std]
+
+#[cfg(test)]
+mod test;
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Env, Symbol, Vec,
+};
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    TeamCounter,
+    Team(u64),
+    Member(u64, Address), // Membership record of an address in a team
+}
+
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    NotAuthorized = 1,
+    TeamNotFound = 2,
+    MemberNotFound = 3,
+    AlreadyMember = 4,
+    AlreadyApproved = 5,
+}
+
+// A patient's care team, led by a primary provider
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CareTeam {
+    pub patient: Address,
+    pub primary: Address,      // Provider who assembles the team
+    pub approved: bool,        // Patient approved the team itself
+    pub members: Vec<Address>, // Active and proposed members, excluding the primary
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TeamMember {
+    pub role: Symbol,   // e.g. "nurse", "pharm"
+    pub approved: bool, // Patient approved this member
+    pub added: u64,     // When the member was proposed
+}
+
+fn read_team(env: &Env, team_id: u64) -> Result<CareTeam, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Team(team_id))
+        .ok_or(Error::TeamNotFound)
+}
+
+fn write_team(env: &Env, team_id: u64, team: &CareTeam) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Team(team_id), team);
+}
+
+fn read_member(env: &Env, team_id: u64, member: &Address) -> Result<TeamMember, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Member(team_id, member.clone()))
+        .ok_or(Error::MemberNotFound)
+}
+
+#[contract]
+pub struct CareTeamsContract;
+
+#[contractimpl]
+impl CareTeamsContract {
+    /// Start a care team for a patient. The team grants nothing until the
+    /// patient approves it.
+    pub fn create_team(env: Env, primary: Address, patient: Address) -> u64 {
+        primary.require_auth();
+        let team_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TeamCounter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TeamCounter, &(team_id + 1));
+
+        write_team(
+            &env,
+            team_id,
+            &CareTeam {
+                patient: patient.clone(),
+                primary: primary.clone(),
+                approved: false,
+                members: Vec::new(&env),
+            },
+        );
+        env.events().publish(
+            (symbol_short!("team"), symbol_short!("created")),
+            (team_id, primary, patient),
+        );
+        team_id
+    }
+
+    /// Approve a team led by the primary provider (patient only)
+    pub fn approve_team(env: Env, team_id: u64) -> Result<(), Error> {
+        let mut team = read_team(&env, team_id)?;
+        team.patient.require_auth();
+        if team.approved {
+            return Err(Error::AlreadyApproved);
+        }
+        team.approved = true;
+        write_team(&env, team_id, &team);
+        env.events()
+            .publish((symbol_short!("team"), symbol_short!("approved")), team_id);
+        Ok(())
+    }
+
+    /// Propose a member with a role (primary provider only). The member has
+    /// no access until the patient approves them.
+    pub fn propose_member(
+        env: Env,
+        team_id: u64,
+        member: Address,
+        role: Symbol,
+    ) -> Result<(), Error> {
+        let mut team = read_team(&env, team_id)?;
+        team.primary.require_auth();
+        if member == team.primary || team.members.contains(&member) {
+            return Err(Error::AlreadyMember);
+        }
+
+        env.storage().persistent().set(
+            &DataKey::Member(team_id, member.clone()),
+            &TeamMember {
+                role: role.clone(),
+                approved: false,
+                added: env.ledger().timestamp(),
+            },
+        );
+        team.members.push_back(member.clone());
+        write_team(&env, team_id, &team);
+        env.events().publish(
+            (symbol_short!("team"), symbol_short!("proposed")),
+            (team_id, member, role),
+        );
+        Ok(())
+    }
+
+    /// Approve a proposed member (patient only)
+    pub fn approve_member(env: Env, team_id: u64, member: Address) -> Result<(), Error> {
+        let team = read_team(&env, team_id)?;
+        team.patient.require_auth();
+        let mut record = read_member(&env, team_id, &member)?;
+        if record.approved {
+            return Err(Error::AlreadyApproved);
+        }
+        record.approved = true;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Member(team_id, member.clone()), &record);
+        env.events().publish(
+            (symbol_short!("team"), symbol_short!("joined")),
+            (team_id, member),
+        );
+        Ok(())
+    }
+
+    /// Remove a member. The patient or the primary provider can remove anyone;
+    /// a member can also leave on their own.
+    pub fn remove_member(
+        env: Env,
+        caller: Address,
+        team_id: u64,
+        member: Address,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        let mut team = read_team(&env, team_id)?;
+        if caller != team.patient && caller != team.primary && caller != member {
+            return Err(Error::NotAuthorized);
+        }
+        let index = team
+            .members
+            .first_index_of(&member)
+            .ok_or(Error::MemberNotFound)?;
+
+        team.members.remove(index);
+        write_team(&env, team_id, &team);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Member(team_id, member.clone()));
+        env.events().publish(
+            (symbol_short!("team"), symbol_short!("removed")),
+            (team_id, member, caller),
+        );
+        Ok(())
+    }
+
+    /// Whether an address currently acts for the team: the primary provider or
+    /// an approved member of a team the patient has approved
+    pub fn is_member(env: Env, team_id: u64, member: Address) -> bool {
+        let Ok(team) = read_team(&env, team_id) else {
+            return false;
+        };
+        if !team.approved {
+            return false;
+        }
+        member == team.primary
+            || read_member(&env, team_id, &member).is_ok_and(|record| record.approved)
+    }
+
+    /// Get the patient a team cares for
+    pub fn get_team_patient(env: Env, team_id: u64) -> Result<Address, Error> {
+        Ok(read_team(&env, team_id)?.patient)
+    }
+
+    /// Get a team
+    pub fn get_team(env: Env, team_id: u64) -> Result<CareTeam, Error> {
+        read_team(&env, team_id)
+    }
+
+    /// Get a member's role and approval state
+    pub fn get_member(env: Env, team_id: u64, member: Address) -> Result<TeamMember, Error> {
+        read_member(&env, team_id, &member)
+    }
+}
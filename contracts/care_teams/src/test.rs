@@ -0,0 +1,69 @@
+#![cfg(test)]
+
+use crate::{CareTeamsContract, CareTeamsContractClient, Error};
+use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env};
+
+fn setup(env: &Env) -> (CareTeamsContractClient<'_>, Address, Address, u64) {
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, CareTeamsContract);
+    let client = CareTeamsContractClient::new(env, &contract_id);
+    let primary = Address::generate(env);
+    let patient = Address::generate(env);
+    let team_id = client.create_team(&primary, &patient);
+    (client, primary, patient, team_id)
+}
+
+#[test]
+fn test_membership_needs_patient_approval() {
+    let env = Env::default();
+    let (client, primary, patient, team_id) = setup(&env);
+    let nurse = Address::generate(&env);
+
+    client.propose_member(&team_id, &nurse, &symbol_short!("nurse"));
+    // Nothing counts until the patient approves the team and the member
+    assert!(!client.is_member(&team_id, &primary));
+    client.approve_team(&team_id);
+    assert!(client.is_member(&team_id, &primary));
+    assert!(!client.is_member(&team_id, &nurse));
+
+    client.approve_member(&team_id, &nurse);
+    assert!(client.is_member(&team_id, &nurse));
+    assert_eq!(
+        client.get_member(&team_id, &nurse).role,
+        symbol_short!("nurse")
+    );
+    assert_eq!(client.get_team_patient(&team_id), patient);
+
+    assert_eq!(
+        client.try_propose_member(&team_id, &nurse, &symbol_short!("pharm")),
+        Err(Ok(Error::AlreadyMember))
+    );
+    assert_eq!(
+        client.try_approve_team(&team_id),
+        Err(Ok(Error::AlreadyApproved))
+    );
+}
+
+#[test]
+fn test_remove_member() {
+    let env = Env::default();
+    let (client, _primary, patient, team_id) = setup(&env);
+    client.approve_team(&team_id);
+    let nurse = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    client.propose_member(&team_id, &nurse, &symbol_short!("nurse"));
+    client.approve_member(&team_id, &nurse);
+
+    assert_eq!(
+        client.try_remove_member(&outsider, &team_id, &nurse),
+        Err(Ok(Error::NotAuthorized))
+    );
+    client.remove_member(&patient, &team_id, &nurse);
+    assert!(!client.is_member(&team_id, &nurse));
+    assert_eq!(client.get_team(&team_id).members.len(), 0);
+    assert_eq!(
+        client.try_remove_member(&patient, &team_id, &nurse),
+        Err(Ok(Error::MemberNotFound))
+    );
+    assert_eq!(client.try_get_team(&7), Err(Ok(Error::TeamNotFound)));
+}
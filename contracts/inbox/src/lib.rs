@@ -0,0 +1,225 @@
+#![no_std]
+
+#[cfg(test)]
+mod test;
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Env, Symbol, Vec,
+};
+use uzima_common::{admin, allowlist, clock, page};
+
+/// Most notifications kept per patient; posting more drops the oldest
+pub const MAX_NOTIFICATIONS: u32 = 100;
+
+/// Largest page returned by `list`
+pub const MAX_PAGE: u32 = 50;
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    Senders,                    // Contracts allowed to post notifications
+    State(Address),             // Patient -> inbox cursors
+    Notification(Address, u32), // Patient + sequence id
+}
+
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    NotAuthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    NotificationNotFound = 4,
+}
+
+// Compact notification; wallets render the text from `kind` and `ref_id`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Notification {
+    pub seq: u32,
+    pub sender: Address, // Contract that posted the notification
+    pub kind: Symbol,    // e.g. "expiring", "revoked"
+    pub ref_id: u64,     // Id of the referenced record (e.g. a token id)
+    pub timestamp: u64,
+    pub read: bool,
+}
+
+// Cursor positions of a patient's inbox
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InboxState {
+    pub first_seq: u32, // Oldest notification still kept
+    pub next_seq: u32,  // Sequence id the next notification will get
+    pub unread: u32,
+}
+
+fn read_admin(env: &Env) -> Result<Address, Error> {
+    admin::read(env, &DataKey::Admin).ok_or(Error::NotInitialized)
+}
+
+fn senders(env: &Env) -> Vec<Address> {
+    allowlist::members(env, &DataKey::Senders)
+}
+
+fn state(env: &Env, patient: &Address) -> InboxState {
+    env.storage()
+        .persistent()
+        .get(&DataKey::State(patient.clone()))
+        .unwrap_or(InboxState {
+            first_seq: 0,
+            next_seq: 0,
+            unread: 0,
+        })
+}
+
+fn write_state(env: &Env, patient: &Address, state: &InboxState) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::State(patient.clone()), state);
+}
+
+fn read_notification(env: &Env, patient: &Address, seq: u32) -> Option<Notification> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Notification(patient.clone(), seq))
+}
+
+/// Delete notifications from the front of the inbox up to (not including) `until`
+fn drop_until(env: &Env, patient: &Address, state: &mut InboxState, until: u32) {
+    while state.first_seq < until {
+        let key = DataKey::Notification(patient.clone(), state.first_seq);
+        if let Some(notification) = env.storage().persistent().get::<_, Notification>(&key) {
+            if !notification.read {
+                state.unread -= 1;
+            }
+            env.storage().persistent().remove(&key);
+        }
+        state.first_seq += 1;
+    }
+}
+
+#[contract]
+pub struct InboxContract;
+
+#[contractimpl]
+impl InboxContract {
+    /// Initialize the inbox with an admin
+    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+        if !admin::init(&env, &DataKey::Admin, &admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        Ok(())
+    }
+
+    /// Allow a contract to post notifications (admin only)
+    pub fn add_sender(env: Env, sender: Address) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        allowlist::add(&env, &DataKey::Senders, &sender);
+        Ok(())
+    }
+
+    /// Stop a contract from posting notifications (admin only)
+    pub fn remove_sender(env: Env, sender: Address) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        allowlist::remove(&env, &DataKey::Senders, &sender);
+        Ok(())
+    }
+
+    /// Post a notification to a patient's inbox and return its sequence id.
+    /// A full inbox drops its oldest notification, read or not.
+    pub fn post(
+        env: Env,
+        sender: Address,
+        patient: Address,
+        kind: Symbol,
+        ref_id: u64,
+    ) -> Result<u32, Error> {
+        if !allowlist::require_member(&env, &DataKey::Senders, &sender) {
+            return Err(Error::NotAuthorized);
+        }
+        let mut state = state(&env, &patient);
+        if state.next_seq - state.first_seq >= MAX_NOTIFICATIONS {
+            let until = state.first_seq + 1;
+            drop_until(&env, &patient, &mut state, until);
+        }
+
+        let seq = state.next_seq;
+        env.storage().persistent().set(
+            &DataKey::Notification(patient.clone(), seq),
+            &Notification {
+                seq,
+                sender,
+                kind: kind.clone(),
+                ref_id,
+                timestamp: clock::now(&env),
+                read: false,
+            },
+        );
+        state.next_seq += 1;
+        state.unread += 1;
+        write_state(&env, &patient, &state);
+
+        env.events().publish(
+            (symbol_short!("inbox"), symbol_short!("post"), patient),
+            (seq, kind, ref_id),
+        );
+        Ok(seq)
+    }
+
+    /// Read up to `limit` notifications (at most 50) starting at `from_seq`, oldest first
+    pub fn list(env: Env, patient: Address, from_seq: u32, limit: u32) -> Vec<Notification> {
+        let state = state(&env, &patient);
+        let limit = page::limit(limit, MAX_PAGE);
+
+        let mut notifications = Vec::new(&env);
+        let mut seq = from_seq.max(state.first_seq);
+        while seq < state.next_seq && notifications.len() < limit {
+            if let Some(notification) = read_notification(&env, &patient, seq) {
+                notifications.push_back(notification);
+            }
+            seq += 1;
+        }
+        notifications
+    }
+
+    /// Mark a notification as read (patient only)
+    pub fn ack(env: Env, patient: Address, seq: u32) -> Result<(), Error> {
+        patient.require_auth();
+        let mut notification =
+            read_notification(&env, &patient, seq).ok_or(Error::NotificationNotFound)?;
+        if notification.read {
+            return Ok(());
+        }
+        notification.read = true;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Notification(patient.clone(), seq), &notification);
+
+        let mut state = state(&env, &patient);
+        state.unread -= 1;
+        write_state(&env, &patient, &state);
+        Ok(())
+    }
+
+    /// Delete every notification up to and including `seq` (patient only)
+    pub fn clear(env: Env, patient: Address, seq: u32) -> Result<(), Error> {
+        patient.require_auth();
+        let mut state = state(&env, &patient);
+        let until = seq.saturating_add(1).min(state.next_seq);
+        drop_until(&env, &patient, &mut state, until);
+        write_state(&env, &patient, &state);
+        Ok(())
+    }
+
+    /// Get a patient's inbox cursors and unread count
+    pub fn get_state(env: Env, patient: Address) -> InboxState {
+        state(&env, &patient)
+    }
+
+    /// Get the addresses allowed to post notifications
+    pub fn get_senders(env: Env) -> Vec<Address> {
+        senders(&env)
+    }
+}
@@ -0,0 +1,72 @@
+#![cfg(test)]
+
+use crate::{Error, InboxContract, InboxContractClient, MAX_NOTIFICATIONS};
+use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env};
+
+fn setup(env: &Env) -> (InboxContractClient<'_>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, InboxContract);
+    let client = InboxContractClient::new(env, &contract_id);
+    client.initialize(&Address::generate(env));
+
+    let sender = Address::generate(env);
+    client.add_sender(&sender);
+    (client, sender)
+}
+
+#[test]
+fn test_post_ack_clear() {
+    let env = Env::default();
+    let (client, sender) = setup(&env);
+    let patient = Address::generate(&env);
+
+    assert_eq!(
+        client.post(&sender, &patient, &symbol_short!("expiring"), &4),
+        0
+    );
+    assert_eq!(
+        client.post(&sender, &patient, &symbol_short!("revoked"), &5),
+        1
+    );
+    assert_eq!(client.get_state(&patient).unread, 2);
+
+    let page = client.list(&patient, &0, &10);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap().kind, symbol_short!("expiring"));
+    assert_eq!(page.get(1).unwrap().ref_id, 5);
+
+    client.ack(&patient, &0);
+    client.ack(&patient, &0);
+    assert_eq!(client.get_state(&patient).unread, 1);
+    assert!(client.list(&patient, &0, &1).get(0).unwrap().read);
+
+    client.clear(&patient, &0);
+    let state = client.get_state(&patient);
+    assert_eq!(state.first_seq, 1);
+    assert_eq!(state.unread, 1);
+    assert_eq!(
+        client.try_ack(&patient, &0),
+        Err(Ok(Error::NotificationNotFound))
+    );
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_post(&stranger, &patient, &symbol_short!("spam"), &0),
+        Err(Ok(Error::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_full_inbox_drops_oldest() {
+    let env = Env::default();
+    let (client, sender) = setup(&env);
+    let patient = Address::generate(&env);
+
+    for ref_id in 0..MAX_NOTIFICATIONS as u64 + 2 {
+        client.post(&sender, &patient, &symbol_short!("expiring"), &ref_id);
+    }
+    let state = client.get_state(&patient);
+    assert_eq!(state.first_seq, 2);
+    assert_eq!(state.unread, MAX_NOTIFICATIONS);
+    assert_eq!(client.list(&patient, &0, &1).get(0).unwrap().ref_id, 2);
+}
@@ -0,0 +1,133 @@
+use super::*;
+use soroban_sdk::BytesN;
+
+// Storage keys for consent usage logging
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AccessLogKey {
+    Verifiers,        // Services allowed to log access
+    AccessCount(u64), // Number of entries in a token's access log
+    Access(u64, u32), // Token id + entry index
+}
+
+// One use of a consent to access patient data
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccessEntry {
+    pub verifier: Address,        // Service that logged the access
+    pub accessor: Address,        // Who accessed the data
+    pub purpose_hash: BytesN<32>, // Hash of the stated purpose, kept off-chain
+    pub timestamp: u64,
+}
+
+pub(crate) fn verifiers(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&AccessLogKey::Verifiers)
+        .unwrap_or(Vec::new(env))
+}
+
+pub(crate) fn access_count(env: &Env, token_id: u64) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&AccessLogKey::AccessCount(token_id))
+        .unwrap_or(0)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Allow a service to log consent usage (admin only)
+    pub fn add_verifier(env: Env, verifier: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut list = verifiers(&env);
+        if !list.contains(&verifier) {
+            list.push_back(verifier);
+            env.storage()
+                .instance()
+                .set(&AccessLogKey::Verifiers, &list);
+        }
+        Ok(())
+    }
+
+    /// Stop a service logging consent usage (admin only)
+    pub fn remove_verifier(env: Env, verifier: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut list = verifiers(&env);
+        if let Some(index) = list.first_index_of(&verifier) {
+            list.remove(index);
+            env.storage()
+                .instance()
+                .set(&AccessLogKey::Verifiers, &list);
+        }
+        Ok(())
+    }
+
+    /// Check if an address may log consent usage
+    pub fn is_verifier(env: Env, address: Address) -> bool {
+        verifiers(&env).contains(&address)
+    }
+
+    /// Record that a consent was used to access data (registered verifier only).
+    /// Only valid consents can be logged against. Returns the entry's index.
+    pub fn log_access(
+        env: Env,
+        verifier: Address,
+        token_id: u64,
+        accessor: Address,
+        purpose_hash: BytesN<32>,
+    ) -> Result<u32, ContractError> {
+        verifier.require_auth();
+        if !verifiers(&env).contains(&verifier) {
+            return Err(ContractError::NotAuthorized);
+        }
+        let metadata = read_metadata(&env, token_id)?;
+        ensure_active(&env, token_id, &metadata)?;
+
+        let index = access_count(&env, token_id);
+        let key = AccessLogKey::Access(token_id, index);
+        env.storage().persistent().set(
+            &key,
+            &AccessEntry {
+                verifier: verifier.clone(),
+                accessor: accessor.clone(),
+                purpose_hash,
+                timestamp: clock::now(&env),
+            },
+        );
+        let count_key = AccessLogKey::AccessCount(token_id);
+        env.storage().persistent().set(&count_key, &(index + 1));
+        ttl::bump_entry(&env, &key);
+        ttl::bump_entry(&env, &count_key);
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("access"), symbol_short!("logged")),
+            token_id,
+            (token_id, verifier, accessor),
+        );
+        Ok(index)
+    }
+
+    /// Get a page of a token's access log, oldest first (at most 100 entries)
+    pub fn get_access_log(env: Env, token_id: u64, start: u32, limit: u32) -> Vec<AccessEntry> {
+        let end = access_count(&env, token_id).min(start.saturating_add(audit::page_size(limit)));
+        let mut page = Vec::new(&env);
+        for index in start..end {
+            if let Some(entry) = env
+                .storage()
+                .persistent()
+                .get(&AccessLogKey::Access(token_id, index))
+            {
+                page.push_back(entry);
+            }
+        }
+        page
+    }
+
+    /// Get the number of entries in a token's access log
+    pub fn get_access_count(env: Env, token_id: u64) -> u32 {
+        access_count(&env, token_id)
+    }
+}
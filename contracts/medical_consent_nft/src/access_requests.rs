@@ -0,0 +1,329 @@
+use super::*;
+use crate::grants::store_grant;
+use soroban_sdk::BytesN;
+
+/// Most requests a consent can have awaiting the patient, so one batch call
+/// can answer them all
+pub const MAX_PENDING_REQUESTS: u32 = batch::MAX_BATCH_SIZE;
+
+// Storage keys for access requests made by data consumers
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AccessRequestKey {
+    Counter,
+    Request(u64),
+    Pending(u64), // Token id -> requests awaiting the patient
+}
+
+// A data consumer asking the patient for access to a consent's data
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccessRequest {
+    pub token_id: u64,
+    pub requester: Address,
+    pub scope: u32,                     // Scopes of the consent asked for
+    pub justification_hash: BytesN<32>, // Hash of the off-chain justification
+    pub requested_timestamp: u64,
+    pub status: RequestStatus,
+}
+
+fn pending(env: &Env, token_id: u64) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&AccessRequestKey::Pending(token_id))
+        .unwrap_or(Vec::new(env))
+}
+
+/// A token's pending list without the requests that have expired since
+fn unexpired_pending(env: &Env, token_id: u64) -> Vec<u64> {
+    let mut ids = Vec::new(env);
+    for request_id in pending(env, token_id).iter() {
+        if read_access_request(env, request_id)
+            .is_ok_and(|request| request.status == RequestStatus::Pending)
+        {
+            ids.push_back(request_id);
+        }
+    }
+    ids
+}
+
+/// Read a request, reporting pending requests past the request window as expired
+fn read_access_request(env: &Env, request_id: u64) -> Result<AccessRequest, ContractError> {
+    let mut request: AccessRequest = env
+        .storage()
+        .persistent()
+        .get(&AccessRequestKey::Request(request_id))
+        .ok_or(ContractError::RequestNotFound)?;
+    if request.status == RequestStatus::Pending
+        && clock::now(env) > request.requested_timestamp + requests::request_window(env)
+    {
+        request.status = RequestStatus::Expired;
+    }
+    Ok(request)
+}
+
+/// Record the patient's answer and take the request off the token's pending list
+fn resolve(
+    env: &Env,
+    request_id: u64,
+    mut request: AccessRequest,
+    status: RequestStatus,
+) -> AccessRequest {
+    request.status = status;
+    let key = AccessRequestKey::Request(request_id);
+    env.storage().persistent().set(&key, &request);
+    ttl::bump_entry(env, &key);
+
+    let mut list = pending(env, request.token_id);
+    if let Some(index) = list.first_index_of(request_id) {
+        list.remove(index);
+        let list_key = AccessRequestKey::Pending(request.token_id);
+        env.storage().persistent().set(&list_key, &list);
+        ttl::bump_entry(env, &list_key);
+    }
+    request
+}
+
+// Outcome of answering many access requests at once
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchRequestResult {
+    pub succeeded: Vec<u64>,
+    pub failed: Vec<u64>, // Not found, already answered, expired, or another patient's
+}
+
+/// Load a request that can still be answered, with its consent
+fn read_answerable(
+    env: &Env,
+    request_id: u64,
+) -> Result<(AccessRequest, ConsentMetadata), ContractError> {
+    let request = read_access_request(env, request_id)?;
+    let metadata = read_metadata(env, request.token_id)?;
+    match request.status {
+        RequestStatus::Pending => Ok((request, metadata)),
+        RequestStatus::Expired => Err(ContractError::RequestExpired),
+        _ => Err(ContractError::RequestNotPending),
+    }
+}
+
+/// Load a request that `patient` can still answer
+fn read_answerable_by(
+    env: &Env,
+    request_id: u64,
+    patient: &Address,
+) -> Result<(AccessRequest, ConsentMetadata), ContractError> {
+    let (request, metadata) = read_answerable(env, request_id)?;
+    if metadata.patient != *patient {
+        return Err(ContractError::NotAuthorized);
+    }
+    Ok((request, metadata))
+}
+
+fn approve(
+    env: &Env,
+    request_id: u64,
+    request: AccessRequest,
+    metadata: ConsentMetadata,
+    directive: Option<u64>,
+) -> Result<(), ContractError> {
+    features::ensure_enabled(env, features::FEATURE_GRANTS)?;
+    pause::ensure_not_paused(env, pause::PAUSE_GRANTS)?;
+    ensure_active(env, request.token_id, &metadata)?;
+    let expiry = preferences::apply_to_grant(env, &metadata, metadata.expiry_timestamp)?;
+    let request = resolve(env, request_id, request, RequestStatus::Approved);
+    store_grant(
+        env,
+        request.token_id,
+        &metadata,
+        &request.requester,
+        AccessGrant {
+            level: AccessLevel::Read,
+            scope: request.scope,
+            expiry,
+            granted_by: metadata.patient.clone(),
+            depth: 0,
+            granted_at: clock::now(env),
+            directive,
+        },
+    );
+
+    events::publish_token(
+        env,
+        (symbol_short!("access"), symbol_short!("approved")),
+        request.token_id,
+        (request_id, request.token_id, request.requester),
+    );
+    Ok(())
+}
+
+fn deny(env: &Env, request_id: u64, request: AccessRequest) {
+    let request = resolve(env, request_id, request, RequestStatus::Rejected);
+
+    events::publish_token(
+        env,
+        (symbol_short!("access"), symbol_short!("denied")),
+        request.token_id,
+        (request_id, request.token_id, request.requester),
+    );
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Ask the patient for access to the data a consent covers (requester
+    /// authorizes). `scope` must be covered by the consent, and at most
+    /// `MAX_PENDING_REQUESTS` can await the patient per consent. The request is
+    /// approved at once if one of the patient's standing directives covers it,
+    /// and otherwise expires unanswered after the request window. Returns a
+    /// request ID.
+    pub fn request_access(
+        env: Env,
+        token_id: u64,
+        requester: Address,
+        scope: u32,
+        justification_hash: BytesN<32>,
+    ) -> Result<u64, ContractError> {
+        requester.require_auth();
+        features::ensure_enabled(&env, features::FEATURE_GRANTS)?;
+        pause::ensure_not_paused(&env, pause::PAUSE_GRANTS)?;
+        let metadata = read_metadata(&env, token_id)?;
+        ensure_active(&env, token_id, &metadata)?;
+        if requester == metadata.patient {
+            return Err(ContractError::NotAuthorized);
+        }
+        if scope == 0 || token_scopes(&env, token_id) & scope != scope {
+            return Err(ContractError::ScopeNotGranted);
+        }
+        let mut list = unexpired_pending(&env, token_id);
+        if list.len() >= MAX_PENDING_REQUESTS {
+            return Err(ContractError::QuotaExceeded);
+        }
+
+        let request_id: u64 = env
+            .storage()
+            .instance()
+            .get(&AccessRequestKey::Counter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&AccessRequestKey::Counter, &(request_id + 1));
+
+        let request = AccessRequest {
+            token_id,
+            requester: requester.clone(),
+            scope,
+            justification_hash,
+            requested_timestamp: clock::now(&env),
+            status: RequestStatus::Pending,
+        };
+        let key = AccessRequestKey::Request(request_id);
+        env.storage().persistent().set(&key, &request);
+        ttl::bump_entry(&env, &key);
+        list.push_back(request_id);
+        let list_key = AccessRequestKey::Pending(token_id);
+        env.storage().persistent().set(&list_key, &list);
+        ttl::bump_entry(&env, &list_key);
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("access"), symbol_short!("requested")),
+            token_id,
+            (
+                request_id,
+                token_id,
+                requester.clone(),
+                metadata.patient.clone(),
+            ),
+        );
+        // A standing directive answers for the patient straight away
+        if let Some(directive_id) = directives::matching(&env, &metadata, &requester, scope) {
+            approve(&env, request_id, request, metadata, Some(directive_id))?;
+        }
+        Ok(request_id)
+    }
+
+    /// Approve a pending access request (patient only), granting the requester
+    /// Read access to the requested scopes for the life of the consent, or
+    /// less if the patient's preferences cap grant durations
+    pub fn approve_access_request(env: Env, request_id: u64) -> Result<(), ContractError> {
+        let (request, metadata) = read_answerable(&env, request_id)?;
+        metadata.patient.require_auth();
+        approve(&env, request_id, request, metadata, None)
+    }
+
+    /// Deny a pending access request (patient only)
+    pub fn deny_access_request(env: Env, request_id: u64) -> Result<(), ContractError> {
+        let (request, metadata) = read_answerable(&env, request_id)?;
+        metadata.patient.require_auth();
+        deny(&env, request_id, request);
+        Ok(())
+    }
+
+    /// Approve many of `patient`'s pending access requests with one signature
+    /// (at most `MAX_BATCH_SIZE`). Requests that can't be approved are skipped
+    /// and reported in `failed`.
+    pub fn approve_access_requests(
+        env: Env,
+        patient: Address,
+        request_ids: Vec<u64>,
+    ) -> Result<BatchRequestResult, ContractError> {
+        patient.require_auth();
+        batch::ensure_batch_size(&request_ids)?;
+        let mut result = BatchRequestResult {
+            succeeded: Vec::new(&env),
+            failed: Vec::new(&env),
+        };
+        for request_id in request_ids.iter() {
+            match read_answerable_by(&env, request_id, &patient)
+                .and_then(|(request, metadata)| approve(&env, request_id, request, metadata, None))
+            {
+                Ok(()) => result.succeeded.push_back(request_id),
+                Err(error) => {
+                    telemetry::record_error(&env, error);
+                    result.failed.push_back(request_id);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Deny many of `patient`'s pending access requests with one signature
+    /// (at most `MAX_BATCH_SIZE`). Requests that can't be denied are skipped
+    /// and reported in `failed`.
+    pub fn deny_access_requests(
+        env: Env,
+        patient: Address,
+        request_ids: Vec<u64>,
+    ) -> Result<BatchRequestResult, ContractError> {
+        patient.require_auth();
+        batch::ensure_batch_size(&request_ids)?;
+        let mut result = BatchRequestResult {
+            succeeded: Vec::new(&env),
+            failed: Vec::new(&env),
+        };
+        for request_id in request_ids.iter() {
+            match read_answerable_by(&env, request_id, &patient) {
+                Ok((request, _)) => {
+                    deny(&env, request_id, request);
+                    result.succeeded.push_back(request_id);
+                }
+                Err(error) => {
+                    telemetry::record_error(&env, error);
+                    result.failed.push_back(request_id);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Get an access request and its current state
+    pub fn get_access_request(env: Env, request_id: u64) -> Result<AccessRequest, ContractError> {
+        read_access_request(&env, request_id)
+    }
+
+    /// Get the IDs of a consent's requests still awaiting the patient, oldest first
+    pub fn get_pending_access_requests(env: Env, token_id: u64) -> Vec<u64> {
+        unexpired_pending(&env, token_id)
+    }
+}
@@ -0,0 +1,106 @@
+use super::*;
+
+// Storage keys for the per-owner index of live consents by type
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ActiveKey {
+    ActiveByType(Address, String), // Owner + consent type -> tokens not known to be inactive
+}
+
+fn active_by_type(env: &Env, owner: &Address, consent_type: &String) -> Vec<u64> {
+    let key = ActiveKey::ActiveByType(owner.clone(), consent_type.clone());
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_active_by_type(env: &Env, owner: &Address, consent_type: &String, token_ids: &Vec<u64>) {
+    let key = ActiveKey::ActiveByType(owner.clone(), consent_type.clone());
+    if token_ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, token_ids);
+        ttl::bump_entry(env, &key);
+    }
+}
+
+fn is_active(env: &Env, token_id: u64) -> bool {
+    read_metadata(env, token_id)
+        .is_ok_and(|metadata| ensure_active(env, token_id, &metadata).is_ok())
+}
+
+/// Drop the tokens that have expired or been revoked since they were indexed
+fn prune(env: &Env, token_ids: Vec<u64>) -> Vec<u64> {
+    let mut live = Vec::new(env);
+    for token_id in token_ids.iter() {
+        if is_active(env, token_id) {
+            live.push_back(token_id);
+        }
+    }
+    live
+}
+
+/// Index a token its owner has just received, pruning stale entries on the way
+pub(crate) fn record_added(env: &Env, owner: &Address, consent_type: &String, token_id: u64) {
+    let mut token_ids = prune(env, active_by_type(env, owner, consent_type));
+    token_ids.push_back(token_id);
+    set_active_by_type(env, owner, consent_type, &token_ids);
+}
+
+/// Take a token out of its owner's index after a revocation or transfer
+pub(crate) fn record_removed(env: &Env, owner: &Address, consent_type: &String, token_id: u64) {
+    let mut token_ids = active_by_type(env, owner, consent_type);
+    if let Some(index) = token_ids.first_index_of(token_id) {
+        token_ids.remove(index);
+        set_active_by_type(env, owner, consent_type, &token_ids);
+    }
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Whether `owner` holds a valid consent of `consent_type`. Reads one
+    /// index entry and stops at the first live token in it, so other contracts
+    /// can afford to call it on every operation.
+    pub fn has_active_consent(env: Env, owner: Address, consent_type: String) -> bool {
+        active_by_type(&env, &owner, &consent_type)
+            .iter()
+            .any(|token_id| is_active(&env, token_id))
+    }
+
+    /// Find a valid consent of `consent_type` held by `owner` and minted under
+    /// `institution_id`, e.g. for a prescriptions contract checking that a
+    /// treatment consent covers the prescriber's organization before issuing.
+    /// Returns the first such token, to record as the authority relied on.
+    pub fn find_active_consent(
+        env: Env,
+        owner: Address,
+        consent_type: String,
+        institution_id: u64,
+    ) -> Option<u64> {
+        active_by_type(&env, &owner, &consent_type)
+            .iter()
+            .find(|token_id| {
+                institutions::token_institution(&env, *token_id) == Some(institution_id)
+                    && is_active(&env, *token_id)
+            })
+    }
+
+    /// Get the valid consents of `consent_type` that `owner` holds
+    pub fn get_active_consents(env: Env, owner: Address, consent_type: String) -> Vec<u64> {
+        prune(&env, active_by_type(&env, &owner, &consent_type))
+    }
+
+    /// Drop expired and revoked tokens from an owner's index for a type.
+    /// Anyone can call it; it only removes entries that no longer count.
+    /// Returns how many entries were dropped.
+    pub fn prune_active_consents(env: Env, owner: Address, consent_type: String) -> u32 {
+        let token_ids = active_by_type(&env, &owner, &consent_type);
+        let live = prune(&env, token_ids.clone());
+        if live.len() != token_ids.len() {
+            set_active_by_type(&env, &owner, &consent_type, &live);
+        }
+        token_ids.len() - live.len()
+    }
+}
@@ -0,0 +1,198 @@
+use super::*;
+
+// Storage keys for suspicious-activity detection
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ActivityKey {
+    Thresholds,
+    Activity(Address), // Issuer -> counters for the current window
+    Flag(Address),     // Issuer -> why it was flagged, until the admin clears it
+}
+
+/// Limits on what one issuer may do within a window before it is flagged.
+/// A limit of 0 disables that check; a window of 0 disables them all.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActivityThresholds {
+    pub window: u64,          // Seconds
+    pub max_mints: u32,       // Mints per window
+    pub max_revocations: u32, // Revocations made by the issuer per window
+    pub max_failures: u32,    // Rejected actions (e.g. revoking others' tokens) per window
+}
+
+// One issuer's counters for the window that started at `window_start`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IssuerActivity {
+    pub window_start: u64,
+    pub mints: u32,
+    pub revocations: u32,
+    pub failures: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FlagReason {
+    MintBurst,
+    RevocationBurst,
+    RepeatedFailures,
+}
+
+// An issuer held for admin review
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IssuerFlag {
+    pub reason: FlagReason,
+    pub flagged_at: u64,
+}
+
+fn thresholds(env: &Env) -> ActivityThresholds {
+    env.storage()
+        .instance()
+        .get(&ActivityKey::Thresholds)
+        .unwrap_or(ActivityThresholds {
+            window: 0,
+            max_mints: 0,
+            max_revocations: 0,
+            max_failures: 0,
+        })
+}
+
+/// The issuer's counters, starting a fresh window once the last one has passed
+fn current_activity(env: &Env, issuer: &Address, window: u64) -> IssuerActivity {
+    let now = clock::now(env);
+    env.storage()
+        .persistent()
+        .get(&ActivityKey::Activity(issuer.clone()))
+        .filter(|activity: &IssuerActivity| now < activity.window_start.saturating_add(window))
+        .unwrap_or(IssuerActivity {
+            window_start: now,
+            mints: 0,
+            revocations: 0,
+            failures: 0,
+        })
+}
+
+fn read_flag(env: &Env, issuer: &Address) -> Option<IssuerFlag> {
+    env.storage()
+        .persistent()
+        .get(&ActivityKey::Flag(issuer.clone()))
+}
+
+/// Count one action by `issuer` and flag it if that takes it over a limit
+fn record(env: &Env, issuer: &Address, count: impl Fn(&mut IssuerActivity) -> Option<FlagReason>) {
+    let limits = thresholds(env);
+    if limits.window == 0 {
+        return;
+    }
+    let mut activity = current_activity(env, issuer, limits.window);
+    let tripped = count(&mut activity);
+    let key = ActivityKey::Activity(issuer.clone());
+    env.storage().persistent().set(&key, &activity);
+    ttl::bump_entry(env, &key);
+
+    if let Some(reason) = tripped {
+        if read_flag(env, issuer).is_none() {
+            let key = ActivityKey::Flag(issuer.clone());
+            env.storage().persistent().set(
+                &key,
+                &IssuerFlag {
+                    reason,
+                    flagged_at: clock::now(env),
+                },
+            );
+            ttl::bump_entry(env, &key);
+            env.events().publish(
+                (symbol_short!("issuer"), symbol_short!("flagged")),
+                (issuer.clone(), reason),
+            );
+        }
+    }
+}
+
+fn over(count: u32, limit: u32) -> bool {
+    limit != 0 && count > limit
+}
+
+pub(crate) fn record_mint(env: &Env, issuer: &Address) {
+    let limit = thresholds(env).max_mints;
+    record(env, issuer, |activity| {
+        activity.mints += 1;
+        over(activity.mints, limit).then_some(FlagReason::MintBurst)
+    });
+}
+
+pub(crate) fn record_revocation(env: &Env, issuer: &Address) {
+    let limit = thresholds(env).max_revocations;
+    record(env, issuer, |activity| {
+        activity.revocations += 1;
+        over(activity.revocations, limit).then_some(FlagReason::RevocationBurst)
+    });
+}
+
+/// Count an action by `issuer` that was rejected but didn't fail the call
+pub(crate) fn record_failure(env: &Env, issuer: &Address) {
+    let limit = thresholds(env).max_failures;
+    record(env, issuer, |activity| {
+        activity.failures += 1;
+        over(activity.failures, limit).then_some(FlagReason::RepeatedFailures)
+    });
+}
+
+/// Fails with `IssuerFlagged` while the issuer awaits admin review
+pub(crate) fn ensure_not_flagged(env: &Env, issuer: &Address) -> Result<(), ContractError> {
+    if read_flag(env, issuer).is_some() {
+        return Err(ContractError::IssuerFlagged);
+    }
+    Ok(())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set the limits that flag an issuer for review (admin only)
+    pub fn set_activity_thresholds(
+        env: Env,
+        thresholds: ActivityThresholds,
+    ) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&ActivityKey::Thresholds, &thresholds);
+        Ok(())
+    }
+
+    /// Get the limits that flag an issuer for review
+    pub fn get_activity_thresholds(env: Env) -> ActivityThresholds {
+        thresholds(&env)
+    }
+
+    /// Get an issuer's counters for the current window
+    pub fn get_issuer_activity(env: Env, issuer: Address) -> IssuerActivity {
+        current_activity(&env, &issuer, thresholds(&env).window)
+    }
+
+    /// Get why an issuer was flagged, if it awaits review
+    pub fn get_issuer_flag(env: Env, issuer: Address) -> Option<IssuerFlag> {
+        read_flag(&env, &issuer)
+    }
+
+    /// Clear an issuer's flag after review, letting it mint again, and start
+    /// its counters afresh (admin only)
+    pub fn clear_issuer_flag(env: Env, issuer: Address) -> Result<(), ContractError> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        env.storage()
+            .persistent()
+            .remove(&ActivityKey::Flag(issuer.clone()));
+        env.storage()
+            .persistent()
+            .remove(&ActivityKey::Activity(issuer.clone()));
+
+        env.events().publish(
+            (symbol_short!("issuer"), symbol_short!("cleared")),
+            (issuer, admin),
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use super::*;
+use soroban_sdk::{contractclient, xdr::ToXdr, BytesN, Symbol};
+
+/// Most anchors kept locally; past this the oldest are dropped
+pub const MAX_PENDING_ANCHORS: u32 = batch::MAX_BATCH_SIZE;
+
+// Storage keys for anchoring records into the audit log contract
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AnchorKey {
+    AuditLog,
+    PendingStart, // Index of the oldest anchor not yet delivered
+    PendingEnd,   // Index the next undelivered anchor is stored at
+    Pending(u32), // Anchor kept locally until the audit log accepts it
+    Dropped,      // Anchors dropped because the local queue was full
+}
+
+// A record committed to the audit log: what happened, to which token, and a
+// hash of the full record kept by this contract
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditAnchor {
+    pub kind: Symbol, // `receipt` or `brk_glass`
+    pub token_id: u64,
+    pub record_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+/// The part of the audit log contract's interface this contract writes to.
+/// No audit log contract ships in this workspace; deployments point
+/// `set_audit_log` at an external one implementing this, and the tests
+/// exercise the call against a mock.
+#[allow(dead_code)] // Only used through the generated client
+#[contractclient(name = "AuditLogClient")]
+pub trait AuditLogInterface {
+    fn anchor(
+        env: Env,
+        writer: Address,
+        kind: Symbol,
+        subject_id: u64,
+        record_hash: BytesN<32>,
+    ) -> u64;
+}
+
+fn audit_log(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&AnchorKey::AuditLog)
+}
+
+fn pending_bounds(env: &Env) -> (u32, u32) {
+    let storage = env.storage().instance();
+    (
+        storage.get(&AnchorKey::PendingStart).unwrap_or(0),
+        storage.get(&AnchorKey::PendingEnd).unwrap_or(0),
+    )
+}
+
+fn dropped_count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&AnchorKey::Dropped)
+        .unwrap_or(0)
+}
+
+fn deliver(env: &Env, anchor: &AuditAnchor) -> bool {
+    let Some(audit_log) = audit_log(env) else {
+        return false;
+    };
+    matches!(
+        AuditLogClient::new(env, &audit_log).try_anchor(
+            &env.current_contract_address(),
+            &anchor.kind,
+            &anchor.token_id,
+            &anchor.record_hash,
+        ),
+        Ok(Ok(_))
+    )
+}
+
+/// Anchor a record into the audit log. While the audit log is unset, paused
+/// or otherwise failing, the anchor is kept locally instead and delivered by
+/// `flush_anchors`; the operation being recorded never fails because of it.
+/// At most `MAX_PENDING_ANCHORS` are kept, dropping the oldest past that.
+pub(crate) fn anchor<T>(env: &Env, kind: Symbol, token_id: u64, record: T)
+where
+    T: ToXdr,
+{
+    let anchor = AuditAnchor {
+        kind,
+        token_id,
+        record_hash: env.crypto().sha256(&record.to_xdr(env)),
+        timestamp: clock::now(env),
+    };
+    let (start, end) = pending_bounds(env);
+    // Deliver in order: nothing jumps the queue of anchors still pending
+    if start == end && deliver(env, &anchor) {
+        return;
+    }
+    if end - start >= MAX_PENDING_ANCHORS {
+        env.storage()
+            .persistent()
+            .remove(&AnchorKey::Pending(start));
+        let storage = env.storage().instance();
+        storage.set(&AnchorKey::PendingStart, &(start + 1));
+        storage.set(&AnchorKey::Dropped, &(dropped_count(env) + 1));
+    }
+    let key = AnchorKey::Pending(end);
+    env.storage().persistent().set(&key, &anchor);
+    ttl::bump_entry(env, &key);
+    env.storage()
+        .instance()
+        .set(&AnchorKey::PendingEnd, &(end + 1));
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Anchor verification receipts and break-glass accesses into an audit log
+    /// contract (admin only). This contract must be registered as a writer on
+    /// the audit log.
+    pub fn set_audit_log(env: Env, audit_log: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&AnchorKey::AuditLog, &audit_log);
+        Ok(())
+    }
+
+    /// Stop anchoring into the audit log; anchors are kept locally until one
+    /// is set again (admin only)
+    pub fn clear_audit_log(env: Env) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage().instance().remove(&AnchorKey::AuditLog);
+        Ok(())
+    }
+
+    /// Get the configured audit log contract
+    pub fn get_audit_log(env: Env) -> Option<Address> {
+        audit_log(&env)
+    }
+
+    /// Get a page of the anchors kept locally because the audit log was unset
+    /// or unavailable, oldest first (at most 100 entries)
+    pub fn get_pending_anchors(env: Env, start: u32, limit: u32) -> Vec<AuditAnchor> {
+        let (first, end) = pending_bounds(&env);
+        let from = first.saturating_add(start);
+        let to = end.min(from.saturating_add(audit::page_size(limit)));
+        let mut page = Vec::new(&env);
+        for index in from..to {
+            if let Some(anchor) = env.storage().persistent().get(&AnchorKey::Pending(index)) {
+                page.push_back(anchor);
+            }
+        }
+        page
+    }
+
+    /// Get how many anchors are waiting to be delivered to the audit log
+    pub fn pending_anchor_count(env: Env) -> u32 {
+        let (start, end) = pending_bounds(&env);
+        end.saturating_sub(start)
+    }
+
+    /// Get how many anchors were dropped because the local queue was full
+    pub fn dropped_anchor_count(env: Env) -> u64 {
+        dropped_count(&env)
+    }
+
+    /// Keeper entrypoint: deliver up to `limit` (at most `MAX_BATCH_SIZE`)
+    /// locally kept anchors to the audit log, oldest first, stopping at the
+    /// first one it refuses. Anyone may call it. Returns how many were
+    /// delivered.
+    pub fn flush_anchors(env: Env, limit: u32) -> Result<u32, ContractError> {
+        if limit > batch::MAX_BATCH_SIZE {
+            return Err(ContractError::QuotaExceeded);
+        }
+        let (mut start, end) = pending_bounds(&env);
+        let mut delivered = 0;
+        while start < end && delivered < limit {
+            let key = AnchorKey::Pending(start);
+            if let Some(anchor) = env.storage().persistent().get::<_, AuditAnchor>(&key) {
+                if !deliver(&env, &anchor) {
+                    break;
+                }
+                env.storage().persistent().remove(&key);
+            }
+            start += 1;
+            delivered += 1;
+        }
+        env.storage()
+            .instance()
+            .set(&AnchorKey::PendingStart, &start);
+        Ok(delivered)
+    }
+}
@@ -0,0 +1,117 @@
+use super::*;
+use soroban_sdk::{xdr::ToXdr, BytesN};
+
+// Storage keys for archival commitments
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArchiveKey {
+    TokenCommitment(u64), // sha256 of (owner, metadata, revoked)
+}
+
+fn hash_record(
+    env: &Env,
+    owner: &Address,
+    metadata: &ConsentMetadata,
+    revoked: bool,
+) -> BytesN<32> {
+    let record = (owner.clone(), metadata.clone(), revoked);
+    env.crypto().sha256(&record.to_xdr(env))
+}
+
+/// Recompute and store the commitment for a token's current record. Called
+/// after every write so a restored record can always be checked against it.
+pub(crate) fn commit_token(env: &Env, token_id: u64) {
+    let owner: Option<Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TokenOwner(token_id));
+    let metadata: Option<ConsentMetadata> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TokenMetadata(token_id));
+    let (Some(owner), Some(metadata)) = (owner, metadata) else {
+        return;
+    };
+    let revoked: bool = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TokenRevoked(token_id))
+        .unwrap_or(false);
+
+    let key = ArchiveKey::TokenCommitment(token_id);
+    let commitment = hash_record(env, &owner, &metadata, revoked);
+    env.storage().persistent().set(&key, &commitment);
+    // Keep the commitment alive for as long as the network allows so it
+    // outlives the entries it commits to
+    let max_ttl = env.storage().max_ttl();
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, max_ttl, max_ttl);
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Get the hash commitment of a token's record
+    pub fn get_commitment(env: Env, token_id: u64) -> Result<BytesN<32>, ContractError> {
+        env.storage()
+            .persistent()
+            .get(&ArchiveKey::TokenCommitment(token_id))
+            .ok_or(ContractError::TokenNotFound)
+    }
+
+    /// Re-validate a token after its entries were restored from the ledger
+    /// archive (or rebuild them from a backup). The supplied record must hash to
+    /// the stored commitment; any entries that are still missing are rewritten.
+    pub fn restore_token(
+        env: Env,
+        token_id: u64,
+        owner: Address,
+        metadata: ConsentMetadata,
+        revoked: bool,
+    ) -> Result<(), ContractError> {
+        let commitment: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&ArchiveKey::TokenCommitment(token_id))
+            .ok_or(ContractError::TokenNotFound)?;
+
+        if hash_record(&env, &owner, &metadata, revoked) != commitment {
+            return Err(ContractError::CommitmentMismatch);
+        }
+
+        // Entries that are present must agree with the commitment too
+        let storage = env.storage().persistent();
+        let stored_owner: Option<Address> = storage.get(&DataKey::TokenOwner(token_id));
+        let stored_metadata: Option<ConsentMetadata> =
+            storage.get(&DataKey::TokenMetadata(token_id));
+        let stored_revoked: Option<bool> = storage.get(&DataKey::TokenRevoked(token_id));
+        if stored_owner.as_ref().is_some_and(|o| *o != owner)
+            || stored_metadata.as_ref().is_some_and(|m| *m != metadata)
+            || stored_revoked.is_some_and(|r| r != revoked)
+        {
+            return Err(ContractError::CommitmentMismatch);
+        }
+
+        if stored_owner.is_none() {
+            storage.set(&DataKey::TokenOwner(token_id), &owner);
+        }
+        if stored_metadata.is_none() {
+            storage.set(&DataKey::TokenMetadata(token_id), &metadata);
+        }
+        if stored_revoked.is_none() {
+            storage.set(&DataKey::TokenRevoked(token_id), &revoked);
+        }
+
+        append_history(&env, token_id, "restored", &owner, &metadata.metadata_uri);
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("restored")),
+            token_id,
+            (token_id, owner),
+        );
+        Ok(())
+    }
+}
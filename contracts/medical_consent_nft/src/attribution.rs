@@ -0,0 +1,115 @@
+use super::*;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// Storage keys for read attribution
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AttributionKey {
+    ReadPolicy,
+    ReadCount(Address, u64), // Caller + day number -> reads that day
+}
+
+/// Whether reads must be attributed to a caller, and how many a caller gets per day
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReadPolicy {
+    pub enabled: bool,
+    pub daily_cap: u32, // 0 = count without a cap
+}
+
+fn read_policy(env: &Env) -> ReadPolicy {
+    env.storage()
+        .instance()
+        .get(&AttributionKey::ReadPolicy)
+        .unwrap_or(ReadPolicy {
+            enabled: false,
+            daily_cap: 0,
+        })
+}
+
+fn today(env: &Env) -> u64 {
+    clock::now(env) / SECONDS_PER_DAY
+}
+
+/// Whether anonymous metadata/history reads are turned off
+pub(crate) fn required(env: &Env) -> bool {
+    read_policy(env).enabled
+}
+
+/// Count a read against `caller`'s daily allowance while attribution is on
+pub(crate) fn record_read(env: &Env, caller: &Address) -> Result<(), ContractError> {
+    let policy = read_policy(env);
+    if !policy.enabled {
+        return Ok(());
+    }
+    let key = AttributionKey::ReadCount(caller.clone(), today(env));
+    let count: u32 = env.storage().temporary().get(&key).unwrap_or(0);
+    if policy.daily_cap != 0 && count >= policy.daily_cap {
+        return Err(ContractError::QuotaExceeded);
+    }
+    // Counters only matter for the day they belong to, so they can expire
+    env.storage().temporary().set(&key, &(count + 1));
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, ttl::DAY_IN_LEDGERS, 2 * ttl::DAY_IN_LEDGERS);
+    Ok(())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Require attributed reads of metadata and history, capped per caller per
+    /// day (admin only). While enabled, the anonymous getters are refused.
+    pub fn set_read_policy(env: Env, enabled: bool, daily_cap: u32) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage().instance().set(
+            &AttributionKey::ReadPolicy,
+            &ReadPolicy { enabled, daily_cap },
+        );
+        Ok(())
+    }
+
+    /// Get the read attribution policy
+    pub fn get_read_policy(env: Env) -> ReadPolicy {
+        read_policy(&env)
+    }
+
+    /// Get consent metadata as `caller`, counted against their daily allowance
+    pub fn get_metadata_as(
+        env: Env,
+        caller: Address,
+        token_id: u64,
+    ) -> Result<ConsentMetadata, ContractError> {
+        caller.require_auth();
+        record_read(&env, &caller)?;
+        let metadata = read_metadata(&env, token_id)?;
+        ttl::bump_token(&env, token_id);
+        Ok(metadata)
+    }
+
+    /// Get a consent's history as `caller`, counted against their daily allowance
+    pub fn get_history_as(
+        env: Env,
+        caller: Address,
+        token_id: u64,
+    ) -> Result<Vec<ConsentHistoryEntry>, ContractError> {
+        caller.require_auth();
+        record_read(&env, &caller)?;
+        let history = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ConsentHistory(token_id))
+            .unwrap_or(Vec::new(&env));
+        ttl::bump_token(&env, token_id);
+        Ok(history)
+    }
+
+    /// Get how many attributed reads `caller` made today
+    pub fn get_read_count(env: Env, caller: Address) -> u32 {
+        env.storage()
+            .temporary()
+            .get(&AttributionKey::ReadCount(caller, today(&env)))
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,155 @@
+use super::*;
+
+/// Largest page returned by the export endpoints
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+// Storage keys for the auditor role
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AuditKey {
+    Auditors,
+}
+
+// A revocation as reconstructed from a token's audit trail
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RevocationRecord {
+    pub token_id: u64,
+    pub revoked_by: Address,
+    pub timestamp: u64,
+}
+
+fn auditors(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&AuditKey::Auditors)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Auditors and the admin may read across all tokens
+pub(crate) fn require_auditor(env: &Env, auditor: &Address) -> Result<(), ContractError> {
+    auditor.require_auth();
+    if auditors(env).contains(auditor) || read_admin(env)? == *auditor {
+        Ok(())
+    } else {
+        Err(ContractError::NotAuthorized)
+    }
+}
+
+pub(crate) fn page_size(limit: u32) -> u32 {
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        MAX_PAGE_SIZE
+    } else {
+        limit
+    }
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Grant the read-only auditor role (admin only)
+    pub fn add_auditor(env: Env, auditor: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut list = auditors(&env);
+        if !list.contains(&auditor) {
+            list.push_back(auditor);
+            env.storage().instance().set(&AuditKey::Auditors, &list);
+        }
+        Ok(())
+    }
+
+    /// Revoke the auditor role (admin only)
+    pub fn remove_auditor(env: Env, auditor: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut list = auditors(&env);
+        if let Some(index) = list.first_index_of(&auditor) {
+            list.remove(index);
+            env.storage().instance().set(&AuditKey::Auditors, &list);
+        }
+        Ok(())
+    }
+
+    /// Check if address holds the auditor role
+    pub fn is_auditor(env: Env, address: Address) -> bool {
+        auditors(&env).contains(&address)
+    }
+
+    /// Export a token's history entries within [from_ts, to_ts], skipping the
+    /// first `start` matches and returning at most `limit` (auditor only)
+    pub fn export_history_range(
+        env: Env,
+        auditor: Address,
+        token_id: u64,
+        from_ts: u64,
+        to_ts: u64,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<ConsentHistoryEntry>, ContractError> {
+        require_auditor(&env, &auditor)?;
+        read_metadata(&env, token_id)?;
+
+        let history: Vec<ConsentHistoryEntry> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ConsentHistory(token_id))
+            .unwrap_or(Vec::new(&env));
+        let limit = page_size(limit);
+
+        let mut page = Vec::new(&env);
+        let mut skipped = 0;
+        for entry in history.iter() {
+            if entry.timestamp < from_ts || entry.timestamp > to_ts {
+                continue;
+            }
+            if skipped < start {
+                skipped += 1;
+                continue;
+            }
+            page.push_back(entry);
+            if page.len() == limit {
+                break;
+            }
+        }
+        ttl::bump_token(&env, token_id);
+        Ok(page)
+    }
+
+    /// Export revocations across all tokens, scanning up to `limit` token IDs
+    /// starting at `from_token_id` (auditor only)
+    pub fn export_revocations(
+        env: Env,
+        auditor: Address,
+        from_token_id: u64,
+        limit: u32,
+    ) -> Result<Vec<RevocationRecord>, ContractError> {
+        require_auditor(&env, &auditor)?;
+
+        let token_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenCounter)
+            .unwrap_or(0);
+        let end = token_count.min(from_token_id.saturating_add(page_size(limit) as u64));
+
+        let mut records = Vec::new(&env);
+        for token_id in from_token_id..end {
+            if !Self::is_revoked(env.clone(), token_id) {
+                continue;
+            }
+            let history: Vec<ConsentHistoryEntry> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::ConsentHistory(token_id))
+                .unwrap_or(Vec::new(&env));
+            let revoked = String::from_str(&env, "revoked");
+            if let Some(entry) = history.iter().find(|entry| entry.action == revoked) {
+                records.push_back(RevocationRecord {
+                    token_id,
+                    revoked_by: entry.actor,
+                    timestamp: entry.timestamp,
+                });
+            }
+        }
+        Ok(records)
+    }
+}
@@ -0,0 +1,280 @@
+use super::*;
+
+/// Most tokens accepted by a single batch call
+pub const MAX_BATCH_SIZE: u32 = 100;
+
+/// Longest metadata URI `rebase_uris` rewrites, before or after the rewrite
+pub const MAX_REBASE_URI_LEN: u32 = 256;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConsentStatus {
+    Valid,
+    Revoked,
+    Expired,
+    Dormant,
+    Suspended,
+    PendingActivation,
+    NotFound,
+}
+
+// Compact validity view of a single token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsentStatusView {
+    pub token_id: u64,
+    pub status: ConsentStatus,
+    pub expiry_timestamp: u64, // 0 = no expiry (or token not found)
+    pub scopes: u32,
+}
+
+// Outcome of a bulk revocation
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchRevokeResult {
+    pub succeeded: Vec<u64>,
+    pub failed: Vec<u64>, // Not found, already revoked, or not issued by the caller
+}
+
+// Outcome of a bulk URI rebase
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchRebaseResult {
+    pub rebased: Vec<u64>,
+    pub skipped: Vec<u64>, // Not found, not issued by the caller, not under the old prefix, or not updatable
+}
+
+pub(crate) fn ensure_batch_size(token_ids: &Vec<u64>) -> Result<(), ContractError> {
+    if token_ids.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::QuotaExceeded);
+    }
+    Ok(())
+}
+
+/// `uri` with `old_prefix` replaced by `new_prefix`, or `None` if it doesn't
+/// start with `old_prefix` or either URI is longer than `MAX_REBASE_URI_LEN`
+fn rebased_uri(
+    env: &Env,
+    uri: &String,
+    old_prefix: &String,
+    new_prefix: &String,
+) -> Option<String> {
+    const MAX: usize = MAX_REBASE_URI_LEN as usize;
+    let (uri_len, old_len, new_len) = (
+        uri.len() as usize,
+        old_prefix.len() as usize,
+        new_prefix.len() as usize,
+    );
+    let rebased_len = (uri_len + new_len).checked_sub(old_len)?;
+    if uri_len > MAX || old_len > uri_len || rebased_len > MAX {
+        return None;
+    }
+    let mut current = [0u8; MAX];
+    let mut prefix = [0u8; MAX];
+    uri.copy_into_slice(&mut current[..uri_len]);
+    old_prefix.copy_into_slice(&mut prefix[..old_len]);
+    if current[..old_len] != prefix[..old_len] {
+        return None;
+    }
+
+    let mut rebased = [0u8; MAX];
+    new_prefix.copy_into_slice(&mut rebased[..new_len]);
+    rebased[new_len..rebased_len].copy_from_slice(&current[old_len..uri_len]);
+    Some(String::from_bytes(env, &rebased[..rebased_len]))
+}
+
+/// The status of an existing consent. Every status readout (batch views,
+/// verification payloads, rendered JSON) derives from this one mapping.
+pub(crate) fn consent_status(
+    env: &Env,
+    token_id: u64,
+    metadata: &ConsentMetadata,
+) -> ConsentStatus {
+    match ensure_active(env, token_id, metadata) {
+        Ok(()) => ConsentStatus::Valid,
+        Err(ContractError::ExpiredConsent) => ConsentStatus::Expired,
+        Err(ContractError::ConsentDormant) => ConsentStatus::Dormant,
+        Err(ContractError::FrozenToken) => ConsentStatus::Suspended,
+        Err(ContractError::NotYetActive) => ConsentStatus::PendingActivation,
+        Err(_) => ConsentStatus::Revoked,
+    }
+}
+
+fn status_view(env: &Env, token_id: u64) -> ConsentStatusView {
+    let metadata = match read_metadata(env, token_id) {
+        Ok(metadata) => metadata,
+        Err(_) => {
+            return ConsentStatusView {
+                token_id,
+                status: ConsentStatus::NotFound,
+                expiry_timestamp: 0,
+                scopes: 0,
+            }
+        }
+    };
+    let status = consent_status(env, token_id, &metadata);
+    ttl::bump_token(env, token_id);
+    ConsentStatusView {
+        token_id,
+        status,
+        expiry_timestamp: metadata.expiry_timestamp,
+        scopes: token_scopes(env, token_id),
+    }
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Get the status of many consents in one call (at most `MAX_BATCH_SIZE`)
+    pub fn get_statuses(
+        env: Env,
+        token_ids: Vec<u64>,
+    ) -> Result<Vec<ConsentStatusView>, ContractError> {
+        ensure_batch_size(&token_ids)?;
+        let mut views = Vec::new(&env);
+        for token_id in token_ids.iter() {
+            views.push_back(status_view(&env, token_id));
+        }
+        Ok(views)
+    }
+
+    /// Revoke many consents at once for compliance actions (e.g. a breached consent
+    /// form). The admin can revoke any token, an issuer only the tokens it issued.
+    /// Tokens that can't be revoked are skipped and reported in `failed`.
+    pub fn revoke_batch(
+        env: Env,
+        caller: Address,
+        token_ids: Vec<u64>,
+        reason: String,
+    ) -> Result<BatchRevokeResult, ContractError> {
+        caller.require_auth();
+        ensure_batch_size(&token_ids)?;
+        let is_admin = read_admin(&env)? == caller;
+        if !is_admin && !Self::is_issuer(env.clone(), caller.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+        if !is_admin {
+            pause::ensure_issuer_not_paused(&env, &caller)?;
+        }
+
+        let mut result = BatchRevokeResult {
+            succeeded: Vec::new(&env),
+            failed: Vec::new(&env),
+        };
+        for token_id in token_ids.iter() {
+            let metadata = match read_metadata(&env, token_id) {
+                Ok(metadata) if is_admin || metadata.issuer == caller => metadata,
+                Ok(_) => {
+                    telemetry::record_error(&env, ContractError::NotAuthorized);
+                    activity::record_failure(&env, &caller);
+                    result.failed.push_back(token_id);
+                    continue;
+                }
+                Err(error) => {
+                    telemetry::record_error(&env, error);
+                    result.failed.push_back(token_id);
+                    continue;
+                }
+            };
+            if Self::is_revoked(env.clone(), token_id) {
+                telemetry::record_error(&env, ContractError::ConsentRevoked);
+                result.failed.push_back(token_id);
+                continue;
+            }
+            mark_revoked(&env, token_id, &caller, &metadata.metadata_uri);
+            if !is_admin {
+                activity::record_revocation(&env, &caller);
+            }
+            result.succeeded.push_back(token_id);
+        }
+
+        env.events().publish(
+            (symbol_short!("consent"), symbol_short!("bulk_rev")),
+            (
+                caller,
+                reason,
+                result.succeeded.clone(),
+                result.failed.clone(),
+            ),
+        );
+        Ok(result)
+    }
+
+    /// Rewrite the metadata URIs of many consents from `old_prefix` to
+    /// `new_prefix` after a storage provider migration, without the owners
+    /// signing each update. The admin can rebase any token, an issuer only
+    /// the tokens it issued. Revoked and expired consents are rebased too, so
+    /// their records keep resolving. Each rebase bumps the version and is
+    /// recorded in the token's history, so tokens whose history is full under
+    /// a rejecting cap are skipped, as is every token while updates must carry
+    /// a document hash. Tokens that can't be rebased are reported in `skipped`.
+    pub fn rebase_uris(
+        env: Env,
+        caller: Address,
+        token_ids: Vec<u64>,
+        old_prefix: String,
+        new_prefix: String,
+    ) -> Result<BatchRebaseResult, ContractError> {
+        caller.require_auth();
+        ensure_batch_size(&token_ids)?;
+        let is_admin = read_admin(&env)? == caller;
+        if !is_admin && !Self::is_issuer(env.clone(), caller.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+        if !is_admin {
+            pause::ensure_issuer_not_paused(&env, &caller)?;
+        }
+        if old_prefix.len() == 0 {
+            return Err(ContractError::InvalidUri);
+        }
+
+        let mut result = BatchRebaseResult {
+            rebased: Vec::new(&env),
+            skipped: Vec::new(&env),
+        };
+        for token_id in token_ids.iter() {
+            let before = match read_metadata(&env, token_id) {
+                Ok(metadata)
+                    if (is_admin || metadata.issuer == caller)
+                        && history::ensure_room(&env, token_id).is_ok()
+                        && documents::ensure_hash_not_required(&env).is_ok() =>
+                {
+                    metadata
+                }
+                _ => {
+                    result.skipped.push_back(token_id);
+                    continue;
+                }
+            };
+            let Some(metadata_uri) =
+                rebased_uri(&env, &before.metadata_uri, &old_prefix, &new_prefix)
+                    .filter(|uri| validation::validate_uri(uri).is_ok())
+            else {
+                result.skipped.push_back(token_id);
+                continue;
+            };
+
+            let mut metadata = before.clone();
+            metadata.metadata_uri = metadata_uri;
+            metadata.version += 1;
+            env.storage()
+                .persistent()
+                .set(&DataKey::TokenMetadata(token_id), &metadata);
+            append_history_diff(&env, token_id, "rebased", &caller, &before, &metadata);
+            archive::commit_token(&env, token_id);
+            ttl::bump_token(&env, token_id);
+            result.rebased.push_back(token_id);
+        }
+
+        env.events().publish(
+            (symbol_short!("consent"), symbol_short!("rebased")),
+            (
+                caller,
+                old_prefix,
+                new_prefix,
+                result.rebased.clone(),
+                result.skipped.clone(),
+            ),
+        );
+        Ok(result)
+    }
+}
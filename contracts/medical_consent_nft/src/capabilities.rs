@@ -0,0 +1,63 @@
+use super::*;
+use soroban_sdk::Symbol;
+
+/// Major interface version: bumped when an entrypoint changes incompatibly,
+/// so callers built against another major must not call this build
+pub const INTERFACE_MAJOR: u32 = 1;
+
+/// Minor interface version: bumped when entrypoints are added
+pub const INTERFACE_MINOR: u32 = 1;
+
+/// Interface versions this build implements, by major
+pub const INTERFACES: [&str; 1] = ["consent_v1"];
+
+// The interface a deployment implements
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InterfaceVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+/// Optional subsystems compiled into this build that no flag switches off
+pub const EXTENSIONS: [&str; 14] = [
+    "access_log",
+    "batch",
+    "care_teams",
+    "codes",
+    "documents",
+    "dry_run",
+    "pause",
+    "recovery",
+    "residency",
+    "responders",
+    "scopes",
+    "service_keys",
+    "subscriptions",
+    "templates",
+];
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Get the interface version this deployment implements. Callers should
+    /// refuse to call a deployment whose major differs from the one they were
+    /// built against.
+    pub fn interface_version(_env: Env) -> InterfaceVersion {
+        InterfaceVersion {
+            major: INTERFACE_MAJOR,
+            minor: INTERFACE_MINOR,
+        }
+    }
+
+    /// List what this deployment supports, so callers can feature-detect at
+    /// runtime: the interface versions it implements, then its extensions,
+    /// then the feature flags that are currently on
+    pub fn capabilities(env: Env) -> Vec<Symbol> {
+        let mut all = Vec::new(&env);
+        for name in INTERFACES.iter().chain(EXTENSIONS.iter()) {
+            all.push_back(Symbol::new(&env, name));
+        }
+        all.append(&features::enabled(&env));
+        all
+    }
+}
@@ -0,0 +1,131 @@
+use super::*;
+use soroban_sdk::contractclient;
+
+// Storage keys for care team grants
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CareTeamKey {
+    Registry,       // care_teams contract
+    TokenTeam(u64), // Care team a consent is granted to
+}
+
+/// The part of the care_teams contract's interface this contract reads
+#[allow(dead_code)] // Only used through the generated client
+#[contractclient(name = "CareTeamsClient")]
+pub trait CareTeamsInterface {
+    fn is_member(env: Env, team_id: u64, member: Address) -> bool;
+    fn get_team_patient(env: Env, team_id: u64) -> Address;
+}
+
+fn registry(env: &Env) -> Result<CareTeamsClient<'_>, ContractError> {
+    let registry: Address = env
+        .storage()
+        .instance()
+        .get(&CareTeamKey::Registry)
+        .ok_or(ContractError::NotInitialized)?;
+    Ok(CareTeamsClient::new(env, &registry))
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set the care_teams contract that team grants are resolved against (admin only)
+    pub fn set_care_team_registry(env: Env, registry: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&CareTeamKey::Registry, &registry);
+        Ok(())
+    }
+
+    /// Grant a consent to one of the patient's care teams (patient only). Access
+    /// follows the team's membership from then on.
+    pub fn grant_to_care_team(env: Env, token_id: u64, team_id: u64) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.patient.require_auth();
+        ensure_active(&env, token_id, &metadata)?;
+
+        let team_patient = match registry(&env)?.try_get_team_patient(&team_id) {
+            Ok(Ok(patient)) => patient,
+            _ => return Err(ContractError::CareTeamNotFound),
+        };
+        if team_patient != metadata.patient {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        let key = CareTeamKey::TokenTeam(token_id);
+        env.storage().persistent().set(&key, &team_id);
+        ttl::bump_entry(&env, &key);
+        append_history(
+            &env,
+            token_id,
+            "team_granted",
+            &metadata.patient,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("team_grnt")),
+            token_id,
+            (token_id, team_id),
+        );
+        Ok(())
+    }
+
+    /// Withdraw a consent's care team grant (patient only)
+    pub fn revoke_care_team_grant(env: Env, token_id: u64) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.patient.require_auth();
+        let key = CareTeamKey::TokenTeam(token_id);
+        let team_id: u64 = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ContractError::CareTeamNotFound)?;
+
+        env.storage().persistent().remove(&key);
+        append_history(
+            &env,
+            token_id,
+            "team_revoked",
+            &metadata.patient,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("team_rev")),
+            token_id,
+            (token_id, team_id),
+        );
+        Ok(())
+    }
+
+    /// Get the care team a consent is granted to
+    pub fn get_token_care_team(env: Env, token_id: u64) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&CareTeamKey::TokenTeam(token_id))
+    }
+
+    /// Check whether `member` has access through the consent's care team grant.
+    /// Membership is read live, so team changes take effect immediately.
+    pub fn has_care_team_access(env: Env, token_id: u64, member: Address) -> bool {
+        let Ok(metadata) = read_metadata(&env, token_id) else {
+            return false;
+        };
+        if ensure_active(&env, token_id, &metadata).is_err() {
+            return false;
+        }
+        let Some(team_id) = Self::get_token_care_team(env.clone(), token_id) else {
+            return false;
+        };
+        let Ok(registry) = registry(&env) else {
+            return false;
+        };
+        matches!(registry.try_is_member(&team_id, &member), Ok(Ok(true)))
+    }
+}
@@ -0,0 +1,207 @@
+use super::*;
+use soroban_sdk::Symbol;
+
+/// Most codes a single consent can be tagged with
+pub const MAX_CODES_PER_TOKEN: u32 = 10;
+
+/// Longest code accepted, in bytes
+pub const MAX_CODE_LEN: u32 = 32;
+
+// Storage keys for clinical code tagging
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CodeKey {
+    Systems,                           // Registered code systems
+    TokenCodes(u64),                   // Token -> codes it is tagged with
+    OwnerIndex(Address, ClinicalCode), // Owner + code -> tagged tokens they hold
+}
+
+// A procedure or diagnosis code from a registered code system
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClinicalCode {
+    pub system: Symbol, // e.g. "ICD10", "SNOMED"
+    pub code: String,   // e.g. "E11.9"
+}
+
+fn systems(env: &Env) -> Vec<Symbol> {
+    env.storage()
+        .instance()
+        .get(&CodeKey::Systems)
+        .unwrap_or(Vec::new(env))
+}
+
+fn token_codes(env: &Env, token_id: u64) -> Vec<ClinicalCode> {
+    env.storage()
+        .persistent()
+        .get(&CodeKey::TokenCodes(token_id))
+        .unwrap_or(Vec::new(env))
+}
+
+fn indexed(env: &Env, owner: &Address, code: &ClinicalCode) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&CodeKey::OwnerIndex(owner.clone(), code.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_indexed(env: &Env, owner: &Address, code: &ClinicalCode, token_ids: &Vec<u64>) {
+    let key = CodeKey::OwnerIndex(owner.clone(), code.clone());
+    if token_ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, token_ids);
+        ttl::bump_entry(env, &key);
+    }
+}
+
+fn index(env: &Env, owner: &Address, code: &ClinicalCode, token_id: u64) {
+    let mut token_ids = indexed(env, owner, code);
+    token_ids.push_back(token_id);
+    set_indexed(env, owner, code, &token_ids);
+}
+
+fn unindex(env: &Env, owner: &Address, code: &ClinicalCode, token_id: u64) {
+    let mut token_ids = indexed(env, owner, code);
+    if let Some(index) = token_ids.first_index_of(token_id) {
+        token_ids.remove(index);
+        set_indexed(env, owner, code, &token_ids);
+    }
+}
+
+/// Move a token's code index entries to its new owner
+pub(crate) fn record_transfer(env: &Env, token_id: u64, from: &Address, to: &Address) {
+    for code in token_codes(env, token_id).iter() {
+        unindex(env, from, &code, token_id);
+        index(env, to, &code, token_id);
+    }
+}
+
+/// Authorize `caller` as the token's issuer or owner and return the owner
+fn authorize_tagger(env: &Env, caller: &Address, token_id: u64) -> Result<Address, ContractError> {
+    caller.require_auth();
+    let metadata = read_metadata(env, token_id)?;
+    let owner = PatientConsentToken::owner_of(env.clone(), token_id)?;
+    if *caller != metadata.issuer && *caller != owner {
+        return Err(ContractError::NotAuthorized);
+    }
+    if PatientConsentToken::is_revoked(env.clone(), token_id) {
+        return Err(ContractError::ConsentRevoked);
+    }
+    Ok(owner)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Register a code system (e.g. "ICD10", "SNOMED") consents can be tagged
+    /// from (admin only)
+    pub fn register_code_system(env: Env, system: Symbol) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut list = systems(&env);
+        if !list.contains(&system) {
+            list.push_back(system.clone());
+            env.storage().instance().set(&CodeKey::Systems, &list);
+            env.events()
+                .publish((symbol_short!("code_sys"), symbol_short!("added")), system);
+        }
+        Ok(())
+    }
+
+    /// Stop new tags from a code system (admin only). Existing tags stay.
+    pub fn remove_code_system(env: Env, system: Symbol) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut list = systems(&env);
+        if let Some(index) = list.first_index_of(&system) {
+            list.remove(index);
+            env.storage().instance().set(&CodeKey::Systems, &list);
+            env.events().publish(
+                (symbol_short!("code_sys"), symbol_short!("removed")),
+                system,
+            );
+        }
+        Ok(())
+    }
+
+    /// Get the registered code systems
+    pub fn get_code_systems(env: Env) -> Vec<Symbol> {
+        systems(&env)
+    }
+
+    /// Tag a consent with a code from a registered system (issuer or owner).
+    /// At most `MAX_CODES_PER_TOKEN` codes per consent.
+    pub fn tag_consent(
+        env: Env,
+        caller: Address,
+        token_id: u64,
+        code: ClinicalCode,
+    ) -> Result<(), ContractError> {
+        let owner = authorize_tagger(&env, &caller, token_id)?;
+        if !systems(&env).contains(&code.system) {
+            return Err(ContractError::UnknownCodeSystem);
+        }
+        if code.code.len() == 0 || code.code.len() > MAX_CODE_LEN {
+            return Err(ContractError::PolicyViolation);
+        }
+        let mut codes = token_codes(&env, token_id);
+        if codes.contains(&code) {
+            return Ok(());
+        }
+        if codes.len() >= MAX_CODES_PER_TOKEN {
+            return Err(ContractError::QuotaExceeded);
+        }
+
+        codes.push_back(code.clone());
+        let key = CodeKey::TokenCodes(token_id);
+        env.storage().persistent().set(&key, &codes);
+        ttl::bump_entry(&env, &key);
+        index(&env, &owner, &code, token_id);
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("code"), symbol_short!("tagged")),
+            token_id,
+            (token_id, code, caller),
+        );
+        Ok(())
+    }
+
+    /// Remove a code from a consent (issuer or owner)
+    pub fn untag_consent(
+        env: Env,
+        caller: Address,
+        token_id: u64,
+        code: ClinicalCode,
+    ) -> Result<(), ContractError> {
+        let owner = authorize_tagger(&env, &caller, token_id)?;
+        let mut codes = token_codes(&env, token_id);
+        let Some(index) = codes.first_index_of(&code) else {
+            return Ok(());
+        };
+
+        codes.remove(index);
+        let key = CodeKey::TokenCodes(token_id);
+        env.storage().persistent().set(&key, &codes);
+        ttl::bump_entry(&env, &key);
+        unindex(&env, &owner, &code, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("code"), symbol_short!("untagged")),
+            token_id,
+            (token_id, code, caller),
+        );
+        Ok(())
+    }
+
+    /// Get the codes a consent is tagged with
+    pub fn get_consent_codes(env: Env, token_id: u64) -> Vec<ClinicalCode> {
+        token_codes(&env, token_id)
+    }
+
+    /// Get the tokens `owner` holds that are tagged with `code`, in tagging order
+    pub fn find_tokens_by_code(env: Env, owner: Address, code: ClinicalCode) -> Vec<u64> {
+        indexed(&env, &owner, &code)
+    }
+}
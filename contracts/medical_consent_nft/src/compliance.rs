@@ -0,0 +1,110 @@
+use super::*;
+use crate::access_log::{access_count, AccessEntry, AccessLogKey};
+
+/// Consent type whose uses count as break-glass access
+pub const EMERGENCY_CONSENT_TYPE: &str = "emergency";
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// A grant that only ends when revoked
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OpenGrant {
+    pub token_id: u64,
+    pub grantee: Address,
+}
+
+// What an audit needs to follow up on across a patient's consents
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComplianceReport {
+    pub patient: Address,
+    pub generated_at: u64,
+    pub tokens_checked: u32,
+    pub missing_document_hash: Vec<u64>, // Live, no hash for the current version
+    pub expired_unrevoked: Vec<u64>,     // Expired but never revoked
+    pub open_ended_grants: Vec<OpenGrant>, // Grants on live consents with no expiry
+    pub emergency_accesses: u32,         // Emergency consent uses in the window
+}
+
+/// How many of a token's access log entries were made at or after `since`
+fn accesses_since(env: &Env, token_id: u64, since: u64) -> u32 {
+    let mut count = 0;
+    // Entries are appended in time order, so stop at the first older one
+    for index in (0..access_count(env, token_id)).rev() {
+        let entry: Option<AccessEntry> = env
+            .storage()
+            .persistent()
+            .get(&AccessLogKey::Access(token_id, index));
+        match entry {
+            Some(entry) if entry.timestamp >= since => count += 1,
+            Some(_) => break,
+            None => {}
+        }
+    }
+    count
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Summarize what an audit of a patient's consents should follow up on:
+    /// live consents without a document hash for their current version,
+    /// expired consents that were never revoked, grants without any expiry,
+    /// and uses of emergency consents logged in the last `days` days
+    /// (auditor only)
+    pub fn compliance_report(
+        env: Env,
+        auditor: Address,
+        patient: Address,
+        days: u32,
+    ) -> Result<ComplianceReport, ContractError> {
+        audit::require_auditor(&env, &auditor)?;
+        let now = clock::now(&env);
+        let since = now.saturating_sub(days as u64 * SECONDS_PER_DAY);
+        let emergency = String::from_str(&env, EMERGENCY_CONSENT_TYPE);
+
+        let token_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::PatientConsents(patient.clone()))
+            .unwrap_or(Vec::new(&env));
+        let mut report = ComplianceReport {
+            patient,
+            generated_at: now,
+            tokens_checked: 0,
+            missing_document_hash: Vec::new(&env),
+            expired_unrevoked: Vec::new(&env),
+            open_ended_grants: Vec::new(&env),
+            emergency_accesses: 0,
+        };
+        for token_id in token_ids.iter() {
+            let Ok(metadata) = read_metadata(&env, token_id) else {
+                continue;
+            };
+            report.tokens_checked += 1;
+            if metadata.consent_type == emergency {
+                report.emergency_accesses += accesses_since(&env, token_id, since);
+            }
+            if Self::is_revoked(env.clone(), token_id) {
+                continue;
+            }
+            if validity::is_expired(&env, metadata.expiry_timestamp) {
+                report.expired_unrevoked.push_back(token_id);
+                continue;
+            }
+            if Self::get_document_hash(env.clone(), token_id, metadata.version).is_none() {
+                report.missing_document_hash.push_back(token_id);
+            }
+            for grantee in grants::grantees(&env, token_id).iter() {
+                if grants::read_grant(&env, token_id, &grantee)
+                    .is_some_and(|grant| grant.expiry == 0)
+                {
+                    report
+                        .open_ended_grants
+                        .push_back(OpenGrant { token_id, grantee });
+                }
+            }
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,152 @@
+use super::*;
+use crate::ttl::TtlPolicy;
+use soroban_sdk::{Map, Symbol};
+
+/// Everything `initialize` sets up. New settings are added here rather than
+/// as new `initialize` parameters, so the entrypoint keeps its signature
+/// across releases.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InitConfig {
+    pub admin: Address,
+    pub issuers: Vec<Address>,      // Issuers authorized from the start
+    pub consent_types: Vec<String>, // Consent types accepted at mint (empty = defaults)
+    pub max_expiry_duration: u64,   // 0 = unlimited
+    pub max_delegation_depth: u32,
+    pub request_window: u64,       // Seconds a request stays answerable
+    pub clock_skew_tolerance: u64, // Seconds, at most MAX_CLOCK_SKEW
+    pub ttl_policy: TtlPolicy,
+    pub features: Map<Symbol, bool>, // Feature flags to set (others keep their default)
+}
+
+impl InitConfig {
+    /// The settings a deployment gets unless told otherwise
+    pub fn new(env: &Env, admin: Address) -> Self {
+        InitConfig {
+            admin,
+            issuers: Vec::new(env),
+            consent_types: validation::default_consent_types(env),
+            max_expiry_duration: 0,
+            max_delegation_depth: grants::DEFAULT_MAX_DELEGATION_DEPTH,
+            request_window: requests::DEFAULT_REQUEST_WINDOW,
+            clock_skew_tolerance: 0,
+            ttl_policy: ttl::DEFAULT_TTL_POLICY,
+            features: Map::new(env),
+        }
+    }
+}
+
+/// Settings to change with `reconfigure`. `None` (or an empty list) leaves a
+/// setting as it is.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigUpdate {
+    pub consent_types: Vec<String>,
+    pub max_expiry_duration: Option<u64>,
+    pub max_delegation_depth: Option<u32>,
+    pub request_window: Option<u64>,
+    pub clock_skew_tolerance: Option<u64>,
+    pub ttl_threshold: Option<u32>,
+    pub ttl_extend_to: Option<u32>,
+}
+
+impl ConfigUpdate {
+    /// An update that changes nothing, to fill in with the settings to change
+    pub fn new(env: &Env) -> Self {
+        ConfigUpdate {
+            consent_types: Vec::new(env),
+            max_expiry_duration: None,
+            max_delegation_depth: None,
+            request_window: None,
+            clock_skew_tolerance: None,
+            ttl_threshold: None,
+            ttl_extend_to: None,
+        }
+    }
+}
+
+/// Store the policy part of an initial config
+pub(crate) fn apply_init(env: &Env, config: &InitConfig) -> Result<(), ContractError> {
+    if !config.features.is_empty() {
+        features::write_flags(env, &config.features);
+    }
+    let consent_types = if config.consent_types.is_empty() {
+        validation::default_consent_types(env)
+    } else {
+        config.consent_types.clone()
+    };
+    apply(
+        env,
+        &ConfigUpdate {
+            consent_types,
+            max_expiry_duration: Some(config.max_expiry_duration),
+            max_delegation_depth: Some(config.max_delegation_depth),
+            request_window: Some(config.request_window),
+            clock_skew_tolerance: Some(config.clock_skew_tolerance),
+            ttl_threshold: Some(config.ttl_policy.threshold),
+            ttl_extend_to: Some(config.ttl_policy.extend_to),
+        },
+    )
+}
+
+/// Validate and store every setting present in `update`. Nothing is kept
+/// if one of them is rejected, as the invocation fails.
+fn apply(env: &Env, update: &ConfigUpdate) -> Result<(), ContractError> {
+    if !update.consent_types.is_empty() {
+        validation::write_consent_types(env, &update.consent_types);
+    }
+    if let Some(max_duration) = update.max_expiry_duration {
+        validation::write_max_expiry_duration(env, max_duration);
+    }
+    if let Some(depth) = update.max_delegation_depth {
+        grants::write_max_depth(env, depth);
+    }
+    if let Some(window) = update.request_window {
+        requests::write_request_window(env, window)?;
+    }
+    if let Some(seconds) = update.clock_skew_tolerance {
+        validity::write_skew_tolerance(env, seconds)?;
+    }
+    if update.ttl_threshold.is_some() || update.ttl_extend_to.is_some() {
+        let current = ttl::ttl_policy(env);
+        ttl::write_policy(
+            env,
+            &TtlPolicy {
+                threshold: update.ttl_threshold.unwrap_or(current.threshold),
+                extend_to: update.ttl_extend_to.unwrap_or(current.extend_to),
+            },
+        )?;
+    }
+    Ok(())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Change several settings in one call (admin only). Deployments that put
+    /// a timelock contract in the admin seat get every change delayed through
+    /// it. Fails without changing anything if any setting is invalid.
+    pub fn reconfigure(env: Env, update: ConfigUpdate) -> Result<(), ContractError> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        apply(&env, &update)?;
+
+        env.events()
+            .publish((symbol_short!("config"), symbol_short!("updated")), admin);
+        Ok(())
+    }
+
+    /// Get the current settings in the shape `initialize` takes them
+    pub fn get_config(env: Env) -> Result<InitConfig, ContractError> {
+        Ok(InitConfig {
+            admin: read_admin(&env)?,
+            issuers: allowlist::members(&env, &DataKey::Issuers),
+            consent_types: validation::consent_types(&env),
+            max_expiry_duration: Self::get_max_expiry_duration(env.clone()),
+            max_delegation_depth: grants::max_depth(&env),
+            request_window: requests::request_window(&env),
+            clock_skew_tolerance: validity::skew_tolerance(&env),
+            ttl_policy: ttl::ttl_policy(&env),
+            features: features::flags(&env),
+        })
+    }
+}
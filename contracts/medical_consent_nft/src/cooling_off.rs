@@ -0,0 +1,119 @@
+use super::*;
+
+// Storage keys for cooling-off periods
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CoolingOffKey {
+    Period(String),   // Consent type -> seconds a new consent waits before it activates
+    ActivatesAt(u64), // Token -> when its cooling-off period ends
+}
+
+fn period(env: &Env, consent_type: &String) -> u64 {
+    env.storage()
+        .instance()
+        .get(&CoolingOffKey::Period(consent_type.clone()))
+        .unwrap_or(0)
+}
+
+fn activates_at(env: &Env, token_id: u64) -> u64 {
+    let key = CoolingOffKey::ActivatesAt(token_id);
+    ttl::bump_entry(env, &key);
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Whether a consent is still in its cooling-off period
+pub(crate) fn is_pending(env: &Env, token_id: u64) -> bool {
+    clock::seconds_until(env, activates_at(env, token_id)) > 0
+}
+
+/// Fails with `NotYetActive` while a consent is in its cooling-off period
+pub(crate) fn ensure_activated(env: &Env, token_id: u64) -> Result<(), ContractError> {
+    if is_pending(env, token_id) {
+        return Err(ContractError::NotYetActive);
+    }
+    Ok(())
+}
+
+/// Start a freshly minted consent's cooling-off period, if its type has one
+pub(crate) fn record_mint(env: &Env, token_id: u64, consent_type: &String) {
+    let seconds = period(env, consent_type);
+    if seconds == 0 {
+        return;
+    }
+    let key = CoolingOffKey::ActivatesAt(token_id);
+    env.storage()
+        .persistent()
+        .set(&key, &clock::now(env).saturating_add(seconds));
+    ttl::bump_entry(env, &key);
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Make new consents of a type wait `seconds` before they can be used, as
+    /// some jurisdictions require for e.g. research consents (admin only).
+    /// 0 removes the period. Consents already minted keep theirs.
+    pub fn set_cooling_off_period(
+        env: Env,
+        consent_type: String,
+        seconds: u64,
+    ) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let key = CoolingOffKey::Period(consent_type);
+        if seconds == 0 {
+            env.storage().instance().remove(&key);
+        } else {
+            env.storage().instance().set(&key, &seconds);
+        }
+        Ok(())
+    }
+
+    /// Get a type's cooling-off period in seconds (0 = none)
+    pub fn get_cooling_off_period(env: Env, consent_type: String) -> u64 {
+        period(&env, &consent_type)
+    }
+
+    /// Get when a consent's cooling-off period ends (0 = it never had one)
+    pub fn get_activation_time(env: Env, token_id: u64) -> u64 {
+        activates_at(&env, token_id)
+    }
+
+    /// Cancel a consent during its cooling-off period (patient only). The
+    /// consent can never be used, like a revoked one, but the cancellation
+    /// isn't counted or exported as a revocation.
+    pub fn cancel_pending_consent(env: Env, token_id: u64) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.patient.require_auth();
+        joint::ensure_sole_owner(&env, token_id)?;
+        if Self::is_revoked(env.clone(), token_id) {
+            return Err(ContractError::ConsentRevoked);
+        }
+        if !is_pending(&env, token_id) {
+            return Err(ContractError::PolicyViolation);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::TokenRevoked(token_id), &true);
+        if let Ok(owner) = Self::owner_of(env.clone(), token_id) {
+            active::record_removed(&env, &owner, &metadata.consent_type, token_id);
+        }
+        append_history(
+            &env,
+            token_id,
+            "cancelled",
+            &metadata.patient,
+            &metadata.metadata_uri,
+        );
+        archive::commit_token(&env, token_id);
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("withdrawn")),
+            token_id,
+            (token_id, metadata.patient),
+        );
+        Ok(())
+    }
+}
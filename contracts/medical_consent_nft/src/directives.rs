@@ -0,0 +1,173 @@
+use super::*;
+
+/// Most standing directives a patient can hold at once
+pub const MAX_DIRECTIVES_PER_PATIENT: u32 = 10;
+
+// Storage keys for patients' standing access directives
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DirectiveKey {
+    Counter,
+    Directive(u64),
+    PatientDirectives(Address),
+}
+
+/// Whose access requests a directive approves
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DirectiveTarget {
+    Requester(Address), // One data consumer
+    Institution(u64),   // Any issuer or the admin of an institution
+}
+
+// A patient's standing rule to approve matching access requests automatically
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsentDirective {
+    pub patient: Address,
+    pub target: DirectiveTarget,
+    pub scope: u32,           // Requests must stay within these scopes
+    pub consent_type: String, // Only consents of this type (empty = any)
+    pub created_at: u64,
+}
+
+fn patient_directives(env: &Env, patient: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&DirectiveKey::PatientDirectives(patient.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_patient_directives(env: &Env, patient: &Address, ids: &Vec<u64>) {
+    let key = DirectiveKey::PatientDirectives(patient.clone());
+    env.storage().persistent().set(&key, ids);
+    ttl::bump_entry(env, &key);
+}
+
+fn read_directive(env: &Env, directive_id: u64) -> Option<ConsentDirective> {
+    env.storage()
+        .persistent()
+        .get(&DirectiveKey::Directive(directive_id))
+}
+
+fn targets(env: &Env, target: &DirectiveTarget, requester: &Address) -> bool {
+    match target {
+        DirectiveTarget::Requester(address) => address == requester,
+        DirectiveTarget::Institution(institution_id) => {
+            institutions::issuer_institution(env, requester) == Some(*institution_id)
+                || institutions::read_institution(env, *institution_id)
+                    .is_ok_and(|institution| institution.admin == *requester)
+        }
+    }
+}
+
+/// The first of the patient's directives that approves `requester` asking for
+/// `scope` of a consent, if any
+pub(crate) fn matching(
+    env: &Env,
+    metadata: &ConsentMetadata,
+    requester: &Address,
+    scope: u32,
+) -> Option<u64> {
+    patient_directives(env, &metadata.patient)
+        .iter()
+        .find(|directive_id| {
+            read_directive(env, *directive_id).is_some_and(|directive| {
+                targets(env, &directive.target, requester)
+                    && directive.scope & scope == scope
+                    && (directive.consent_type.len() == 0
+                        || directive.consent_type == metadata.consent_type)
+            })
+        })
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Add a standing directive (patient authorizes): access requests from the
+    /// target within `scope`, on consents of `consent_type` (empty = any), are
+    /// approved as soon as they arrive. At most `MAX_DIRECTIVES_PER_PATIENT`.
+    pub fn add_directive(
+        env: Env,
+        patient: Address,
+        target: DirectiveTarget,
+        scope: u32,
+        consent_type: String,
+    ) -> Result<u64, ContractError> {
+        patient.require_auth();
+        features::ensure_enabled(&env, features::FEATURE_GRANTS)?;
+        pause::ensure_not_paused(&env, pause::PAUSE_GRANTS)?;
+        if scope == 0 {
+            return Err(ContractError::ScopeNotGranted);
+        }
+        if consent_type.len() != 0 {
+            validation::validate_consent_type(&env, &consent_type)?;
+        }
+        let mut ids = patient_directives(&env, &patient);
+        if ids.len() >= MAX_DIRECTIVES_PER_PATIENT {
+            return Err(ContractError::QuotaExceeded);
+        }
+
+        let directive_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DirectiveKey::Counter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DirectiveKey::Counter, &(directive_id + 1));
+        let key = DirectiveKey::Directive(directive_id);
+        env.storage().persistent().set(
+            &key,
+            &ConsentDirective {
+                patient: patient.clone(),
+                target,
+                scope,
+                consent_type,
+                created_at: clock::now(&env),
+            },
+        );
+        ttl::bump_entry(&env, &key);
+        ids.push_back(directive_id);
+        set_patient_directives(&env, &patient, &ids);
+
+        env.events().publish(
+            (symbol_short!("directive"), symbol_short!("added")),
+            (directive_id, patient),
+        );
+        Ok(directive_id)
+    }
+
+    /// Remove a standing directive (patient only). Grants it already approved
+    /// stay in place until revoked with `revoke_access`.
+    pub fn revoke_directive(env: Env, directive_id: u64) -> Result<(), ContractError> {
+        let directive =
+            read_directive(&env, directive_id).ok_or(ContractError::DirectiveNotFound)?;
+        directive.patient.require_auth();
+
+        env.storage()
+            .persistent()
+            .remove(&DirectiveKey::Directive(directive_id));
+        let mut ids = patient_directives(&env, &directive.patient);
+        if let Some(index) = ids.first_index_of(directive_id) {
+            ids.remove(index);
+            set_patient_directives(&env, &directive.patient, &ids);
+        }
+
+        env.events().publish(
+            (symbol_short!("directive"), symbol_short!("revoked")),
+            (directive_id, directive.patient),
+        );
+        Ok(())
+    }
+
+    /// Get a standing directive
+    pub fn get_directive(env: Env, directive_id: u64) -> Option<ConsentDirective> {
+        read_directive(&env, directive_id)
+    }
+
+    /// Get the IDs of a patient's standing directives
+    pub fn get_directives(env: Env, patient: Address) -> Vec<u64> {
+        patient_directives(&env, &patient)
+    }
+}
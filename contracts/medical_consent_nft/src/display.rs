@@ -0,0 +1,107 @@
+use super::*;
+
+// Longest consent type rendered verbatim; longer ones render as "other"
+const MAX_TYPE_LEN: usize = 64;
+// Keys and punctuation, two u64s of up to 20 digits and the longest status
+const MAX_FIXED_LEN: usize = 52 + 2 * 20 + "suspended".len();
+// Fits the fixed fields plus a maximum-length type with every byte escaped
+// as `\u00XX`
+const MAX_JSON_LEN: usize = MAX_FIXED_LEN + 6 * MAX_TYPE_LEN;
+
+/// Fixed-size buffer for assembling the JSON without an allocator
+struct JsonWriter {
+    buf: [u8; MAX_JSON_LEN],
+    len: usize,
+}
+
+impl JsonWriter {
+    fn new() -> Self {
+        JsonWriter {
+            buf: [0; MAX_JSON_LEN],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn push_escaped(&mut self, bytes: &[u8]) {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        for byte in bytes {
+            match *byte {
+                b'"' | b'\\' => self.push(&[b'\\', *byte]),
+                // JSON strings can't hold raw control characters
+                0x00..=0x1f => self.push(&[
+                    b'\\',
+                    b'u',
+                    b'0',
+                    b'0',
+                    HEX[(*byte >> 4) as usize],
+                    HEX[(*byte & 0xf) as usize],
+                ]),
+                _ => self.push(&[*byte]),
+            }
+        }
+    }
+
+    fn push_u64(&mut self, mut value: u64) {
+        let mut digits = [0u8; 20];
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        self.push(&digits[start..]);
+    }
+
+    fn to_string(&self, env: &Env) -> String {
+        String::from_bytes(env, &self.buf[..self.len])
+    }
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Render a minimal JSON description of a consent from on-chain fields, so
+    /// generic NFT viewers can show something before fetching `metadata_uri`
+    pub fn render_token_json(env: Env, token_id: u64) -> Result<String, ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        let status: &[u8] = match batch::consent_status(&env, token_id, &metadata) {
+            ConsentStatus::Valid => b"valid",
+            ConsentStatus::Expired => b"expired",
+            ConsentStatus::Dormant => b"dormant",
+            ConsentStatus::Suspended => b"suspended",
+            ConsentStatus::PendingActivation => b"pending",
+            // Only existing tokens are rendered
+            ConsentStatus::Revoked | ConsentStatus::NotFound => b"revoked",
+        };
+
+        let mut json = JsonWriter::new();
+        json.push(b"{\"name\":\"Consent #");
+        json.push_u64(token_id);
+        json.push(b"\",\"type\":\"");
+        let type_len = metadata.consent_type.len() as usize;
+        if type_len <= MAX_TYPE_LEN {
+            let mut consent_type = [0u8; MAX_TYPE_LEN];
+            metadata
+                .consent_type
+                .copy_into_slice(&mut consent_type[..type_len]);
+            json.push_escaped(&consent_type[..type_len]);
+        } else {
+            json.push(b"other");
+        }
+        json.push(b"\",\"status\":\"");
+        json.push(status);
+        json.push(b"\",\"expiry\":");
+        json.push_u64(metadata.expiry_timestamp);
+        json.push(b"}");
+
+        ttl::bump_token(&env, token_id);
+        Ok(json.to_string(&env))
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+use soroban_sdk::BytesN;
+
+// Storage keys for content-addressed consent documents
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DocumentKey {
+    HashRequired,          // Updates must carry the new document's hash
+    VersionHash(u64, u32), // Token id + metadata version -> document hash
+}
+
+pub(crate) fn ensure_hash_not_required(env: &Env) -> Result<(), ContractError> {
+    let required: bool = env
+        .storage()
+        .instance()
+        .get(&DocumentKey::HashRequired)
+        .unwrap_or(false);
+    if required {
+        return Err(ContractError::PolicyViolation);
+    }
+    Ok(())
+}
+
+pub(crate) fn store_hash(env: &Env, token_id: u64, version: u32, content_hash: &BytesN<32>) {
+    let key = DocumentKey::VersionHash(token_id, version);
+    env.storage().persistent().set(&key, content_hash);
+    ttl::bump_entry(env, &key);
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Require every metadata update to carry the new document's content hash
+    /// (admin only). While set, `update_consent` fails with `PolicyViolation`.
+    pub fn set_require_document_hash(env: Env, required: bool) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&DocumentKey::HashRequired, &required);
+        Ok(())
+    }
+
+    /// Check whether metadata updates must carry a document hash
+    pub fn is_document_hash_required(env: Env) -> bool {
+        ensure_hash_not_required(&env).is_err()
+    }
+
+    /// Update consent metadata and record the SHA-256 of the document the new
+    /// URI points to, so that version can be verified later. Returns the new version.
+    pub fn update_consent_with_hash(
+        env: Env,
+        token_id: u64,
+        new_metadata_uri: String,
+        content_hash: BytesN<32>,
+    ) -> Result<u32, ContractError> {
+        let version = update_metadata_uri(&env, token_id, new_metadata_uri)?;
+        store_hash(&env, token_id, version, &content_hash);
+        Ok(version)
+    }
+
+    /// Record the hash of a token's original document (issuer only). Can only
+    /// be done once, while the token is still at version 1.
+    pub fn record_initial_document_hash(
+        env: Env,
+        token_id: u64,
+        content_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.issuer.require_auth();
+        if metadata.version != 1
+            || env
+                .storage()
+                .persistent()
+                .has(&DocumentKey::VersionHash(token_id, 1))
+        {
+            return Err(ContractError::PolicyViolation);
+        }
+        store_hash(&env, token_id, 1, &content_hash);
+        Ok(())
+    }
+
+    /// Get the document hash recorded for a version of a token
+    pub fn get_document_hash(env: Env, token_id: u64, version: u32) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DocumentKey::VersionHash(token_id, version))
+    }
+
+    /// Check a document against the hash recorded for any version of a token,
+    /// not just the current one. False if no hash was recorded for that version.
+    pub fn verify_version_document(
+        env: Env,
+        token_id: u64,
+        version: u32,
+        content_hash: BytesN<32>,
+    ) -> bool {
+        Self::get_document_hash(env, token_id, version) == Some(content_hash)
+    }
+}
@@ -0,0 +1,137 @@
+use super::*;
+use crate::archive::ArchiveKey;
+use crate::limits::LimitKey;
+use soroban_sdk::{xdr::ToXdr, BytesN, IntoVal, Val};
+
+// Read-only checks so frontends can surface the exact error before asking
+// the user to sign. They never require auth and never write to storage.
+
+/// Metadata URI assumed by `estimate_mint_footprint`, the length of an
+/// `ipfs://` URI with a CIDv1
+const SAMPLE_URI: &str = "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+// Projected storage for one minted token, to budget rent before onboarding
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FootprintEstimate {
+    pub token_entries: u32, // Persistent entries created for the token itself
+    pub token_bytes: u32,   // Their keys and values in XDR, once history is full
+    pub index_entries: u32, // Per-patient and per-issuer entries the mint appends to
+    pub index_bytes: u32,   // What the mint adds to those entries
+    pub extend_to: u32,     // Ledgers of rent each entry is topped up to when touched
+}
+
+/// XDR size of a storage key and its value
+fn entry_size<K, V>(env: &Env, key: K, value: V) -> u32
+where
+    K: IntoVal<Env, Val>,
+    V: IntoVal<Env, Val>,
+{
+    key.to_xdr(env).len() + value.to_xdr(env).len()
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Check whether `issuer` could mint a consent of `consent_type` to `to` right now
+    pub fn can_mint(
+        env: Env,
+        issuer: Address,
+        to: Address,
+        consent_type: String,
+        expiry_timestamp: u64,
+    ) -> Result<(), ContractError> {
+        if !Self::is_issuer(env.clone(), issuer.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+        check_mint_policy(&env, &issuer, &to, &consent_type, expiry_timestamp)?;
+        Ok(())
+    }
+
+    /// Check whether `from` could transfer the token to `to` right now
+    pub fn can_transfer(
+        env: Env,
+        from: Address,
+        to: Address,
+        token_id: u64,
+    ) -> Result<(), ContractError> {
+        check_transfer(&env, &from, token_id)?;
+        if !transfer_policy::recipient_allowed(&env, token_id, &to) {
+            return Err(ContractError::RecipientNotAllowed);
+        }
+        Ok(())
+    }
+
+    /// Estimate the storage a mint creates, for a token whose history will
+    /// grow to `history_capacity` entries and on which `scopes_count` scopes
+    /// will be narrowed. Sizes assume a 64-character IPFS metadata URI and the
+    /// consent type `treatment`; entries written only by optional features
+    /// (institutions, templates, codes, ...) are not counted.
+    pub fn estimate_mint_footprint(
+        env: Env,
+        history_capacity: u32,
+        scopes_count: u32,
+    ) -> FootprintEstimate {
+        let token_id = u64::MAX;
+        let address = env.current_contract_address();
+        let uri = String::from_str(&env, SAMPLE_URI);
+        let metadata = ConsentMetadata {
+            metadata_uri: uri.clone(),
+            consent_type: String::from_str(&env, "treatment"),
+            issued_timestamp: u64::MAX,
+            expiry_timestamp: u64::MAX,
+            issuer: address.clone(),
+            patient: address.clone(),
+            version: u32::MAX,
+        };
+        let entry = ConsentHistoryEntry {
+            action: String::from_str(&env, "scope_revoked"),
+            timestamp: u64::MAX,
+            actor: address.clone(),
+            metadata_uri: uri.clone(),
+            old_uri: uri.clone(),
+            new_uri: uri,
+            old_expiry: u64::MAX,
+            new_expiry: u64::MAX,
+            old_scopes: u32::MAX,
+            new_scopes: u32::MAX,
+            changed_fields: u32::MAX,
+        };
+        let mut history = Vec::new(&env);
+        for _ in 0..history_capacity.max(1) {
+            history.push_back(entry.clone());
+        }
+
+        let mut token_entries = 5;
+        let mut token_bytes = entry_size(&env, DataKey::TokenOwner(token_id), address.clone())
+            + entry_size(&env, DataKey::TokenMetadata(token_id), metadata)
+            + entry_size(&env, DataKey::TokenRevoked(token_id), false)
+            + entry_size(&env, DataKey::ConsentHistory(token_id), history)
+            + entry_size(
+                &env,
+                ArchiveKey::TokenCommitment(token_id),
+                BytesN::from_array(&env, &[0; 32]),
+            );
+        if scopes_count > 0 {
+            token_entries += 1;
+            token_bytes += entry_size(&env, DataKey::TokenScopes(token_id), u32::MAX);
+        }
+
+        // Three token lists grow by one id; the issuer-owner counter only
+        // changes value, but is created by an issuer's first mint to a patient
+        let id_bytes = token_id.to_xdr(&env).len();
+        let index_bytes = 3 * id_bytes
+            + entry_size(
+                &env,
+                LimitKey::IssuerOwnerCount(address.clone(), address.clone()),
+                u32::MAX,
+            );
+
+        FootprintEstimate {
+            token_entries,
+            token_bytes,
+            index_entries: 4,
+            index_bytes,
+            extend_to: ttl::ttl_policy(&env).extend_to,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+
+// Storage keys for per-type duration policies
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DurationKey {
+    Policy(String), // Consent type -> duration bounds
+}
+
+/// Bounds on how long a consent of one type may run, in seconds from mint or renewal
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DurationPolicy {
+    pub min: u64, // 0 = no minimum
+    pub max: u64, // 0 = no maximum; with a maximum, permanent consents are refused
+}
+
+fn read_policy(env: &Env, consent_type: &String) -> Option<DurationPolicy> {
+    env.storage()
+        .instance()
+        .get(&DurationKey::Policy(consent_type.clone()))
+}
+
+/// Check an expiry against its type's bounds. `ExpiryTooShort` and
+/// `ExpiryTooLong` tell the caller which bound was violated.
+pub(crate) fn check(
+    env: &Env,
+    consent_type: &String,
+    expiry_timestamp: u64,
+) -> Result<(), ContractError> {
+    let Some(policy) = read_policy(env, consent_type) else {
+        return Ok(());
+    };
+    if expiry_timestamp == 0 {
+        if policy.max != 0 {
+            return Err(ContractError::ExpiryTooLong);
+        }
+        return Ok(());
+    }
+    let duration = clock::seconds_until(env, expiry_timestamp);
+    if duration < policy.min {
+        return Err(ContractError::ExpiryTooShort);
+    }
+    if policy.max != 0 && duration > policy.max {
+        return Err(ContractError::ExpiryTooLong);
+    }
+    Ok(())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Bound how long consents of a type may run, e.g. research consents must
+    /// expire within 2 years (admin only). Enforced at mint and renewal.
+    pub fn set_duration_policy(
+        env: Env,
+        consent_type: String,
+        min: u64,
+        max: u64,
+    ) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        if max != 0 && min > max {
+            return Err(ContractError::PolicyViolation);
+        }
+        env.storage().instance().set(
+            &DurationKey::Policy(consent_type),
+            &DurationPolicy { min, max },
+        );
+        Ok(())
+    }
+
+    /// Remove a type's duration bounds (admin only)
+    pub fn clear_duration_policy(env: Env, consent_type: String) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .remove(&DurationKey::Policy(consent_type));
+        Ok(())
+    }
+
+    /// Get a type's duration bounds
+    pub fn get_duration_policy(env: Env, consent_type: String) -> Option<DurationPolicy> {
+        read_policy(&env, &consent_type)
+    }
+
+    /// Move a consent's expiry (patient only). The new expiry must satisfy the
+    /// same checks as a fresh mint, including the type's duration bounds.
+    pub fn renew_consent(env: Env, token_id: u64, new_expiry: u64) -> Result<(), ContractError> {
+        let before = read_metadata(&env, token_id)?;
+        before.patient.require_auth();
+        if Self::is_revoked(env.clone(), token_id) {
+            return Err(ContractError::ConsentRevoked);
+        }
+        validation::validate_expiry(&env, new_expiry)?;
+        check(&env, &before.consent_type, new_expiry)?;
+        history::ensure_room(&env, token_id)?;
+
+        let mut after = before.clone();
+        after.expiry_timestamp = new_expiry;
+        env.storage()
+            .persistent()
+            .set(&DataKey::TokenMetadata(token_id), &after);
+        append_history_diff(&env, token_id, "renewed", &before.patient, &before, &after);
+        archive::commit_token(&env, token_id);
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("renewed")),
+            token_id,
+            (token_id, before.expiry_timestamp, new_expiry),
+        );
+        Ok(())
+    }
+}
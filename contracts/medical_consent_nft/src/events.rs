@@ -0,0 +1,35 @@
+use super::*;
+use soroban_sdk::{IntoVal, Symbol, Val};
+
+// Event topic ordering
+//
+// Every event's topics start with a category (`consent`, `access`, `code`,
+// ...) and an action. Events about a single consent add the consent's type
+// as the third topic, so indexers can subscribe to, say, only research
+// consent events without decoding event data:
+//
+//   0: category
+//   1: action
+//   2: consent type (events about a single consent only)
+//   3: subscriber (tagged copies of subscribable consent actions only)
+//
+// Events that aren't about one consent (issuer, registry and configuration
+// changes, batch summaries) have just the first two topics.
+
+/// The consent type topic for a token, empty if the token doesn't exist
+pub(crate) fn consent_type(env: &Env, token_id: u64) -> String {
+    read_metadata(env, token_id)
+        .map(|metadata| metadata.consent_type)
+        .unwrap_or(String::from_str(env, ""))
+}
+
+/// Publish a `(category, action)` event about one consent, with the
+/// consent's type as the third topic
+pub(crate) fn publish_token<D>(env: &Env, topics: (Symbol, Symbol), token_id: u64, data: D)
+where
+    D: IntoVal<Env, Val>,
+{
+    let (category, action) = topics;
+    env.events()
+        .publish((category, action, consent_type(env, token_id)), data);
+}
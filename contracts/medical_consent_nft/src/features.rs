@@ -0,0 +1,86 @@
+use super::*;
+use soroban_sdk::{Map, Symbol};
+
+/// Access grants, access requests and standing directives
+pub const FEATURE_GRANTS: &str = "grants";
+
+/// Subsystems that were live before feature flags existed, and stay on on
+/// existing deployments until switched off. Anything else starts off.
+const ON_BY_DEFAULT: [&str; 1] = [FEATURE_GRANTS];
+
+// Storage keys for feature flags
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FeatureKey {
+    Flags, // Flag -> enabled, for every flag the admin has set
+}
+
+pub(crate) fn flags(env: &Env) -> Map<Symbol, bool> {
+    env.storage()
+        .instance()
+        .get(&FeatureKey::Flags)
+        .unwrap_or(Map::new(env))
+}
+
+pub(crate) fn write_flags(env: &Env, flags: &Map<Symbol, bool>) {
+    env.storage().instance().set(&FeatureKey::Flags, flags);
+}
+
+pub(crate) fn is_enabled(env: &Env, flag: &Symbol) -> bool {
+    flags(env).get(flag.clone()).unwrap_or_else(|| {
+        ON_BY_DEFAULT
+            .iter()
+            .any(|name| Symbol::new(env, name) == *flag)
+    })
+}
+
+/// The flags that are currently on, defaults first
+pub(crate) fn enabled(env: &Env) -> Vec<Symbol> {
+    let mut on = Vec::new(env);
+    for name in ON_BY_DEFAULT {
+        let flag = Symbol::new(env, name);
+        if is_enabled(env, &flag) {
+            on.push_back(flag);
+        }
+    }
+    for (flag, enabled) in flags(env).iter() {
+        if enabled && !on.contains(&flag) {
+            on.push_back(flag);
+        }
+    }
+    on
+}
+
+/// Fails with `FeatureDisabled` unless the subsystem behind `flag` is on
+pub(crate) fn ensure_enabled(env: &Env, flag: &str) -> Result<(), ContractError> {
+    if !is_enabled(env, &Symbol::new(env, flag)) {
+        return Err(ContractError::FeatureDisabled);
+    }
+    Ok(())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Switch a subsystem on or off on this deployment (admin only)
+    pub fn set_feature(env: Env, flag: Symbol, enabled: bool) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut all = flags(&env);
+        all.set(flag.clone(), enabled);
+        write_flags(&env, &all);
+
+        env.events()
+            .publish((symbol_short!("feature"), flag), enabled);
+        Ok(())
+    }
+
+    /// Check whether a subsystem is switched on
+    pub fn is_feature_enabled(env: Env, flag: Symbol) -> bool {
+        is_enabled(&env, &flag)
+    }
+
+    /// Get every flag the admin has set. Flags not listed have their default.
+    pub fn get_features(env: Env) -> Map<Symbol, bool> {
+        flags(&env)
+    }
+}
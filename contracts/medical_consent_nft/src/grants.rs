@@ -0,0 +1,381 @@
+use super::*;
+
+/// How many levels of sub-grants are allowed below the patient's grants until
+/// the admin configures otherwise
+pub const DEFAULT_MAX_DELEGATION_DEPTH: u32 = 3;
+
+// Storage keys for per-grantee access grants
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GrantKey {
+    Grant(u64, Address),             // Token id + grantee
+    Grantees(u64),                   // Everyone holding a grant on a token
+    MaxDepth,                        // Deepest sub-grant allowed
+    PatientGrants(Address, Address), // Patient + grantee -> tokens with a grant
+}
+
+/// What a grantee may do with the data a consent covers. Each level includes
+/// the ones before it.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum AccessLevel {
+    Read,  // View the data
+    Write, // Also add to or amend it
+    Share, // Also grant others access, up to this level
+}
+
+// One grantee's access to a consent, with where it came from
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccessGrant {
+    pub level: AccessLevel,
+    pub scope: u32,  // Scopes the grantee may use (ALL_SCOPES unless asked for fewer)
+    pub expiry: u64, // 0 = lasts as long as the consent
+    pub granted_by: Address, // Patient, or the Share-level grantee that sub-granted
+    pub depth: u32,  // 0 for grants made by the patient
+    pub granted_at: u64,
+    pub directive: Option<u64>, // Standing directive that approved it automatically
+}
+
+pub(crate) fn read_grant(env: &Env, token_id: u64, grantee: &Address) -> Option<AccessGrant> {
+    env.storage()
+        .persistent()
+        .get(&GrantKey::Grant(token_id, grantee.clone()))
+}
+
+pub(crate) fn grantees(env: &Env, token_id: u64) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&GrantKey::Grantees(token_id))
+        .unwrap_or(Vec::new(env))
+}
+
+fn patient_grants(env: &Env, patient: &Address, grantee: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&GrantKey::PatientGrants(patient.clone(), grantee.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_patient_grants(env: &Env, patient: &Address, grantee: &Address, token_ids: &Vec<u64>) {
+    let key = GrantKey::PatientGrants(patient.clone(), grantee.clone());
+    if token_ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, token_ids);
+        ttl::bump_entry(env, &key);
+    }
+}
+
+/// Move a token's grants from `from`'s patient index to `to`'s after the
+/// consent is rebound to a new patient address. Grants `from` made are
+/// re-rooted at `to` so they stay in force.
+pub(crate) fn rebind_patient(env: &Env, token_id: u64, from: &Address, to: &Address) {
+    for grantee in grantees(env, token_id).iter() {
+        if let Some(mut grant) = read_grant(env, token_id, &grantee) {
+            if grant.granted_by == *from {
+                grant.granted_by = to.clone();
+                env.storage()
+                    .persistent()
+                    .set(&GrantKey::Grant(token_id, grantee.clone()), &grant);
+            }
+        }
+        let mut from_tokens = patient_grants(env, from, &grantee);
+        if let Some(index) = from_tokens.first_index_of(token_id) {
+            from_tokens.remove(index);
+            set_patient_grants(env, from, &grantee, &from_tokens);
+            let mut to_tokens = patient_grants(env, to, &grantee);
+            to_tokens.push_back(token_id);
+            set_patient_grants(env, to, &grantee, &to_tokens);
+        }
+    }
+}
+
+pub(crate) fn max_depth(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&GrantKey::MaxDepth)
+        .unwrap_or(DEFAULT_MAX_DELEGATION_DEPTH)
+}
+
+pub(crate) fn write_max_depth(env: &Env, depth: u32) {
+    env.storage().instance().set(&GrantKey::MaxDepth, &depth);
+}
+
+fn unexpired(env: &Env, grant: &AccessGrant) -> bool {
+    !clock::has_passed(env, grant.expiry)
+}
+
+/// The grant `grantee` currently holds, if it and every grant it was derived
+/// from are still in force. A sub-grant lapses as soon as its grantor's does.
+pub(crate) fn effective_grant(
+    env: &Env,
+    token_id: u64,
+    patient: &Address,
+    grantee: &Address,
+) -> Option<AccessGrant> {
+    let grant = read_grant(env, token_id, grantee)?;
+    // Grants made before the limit was lowered stop counting too
+    if grant.depth > max_depth(env) {
+        return None;
+    }
+    let mut current = grant.clone();
+    loop {
+        if !unexpired(env, &current) {
+            return None;
+        }
+        if current.granted_by == *patient {
+            return Some(grant);
+        }
+        let parent = read_grant(env, token_id, &current.granted_by)?;
+        // A grantor that has since been re-granted further down the chain no
+        // longer backs this grant, which also rules out cycles
+        if parent.level != AccessLevel::Share || parent.depth >= current.depth {
+            return None;
+        }
+        current = parent;
+    }
+}
+
+/// Store a grant that has already been checked, replacing any previous one
+pub(crate) fn store_grant(
+    env: &Env,
+    token_id: u64,
+    metadata: &ConsentMetadata,
+    grantee: &Address,
+    grant: AccessGrant,
+) {
+    let key = GrantKey::Grant(token_id, grantee.clone());
+    env.storage().persistent().set(&key, &grant);
+    let mut list = grantees(env, token_id);
+    if !list.contains(grantee) {
+        list.push_back(grantee.clone());
+        let list_key = GrantKey::Grantees(token_id);
+        env.storage().persistent().set(&list_key, &list);
+        ttl::bump_entry(env, &list_key);
+        let mut token_ids = patient_grants(env, &metadata.patient, grantee);
+        token_ids.push_back(token_id);
+        set_patient_grants(env, &metadata.patient, grantee, &token_ids);
+    }
+    append_history(
+        env,
+        token_id,
+        "access_granted",
+        &grant.granted_by,
+        &metadata.metadata_uri,
+    );
+    telemetry::record_activity(env, telemetry::Activity::Grant);
+    ttl::bump_entry(env, &key);
+    ttl::bump_token(env, token_id);
+
+    events::publish_token(
+        env,
+        (symbol_short!("access"), symbol_short!("granted")),
+        token_id,
+        (token_id, grant.granted_by, grantee.clone(), grant.level),
+    );
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set how many levels of sub-grants are allowed below the patient's grants
+    /// (admin only). `0` disables sub-granting.
+    pub fn set_max_delegation_depth(env: Env, depth: u32) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        write_max_depth(&env, depth);
+        Ok(())
+    }
+
+    /// Get the deepest sub-grant allowed
+    pub fn get_max_delegation_depth(env: Env) -> u32 {
+        max_depth(&env)
+    }
+
+    /// Grant `grantee` access to the data a consent covers. The patient can grant
+    /// any level; a Share-level grantee can sub-grant up to its own level and
+    /// expiry. Re-granting replaces the grantee's previous grant. The patient's
+    /// preferences bound every grant: blocked consent types can't be granted
+    /// on, and expiries are capped at their maximum grant duration.
+    pub fn grant_access(
+        env: Env,
+        token_id: u64,
+        grantor: Address,
+        grantee: Address,
+        level: AccessLevel,
+        expiry: u64,
+    ) -> Result<(), ContractError> {
+        grantor.require_auth();
+        features::ensure_enabled(&env, features::FEATURE_GRANTS)?;
+        pause::ensure_not_paused(&env, pause::PAUSE_GRANTS)?;
+        let metadata = read_metadata(&env, token_id)?;
+        ensure_active(&env, token_id, &metadata)?;
+        if grantee == metadata.patient || grantee == grantor {
+            return Err(ContractError::NotAuthorized);
+        }
+        if clock::has_passed(&env, expiry) {
+            return Err(ContractError::PastTimestamp);
+        }
+
+        let (ceiling, depth, scope) = if grantor == metadata.patient {
+            (metadata.expiry_timestamp, 0, ALL_SCOPES)
+        } else {
+            let parent = effective_grant(&env, token_id, &metadata.patient, &grantor)
+                .ok_or(ContractError::GrantNotFound)?;
+            if parent.level != AccessLevel::Share || level > parent.level {
+                return Err(ContractError::NotAuthorized);
+            }
+            if parent.depth + 1 > max_depth(&env) {
+                return Err(ContractError::DelegationTooDeep);
+            }
+            (parent.expiry, parent.depth + 1, parent.scope)
+        };
+        // Nothing outlives what it was derived from
+        let expiry = match (ceiling, expiry) {
+            (0, expiry) => expiry,
+            (ceiling, 0) => ceiling,
+            (ceiling, expiry) if expiry > ceiling => return Err(ContractError::ExpiryTooLong),
+            (_, expiry) => expiry,
+        };
+        let expiry = preferences::apply_to_grant(&env, &metadata, expiry)?;
+
+        store_grant(
+            &env,
+            token_id,
+            &metadata,
+            &grantee,
+            AccessGrant {
+                level,
+                scope,
+                expiry,
+                granted_by: grantor,
+                depth,
+                granted_at: clock::now(&env),
+                directive: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Withdraw a grant. The patient can revoke any grant, a grantor the ones it
+    /// made. Sub-grants derived from it lapse with it.
+    pub fn revoke_access(
+        env: Env,
+        token_id: u64,
+        caller: Address,
+        grantee: Address,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        let metadata = read_metadata(&env, token_id)?;
+        let grant = read_grant(&env, token_id, &grantee).ok_or(ContractError::GrantNotFound)?;
+        if caller != metadata.patient && caller != grant.granted_by {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&GrantKey::Grant(token_id, grantee.clone()));
+        let mut list = grantees(&env, token_id);
+        if let Some(index) = list.first_index_of(&grantee) {
+            list.remove(index);
+            let list_key = GrantKey::Grantees(token_id);
+            env.storage().persistent().set(&list_key, &list);
+            ttl::bump_entry(&env, &list_key);
+        }
+        let mut token_ids = patient_grants(&env, &metadata.patient, &grantee);
+        if let Some(index) = token_ids.first_index_of(token_id) {
+            token_ids.remove(index);
+            set_patient_grants(&env, &metadata.patient, &grantee, &token_ids);
+        }
+        append_history(
+            &env,
+            token_id,
+            "access_revoked",
+            &caller,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("access"), symbol_short!("ungranted")),
+            token_id,
+            (token_id, caller, grantee),
+        );
+        Ok(())
+    }
+
+    /// Check whether `accessor` may use a valid consent at `level`. The patient
+    /// always has every level.
+    pub fn check_access(env: Env, token_id: u64, accessor: Address, level: AccessLevel) -> bool {
+        let Ok(metadata) = read_metadata(&env, token_id) else {
+            return false;
+        };
+        if ensure_active(&env, token_id, &metadata).is_err() {
+            return false;
+        }
+        if accessor == metadata.patient {
+            return true;
+        }
+        effective_grant(&env, token_id, &metadata.patient, &accessor)
+            .is_some_and(|grant| grant.level >= level)
+    }
+
+    /// Find one of `patient`'s consents that currently lets `grantee` use the
+    /// data behind `scope`: the consent is valid, it and the grantee's grant
+    /// on it cover every scope asked for, and the grant is in force. Returns the oldest such
+    /// token, so verifiers don't need to know token ids in advance.
+    pub fn effective_access(
+        env: Env,
+        patient: Address,
+        grantee: Address,
+        scope: u32,
+    ) -> Option<u64> {
+        patient_grants(&env, &patient, &grantee)
+            .iter()
+            .find(|token_id| {
+                token_scopes(&env, *token_id) & scope == scope
+                    && read_grant(&env, *token_id, &grantee)
+                        .is_some_and(|grant| grant.scope & scope == scope)
+                    && Self::check_access(
+                        env.clone(),
+                        *token_id,
+                        grantee.clone(),
+                        AccessLevel::Read,
+                    )
+            })
+    }
+
+    /// Get the grant stored for a grantee, whether or not it is still in force
+    pub fn get_grant(env: Env, token_id: u64, grantee: Address) -> Option<AccessGrant> {
+        read_grant(&env, token_id, &grantee)
+    }
+
+    /// Get everyone holding a grant on a consent
+    pub fn get_grantees(env: Env, token_id: u64) -> Vec<Address> {
+        grantees(&env, token_id)
+    }
+
+    /// Get the chain of grants `grantee`'s access derives from, starting with
+    /// the patient's grant and ending with the grantee's own. Each grant's
+    /// `granted_by` names the previous grantee. The chain is returned as stored;
+    /// use `check_access` to see whether it is still in force. It stops short of
+    /// the patient when a link has been revoked.
+    pub fn get_grant_chain(env: Env, token_id: u64, grantee: Address) -> Vec<AccessGrant> {
+        let mut chain = Vec::new(&env);
+        let Ok(metadata) = read_metadata(&env, token_id) else {
+            return chain;
+        };
+        let mut next = read_grant(&env, token_id, &grantee);
+        while let Some(grant) = next {
+            next = if grant.granted_by == metadata.patient {
+                None
+            } else {
+                read_grant(&env, token_id, &grant.granted_by)
+                    .filter(|parent| parent.depth < grant.depth)
+            };
+            chain.push_front(grant);
+        }
+        chain
+    }
+}
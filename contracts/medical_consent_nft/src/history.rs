@@ -0,0 +1,161 @@
+use super::*;
+use soroban_sdk::{xdr::ToXdr, Bytes, BytesN};
+
+// Storage keys for history length limits
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HistoryKey {
+    Policy,
+    Sealed(u64), // Token -> digest of the entries sealed out of its history
+}
+
+/// What happens once a token's history reaches the cap
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HistoryOverflow {
+    Seal,   // Hash the oldest entries into the sealed digest and drop them
+    Reject, // Refuse further metadata updates
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HistoryPolicy {
+    pub max_len: u32, // Entries kept per token (0 = unlimited)
+    pub overflow: HistoryOverflow,
+}
+
+// The entries sealed out of a token's history so far. Each sealed chunk is
+// folded in as sha256(digest || chunk XDR), oldest first.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SealedHistory {
+    pub entries: u32,
+    pub digest: BytesN<32>,
+}
+
+fn policy(env: &Env) -> HistoryPolicy {
+    env.storage()
+        .instance()
+        .get(&HistoryKey::Policy)
+        .unwrap_or(HistoryPolicy {
+            max_len: 0,
+            overflow: HistoryOverflow::Seal,
+        })
+}
+
+fn sealed(env: &Env, token_id: u64) -> SealedHistory {
+    env.storage()
+        .persistent()
+        .get(&HistoryKey::Sealed(token_id))
+        .unwrap_or(SealedHistory {
+            entries: 0,
+            digest: BytesN::from_array(env, &[0; 32]),
+        })
+}
+
+fn fold(env: &Env, digest: &BytesN<32>, chunk: Vec<ConsentHistoryEntry>) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &digest.to_array());
+    preimage.append(&chunk.to_xdr(env));
+    env.crypto().sha256(&preimage)
+}
+
+/// Fails with `QuotaExceeded` if the token's history is full and the policy
+/// rejects further updates
+pub(crate) fn ensure_room(env: &Env, token_id: u64) -> Result<(), ContractError> {
+    let policy = policy(env);
+    if policy.max_len == 0 || policy.overflow != HistoryOverflow::Reject {
+        return Ok(());
+    }
+    let history: Vec<ConsentHistoryEntry> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::ConsentHistory(token_id))
+        .unwrap_or(Vec::new(env));
+    if history.len() >= policy.max_len {
+        return Err(ContractError::QuotaExceeded);
+    }
+    Ok(())
+}
+
+/// Seal the oldest entries of a history past the cap, keeping the newer half.
+/// Returns what stays in storage.
+pub(crate) fn enforce_cap(
+    env: &Env,
+    token_id: u64,
+    history: Vec<ConsentHistoryEntry>,
+) -> Vec<ConsentHistoryEntry> {
+    // The sealed digest lives as long as the history it continues
+    let key = HistoryKey::Sealed(token_id);
+    ttl::bump_entry(env, &key);
+    let policy = policy(env);
+    if policy.max_len == 0
+        || policy.overflow != HistoryOverflow::Seal
+        || history.len() <= policy.max_len
+    {
+        return history;
+    }
+    let keep = policy.max_len.div_ceil(2);
+    let count = history.len() - keep;
+
+    let chunk = history.slice(0..count);
+    let previous = sealed(env, token_id);
+    let digest = fold(env, &previous.digest, chunk.clone());
+    env.storage().persistent().set(
+        &key,
+        &SealedHistory {
+            entries: previous.entries + count,
+            digest: digest.clone(),
+        },
+    );
+    ttl::bump_entry(env, &key);
+
+    events::publish_token(
+        env,
+        (symbol_short!("history"), symbol_short!("sealed")),
+        token_id,
+        (token_id, chunk, digest),
+    );
+    history.slice(count..)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Cap how many history entries each token keeps and choose what happens
+    /// at the cap (admin only). A cap of 0 lifts it. Revocations and other
+    /// lifecycle events are always recorded; under `Reject` only metadata
+    /// updates are refused.
+    pub fn set_history_policy(env: Env, policy: HistoryPolicy) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage().instance().set(&HistoryKey::Policy, &policy);
+        Ok(())
+    }
+
+    /// Get the history cap and overflow policy
+    pub fn get_history_policy(env: Env) -> HistoryPolicy {
+        policy(&env)
+    }
+
+    /// Get how many entries were sealed out of a token's history and their digest
+    pub fn get_sealed_history(env: Env, token_id: u64) -> SealedHistory {
+        sealed(&env, token_id)
+    }
+
+    /// Check archived entries against a token's sealed digest. `chunks` are
+    /// the sealed chunks in the order they were sealed, as published in the
+    /// `history_sealed` events.
+    pub fn verify_sealed_history(
+        env: Env,
+        token_id: u64,
+        chunks: Vec<Vec<ConsentHistoryEntry>>,
+    ) -> bool {
+        let expected = sealed(&env, token_id);
+        let mut digest = BytesN::from_array(&env, &[0; 32]);
+        let mut entries = 0;
+        for chunk in chunks.iter() {
+            entries += chunk.len();
+            digest = fold(&env, &digest, chunk);
+        }
+        entries == expected.entries && digest == expected.digest
+    }
+}
@@ -0,0 +1,97 @@
+use super::*;
+
+// Storage keys for inactivity-based auto-revocation
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InactivityKey {
+    InactivityPolicy(u64), // Max seconds a patient may go without a heartbeat
+    LastHeartbeat(Address),
+}
+
+fn last_heartbeat(env: &Env, patient: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&InactivityKey::LastHeartbeat(patient.clone()))
+        .unwrap_or(0)
+}
+
+pub(crate) fn record_heartbeat(env: &Env, patient: &Address) {
+    let key = InactivityKey::LastHeartbeat(patient.clone());
+    env.storage().persistent().set(&key, &clock::now(env));
+    ttl::bump_entry(env, &key);
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Revoke a consent automatically if its patient stops sending heartbeats for
+    /// `max_inactive` seconds (patient or issuer of the token, 0 = disable)
+    pub fn set_inactivity_policy(
+        env: Env,
+        caller: Address,
+        token_id: u64,
+        max_inactive: u64,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        let metadata = read_metadata(&env, token_id)?;
+        if caller != metadata.patient && caller != metadata.issuer {
+            return Err(ContractError::NotAuthorized);
+        }
+        ensure_active(&env, token_id, &metadata)?;
+
+        let key = InactivityKey::InactivityPolicy(token_id);
+        if max_inactive == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &max_inactive);
+            ttl::bump_entry(&env, &key);
+            // The inactivity window starts now
+            record_heartbeat(&env, &metadata.patient);
+        }
+        ttl::bump_token(&env, token_id);
+        Ok(())
+    }
+
+    /// Signal that the patient is still engaged, keeping inactivity-bound consents alive
+    pub fn heartbeat(env: Env, patient: Address) {
+        patient.require_auth();
+        record_heartbeat(&env, &patient);
+    }
+
+    /// Get when a patient last sent a heartbeat (0 = never)
+    pub fn get_last_heartbeat(env: Env, patient: Address) -> u64 {
+        last_heartbeat(&env, &patient)
+    }
+
+    /// Get a token's inactivity window in seconds (0 = none)
+    pub fn get_inactivity_policy(env: Env, token_id: u64) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&InactivityKey::InactivityPolicy(token_id))
+            .unwrap_or(0)
+    }
+
+    /// Revoke a consent whose patient has been inactive past its window (callable by anyone)
+    pub fn auto_revoke_inactive(env: Env, token_id: u64) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        ensure_active(&env, token_id, &metadata)?;
+
+        let max_inactive: u64 = env
+            .storage()
+            .persistent()
+            .get(&InactivityKey::InactivityPolicy(token_id))
+            .ok_or(ContractError::PolicyViolation)?;
+        let deadline = last_heartbeat(&env, &metadata.patient).saturating_add(max_inactive);
+        if clock::now(&env) <= deadline {
+            return Err(ContractError::PatientActive);
+        }
+
+        mark_revoked(
+            &env,
+            token_id,
+            &env.current_contract_address(),
+            &metadata.metadata_uri,
+        );
+        Ok(())
+    }
+}
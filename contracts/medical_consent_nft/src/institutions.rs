@@ -0,0 +1,277 @@
+use super::*;
+
+// Storage keys for institution namespacing
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InstitutionKey {
+    InstitutionCounter,
+    Institution(u64),
+    IssuerInstitution(Address), // Institution an issuer mints under
+    TokenInstitution(u64),      // Institution a token was minted under
+    AddedIssuer(Address),       // Issuer an institution put on the global issuer list itself
+    RemovedIssuer(Address),     // Issuer the contract admin removed; institutions can't add it back
+}
+
+// A tenant (e.g. one hospital in a network) with its own admin, issuers and policy
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Institution {
+    pub name: String,
+    pub admin: Address,     // Institution-admin, manages issuers and policy
+    pub token_quota: u64,   // Max tokens minted under the institution (0 = unlimited)
+    pub tokens_issued: u64, // Tokens minted under the institution so far
+    pub consent_types: Vec<String>, // Types allowed on top of the global list (empty = all)
+}
+
+pub(crate) fn read_institution(
+    env: &Env,
+    institution_id: u64,
+) -> Result<Institution, ContractError> {
+    env.storage()
+        .instance()
+        .get(&InstitutionKey::Institution(institution_id))
+        .ok_or(ContractError::InstitutionNotFound)
+}
+
+fn write_institution(env: &Env, institution_id: u64, institution: &Institution) {
+    env.storage()
+        .instance()
+        .set(&InstitutionKey::Institution(institution_id), institution);
+}
+
+pub(crate) fn issuer_institution(env: &Env, issuer: &Address) -> Option<u64> {
+    env.storage()
+        .instance()
+        .get(&InstitutionKey::IssuerInstitution(issuer.clone()))
+}
+
+/// Drop an issuer the contract admin removed from its institution, and keep
+/// institution admins from putting it back until the contract admin does
+pub(crate) fn unbind_issuer(env: &Env, issuer: &Address) {
+    let storage = env.storage().instance();
+    storage.remove(&InstitutionKey::IssuerInstitution(issuer.clone()));
+    storage.remove(&InstitutionKey::AddedIssuer(issuer.clone()));
+    storage.set(&InstitutionKey::RemovedIssuer(issuer.clone()), &true);
+}
+
+/// Lift the hold `unbind_issuer` put on an issuer the contract admin added back
+pub(crate) fn clear_removal(env: &Env, issuer: &Address) {
+    env.storage()
+        .instance()
+        .remove(&InstitutionKey::RemovedIssuer(issuer.clone()));
+}
+
+pub(crate) fn token_institution(env: &Env, token_id: u64) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get(&InstitutionKey::TokenInstitution(token_id))
+}
+
+/// Enforce the issuer's institution quota and consent type policy
+pub(crate) fn check_mint(
+    env: &Env,
+    issuer: &Address,
+    consent_type: &String,
+) -> Result<(), ContractError> {
+    let Some(institution_id) = issuer_institution(env, issuer) else {
+        return Ok(());
+    };
+    let institution = read_institution(env, institution_id)?;
+    if institution.token_quota != 0 && institution.tokens_issued >= institution.token_quota {
+        return Err(ContractError::QuotaExceeded);
+    }
+    if !institution.consent_types.is_empty() && !institution.consent_types.contains(consent_type) {
+        return Err(ContractError::UnknownConsentType);
+    }
+    Ok(())
+}
+
+/// Record a token under its issuer's institution
+pub(crate) fn record_mint(env: &Env, issuer: &Address, token_id: u64) {
+    let Some(institution_id) = issuer_institution(env, issuer) else {
+        return;
+    };
+    if let Ok(mut institution) = read_institution(env, institution_id) {
+        institution.tokens_issued += 1;
+        write_institution(env, institution_id, &institution);
+        let key = InstitutionKey::TokenInstitution(token_id);
+        env.storage().persistent().set(&key, &institution_id);
+        ttl::bump_entry(env, &key);
+    }
+}
+
+/// The institution-admin or the contract admin (super-admin) may manage an institution
+fn require_institution_admin(
+    env: &Env,
+    institution: &Institution,
+    caller: &Address,
+) -> Result<(), ContractError> {
+    caller.require_auth();
+    if *caller != institution.admin && *caller != read_admin(env)? {
+        return Err(ContractError::NotAuthorized);
+    }
+    Ok(())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Create an institution with its own admin (super-admin only)
+    pub fn create_institution(
+        env: Env,
+        name: String,
+        institution_admin: Address,
+    ) -> Result<u64, ContractError> {
+        read_admin(&env)?.require_auth();
+
+        let institution_id: u64 = env
+            .storage()
+            .instance()
+            .get(&InstitutionKey::InstitutionCounter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&InstitutionKey::InstitutionCounter, &(institution_id + 1));
+
+        let institution = Institution {
+            name,
+            admin: institution_admin.clone(),
+            token_quota: 0,
+            tokens_issued: 0,
+            consent_types: Vec::new(&env),
+        };
+        write_institution(&env, institution_id, &institution);
+
+        env.events().publish(
+            (symbol_short!("inst"), symbol_short!("created")),
+            (institution_id, institution_admin),
+        );
+        Ok(institution_id)
+    }
+
+    /// Hand an institution over to a new institution-admin
+    pub fn set_institution_admin(
+        env: Env,
+        caller: Address,
+        institution_id: u64,
+        new_admin: Address,
+    ) -> Result<(), ContractError> {
+        let mut institution = read_institution(&env, institution_id)?;
+        require_institution_admin(&env, &institution, &caller)?;
+        institution.admin = new_admin;
+        write_institution(&env, institution_id, &institution);
+        Ok(())
+    }
+
+    /// Authorize an issuer to mint under an institution
+    pub fn add_institution_issuer(
+        env: Env,
+        caller: Address,
+        institution_id: u64,
+        issuer: Address,
+    ) -> Result<(), ContractError> {
+        let institution = read_institution(&env, institution_id)?;
+        require_institution_admin(&env, &institution, &caller)?;
+        // An issuer belongs to at most one institution, and issuers the
+        // contract admin added globally, or removed, can't be adopted by one
+        if env
+            .storage()
+            .instance()
+            .has(&InstitutionKey::RemovedIssuer(issuer.clone()))
+        {
+            return Err(ContractError::NotAuthorized);
+        }
+        match issuer_institution(&env, &issuer) {
+            Some(id) if id != institution_id => return Err(ContractError::NotAuthorized),
+            None if Self::is_issuer(env.clone(), issuer.clone()) => {
+                return Err(ContractError::NotAuthorized)
+            }
+            _ => {}
+        }
+
+        env.storage().instance().set(
+            &InstitutionKey::IssuerInstitution(issuer.clone()),
+            &institution_id,
+        );
+        if !Self::is_issuer(env.clone(), issuer.clone()) {
+            env.storage()
+                .instance()
+                .set(&InstitutionKey::AddedIssuer(issuer.clone()), &true);
+            allowlist::add(&env, &DataKey::Issuers, &issuer);
+        }
+
+        env.events().publish(
+            (symbol_short!("inst"), symbol_short!("issuer")),
+            (institution_id, issuer),
+        );
+        Ok(())
+    }
+
+    /// Remove an institution's issuer (existing tokens are unaffected). It
+    /// only leaves the global issuer list if the institution put it there.
+    pub fn remove_institution_issuer(
+        env: Env,
+        caller: Address,
+        institution_id: u64,
+        issuer: Address,
+    ) -> Result<(), ContractError> {
+        let institution = read_institution(&env, institution_id)?;
+        require_institution_admin(&env, &institution, &caller)?;
+        if issuer_institution(&env, &issuer) != Some(institution_id) {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        env.storage()
+            .instance()
+            .remove(&InstitutionKey::IssuerInstitution(issuer.clone()));
+        let added_key = InstitutionKey::AddedIssuer(issuer.clone());
+        if !env.storage().instance().has(&added_key) {
+            return Ok(());
+        }
+        env.storage().instance().remove(&added_key);
+        allowlist::remove(&env, &DataKey::Issuers, &issuer);
+        Ok(())
+    }
+
+    /// Set an institution's token quota (super-admin only, 0 = unlimited)
+    pub fn set_institution_quota(
+        env: Env,
+        institution_id: u64,
+        token_quota: u64,
+    ) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut institution = read_institution(&env, institution_id)?;
+        institution.token_quota = token_quota;
+        write_institution(&env, institution_id, &institution);
+        Ok(())
+    }
+
+    /// Restrict the consent types an institution's issuers may mint (empty = all global types)
+    pub fn set_institution_consent_types(
+        env: Env,
+        caller: Address,
+        institution_id: u64,
+        consent_types: Vec<String>,
+    ) -> Result<(), ContractError> {
+        let mut institution = read_institution(&env, institution_id)?;
+        require_institution_admin(&env, &institution, &caller)?;
+        institution.consent_types = consent_types;
+        write_institution(&env, institution_id, &institution);
+        Ok(())
+    }
+
+    /// Get an institution
+    pub fn get_institution(env: Env, institution_id: u64) -> Result<Institution, ContractError> {
+        read_institution(&env, institution_id)
+    }
+
+    /// Get the institution a token was minted under, if any
+    pub fn get_token_institution(env: Env, token_id: u64) -> Option<u64> {
+        token_institution(&env, token_id)
+    }
+
+    /// Get the institution an issuer mints under, if any
+    pub fn get_issuer_institution(env: Env, issuer: Address) -> Option<u64> {
+        issuer_institution(&env, &issuer)
+    }
+}
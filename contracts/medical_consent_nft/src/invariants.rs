@@ -0,0 +1,80 @@
+use super::*;
+use soroban_sdk::Symbol;
+
+// A broken storage invariant, found by `verify_invariants`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvariantViolation {
+    pub token_id: u64,
+    pub invariant: Symbol, // Which check failed, e.g. `owner_idx`
+}
+
+fn violation(env: &Env, found: &mut Vec<InvariantViolation>, token_id: u64, invariant: &str) {
+    found.push_back(InvariantViolation {
+        token_id,
+        invariant: Symbol::new(env, invariant),
+    });
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Walk every token and check that storage is consistent. Returns the
+    /// violations found, empty when everything holds. Only compiled with the
+    /// `testutils` feature, for fuzz and integration tests.
+    ///
+    /// - `no_meta`: a token has an owner but no metadata
+    /// - `no_history`: an existing token has an empty audit trail
+    /// - `owner_idx`: a token is missing from its owner's `OwnerTokens` list
+    /// - `stale_idx`: an `OwnerTokens` entry points at a token owned by someone else
+    /// - `past_count`: an `OwnerTokens` entry is at or above `TokenCounter`
+    pub fn verify_invariants(env: Env) -> Vec<InvariantViolation> {
+        let storage = env.storage().persistent();
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenCounter)
+            .unwrap_or(0);
+        let mut found = Vec::new(&env);
+        let mut owners: Vec<Address> = Vec::new(&env);
+
+        for token_id in 0..counter {
+            let Some(owner) = storage.get::<_, Address>(&DataKey::TokenOwner(token_id)) else {
+                continue;
+            };
+            if !storage.has(&DataKey::TokenMetadata(token_id)) {
+                violation(&env, &mut found, token_id, "no_meta");
+            }
+            let history: Vec<ConsentHistoryEntry> = storage
+                .get(&DataKey::ConsentHistory(token_id))
+                .unwrap_or(Vec::new(&env));
+            if history.is_empty() {
+                violation(&env, &mut found, token_id, "no_history");
+            }
+            let owned: Vec<u64> = storage
+                .get(&DataKey::OwnerTokens(owner.clone()))
+                .unwrap_or(Vec::new(&env));
+            if !owned.contains(token_id) {
+                violation(&env, &mut found, token_id, "owner_idx");
+            }
+            if !owners.contains(&owner) {
+                owners.push_back(owner);
+            }
+        }
+
+        for owner in owners.iter() {
+            let owned: Vec<u64> = storage
+                .get(&DataKey::OwnerTokens(owner.clone()))
+                .unwrap_or(Vec::new(&env));
+            for token_id in owned.iter() {
+                if token_id >= counter {
+                    violation(&env, &mut found, token_id, "past_count");
+                } else if storage.get::<_, Address>(&DataKey::TokenOwner(token_id))
+                    != Some(owner.clone())
+                {
+                    violation(&env, &mut found, token_id, "stale_idx");
+                }
+            }
+        }
+        found
+    }
+}
@@ -0,0 +1,71 @@
+use super::*;
+
+/// Token IDs stored per index page entry
+pub const ISSUER_PAGE_LEN: u32 = 100;
+
+// Storage keys for the per-issuer token index
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IssuerIndexKey {
+    Count(Address),     // Tokens the issuer has minted
+    Page(Address, u32), // Issuer + page number -> up to `ISSUER_PAGE_LEN` token IDs in mint order
+}
+
+pub(crate) fn issuer_token_count(env: &Env, issuer: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&IssuerIndexKey::Count(issuer.clone()))
+        .unwrap_or(0)
+}
+
+fn page(env: &Env, issuer: &Address, page: u32) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&IssuerIndexKey::Page(issuer.clone(), page))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Append a freshly minted token to its issuer's index
+pub(crate) fn record_mint(env: &Env, issuer: &Address, token_id: u64) {
+    let count = issuer_token_count(env, issuer);
+    let page_key = IssuerIndexKey::Page(issuer.clone(), count / ISSUER_PAGE_LEN);
+    let mut token_ids = page(env, issuer, count / ISSUER_PAGE_LEN);
+    token_ids.push_back(token_id);
+    env.storage().persistent().set(&page_key, &token_ids);
+    let count_key = IssuerIndexKey::Count(issuer.clone());
+    env.storage().persistent().set(&count_key, &(count + 1));
+    ttl::bump_entry(env, &page_key);
+    ttl::bump_entry(env, &count_key);
+}
+
+/// The issuer's tokens at index positions `start..end`, in mint order
+pub(crate) fn issuer_tokens(env: &Env, issuer: &Address, start: u32, end: u32) -> Vec<u64> {
+    let end = end.min(issuer_token_count(env, issuer));
+    let mut token_ids = Vec::new(env);
+    let mut position = start;
+    while position < end {
+        let page_start = position / ISSUER_PAGE_LEN * ISSUER_PAGE_LEN;
+        let page_end = (page_start + ISSUER_PAGE_LEN).min(end);
+        let entries = page(env, issuer, position / ISSUER_PAGE_LEN);
+        token_ids.append(&entries.slice(position - page_start..page_end - page_start));
+        position = page_end;
+    }
+    token_ids
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Get a page of the tokens an issuer has minted, in mint order, without
+    /// scanning every token. Revoked and expired tokens stay listed. At most
+    /// `MAX_PAGE_SIZE` per call; `limit` 0 means the maximum.
+    pub fn tokens_by_issuer(env: Env, issuer: Address, start: u32, limit: u32) -> Vec<u64> {
+        let end = start.saturating_add(audit::page_size(limit));
+        issuer_tokens(&env, &issuer, start, end)
+    }
+
+    /// Get how many tokens an issuer has minted
+    pub fn issuer_token_count(env: Env, issuer: Address) -> u32 {
+        issuer_token_count(&env, &issuer)
+    }
+}
@@ -0,0 +1,350 @@
+use super::*;
+use soroban_sdk::{vec, BytesN};
+
+/// Most owners a jointly held token can have
+pub const MAX_JOINT_OWNERS: u32 = 5;
+
+// Storage keys for joint ownership
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JointKey {
+    Owners(u64),      // Token -> its owners and threshold (unset = sole owner)
+    CoOwned(Address), // Co-owner -> tokens it holds jointly without being primary
+    MajorityOracle,   // Address trusted to attest that a patient has come of age
+}
+
+// The owners of a jointly held token. The primary owner is the one recorded
+// as `owner_of` and is always in `owners`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JointOwnership {
+    pub owners: Vec<Address>,
+    pub threshold: u32, // Owners that must approve a transfer or revocation
+}
+
+pub(crate) fn read_joint(env: &Env, token_id: u64) -> Option<JointOwnership> {
+    let key = JointKey::Owners(token_id);
+    ttl::bump_entry(env, &key);
+    env.storage().persistent().get(&key)
+}
+
+fn co_owned(env: &Env, owner: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&JointKey::CoOwned(owner.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_co_owned(env: &Env, owner: &Address, token_ids: &Vec<u64>) {
+    let key = JointKey::CoOwned(owner.clone());
+    if token_ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, token_ids);
+        ttl::bump_entry(env, &key);
+    }
+}
+
+fn index(env: &Env, owner: &Address, token_id: u64) {
+    let mut token_ids = co_owned(env, owner);
+    if !token_ids.contains(token_id) {
+        token_ids.push_back(token_id);
+        set_co_owned(env, owner, &token_ids);
+    }
+}
+
+fn unindex(env: &Env, owner: &Address, token_id: u64) {
+    let mut token_ids = co_owned(env, owner);
+    if let Some(index) = token_ids.first_index_of(token_id) {
+        token_ids.remove(index);
+        set_co_owned(env, owner, &token_ids);
+    }
+}
+
+/// Store a token's owners, indexing the co-owners other than `primary`.
+/// A single owner makes the token solely held again.
+pub(crate) fn write_joint(env: &Env, token_id: u64, primary: &Address, joint: &JointOwnership) {
+    if let Some(previous) = read_joint(env, token_id) {
+        for owner in previous.owners.iter() {
+            unindex(env, &owner, token_id);
+        }
+    }
+    let key = JointKey::Owners(token_id);
+    if joint.owners.len() <= 1 {
+        env.storage().persistent().remove(&key);
+        return;
+    }
+    for owner in joint.owners.iter() {
+        if owner != *primary {
+            index(env, &owner, token_id);
+        }
+    }
+    env.storage().persistent().set(&key, joint);
+    ttl::bump_entry(env, &key);
+}
+
+/// Fails with `NotAuthorized` for jointly held tokens, which a single owner
+/// can't transfer or revoke
+pub(crate) fn ensure_sole_owner(env: &Env, token_id: u64) -> Result<(), ContractError> {
+    if read_joint(env, token_id).is_some() {
+        return Err(ContractError::NotAuthorized);
+    }
+    Ok(())
+}
+
+/// Authorize `approvers` as at least the threshold of the token's owners.
+/// Returns the primary owner.
+fn authorize(env: &Env, approvers: &Vec<Address>, token_id: u64) -> Result<Address, ContractError> {
+    let primary = PatientConsentToken::owner_of(env.clone(), token_id)?;
+    let joint = read_joint(env, token_id).unwrap_or(JointOwnership {
+        owners: vec![env, primary.clone()],
+        threshold: 1,
+    });
+    let mut approved = Vec::new(env);
+    for approver in approvers.iter() {
+        if !joint.owners.contains(&approver) {
+            return Err(ContractError::NotAuthorized);
+        }
+        if !approved.contains(&approver) {
+            approver.require_auth();
+            approved.push_back(approver);
+        }
+    }
+    if approved.len() < joint.threshold {
+        return Err(ContractError::NotAuthorized);
+    }
+    Ok(primary)
+}
+
+fn majority_oracle(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&JointKey::MajorityOracle)
+}
+
+/// Replace `old` with `new` among the owners of `old`'s tokens after a
+/// social recovery. `primary_tokens` are the tokens `old` held as primary.
+pub(crate) fn rotate_owner(env: &Env, old: &Address, new: &Address, primary_tokens: &Vec<u64>) {
+    let mut token_ids = primary_tokens.clone();
+    token_ids.append(&co_owned(env, old));
+    for token_id in token_ids.iter() {
+        let Some(mut joint) = read_joint(env, token_id) else {
+            continue;
+        };
+        if let Some(index) = joint.owners.first_index_of(old) {
+            joint.owners.set(index, new.clone());
+        }
+        let primary =
+            PatientConsentToken::owner_of(env.clone(), token_id).unwrap_or_else(|_| new.clone());
+        write_joint(env, token_id, &primary, &joint);
+    }
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set who owns a token jointly, e.g. both parents for a child's consent,
+    /// and how many of them must approve a transfer or revocation. The
+    /// primary owner must stay in `owners`; a single owner ends joint
+    /// ownership. `approvers` must meet the current threshold (the sole owner
+    /// for a token not yet held jointly).
+    pub fn set_joint_owners(
+        env: Env,
+        approvers: Vec<Address>,
+        token_id: u64,
+        owners: Vec<Address>,
+        threshold: u32,
+    ) -> Result<(), ContractError> {
+        let primary = authorize(&env, &approvers, token_id)?;
+        if Self::is_revoked(env.clone(), token_id) {
+            return Err(ContractError::ConsentRevoked);
+        }
+        if owners.len() > MAX_JOINT_OWNERS {
+            return Err(ContractError::QuotaExceeded);
+        }
+        let mut distinct = Vec::new(&env);
+        for owner in owners.iter() {
+            if distinct.contains(&owner) {
+                return Err(ContractError::PolicyViolation);
+            }
+            distinct.push_back(owner);
+        }
+        if !owners.contains(&primary) || threshold == 0 || threshold > owners.len() {
+            return Err(ContractError::PolicyViolation);
+        }
+
+        write_joint(
+            &env,
+            token_id,
+            &primary,
+            &JointOwnership {
+                owners: owners.clone(),
+                threshold,
+            },
+        );
+        let metadata = read_metadata(&env, token_id)?;
+        append_history(
+            &env,
+            token_id,
+            "joint_owners",
+            &approvers.get(0).unwrap_or(primary),
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("joint")),
+            token_id,
+            (token_id, owners, threshold),
+        );
+        Ok(())
+    }
+
+    /// Get a token's owners: the primary owner first for a solely held token,
+    /// or every joint owner
+    pub fn owners_of(env: Env, token_id: u64) -> Result<Vec<Address>, ContractError> {
+        let primary = Self::owner_of(env.clone(), token_id)?;
+        Ok(read_joint(&env, token_id)
+            .map(|joint| joint.owners)
+            .unwrap_or(vec![&env, primary]))
+    }
+
+    /// Get how many owners must approve a transfer or revocation (1 for a
+    /// solely held token)
+    pub fn get_joint_threshold(env: Env, token_id: u64) -> u32 {
+        read_joint(&env, token_id).map_or(1, |joint| joint.threshold)
+    }
+
+    /// Get the tokens `owner` holds jointly without being their primary owner
+    pub fn get_co_owned_tokens(env: Env, owner: Address) -> Vec<u64> {
+        co_owned(&env, &owner)
+    }
+
+    /// Transfer a jointly held token with the approval of at least the
+    /// threshold of its owners. `to` becomes its sole owner.
+    pub fn joint_transfer(
+        env: Env,
+        approvers: Vec<Address>,
+        to: Address,
+        token_id: u64,
+    ) -> Result<(), ContractError> {
+        let primary = authorize(&env, &approvers, token_id)?;
+        let metadata = check_transfer(&env, &primary, token_id)?;
+        transfer_policy::check_recipient(&env, token_id, &primary, &to)?;
+        receivers::check_receiver(&env, token_id, &primary, &to)?;
+
+        write_joint(
+            &env,
+            token_id,
+            &to,
+            &JointOwnership {
+                owners: vec![&env, to.clone()],
+                threshold: 1,
+            },
+        );
+        move_token(&env, &primary, &to, token_id, &metadata);
+        Ok(())
+    }
+
+    /// Revoke a jointly held consent with the approval of at least the
+    /// threshold of its owners
+    pub fn joint_revoke(
+        env: Env,
+        approvers: Vec<Address>,
+        token_id: u64,
+    ) -> Result<(), ContractError> {
+        let primary = authorize(&env, &approvers, token_id)?;
+        if Self::is_revoked(env.clone(), token_id) {
+            return Err(ContractError::ConsentRevoked);
+        }
+        let metadata = read_metadata(&env, token_id)?;
+        mark_revoked(
+            &env,
+            token_id,
+            &approvers.get(0).unwrap_or(primary),
+            &metadata.metadata_uri,
+        );
+        Ok(())
+    }
+
+    /// Update a jointly held consent's metadata with the approval of at least
+    /// the threshold of its owners. `content_hash` is recorded for the new
+    /// version and must be given while document hashes are required.
+    /// Returns the new version.
+    pub fn joint_update_consent(
+        env: Env,
+        approvers: Vec<Address>,
+        token_id: u64,
+        new_metadata_uri: String,
+        content_hash: Option<BytesN<32>>,
+    ) -> Result<u32, ContractError> {
+        let primary = authorize(&env, &approvers, token_id)?;
+        if content_hash.is_none() {
+            documents::ensure_hash_not_required(&env)?;
+        }
+        let version = write_metadata_uri(
+            &env,
+            token_id,
+            &approvers.get(0).unwrap_or(primary),
+            new_metadata_uri,
+        )?;
+        if let Some(content_hash) = content_hash {
+            documents::store_hash(&env, token_id, version, &content_hash);
+        }
+        Ok(version)
+    }
+
+    /// Set the oracle trusted to attest that patients have reached majority
+    /// (admin only)
+    pub fn set_majority_oracle(env: Env, oracle: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&JointKey::MajorityOracle, &oracle);
+        Ok(())
+    }
+
+    /// Hand a jointly held token to its patient as sole owner once they reach
+    /// majority, as attested by the majority oracle or the admin. The
+    /// guardians lose ownership and every owner index is moved over.
+    pub fn emancipate(env: Env, attester: Address, token_id: u64) -> Result<(), ContractError> {
+        attester.require_auth();
+        if Some(attester.clone()) != majority_oracle(&env) && attester != read_admin(&env)? {
+            return Err(ContractError::NotAuthorized);
+        }
+        let metadata = read_metadata(&env, token_id)?;
+        if read_joint(&env, token_id).is_none() {
+            return Err(ContractError::PolicyViolation);
+        }
+        let primary = Self::owner_of(env.clone(), token_id)?;
+        let patient = metadata.patient.clone();
+
+        write_joint(
+            &env,
+            token_id,
+            &patient,
+            &JointOwnership {
+                owners: vec![&env, patient.clone()],
+                threshold: 1,
+            },
+        );
+        if primary != patient {
+            move_token(&env, &primary, &patient, token_id, &metadata);
+        }
+        append_history(
+            &env,
+            token_id,
+            "emancipated",
+            &attester,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("majority")),
+            token_id,
+            (token_id, patient, attester),
+        );
+        Ok(())
+    }
+}
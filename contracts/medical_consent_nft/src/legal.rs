@@ -0,0 +1,81 @@
+use super::*;
+use soroban_sdk::BytesN;
+
+// Storage keys for legal records
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LegalKey {
+    LegalRecord(u64),
+}
+
+// Ties a token to the exact signed terms, without storing the documents
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegalRecord {
+    pub license_hash: BytesN<32>, // Hash of the signed license/terms document
+    pub terms_version: u32,       // Version of the terms that were signed
+    pub signed_by_name_hash: BytesN<32>, // Hash of the signatory's legal name
+    pub recorded_timestamp: u64,  // When the record was attached
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Attach the legal record for a consent (issuer of the token only). The
+    /// record is write-once so it always reflects the terms originally signed.
+    pub fn set_legal_record(
+        env: Env,
+        token_id: u64,
+        license_hash: BytesN<32>,
+        terms_version: u32,
+        signed_by_name_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.issuer.require_auth();
+        ensure_active(&env, token_id, &metadata)?;
+
+        let key = LegalKey::LegalRecord(token_id);
+        if env.storage().persistent().has(&key) {
+            return Err(ContractError::PolicyViolation);
+        }
+        let record = LegalRecord {
+            license_hash,
+            terms_version,
+            signed_by_name_hash,
+            recorded_timestamp: clock::now(&env),
+        };
+        env.storage().persistent().set(&key, &record);
+        ttl::bump_entry(&env, &key);
+
+        append_history(
+            &env,
+            token_id,
+            "legal_recorded",
+            &metadata.issuer,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("legal")),
+            token_id,
+            (token_id, terms_version),
+        );
+        Ok(())
+    }
+
+    /// Get the legal record attached to a consent
+    pub fn get_legal_record(env: Env, token_id: u64) -> Result<LegalRecord, ContractError> {
+        read_metadata(&env, token_id)?;
+        let key = LegalKey::LegalRecord(token_id);
+        let record = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ContractError::LegalRecordNotFound)?;
+        ttl::bump_entry(&env, &key);
+        ttl::bump_token(&env, token_id);
+        Ok(record)
+    }
+}
@@ -1,8 +1,126 @@
 #![no_std]
 
+mod access_log;
+mod access_requests;
+mod active;
+mod activity;
+mod anchoring;
+mod archive;
+mod attribution;
+mod audit;
+mod batch;
+mod capabilities;
+mod care_teams;
+mod codes;
+mod compliance;
+mod config;
+mod cooling_off;
+mod directives;
+mod display;
+mod documents;
+mod dry_run;
+mod durations;
+mod events;
+mod features;
+mod grants;
+mod history;
+mod inactivity;
+mod institutions;
+#[cfg(any(test, feature = "testutils"))]
+mod invariants;
+mod issuer_index;
+mod joint;
+mod legal;
+mod limits;
+mod linking;
+mod localization;
+mod outbox;
+mod pause;
+mod posthumous;
+mod preferences;
+mod pseudonymous;
+mod receipts;
+mod receivers;
+mod recognition;
+mod recovery;
+mod redaction;
+mod requests;
+mod residency;
+mod responders;
+mod scheduling;
+mod scopes;
+mod service_keys;
+mod subscriptions;
+mod suspension;
+mod telemetry;
+mod templates;
+#[cfg(test)]
+#[path = "test.rs"]
+mod tests;
+mod tickets;
+mod transfer_policy;
+mod ttl;
+mod validation;
+mod validity;
+mod verification;
+mod views;
+
+pub use access_log::AccessEntry;
+pub use access_requests::{AccessRequest, BatchRequestResult};
+pub use activity::{ActivityThresholds, FlagReason, IssuerActivity, IssuerFlag};
+pub use anchoring::AuditAnchor;
+pub use attribution::ReadPolicy;
+pub use audit::RevocationRecord;
+pub use batch::{
+    BatchRebaseResult, BatchRevokeResult, ConsentStatus, ConsentStatusView, MAX_REBASE_URI_LEN,
+};
+pub use capabilities::{
+    InterfaceVersion, EXTENSIONS, INTERFACES, INTERFACE_MAJOR, INTERFACE_MINOR,
+};
+pub use codes::ClinicalCode;
+pub use compliance::{ComplianceReport, OpenGrant};
+pub use config::{ConfigUpdate, InitConfig};
+pub use directives::{ConsentDirective, DirectiveTarget};
+pub use dry_run::FootprintEstimate;
+pub use durations::DurationPolicy;
+pub use features::FEATURE_GRANTS;
+pub use grants::{AccessGrant, AccessLevel};
+pub use history::{HistoryOverflow, HistoryPolicy, SealedHistory};
+pub use institutions::Institution;
+#[cfg(any(test, feature = "testutils"))]
+pub use invariants::InvariantViolation;
+pub use issuer_index::ISSUER_PAGE_LEN;
+pub use joint::{JointOwnership, MAX_JOINT_OWNERS};
+pub use legal::LegalRecord;
+pub use limits::TokenLimits;
+pub use linking::LinkRecord;
+pub use pause::{PAUSE_ALL, PAUSE_GRANTS, PAUSE_MINT, PAUSE_TRANSFER};
+pub use posthumous::PosthumousStatus;
+pub use preferences::PatientPrefs;
+pub use receipts::VerificationReceipt;
+pub use recovery::{RecoveryCircle, RecoveryRequest};
+pub use redaction::RedactedMetadata;
+pub use requests::{ConsentRequest, RequestStatus};
+pub use responders::Responder;
+pub use scheduling::ScheduledConsent;
+pub use service_keys::{ServiceKey, KEY_CAN_MINT, KEY_CAN_REVOKE};
+pub use suspension::CascadeProgress;
+pub use telemetry::{ConsentMetrics, DailyActivity, ACTIVITY_DAYS};
+pub use templates::{
+    ConsentTemplate, FieldFormat, TemplateApproval, TemplateField, TemplateStatus,
+};
+pub use tickets::AccessTicket;
+pub use transfer_policy::CustodialPolicy;
+pub use ttl::TtlPolicy;
+pub use validity::{ValidityReason, ValidityReport};
+pub use views::{
+    ConsentViewData, VIEW_ALL, VIEW_EXPIRY, VIEW_ISSUED_AT, VIEW_ISSUER, VIEW_STATUS, VIEW_TYPE,
+};
+
 use soroban_sdk::{
     contract, contracterror, contractimpl, contracttype, symbol_short, Address, Env, String, Vec,
 };
+use uzima_common::{admin, allowlist, clock};
 
 // Storage keys
 
@@ -18,8 +136,12 @@ pub enum DataKey {
     OwnerTokens(Address),
     ConsentHistory(u64),
     PatientConsents(Address), // Track tokens issued for a patient (for revoke access)
+    TokenScopes(u64),         // Scope bitmap covered by a token (unset = all scopes)
 }
 
+/// Scope bitmap for a consent that has not been narrowed to specific scopes
+pub const ALL_SCOPES: u32 = u32::MAX;
+
 #[contracterror]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ContractError {
@@ -28,6 +150,50 @@ pub enum ContractError {
     ConsentRevoked = 3,
     AlreadyInitialized = 4,
     NotTokenOwner = 5,
+    TicketNotFound = 6,
+    NotInitialized = 7,
+    ExpiredConsent = 8,
+    SoulboundToken = 9,
+    FrozenToken = 10,
+    QuotaExceeded = 11,
+    PolicyViolation = 12,
+    NotGrantee = 13,
+    InvalidUri = 14,
+    PastTimestamp = 15,
+    ExpiryTooLong = 16,
+    UnknownConsentType = 17,
+    CommitmentMismatch = 18,
+    ScheduleNotFound = 19,
+    NotYetActive = 20,
+    OwnerLimitReached = 21,
+    IssuerLimitReached = 22,
+    RequestNotFound = 23,
+    RequestNotPending = 24,
+    RequestExpired = 25,
+    RecipientNotAllowed = 26,
+    UnknownAction = 27,
+    LegalRecordNotFound = 28,
+    InstitutionNotFound = 29,
+    NotClaimable = 30,
+    PatientActive = 31,
+    ConsentDormant = 32,
+    ScopeNotGranted = 33,
+    CareTeamNotFound = 34,
+    AlreadyLinked = 35,
+    NotLinked = 36,
+    TemplateNotFound = 37,
+    TemplateNotApproved = 38,
+    ExpiryTooShort = 39,
+    GrantNotFound = 40,
+    DelegationTooDeep = 41,
+    DirectiveNotFound = 42,
+    ServiceKeyNotFound = 43,
+    FeatureDisabled = 44,
+    InvalidTemplateField = 45,
+    UnknownCodeSystem = 46,
+    IssuerFlagged = 47,
+    OutOfRegion = 48,
+    ContractPaused = 49,
 }
 
 // Consent metadata structure - Added patient field
@@ -50,7 +216,474 @@ pub struct ConsentHistoryEntry {
     pub action: String, // "issued", "updated", "revoked"
     pub timestamp: u64,
     pub actor: Address,
-    pub metadata_uri: String,
+    pub metadata_uri: String, // URI after the action
+    pub old_uri: String,      // URI before the action
+    pub new_uri: String,
+    pub old_expiry: u64, // Expiry before the action
+    pub new_expiry: u64,
+    pub old_scopes: u32, // Scope bitmap before the action
+    pub new_scopes: u32,
+    pub changed_fields: u32, // Bitmap of CHANGED_* flags
+}
+
+// Flags for ConsentHistoryEntry::changed_fields
+pub const CHANGED_URI: u32 = 1 << 0;
+pub const CHANGED_EXPIRY: u32 = 1 << 1;
+pub const CHANGED_SCOPES: u32 = 1 << 2;
+
+/// Scope bitmap currently covered by a token
+pub(crate) fn token_scopes(env: &Env, token_id: u64) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TokenScopes(token_id))
+        .unwrap_or(ALL_SCOPES)
+}
+
+pub(crate) fn read_admin(env: &Env) -> Result<Address, ContractError> {
+    admin::read(env, &DataKey::Admin).ok_or(ContractError::NotInitialized)
+}
+
+pub(crate) fn read_metadata(env: &Env, token_id: u64) -> Result<ConsentMetadata, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TokenMetadata(token_id))
+        .ok_or(ContractError::TokenNotFound)
+}
+
+/// Fails with the specific reason a consent can no longer be used
+pub(crate) fn ensure_active(
+    env: &Env,
+    token_id: u64,
+    metadata: &ConsentMetadata,
+) -> Result<(), ContractError> {
+    let is_revoked: bool = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TokenRevoked(token_id))
+        .unwrap_or(false);
+    if is_revoked {
+        return Err(ContractError::ConsentRevoked);
+    }
+    suspension::ensure_not_suspended(env, token_id)?;
+    cooling_off::ensure_activated(env, token_id)?;
+    if validity::is_expired(env, metadata.expiry_timestamp) {
+        return Err(ContractError::ExpiredConsent);
+    }
+    posthumous::ensure_not_dormant(env, token_id, &metadata.patient)
+}
+
+/// Append an entry to a token's audit trail
+pub(crate) fn append_history(
+    env: &Env,
+    token_id: u64,
+    action: &str,
+    actor: &Address,
+    metadata_uri: &String,
+) {
+    let expiry = read_metadata(env, token_id)
+        .map(|metadata| metadata.expiry_timestamp)
+        .unwrap_or(0);
+    let scopes = token_scopes(env, token_id);
+    push_history(
+        env,
+        token_id,
+        ConsentHistoryEntry {
+            action: String::from_str(env, action),
+            timestamp: clock::now(env),
+            actor: actor.clone(),
+            metadata_uri: metadata_uri.clone(),
+            old_uri: metadata_uri.clone(),
+            new_uri: metadata_uri.clone(),
+            old_expiry: expiry,
+            new_expiry: expiry,
+            old_scopes: scopes,
+            new_scopes: scopes,
+            changed_fields: 0,
+        },
+    );
+}
+
+/// Append an entry recording exactly which metadata fields changed
+pub(crate) fn append_history_diff(
+    env: &Env,
+    token_id: u64,
+    action: &str,
+    actor: &Address,
+    before: &ConsentMetadata,
+    after: &ConsentMetadata,
+) {
+    let mut changed_fields = 0;
+    if before.metadata_uri != after.metadata_uri {
+        changed_fields |= CHANGED_URI;
+    }
+    if before.expiry_timestamp != after.expiry_timestamp {
+        changed_fields |= CHANGED_EXPIRY;
+    }
+    let scopes = token_scopes(env, token_id);
+    push_history(
+        env,
+        token_id,
+        ConsentHistoryEntry {
+            action: String::from_str(env, action),
+            timestamp: clock::now(env),
+            actor: actor.clone(),
+            metadata_uri: after.metadata_uri.clone(),
+            old_uri: before.metadata_uri.clone(),
+            new_uri: after.metadata_uri.clone(),
+            old_expiry: before.expiry_timestamp,
+            new_expiry: after.expiry_timestamp,
+            old_scopes: scopes,
+            new_scopes: scopes,
+            changed_fields,
+        },
+    );
+}
+
+pub(crate) fn push_history(env: &Env, token_id: u64, entry: ConsentHistoryEntry) {
+    let key = DataKey::ConsentHistory(token_id);
+    let mut history: Vec<ConsentHistoryEntry> = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env));
+    history.push_back(entry);
+    let history = history::enforce_cap(env, token_id, history);
+    env.storage().persistent().set(&key, &history);
+}
+
+/// Point a solely held token at a new metadata URI on its owner's
+/// authorization, bumping its version. Jointly held tokens are updated
+/// through `joint_update_consent`. Callers check any policy that applies to
+/// how the update was requested. Returns the new version.
+pub(crate) fn update_metadata_uri(
+    env: &Env,
+    token_id: u64,
+    new_metadata_uri: String,
+) -> Result<u32, ContractError> {
+    // Verify token exists
+    let owner: Address = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TokenOwner(token_id))
+        .ok_or(ContractError::TokenNotFound)?;
+    joint::ensure_sole_owner(env, token_id)?;
+
+    // Verify caller is owner (or tighten to issuer/patient if needed)
+    owner.require_auth();
+
+    write_metadata_uri(env, token_id, &owner, new_metadata_uri)
+}
+
+/// Point a token at a new metadata URI once `actor` has been authorized,
+/// bumping its version. Returns the new version.
+pub(crate) fn write_metadata_uri(
+    env: &Env,
+    token_id: u64,
+    actor: &Address,
+    new_metadata_uri: String,
+) -> Result<u32, ContractError> {
+    validation::validate_uri(&new_metadata_uri)?;
+    history::ensure_room(env, token_id)?;
+
+    // Verify token is still active
+    let mut metadata = read_metadata(env, token_id)?;
+    ensure_active(env, token_id, &metadata)?;
+
+    // Update metadata
+    let before = metadata.clone();
+    metadata.metadata_uri = new_metadata_uri.clone();
+    metadata.version += 1;
+
+    env.storage()
+        .persistent()
+        .set(&DataKey::TokenMetadata(token_id), &metadata);
+
+    // Add to history
+    append_history_diff(env, token_id, "updated", actor, &before, &metadata);
+    archive::commit_token(env, token_id);
+    ttl::bump_token(env, token_id);
+
+    // Emit event
+    subscriptions::publish(
+        env,
+        symbol_short!("updated"),
+        token_id,
+        (token_id, metadata.version, new_metadata_uri),
+    );
+    Ok(metadata.version)
+}
+
+/// Mark a token revoked and record who did it. Callers are responsible for
+/// authorization and for checking the token isn't already revoked.
+pub(crate) fn mark_revoked(env: &Env, token_id: u64, actor: &Address, metadata_uri: &String) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::TokenRevoked(token_id), &true);
+    let owner: Option<Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TokenOwner(token_id));
+    if let (Some(owner), Ok(metadata)) = (owner, read_metadata(env, token_id)) {
+        active::record_removed(env, &owner, &metadata.consent_type, token_id);
+    }
+
+    // Add to history
+    append_history(env, token_id, "revoked", actor, metadata_uri);
+    archive::commit_token(env, token_id);
+    telemetry::record_revocation(env);
+    ttl::bump_token(env, token_id);
+
+    // Emit event
+    subscriptions::publish(
+        env,
+        symbol_short!("revoked"),
+        token_id,
+        (token_id, actor.clone()),
+    );
+}
+
+/// Policy checks shared by minting and its dry run (everything but the URI).
+/// Returns the effective expiry after applying the patient's preferences.
+pub(crate) fn check_mint_policy(
+    env: &Env,
+    issuer: &Address,
+    patient: &Address,
+    consent_type: &String,
+    expiry_timestamp: u64,
+) -> Result<u64, ContractError> {
+    pause::ensure_not_paused(env, pause::PAUSE_MINT)?;
+    pause::ensure_issuer_not_paused(env, issuer)?;
+    activity::ensure_not_flagged(env, issuer)?;
+    let expiry_timestamp =
+        preferences::apply_to_mint(env, patient, consent_type, expiry_timestamp)?;
+    validation::validate_expiry(env, expiry_timestamp)?;
+    validation::validate_consent_type(env, consent_type)?;
+    durations::check(env, consent_type, expiry_timestamp)?;
+    institutions::check_mint(env, issuer, consent_type)?;
+    limits::check_mint(env, issuer, patient)?;
+    Ok(expiry_timestamp)
+}
+
+/// Checks shared by transfers and their dry run, except the recipient whitelist
+pub(crate) fn check_transfer(
+    env: &Env,
+    from: &Address,
+    token_id: u64,
+) -> Result<ConsentMetadata, ContractError> {
+    pause::ensure_not_paused(env, pause::PAUSE_TRANSFER)?;
+    let owner: Address = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TokenOwner(token_id))
+        .ok_or(ContractError::TokenNotFound)?;
+
+    if owner != *from {
+        return Err(ContractError::NotTokenOwner);
+    }
+
+    let metadata = read_metadata(env, token_id)?;
+    ensure_active(env, token_id, &metadata)?;
+    Ok(metadata)
+}
+
+/// Validate and store a new consent token. Callers are responsible for
+/// authorizing the issuer.
+pub(crate) fn mint_token(
+    env: &Env,
+    issuer: &Address,
+    patient: &Address,
+    metadata_uri: &String,
+    consent_type: &String,
+    expiry_timestamp: u64,
+) -> Result<u64, ContractError> {
+    validation::validate_uri(metadata_uri)?;
+    let expiry_timestamp = check_mint_policy(env, issuer, patient, consent_type, expiry_timestamp)?;
+
+    // Get and increment token counter
+    let token_id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::TokenCounter)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&DataKey::TokenCounter, &(token_id + 1));
+
+    // Create consent metadata
+    let metadata = ConsentMetadata {
+        metadata_uri: metadata_uri.clone(),
+        consent_type: consent_type.clone(),
+        issued_timestamp: clock::now(env),
+        expiry_timestamp,
+        issuer: issuer.clone(),
+        patient: patient.clone(),
+        version: 1,
+    };
+
+    // Store token data
+    env.storage()
+        .persistent()
+        .set(&DataKey::TokenOwner(token_id), patient);
+    env.storage()
+        .persistent()
+        .set(&DataKey::TokenMetadata(token_id), &metadata);
+    env.storage()
+        .persistent()
+        .set(&DataKey::TokenRevoked(token_id), &false);
+
+    // Add to patient's token list (initial owner)
+    let owner_key = DataKey::OwnerTokens(patient.clone());
+    let mut owner_tokens: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&owner_key)
+        .unwrap_or(Vec::new(env));
+    owner_tokens.push_back(token_id);
+    env.storage().persistent().set(&owner_key, &owner_tokens);
+
+    // Add to patient's consents list (for revoke access)
+    let patient_key = DataKey::PatientConsents(patient.clone());
+    let mut patient_consents: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&patient_key)
+        .unwrap_or(Vec::new(env));
+    patient_consents.push_back(token_id);
+    env.storage()
+        .persistent()
+        .set(&patient_key, &patient_consents);
+    limits::record_mint(env, issuer, patient);
+    institutions::record_mint(env, issuer, token_id);
+    issuer_index::record_mint(env, issuer, token_id);
+    cooling_off::record_mint(env, token_id, consent_type);
+    active::record_added(env, patient, consent_type, token_id);
+    activity::record_mint(env, issuer);
+    telemetry::record_activity(env, telemetry::Activity::Mint);
+
+    // Initialize consent history
+    append_history(env, token_id, "issued", issuer, metadata_uri);
+
+    archive::commit_token(env, token_id);
+    ttl::bump_token(env, token_id);
+    ttl::bump_entry(env, &owner_key);
+    ttl::bump_entry(env, &patient_key);
+
+    // Emit event
+    subscriptions::publish(
+        env,
+        symbol_short!("issued"),
+        token_id,
+        (
+            token_id,
+            patient.clone(),
+            consent_type.clone(),
+            metadata_uri.clone(),
+        ),
+    );
+
+    Ok(token_id)
+}
+
+/// Move a token to a new sole owner, updating every owner index. Callers
+/// are responsible for authorization and transfer checks.
+pub(crate) fn move_token(
+    env: &Env,
+    from: &Address,
+    to: &Address,
+    token_id: u64,
+    metadata: &ConsentMetadata,
+) {
+    // Update ownership
+    env.storage()
+        .persistent()
+        .set(&DataKey::TokenOwner(token_id), to);
+
+    // Update token lists
+    let from_key = DataKey::OwnerTokens(from.clone());
+    let from_tokens: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&from_key)
+        .unwrap_or(Vec::new(env));
+
+    let mut new_from_tokens = Vec::new(env);
+    for i in 0..from_tokens.len() {
+        let tid = from_tokens.get(i).unwrap();
+        if tid != token_id {
+            new_from_tokens.push_back(tid);
+        }
+    }
+    env.storage().persistent().set(&from_key, &new_from_tokens);
+
+    let to_key = DataKey::OwnerTokens(to.clone());
+    let mut to_tokens: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&to_key)
+        .unwrap_or(Vec::new(env));
+    to_tokens.push_back(token_id);
+    env.storage().persistent().set(&to_key, &to_tokens);
+
+    // PatientConsents list unchanged - patient still tracks/revokes it
+    limits::record_transfer(env, &metadata.issuer, from, to);
+    codes::record_transfer(env, token_id, from, to);
+    active::record_removed(env, from, &metadata.consent_type, token_id);
+    active::record_added(env, to, &metadata.consent_type, token_id);
+
+    archive::commit_token(env, token_id);
+    ttl::bump_token(env, token_id);
+    ttl::bump_entry(env, &from_key);
+    ttl::bump_entry(env, &to_key);
+
+    // Emit event
+    subscriptions::publish(
+        env,
+        symbol_short!("transfer"),
+        token_id,
+        (token_id, from.clone(), to.clone()),
+    );
+}
+
+/// Make `to` the patient of a consent issued for `from`, moving it and its
+/// grants between their patient indexes. `to` starts with a fresh heartbeat,
+/// so inactivity-bound consents can't be auto-revoked the moment they move.
+/// Ownership moves separately through `move_token`. Returns the updated
+/// metadata.
+pub(crate) fn rebind_patient(
+    env: &Env,
+    token_id: u64,
+    from: &Address,
+    to: &Address,
+) -> Result<ConsentMetadata, ContractError> {
+    let mut metadata = read_metadata(env, token_id)?;
+    metadata.patient = to.clone();
+    env.storage()
+        .persistent()
+        .set(&DataKey::TokenMetadata(token_id), &metadata);
+
+    let from_key = DataKey::PatientConsents(from.clone());
+    let mut from_consents: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&from_key)
+        .unwrap_or(Vec::new(env));
+    if let Some(index) = from_consents.first_index_of(token_id) {
+        from_consents.remove(index);
+        env.storage().persistent().set(&from_key, &from_consents);
+    }
+    let to_key = DataKey::PatientConsents(to.clone());
+    let mut to_consents: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&to_key)
+        .unwrap_or(Vec::new(env));
+    to_consents.push_back(token_id);
+    env.storage().persistent().set(&to_key, &to_consents);
+    ttl::bump_entry(env, &to_key);
+
+    grants::rebind_patient(env, token_id, from, to);
+    inactivity::record_heartbeat(env, to);
+    Ok(metadata)
 }
 
 #[contract]
@@ -58,83 +691,46 @@ pub struct PatientConsentToken;
 
 #[contractimpl]
 impl PatientConsentToken {
-    /// Initialize the contract with an admin
-    pub fn initialize(env: Env, admin: Address) -> Result<(), ContractError> {
-        if env.storage().instance().has(&DataKey::Admin) {
+    /// Initialize the contract from a config object (`InitConfig::new` gives
+    /// the defaults)
+    pub fn initialize(env: Env, config: InitConfig) -> Result<(), ContractError> {
+        if !admin::init(&env, &DataKey::Admin, &config.admin) {
             return Err(ContractError::AlreadyInitialized);
         }
 
-        admin.require_auth();
-        env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage().instance().set(&DataKey::TokenCounter, &0u64);
+        for issuer in config.issuers.iter() {
+            allowlist::add(&env, &DataKey::Issuers, &issuer);
+        }
 
-        // Initialize empty issuers list
-        let issuers: Vec<Address> = Vec::new(&env);
-        env.storage().instance().set(&DataKey::Issuers, &issuers);
-        Ok(())
+        config::apply_init(&env, &config)
     }
 
     /// Add an authorized issuer (clinic/healthcare provider)
-    pub fn add_issuer(env: Env, issuer: Address) {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .expect("Not initialized");
+    pub fn add_issuer(env: Env, issuer: Address) -> Result<(), ContractError> {
+        let admin = read_admin(&env)?;
         admin.require_auth();
 
-        let mut issuers: Vec<Address> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Issuers)
-            .unwrap_or(Vec::new(&env));
-
-        issuers.push_back(issuer);
-        env.storage().instance().set(&DataKey::Issuers, &issuers);
+        suspension::cancel_cascade(&env, &issuer);
+        institutions::clear_removal(&env, &issuer);
+        allowlist::add(&env, &DataKey::Issuers, &issuer);
+        Ok(())
     }
 
     /// Remove an authorized issuer
-    pub fn remove_issuer(env: Env, issuer: Address) {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .expect("Not initialized");
+    pub fn remove_issuer(env: Env, issuer: Address) -> Result<(), ContractError> {
+        let admin = read_admin(&env)?;
         admin.require_auth();
 
-        let issuers: Vec<Address> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Issuers)
-            .expect("No issuers found");
-
-        let mut new_issuers = Vec::new(&env);
-        for i in 0..issuers.len() {
-            let current = issuers.get(i).unwrap();
-            if current != issuer {
-                new_issuers.push_back(current);
-            }
-        }
-
-        env.storage()
-            .instance()
-            .set(&DataKey::Issuers, &new_issuers);
+        allowlist::remove(&env, &DataKey::Issuers, &issuer);
+        institutions::unbind_issuer(&env, &issuer);
+        suspension::start_cascade(&env, &issuer);
+        Ok(())
     }
 
     /// Check if address is an authorized issuer
     pub fn is_issuer(env: Env, address: Address) -> bool {
-        let issuers: Vec<Address> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Issuers)
-            .unwrap_or(Vec::new(&env));
-
-        for i in 0..issuers.len() {
-            if issuers.get(i).unwrap() == address {
-                return true;
-            }
-        }
-        false
+        allowlist::contains(&env, &DataKey::Issuers, &address)
     }
 
     /// Mint a new consent token - FIXED: Add issuer: Address param, require_auth on it, use for check & metadata (no env.invoker())
@@ -152,143 +748,26 @@ impl PatientConsentToken {
             return Err(ContractError::NotAuthorized);
         }
 
-        // Get and increment token counter
-        let token_id: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenCounter)
-            .unwrap_or(0);
-        env.storage()
-            .instance()
-            .set(&DataKey::TokenCounter, &(token_id + 1));
-
-        // Create consent metadata
-        let metadata = ConsentMetadata {
-            metadata_uri: metadata_uri.clone(),
-            consent_type: consent_type.clone(),
-            issued_timestamp: env.ledger().timestamp(),
+        mint_token(
+            &env,
+            &issuer,
+            &patient,
+            &metadata_uri,
+            &consent_type,
             expiry_timestamp,
-            issuer: issuer.clone(),
-            patient: patient.clone(),
-            version: 1,
-        };
-
-        // Store token data
-        env.storage()
-            .instance()
-            .set(&DataKey::TokenOwner(token_id), &patient);
-        env.storage()
-            .instance()
-            .set(&DataKey::TokenMetadata(token_id), &metadata);
-        env.storage()
-            .instance()
-            .set(&DataKey::TokenRevoked(token_id), &false);
-
-        // Add to patient's token list (initial owner)
-        let owner_key = DataKey::OwnerTokens(patient.clone());
-        let mut owner_tokens: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&owner_key)
-            .unwrap_or(Vec::new(&env));
-        owner_tokens.push_back(token_id);
-        env.storage().instance().set(&owner_key, &owner_tokens);
-
-        // Add to patient's consents list (for revoke access)
-        let patient_key = DataKey::PatientConsents(patient.clone());
-        let mut patient_consents: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&patient_key)
-            .unwrap_or(Vec::new(&env));
-        patient_consents.push_back(token_id);
-        env.storage().instance().set(&patient_key, &patient_consents);
-
-        // Initialize consent history
-        let history_entry = ConsentHistoryEntry {
-            action: String::from_str(&env, "issued"),
-            timestamp: env.ledger().timestamp(),
-            actor: issuer.clone(),
-            metadata_uri: metadata_uri.clone(),
-        };
-        let mut history = Vec::new(&env);
-        history.push_back(history_entry);
-        env.storage()
-            .instance()
-            .set(&DataKey::ConsentHistory(token_id), &history);
-
-        // Emit event
-        env.events().publish(
-            (symbol_short!("consent"), symbol_short!("issued")),
-            (token_id, patient, consent_type, metadata_uri),
-        );
-
-        Ok(token_id)
+        )
     }
 
-    /// Update consent metadata (creates new version)
+    /// Update consent metadata (creates new version). Fails with
+    /// `PolicyViolation` while document hashes are required; use
+    /// `update_consent_with_hash` then.
     pub fn update_consent(
         env: Env,
         token_id: u64,
         new_metadata_uri: String,
     ) -> Result<(), ContractError> {
-        // Verify token exists and is not revoked
-        let owner: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenOwner(token_id))
-            .expect("Token does not exist");
-
-        let is_revoked: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenRevoked(token_id))
-            .unwrap_or(false);
-
-        if is_revoked {
-            return Err(ContractError::ConsentRevoked);
-        }
-
-        // Verify caller is owner (or tighten to issuer/patient if needed)
-        owner.require_auth();
-
-        // Get and update metadata
-        let mut metadata: ConsentMetadata = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenMetadata(token_id))
-            .expect("Metadata not found");
-
-        metadata.metadata_uri = new_metadata_uri.clone();
-        metadata.version += 1;
-
-        env.storage()
-            .instance()
-            .set(&DataKey::TokenMetadata(token_id), &metadata);
-
-        // Add to history
-        let history_entry = ConsentHistoryEntry {
-            action: String::from_str(&env, "updated"),
-            timestamp: env.ledger().timestamp(),
-            actor: owner.clone(),
-            metadata_uri: new_metadata_uri.clone(),
-        };
-
-        let mut history: Vec<ConsentHistoryEntry> = env
-            .storage()
-            .instance()
-            .get(&DataKey::ConsentHistory(token_id))
-            .unwrap_or(Vec::new(&env));
-        history.push_back(history_entry);
-        env.storage()
-            .instance()
-            .set(&DataKey::ConsentHistory(token_id), &history);
-
-        // Emit event
-        env.events().publish(
-            (symbol_short!("consent"), symbol_short!("updated")),
-            (token_id, metadata.version, new_metadata_uri),
-        );
+        documents::ensure_hash_not_required(&env)?;
+        update_metadata_uri(&env, token_id, new_metadata_uri)?;
         Ok(())
     }
 
@@ -297,24 +776,21 @@ impl PatientConsentToken {
         // Verify token exists
         let _: Address = env
             .storage()
-            .instance()
+            .persistent()
             .get(&DataKey::TokenOwner(token_id))
             .ok_or(ContractError::TokenNotFound)?;
 
-        let metadata: ConsentMetadata = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenMetadata(token_id))
-            .ok_or(ContractError::TokenNotFound)?;
+        let metadata = read_metadata(&env, token_id)?;
 
         let patient = metadata.patient;
 
         // Patient must authorize revoke (controls their consent)
         patient.require_auth();
+        joint::ensure_sole_owner(&env, token_id)?;
 
         let is_revoked: bool = env
             .storage()
-            .instance()
+            .persistent()
             .get(&DataKey::TokenRevoked(token_id))
             .unwrap_or(false);
 
@@ -322,35 +798,7 @@ impl PatientConsentToken {
             return Err(ContractError::ConsentRevoked);
         }
 
-        // Mark as revoked
-        env.storage()
-            .instance()
-            .set(&DataKey::TokenRevoked(token_id), &true);
-
-        // Add to history
-        let history_entry = ConsentHistoryEntry {
-            action: String::from_str(&env, "revoked"),
-            timestamp: env.ledger().timestamp(),
-            actor: patient.clone(),
-            metadata_uri: metadata.metadata_uri.clone(),
-        };
-
-        let mut history: Vec<ConsentHistoryEntry> = env
-            .storage()
-            .instance()
-            .get(&DataKey::ConsentHistory(token_id))
-            .unwrap_or(Vec::new(&env));
-        history.push_back(history_entry);
-        env.storage()
-            .instance()
-            .set(&DataKey::ConsentHistory(token_id), &history);
-
-        // Emit event
-        env.events().publish(
-            (symbol_short!("consent"), symbol_short!("revoked")),
-            (token_id, patient),
-        );
-
+        mark_revoked(&env, token_id, &patient, &metadata.metadata_uri);
         Ok(())
     }
 
@@ -362,106 +810,77 @@ impl PatientConsentToken {
         token_id: u64,
     ) -> Result<(), ContractError> {
         from.require_auth();
+        joint::ensure_sole_owner(&env, token_id)?;
 
-        let owner: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenOwner(token_id))
-            .expect("Token does not exist");
-
-        if owner != from {
-            return Err(ContractError::NotTokenOwner);
-        }
-
-        let is_revoked: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenRevoked(token_id))
-            .unwrap_or(false);
-
-        if is_revoked {
-            return Err(ContractError::ConsentRevoked);
-        }
-
-        // Update ownership
-        env.storage()
-            .instance()
-            .set(&DataKey::TokenOwner(token_id), &to);
-
-        // Update token lists
-        let from_key = DataKey::OwnerTokens(from.clone());
-        let from_tokens: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&from_key)
-            .unwrap_or(Vec::new(&env));
-
-        let mut new_from_tokens = Vec::new(&env);
-        for i in 0..from_tokens.len() {
-            let tid = from_tokens.get(i).unwrap();
-            if tid != token_id {
-                new_from_tokens.push_back(tid);
-            }
-        }
-        env.storage().instance().set(&from_key, &new_from_tokens);
-
-        let to_key = DataKey::OwnerTokens(to.clone());
-        let mut to_tokens: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&to_key)
-            .unwrap_or(Vec::new(&env));
-        to_tokens.push_back(token_id);
-        env.storage().instance().set(&to_key, &to_tokens);
-
-        // PatientConsents list unchanged - patient still tracks/revokes it
+        let metadata = check_transfer(&env, &from, token_id)?;
+        transfer_policy::check_recipient(&env, token_id, &from, &to)?;
+        receivers::check_receiver(&env, token_id, &from, &to)?;
 
-        // Emit event
-        env.events().publish(
-            (symbol_short!("consent"), symbol_short!("transfer")),
-            (token_id, from, to),
-        );
+        move_token(&env, &from, &to, token_id, &metadata);
         Ok(())
     }
 
     /// Get token owner
-    pub fn owner_of(env: Env, token_id: u64) -> Address {
-        env.storage()
-            .instance()
+    pub fn owner_of(env: Env, token_id: u64) -> Result<Address, ContractError> {
+        let owner = env
+            .storage()
+            .persistent()
             .get(&DataKey::TokenOwner(token_id))
-            .expect("Token does not exist")
+            .ok_or(ContractError::TokenNotFound)?;
+        ttl::bump_token(&env, token_id);
+        Ok(owner)
     }
 
-    /// Get consent metadata
-    pub fn get_metadata(env: Env, token_id: u64) -> ConsentMetadata {
-        env.storage()
-            .instance()
-            .get(&DataKey::TokenMetadata(token_id))
-            .expect("Token does not exist")
+    /// Get consent metadata (refused while attributed reads are required)
+    pub fn get_metadata(env: Env, token_id: u64) -> Result<ConsentMetadata, ContractError> {
+        if attribution::required(&env) {
+            return Err(ContractError::NotAuthorized);
+        }
+        let metadata = read_metadata(&env, token_id)?;
+        ttl::bump_token(&env, token_id);
+        Ok(metadata)
     }
 
     /// Check if consent is revoked
     pub fn is_revoked(env: Env, token_id: u64) -> bool {
-        env.storage()
-            .instance()
+        let is_revoked = env
+            .storage()
+            .persistent()
             .get(&DataKey::TokenRevoked(token_id))
-            .unwrap_or(false)
+            .unwrap_or(false);
+        ttl::bump_token(&env, token_id);
+        is_revoked
     }
 
-    /// Get consent history (audit trail)
-    pub fn get_history(env: Env, token_id: u64) -> Vec<ConsentHistoryEntry> {
-        env.storage()
-            .instance()
+    /// Get consent history (audit trail), refused while attributed reads are required
+    pub fn get_history(env: Env, token_id: u64) -> Result<Vec<ConsentHistoryEntry>, ContractError> {
+        if attribution::required(&env) {
+            return Err(ContractError::NotAuthorized);
+        }
+        let history = env
+            .storage()
+            .persistent()
             .get(&DataKey::ConsentHistory(token_id))
-            .unwrap_or(Vec::new(&env))
+            .unwrap_or(Vec::new(&env));
+        ttl::bump_token(&env, token_id);
+        Ok(history)
+    }
+
+    /// Get the scope bitmap covered by a consent
+    pub fn get_scopes(env: Env, token_id: u64) -> u32 {
+        token_scopes(&env, token_id)
     }
 
     /// Get all tokens owned by an address
     pub fn tokens_of_owner(env: Env, owner: Address) -> Vec<u64> {
-        env.storage()
-            .instance()
-            .get(&DataKey::OwnerTokens(owner))
-            .unwrap_or(Vec::new(&env))
+        let key = DataKey::OwnerTokens(owner);
+        let tokens = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(&env));
+        ttl::bump_entry(&env, &key);
+        tokens
     }
 
     /// Check if doctor has valid consent for patient and type (for cross-contract access control)
@@ -469,171 +888,145 @@ impl PatientConsentToken {
         let tokens = Self::tokens_of_owner(env.clone(), doctor);
         for i in 0..tokens.len() {
             let token_id = tokens.get(i).unwrap();
-            if Self::is_revoked(env.clone(), token_id) {
-                continue;
-            }
-            let metadata = Self::get_metadata(env.clone(), token_id);
+            let metadata = match read_metadata(&env, token_id) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
             if metadata.patient == patient
                 && metadata.consent_type == consent_type
-                && (metadata.expiry_timestamp == 0 || env.ledger().timestamp() < metadata.expiry_timestamp)
+                && ensure_active(&env, token_id, &metadata).is_ok()
             {
+                ttl::bump_token(&env, token_id);
                 return true;
             }
         }
         false
     }
-
-    /// Check if consent is valid (not revoked and not expired)
-    pub fn is_valid(env: Env, token_id: u64) -> bool {
-        let is_revoked: bool = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenRevoked(token_id))
-            .unwrap_or(false);
-
-        if is_revoked {
-            return false;
-        }
-
-        let metadata: ConsentMetadata = env
-            .storage()
-            .instance()
-            .get(&DataKey::TokenMetadata(token_id))
-            .expect("Token does not exist");
-
-        if metadata.expiry_timestamp == 0 {
-            return true; // No expiry
-        }
-
-        env.ledger().timestamp() < metadata.expiry_timestamp
-    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::testutils::{Accounts, AuthorizedInvocation, AuthorizedFunction};
-    use soroban_sdk::{Symbol, symbol_short};
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    fn register(env: &Env) -> PatientConsentTokenClient<'_> {
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, PatientConsentToken);
+        PatientConsentTokenClient::new(env, &contract_id)
+    }
 
     #[test]
     fn test_mint_and_grant() {
         let env = Env::default();
-        let admin = env.accounts().generate_and_create();
-        let issuer = env.accounts().generate_and_create(); // Will add as issuer
-        let patient = env.accounts().generate_and_create();
-        let doctor = env.accounts().generate_and_create();
+        let client = register(&env);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env); // Will add as issuer
+        let patient = Address::generate(&env);
+        let doctor = Address::generate(&env);
 
         // Setup
-        PatientConsentToken::initialize(env.clone(), admin.clone());
-        admin.clone().mock_all_auths();
-        PatientConsentToken::add_issuer(env.clone(), issuer.clone());
-
-        // FIXED: Mint by issuer - pass issuer addr, mock auth for it
-        issuer.clone().mock_all_auths();
-        let token_id = PatientConsentToken::mint_consent(
-            env.clone(),
-            issuer.clone(), // FIXED: Pass issuer addr
-            patient.clone(),
-            String::from_str(&env, "ipfs://hash"),
-            String::from_str(&env, "treatment"),
-            0, // No expiry
-        ).unwrap();
+        client.initialize(&InitConfig::new(&env, admin.clone()));
+        client.add_issuer(&issuer);
+
+        // FIXED: Mint by issuer - pass issuer addr
+        let token_id = client.mint_consent(
+            &issuer, // FIXED: Pass issuer addr
+            &patient,
+            &String::from_str(&env, "ipfs://hash"),
+            &String::from_str(&env, "treatment"),
+            &0, // No expiry
+        );
 
-        assert_eq!(PatientConsentToken::owner_of(env.clone(), token_id), patient);
-        assert!(PatientConsentToken::is_valid(env.clone(), token_id));
+        assert_eq!(client.owner_of(&token_id), patient);
+        assert!(client.is_valid(&token_id));
 
         // Patient transfers to doctor (grant consent)
-        patient.clone().mock_all_auths();
-        PatientConsentToken::transfer(env.clone(), patient.clone(), doctor.clone(), token_id).unwrap();
-        assert_eq!(PatientConsentToken::owner_of(env.clone(), token_id), doctor);
+        client.transfer(&patient, &doctor, &token_id);
+        assert_eq!(client.owner_of(&token_id), doctor);
 
         // Doctor has consent
-        assert!(PatientConsentToken::has_consent(env.clone(), patient.clone(), doctor.clone(), String::from_str(&env, "treatment")));
+        assert!(client.has_consent(&patient, &doctor, &String::from_str(&env, "treatment")));
     }
 
     #[test]
-    #[should_panic(expected = "ConsentRevoked")]
     fn test_revoke_fails_access() {
         let env = Env::default();
-        let admin = env.accounts().generate_and_create();
-        let issuer = env.accounts().generate_and_create();
-        let patient = env.accounts().generate_and_create();
-        let doctor = env.accounts().generate_and_create();
+        let client = register(&env);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let patient = Address::generate(&env);
+        let doctor = Address::generate(&env);
 
         // Setup & mint & transfer
-        PatientConsentToken::initialize(env.clone(), admin.clone());
-        admin.clone().mock_all_auths();
-        PatientConsentToken::add_issuer(env.clone(), issuer.clone());
-        issuer.clone().mock_all_auths();
-        let token_id = PatientConsentToken::mint_consent(
-            env.clone(),
-            issuer.clone(),
-            patient.clone(),
-            String::from_str(&env, "ipfs://hash"),
-            String::from_str(&env, "treatment"),
-            0,
-        ).unwrap();
-        patient.clone().mock_all_auths();
-        PatientConsentToken::transfer(env.clone(), patient.clone(), doctor.clone(), token_id).unwrap();
+        client.initialize(&InitConfig::new(&env, admin.clone()));
+        client.add_issuer(&issuer);
+        let token_id = client.mint_consent(
+            &issuer,
+            &patient,
+            &String::from_str(&env, "ipfs://hash"),
+            &String::from_str(&env, "treatment"),
+            &0,
+        );
+        client.transfer(&patient, &doctor, &token_id);
 
-        // Patient revokes - mock auth for patient
-        patient.clone().mock_all_auths();
-        PatientConsentToken::revoke_consent(env.clone(), token_id).unwrap();
+        // Patient revokes
+        client.revoke_consent(&token_id);
 
         // Doctor no longer has consent
-        assert!(!PatientConsentToken::has_consent(env.clone(), patient.clone(), doctor.clone(), String::from_str(&env, "treatment")));
+        assert!(!client.has_consent(&patient, &doctor, &String::from_str(&env, "treatment")));
+        assert_eq!(
+            client.try_revoke_consent(&token_id),
+            Err(Ok(ContractError::ConsentRevoked))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "NotAuthorized")]
     fn test_unauthorized_mint_fails() {
         let env = Env::default();
-        let admin = env.accounts().generate_and_create();
-        let fake_issuer = env.accounts().generate_and_create();
-        let patient = env.accounts().generate_and_create();
+        let client = register(&env);
+        let admin = Address::generate(&env);
+        let fake_issuer = Address::generate(&env);
+        let patient = Address::generate(&env);
 
-        PatientConsentToken::initialize(env.clone(), admin.clone());
+        client.initialize(&InitConfig::new(&env, admin.clone()));
         // No add_issuer for fake
 
-        fake_issuer.clone().mock_all_auths();
-        let _ = PatientConsentToken::mint_consent(
-            env.clone(),
-            fake_issuer.clone(), // Pass fake, but not in issuers
-            patient.clone(),
-            String::from_str(&env, "ipfs://hash"),
-            String::from_str(&env, "treatment"),
-            0,
-        ); // Should panic on is_issuer check
+        let result = client.try_mint_consent(
+            &fake_issuer, // Pass fake, but not in issuers
+            &patient,
+            &String::from_str(&env, "ipfs://hash"),
+            &String::from_str(&env, "treatment"),
+            &0,
+        ); // Should fail on is_issuer check
+        assert_eq!(result, Err(Ok(ContractError::NotAuthorized)));
     }
 
     #[test]
     fn test_patient_consents_list() {
         let env = Env::default();
-        let admin = env.accounts().generate_and_create();
-        let issuer = env.accounts().generate_and_create();
-        let patient = env.accounts().generate_and_create();
-
-        PatientConsentToken::initialize(env.clone(), admin.clone());
-        admin.clone().mock_all_auths();
-        PatientConsentToken::add_issuer(env.clone(), issuer.clone());
-        issuer.clone().mock_all_auths();
-        let token1 = PatientConsentToken::mint_consent(
-            env.clone(),
-            issuer.clone(),
-            patient.clone(),
-            String::from_str(&env, "ipfs1"),
-            String::from_str(&env, "treatment"),
-            0,
-        ).unwrap();
-        let token2 = PatientConsentToken::mint_consent(
-            env.clone(),
-            issuer.clone(),
-            patient.clone(),
-            String::from_str(&env, "ipfs2"),
-            String::from_str(&env, "research"),
-            0,
-        ).unwrap();
+        let client = register(&env);
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let patient = Address::generate(&env);
+
+        client.initialize(&InitConfig::new(&env, admin.clone()));
+        client.add_issuer(&issuer);
+        let token1 = client.mint_consent(
+            &issuer,
+            &patient,
+            &String::from_str(&env, "ipfs1"),
+            &String::from_str(&env, "treatment"),
+            &0,
+        );
+        let token2 = client.mint_consent(
+            &issuer,
+            &patient,
+            &String::from_str(&env, "ipfs2"),
+            &String::from_str(&env, "research"),
+            &0,
+        );
 
         // patient_consents works via storage push in mint
+        assert_eq!(client.tokens_of_owner(&patient), vec![&env, token1, token2]);
     }
-}
\ No newline at end of file
+}
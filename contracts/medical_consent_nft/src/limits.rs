@@ -0,0 +1,111 @@
+use super::*;
+
+// Storage keys for per-owner token limits
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LimitKey {
+    TokenLimits,
+    IssuerOwnerCount(Address, Address), // Tokens from an issuer currently held by an owner
+}
+
+/// Caps on how many tokens a single address can be minted (0 = unlimited)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenLimits {
+    pub per_owner: u32,        // Max tokens held by one owner
+    pub per_issuer_owner: u32, // Max tokens one issuer can have minted to one owner
+}
+
+fn token_limits(env: &Env) -> TokenLimits {
+    env.storage()
+        .instance()
+        .get(&LimitKey::TokenLimits)
+        .unwrap_or(TokenLimits {
+            per_owner: 0,
+            per_issuer_owner: 0,
+        })
+}
+
+fn issuer_owner_count(env: &Env, issuer: &Address, owner: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&LimitKey::IssuerOwnerCount(issuer.clone(), owner.clone()))
+        .unwrap_or(0)
+}
+
+fn set_issuer_owner_count(env: &Env, issuer: &Address, owner: &Address, count: u32) {
+    let key = LimitKey::IssuerOwnerCount(issuer.clone(), owner.clone());
+    if count == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &count);
+        ttl::bump_entry(env, &key);
+    }
+}
+
+/// Fails if minting another token from `issuer` to `owner` would exceed a limit
+pub(crate) fn check_mint(
+    env: &Env,
+    issuer: &Address,
+    owner: &Address,
+) -> Result<(), ContractError> {
+    // Unclaimed pseudonymous tokens are held by the contract itself
+    if *owner == env.current_contract_address() {
+        return Ok(());
+    }
+    let limits = token_limits(env);
+    if limits.per_owner != 0 {
+        let held: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::OwnerTokens(owner.clone()))
+            .unwrap_or(Vec::new(env));
+        if held.len() >= limits.per_owner {
+            return Err(ContractError::OwnerLimitReached);
+        }
+    }
+    if limits.per_issuer_owner != 0
+        && issuer_owner_count(env, issuer, owner) >= limits.per_issuer_owner
+    {
+        return Err(ContractError::IssuerLimitReached);
+    }
+    Ok(())
+}
+
+pub(crate) fn record_mint(env: &Env, issuer: &Address, owner: &Address) {
+    let count = issuer_owner_count(env, issuer, owner);
+    set_issuer_owner_count(env, issuer, owner, count + 1);
+}
+
+pub(crate) fn record_transfer(env: &Env, issuer: &Address, from: &Address, to: &Address) {
+    let from_count = issuer_owner_count(env, issuer, from);
+    set_issuer_owner_count(env, issuer, from, from_count.saturating_sub(1));
+    let to_count = issuer_owner_count(env, issuer, to);
+    set_issuer_owner_count(env, issuer, to, to_count + 1);
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set per-owner and per-issuer-owner token limits enforced at mint (admin only, 0 = unlimited)
+    pub fn set_token_limits(
+        env: Env,
+        per_owner: u32,
+        per_issuer_owner: u32,
+    ) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage().instance().set(
+            &LimitKey::TokenLimits,
+            &TokenLimits {
+                per_owner,
+                per_issuer_owner,
+            },
+        );
+        Ok(())
+    }
+
+    /// Get the current token limits
+    pub fn get_token_limits(env: Env) -> TokenLimits {
+        token_limits(&env)
+    }
+}
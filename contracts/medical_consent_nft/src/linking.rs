@@ -0,0 +1,175 @@
+use super::*;
+
+// Storage keys for linked patient addresses
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LinkKey {
+    PrimaryOf(Address),   // Secondary -> primary
+    Secondaries(Address), // Primary -> linked secondaries
+    LinkLog(Address),     // Primary -> link/unlink audit trail
+}
+
+// One link or unlink of a secondary address
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LinkRecord {
+    pub secondary: Address,
+    pub linked: bool, // false for an unlink
+    pub actor: Address,
+    pub timestamp: u64,
+}
+
+fn secondaries(env: &Env, primary: &Address) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&LinkKey::Secondaries(primary.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn primary_of(env: &Env, address: &Address) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get(&LinkKey::PrimaryOf(address.clone()))
+}
+
+fn log_link(env: &Env, primary: &Address, secondary: &Address, linked: bool, actor: &Address) {
+    let key = LinkKey::LinkLog(primary.clone());
+    let mut log: Vec<LinkRecord> = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env));
+    log.push_back(LinkRecord {
+        secondary: secondary.clone(),
+        linked,
+        actor: actor.clone(),
+        timestamp: clock::now(env),
+    });
+    env.storage().persistent().set(&key, &log);
+    ttl::bump_entry(env, &key);
+}
+
+/// Token ids stored under `key_of(address)` for the primary and every linked secondary
+fn collect_linked<F>(env: &Env, primary: &Address, key_of: F) -> Vec<u64>
+where
+    F: Fn(Address) -> DataKey,
+{
+    let mut addresses = Vec::new(env);
+    addresses.push_back(primary.clone());
+    addresses.append(&secondaries(env, primary));
+
+    let mut tokens = Vec::new(env);
+    for address in addresses.iter() {
+        let list: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&key_of(address))
+            .unwrap_or(Vec::new(env));
+        tokens.append(&list);
+    }
+    tokens
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Link a secondary address (e.g. an old wallet) to a patient's primary
+    /// address. Needs both addresses and the admin to authorize. Links are one
+    /// level deep: a primary can't itself be linked elsewhere.
+    pub fn link_addresses(
+        env: Env,
+        primary: Address,
+        secondary: Address,
+    ) -> Result<(), ContractError> {
+        let admin = read_admin(&env)?;
+        primary.require_auth();
+        secondary.require_auth();
+        admin.require_auth();
+
+        if primary == secondary
+            || primary_of(&env, &primary).is_some()
+            || !secondaries(&env, &secondary).is_empty()
+        {
+            return Err(ContractError::PolicyViolation);
+        }
+        if primary_of(&env, &secondary).is_some() {
+            return Err(ContractError::AlreadyLinked);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&LinkKey::PrimaryOf(secondary.clone()), &primary);
+        let mut list = secondaries(&env, &primary);
+        list.push_back(secondary.clone());
+        let key = LinkKey::Secondaries(primary.clone());
+        env.storage().persistent().set(&key, &list);
+        ttl::bump_entry(&env, &key);
+        log_link(&env, &primary, &secondary, true, &admin);
+
+        env.events().publish(
+            (symbol_short!("address"), symbol_short!("linked")),
+            (primary, secondary),
+        );
+        Ok(())
+    }
+
+    /// Remove a link. The primary, the secondary or the admin can unlink; the
+    /// unlink is kept in the primary's link log.
+    pub fn unlink_addresses(
+        env: Env,
+        caller: Address,
+        secondary: Address,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        let primary = primary_of(&env, &secondary).ok_or(ContractError::NotLinked)?;
+        if caller != primary && caller != secondary && caller != read_admin(&env)? {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&LinkKey::PrimaryOf(secondary.clone()));
+        let mut list = secondaries(&env, &primary);
+        if let Some(index) = list.first_index_of(&secondary) {
+            list.remove(index);
+        }
+        env.storage()
+            .persistent()
+            .set(&LinkKey::Secondaries(primary.clone()), &list);
+        log_link(&env, &primary, &secondary, false, &caller);
+
+        env.events().publish(
+            (symbol_short!("address"), symbol_short!("unlinked")),
+            (primary, secondary, caller),
+        );
+        Ok(())
+    }
+
+    /// Get the primary address a secondary is linked to
+    pub fn get_primary_address(env: Env, secondary: Address) -> Option<Address> {
+        primary_of(&env, &secondary)
+    }
+
+    /// Get the secondary addresses linked to a primary
+    pub fn get_linked_addresses(env: Env, primary: Address) -> Vec<Address> {
+        secondaries(&env, &primary)
+    }
+
+    /// Get every link and unlink recorded for a primary
+    pub fn get_link_log(env: Env, primary: Address) -> Vec<LinkRecord> {
+        env.storage()
+            .persistent()
+            .get(&LinkKey::LinkLog(primary))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the tokens held by a primary and all of its linked addresses
+    pub fn tokens_of_linked(env: Env, primary: Address) -> Vec<u64> {
+        collect_linked(&env, &primary, DataKey::OwnerTokens)
+    }
+
+    /// Get the consents issued for a patient under their primary and all linked addresses
+    pub fn consents_of_linked(env: Env, primary: Address) -> Vec<u64> {
+        collect_linked(&env, &primary, DataKey::PatientConsents)
+    }
+}
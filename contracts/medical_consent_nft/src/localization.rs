@@ -0,0 +1,69 @@
+use super::*;
+use soroban_sdk::{Map, Symbol};
+
+// Storage keys for localized consent documents
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LocaleKey {
+    LocalizedUris(u64), // Map<language, uri> for a token
+}
+
+fn localized_uris(env: &Env, token_id: u64) -> Map<Symbol, String> {
+    env.storage()
+        .persistent()
+        .get(&LocaleKey::LocalizedUris(token_id))
+        .unwrap_or(Map::new(env))
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Attach a translated consent document (issuer only). The canonical
+    /// metadata_uri remains the legally binding version.
+    pub fn set_localized_uri(
+        env: Env,
+        token_id: u64,
+        lang: Symbol,
+        uri: String,
+    ) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.issuer.require_auth();
+        ensure_active(&env, token_id, &metadata)?;
+        validation::validate_uri(&uri)?;
+
+        let mut uris = localized_uris(&env, token_id);
+        uris.set(lang.clone(), uri.clone());
+        let key = LocaleKey::LocalizedUris(token_id);
+        env.storage().persistent().set(&key, &uris);
+        ttl::bump_entry(&env, &key);
+
+        append_history(&env, token_id, "localized", &metadata.issuer, &uri);
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("localize")),
+            token_id,
+            (token_id, lang, uri),
+        );
+        Ok(())
+    }
+
+    /// Get the consent document in a language, falling back to the canonical URI
+    pub fn token_uri_localized(
+        env: Env,
+        token_id: u64,
+        lang: Symbol,
+    ) -> Result<String, ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        ttl::bump_token(&env, token_id);
+        Ok(localized_uris(&env, token_id)
+            .get(lang)
+            .unwrap_or(metadata.metadata_uri))
+    }
+
+    /// Get the languages a consent document is available in (besides the canonical one)
+    pub fn get_token_languages(env: Env, token_id: u64) -> Vec<Symbol> {
+        localized_uris(&env, token_id).keys()
+    }
+}
@@ -0,0 +1,57 @@
+use super::*;
+use soroban_sdk::{contractclient, Symbol};
+
+// Storage keys for the outbox channel
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OutboxKey {
+    Outbox,
+}
+
+/// The part of the outbox contract's interface this contract writes to
+#[allow(dead_code)] // Only used through the generated client
+#[contractclient(name = "OutboxClient")]
+pub trait OutboxInterface {
+    fn push(env: Env, writer: Address, action: Symbol, subject_id: u64) -> u64;
+}
+
+/// Push a state-change message to the configured outbox, if any. A failing
+/// outbox never blocks the consent operation itself.
+pub(crate) fn notify(env: &Env, action: &Symbol, token_id: u64) {
+    let Some(outbox) = env
+        .storage()
+        .instance()
+        .get::<_, Address>(&OutboxKey::Outbox)
+    else {
+        return;
+    };
+    let _ = OutboxClient::new(env, &outbox).try_push(
+        &env.current_contract_address(),
+        action,
+        &token_id,
+    );
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Write every consent state change to an outbox contract (admin only).
+    /// This contract must be registered as a writer on the outbox.
+    pub fn set_outbox(env: Env, outbox: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage().instance().set(&OutboxKey::Outbox, &outbox);
+        Ok(())
+    }
+
+    /// Stop writing to the outbox (admin only)
+    pub fn clear_outbox(env: Env) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage().instance().remove(&OutboxKey::Outbox);
+        Ok(())
+    }
+
+    /// Get the configured outbox contract
+    pub fn get_outbox(env: Env) -> Option<Address> {
+        env.storage().instance().get(&OutboxKey::Outbox)
+    }
+}
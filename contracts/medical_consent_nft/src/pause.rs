@@ -0,0 +1,110 @@
+use super::*;
+use uzima_common::pause;
+
+/// Minting, including template, scheduled and service-key mints
+pub const PAUSE_MINT: u32 = 1;
+/// Token transfers
+pub const PAUSE_TRANSFER: u32 = 2;
+/// Access grants, access requests and standing directives
+pub const PAUSE_GRANTS: u32 = 4;
+/// Every capability above
+pub const PAUSE_ALL: u32 = PAUSE_MINT | PAUSE_TRANSFER | PAUSE_GRANTS;
+
+// Storage keys for scoped pausing
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PauseKey {
+    Paused,                // Bitmask of paused capabilities
+    IssuerPaused(Address), // Issuer whose mints and revocations are on hold
+}
+
+fn paused(env: &Env) -> u32 {
+    pause::paused_mask(env, &PauseKey::Paused)
+}
+
+fn is_issuer_paused(env: &Env, issuer: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&PauseKey::IssuerPaused(issuer.clone()))
+}
+
+/// Fails with `ContractPaused` while `capability` is paused
+pub(crate) fn ensure_not_paused(env: &Env, capability: u32) -> Result<(), ContractError> {
+    if paused(env) & capability != 0 {
+        return Err(ContractError::ContractPaused);
+    }
+    Ok(())
+}
+
+/// Fails with `ContractPaused` while the issuer's operations are on hold
+pub(crate) fn ensure_issuer_not_paused(env: &Env, issuer: &Address) -> Result<(), ContractError> {
+    if is_issuer_paused(env, issuer) {
+        return Err(ContractError::ContractPaused);
+    }
+    Ok(())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set which capabilities are paused, as a mask of `PAUSE_*` bits
+    /// (admin only). Bits outside `PAUSE_ALL` are rejected; 0 resumes all.
+    pub fn set_paused(env: Env, capabilities: u32) -> Result<(), ContractError> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        if capabilities & !PAUSE_ALL != 0 {
+            return Err(ContractError::PolicyViolation);
+        }
+        pause::set_paused_mask(&env, &PauseKey::Paused, capabilities);
+
+        env.events().publish(
+            (symbol_short!("pause"), symbol_short!("set")),
+            (capabilities, admin),
+        );
+        Ok(())
+    }
+
+    /// Get the mask of paused capabilities
+    pub fn get_paused(env: Env) -> u32 {
+        paused(&env)
+    }
+
+    /// Put one issuer's mints and revocations on hold, leaving everyone
+    /// else's untouched (admin only). With cascading on, its consents are
+    /// queued for suspension too.
+    pub fn pause_issuer(env: Env, issuer: Address) -> Result<(), ContractError> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        let key = PauseKey::IssuerPaused(issuer.clone());
+        env.storage().persistent().set(&key, &true);
+        ttl::bump_entry(&env, &key);
+        suspension::start_cascade(&env, &issuer);
+
+        env.events().publish(
+            (symbol_short!("issuer"), symbol_short!("paused")),
+            (issuer, admin),
+        );
+        Ok(())
+    }
+
+    /// Lift an issuer's hold (admin only)
+    pub fn unpause_issuer(env: Env, issuer: Address) -> Result<(), ContractError> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        env.storage()
+            .persistent()
+            .remove(&PauseKey::IssuerPaused(issuer.clone()));
+        suspension::cancel_cascade(&env, &issuer);
+
+        env.events().publish(
+            (symbol_short!("issuer"), symbol_short!("unpaused")),
+            (issuer, admin),
+        );
+        Ok(())
+    }
+
+    /// Check whether an issuer's operations are on hold
+    pub fn is_issuer_paused(env: Env, issuer: Address) -> bool {
+        is_issuer_paused(&env, &issuer)
+    }
+}
@@ -0,0 +1,144 @@
+use super::*;
+
+// Storage keys for posthumous data release
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PosthumousKey {
+    DeathOracle,            // Address trusted to attest deaths
+    Executor(u64),          // Executor designated for a posthumous consent
+    DeathAttested(Address), // When a patient's death was attested
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PosthumousStatus {
+    NotPosthumous, // Regular consent
+    Dormant,       // Waiting for an attested death
+    Released,      // Death attested, the executor has access
+}
+
+fn executor(env: &Env, token_id: u64) -> Option<Address> {
+    let key = PosthumousKey::Executor(token_id);
+    ttl::bump_entry(env, &key);
+    env.storage().persistent().get(&key)
+}
+
+fn death_attested(env: &Env, patient: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&PosthumousKey::DeathAttested(patient.clone()))
+}
+
+fn status(env: &Env, token_id: u64, patient: &Address) -> PosthumousStatus {
+    if executor(env, token_id).is_none() {
+        PosthumousStatus::NotPosthumous
+    } else if death_attested(env, patient) {
+        PosthumousStatus::Released
+    } else {
+        PosthumousStatus::Dormant
+    }
+}
+
+/// Posthumous consents can't be used until the patient's death is attested
+pub(crate) fn ensure_not_dormant(
+    env: &Env,
+    token_id: u64,
+    patient: &Address,
+) -> Result<(), ContractError> {
+    if status(env, token_id, patient) == PosthumousStatus::Dormant {
+        return Err(ContractError::ConsentDormant);
+    }
+    Ok(())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set the oracle trusted to attest deaths (admin only)
+    pub fn set_death_oracle(env: Env, oracle: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&PosthumousKey::DeathOracle, &oracle);
+        Ok(())
+    }
+
+    /// Turn a consent into a posthumous release: it stays dormant until the
+    /// patient's death is attested, then `executor` gains access (patient only)
+    pub fn designate_executor(
+        env: Env,
+        token_id: u64,
+        executor: Address,
+    ) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.patient.require_auth();
+        ensure_active(&env, token_id, &metadata)?;
+
+        let key = PosthumousKey::Executor(token_id);
+        env.storage().persistent().set(&key, &executor);
+        ttl::bump_entry(&env, &key);
+        append_history(
+            &env,
+            token_id,
+            "executor_designated",
+            &metadata.patient,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("dormant")),
+            token_id,
+            (token_id, executor),
+        );
+        Ok(())
+    }
+
+    /// Record an oracle-attested death, releasing the patient's posthumous consents
+    pub fn attest_death(env: Env, patient: Address) -> Result<(), ContractError> {
+        let oracle: Address = env
+            .storage()
+            .instance()
+            .get(&PosthumousKey::DeathOracle)
+            .ok_or(ContractError::NotAuthorized)?;
+        oracle.require_auth();
+
+        let key = PosthumousKey::DeathAttested(patient.clone());
+        if env.storage().persistent().has(&key) {
+            return Ok(());
+        }
+        env.storage().persistent().set(&key, &clock::now(&env));
+        ttl::bump_entry(&env, &key);
+
+        env.events().publish(
+            (symbol_short!("consent"), symbol_short!("released")),
+            patient,
+        );
+        Ok(())
+    }
+
+    /// Get where a consent is in the posthumous release lifecycle
+    pub fn get_posthumous_status(
+        env: Env,
+        token_id: u64,
+    ) -> Result<PosthumousStatus, ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        Ok(status(&env, token_id, &metadata.patient))
+    }
+
+    /// Scopes `executor` may access under a released posthumous consent (0 = none)
+    pub fn executor_scopes(env: Env, token_id: u64, executor: Address) -> u32 {
+        let Ok(metadata) = read_metadata(&env, token_id) else {
+            return 0;
+        };
+        if self::executor(&env, token_id) != Some(executor)
+            || ensure_active(&env, token_id, &metadata).is_err()
+            || status(&env, token_id, &metadata.patient) != PosthumousStatus::Released
+        {
+            return 0;
+        }
+        ttl::bump_token(&env, token_id);
+        token_scopes(&env, token_id)
+    }
+}
@@ -0,0 +1,80 @@
+use super::*;
+
+// Storage keys for patient privacy preferences
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PrefsKey {
+    Preferences(Address),
+}
+
+// Defaults a patient sets once instead of per token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatientPrefs {
+    pub blocked_consent_types: Vec<String>, // Consent types never to be issued (e.g. "research")
+    pub max_grant_duration: u64, // Consents auto-expire after this many seconds (0 = no limit)
+}
+
+fn read_preferences(env: &Env, owner: &Address) -> Option<PatientPrefs> {
+    let key = PrefsKey::Preferences(owner.clone());
+    let prefs = env.storage().persistent().get(&key);
+    ttl::bump_entry(env, &key);
+    prefs
+}
+
+/// Apply a patient's preferences to a mint, returning the effective expiry.
+/// Blocked types are rejected; expiries beyond the patient's maximum are shortened.
+pub(crate) fn apply_to_mint(
+    env: &Env,
+    patient: &Address,
+    consent_type: &String,
+    expiry_timestamp: u64,
+) -> Result<u64, ContractError> {
+    let Some(prefs) = read_preferences(env, patient) else {
+        return Ok(expiry_timestamp);
+    };
+    if prefs.blocked_consent_types.contains(consent_type) {
+        return Err(ContractError::PolicyViolation);
+    }
+    if prefs.max_grant_duration == 0 {
+        return Ok(expiry_timestamp);
+    }
+    let latest = clock::now(env).saturating_add(prefs.max_grant_duration);
+    if expiry_timestamp == 0 || expiry_timestamp > latest {
+        Ok(latest)
+    } else {
+        Ok(expiry_timestamp)
+    }
+}
+
+/// Apply the patient's preferences to an access grant on one of their
+/// consents, the same way as to a mint: grants on a blocked consent type are
+/// rejected, and the grant's expiry is shortened to the patient's maximum.
+/// Preferences set after the consent was minted bind its grants too.
+pub(crate) fn apply_to_grant(
+    env: &Env,
+    metadata: &ConsentMetadata,
+    expiry: u64,
+) -> Result<u64, ContractError> {
+    apply_to_mint(env, &metadata.patient, &metadata.consent_type, expiry)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set default privacy preferences applied to every consent minted for `owner`
+    pub fn set_preferences(env: Env, owner: Address, prefs: PatientPrefs) {
+        owner.require_auth();
+        let key = PrefsKey::Preferences(owner.clone());
+        env.storage().persistent().set(&key, &prefs);
+        ttl::bump_entry(&env, &key);
+
+        env.events()
+            .publish((symbol_short!("prefs"), symbol_short!("set")), owner);
+    }
+
+    /// Get a patient's privacy preferences, if any
+    pub fn get_preferences(env: Env, owner: Address) -> Option<PatientPrefs> {
+        read_preferences(&env, &owner)
+    }
+}
@@ -0,0 +1,142 @@
+use super::*;
+use soroban_sdk::{xdr::ToXdr, Bytes, BytesN};
+
+// Storage keys for pseudonymous (hash-bound) consents
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PseudonymKey {
+    PatientCommitment(u64), // sha256(salt || patient identifier) of an unclaimed token
+    ClaimCommitment(u64, Address), // Token + claimant -> (sha256(preimage || claimant XDR), ledger)
+}
+
+/// The hash a claimant commits to before revealing the preimage, binding
+/// the claim to their address
+fn claim_hash(env: &Env, preimage: &Bytes, claimant: &Address) -> BytesN<32> {
+    let mut bound = preimage.clone();
+    bound.append(&claimant.clone().to_xdr(env));
+    env.crypto().sha256(&bound)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Mint a consent bound to a salted hash of a patient identifier (e.g. a paper
+    /// consent signed before the patient has a wallet). The contract holds the
+    /// token until the patient claims it with the preimage.
+    pub fn mint_pseudonymous(
+        env: Env,
+        issuer: Address,
+        patient_commitment: BytesN<32>,
+        metadata_uri: String,
+        consent_type: String,
+        expiry_timestamp: u64,
+    ) -> Result<u64, ContractError> {
+        issuer.require_auth();
+        if !Self::is_issuer(env.clone(), issuer.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        let custodian = env.current_contract_address();
+        let token_id = mint_token(
+            &env,
+            &issuer,
+            &custodian,
+            &metadata_uri,
+            &consent_type,
+            expiry_timestamp,
+        )?;
+        let key = PseudonymKey::PatientCommitment(token_id);
+        env.storage().persistent().set(&key, &patient_commitment);
+        ttl::bump_entry(&env, &key);
+        Ok(token_id)
+    }
+
+    /// First step of a claim: commit to `sha256(preimage || claimant XDR)`
+    /// without revealing the preimage (claimant authorizes). The claim itself
+    /// can only be made in a later ledger, so whoever sees the preimage once
+    /// it is revealed can't claim the token for themselves.
+    pub fn commit_claim(
+        env: Env,
+        token_id: u64,
+        claimant: Address,
+        claim_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        claimant.require_auth();
+        if !Self::is_claimable(env.clone(), token_id) {
+            return Err(ContractError::NotClaimable);
+        }
+        let key = PseudonymKey::ClaimCommitment(token_id, claimant);
+        env.storage()
+            .persistent()
+            .set(&key, &(claim_hash, env.ledger().sequence()));
+        ttl::bump_entry(&env, &key);
+        Ok(())
+    }
+
+    /// Bind a pseudonymous consent to the patient's address. `preimage` must
+    /// hash to the commitment the token was minted with, and `new_owner` must
+    /// have committed to it with `commit_claim` in an earlier ledger.
+    pub fn claim(
+        env: Env,
+        token_id: u64,
+        preimage: Bytes,
+        new_owner: Address,
+    ) -> Result<(), ContractError> {
+        new_owner.require_auth();
+
+        let key = PseudonymKey::PatientCommitment(token_id);
+        let commitment: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ContractError::NotClaimable)?;
+        if env.crypto().sha256(&preimage) != commitment {
+            return Err(ContractError::CommitmentMismatch);
+        }
+        let claim_key = PseudonymKey::ClaimCommitment(token_id, new_owner.clone());
+        let (hash, ledger): (BytesN<32>, u32) = env
+            .storage()
+            .persistent()
+            .get(&claim_key)
+            .ok_or(ContractError::CommitmentMismatch)?;
+        if hash != claim_hash(&env, &preimage, &new_owner) {
+            return Err(ContractError::CommitmentMismatch);
+        }
+        if ledger >= env.ledger().sequence() {
+            return Err(ContractError::PolicyViolation);
+        }
+
+        let metadata = read_metadata(&env, token_id)?;
+        ensure_active(&env, token_id, &metadata)?;
+        limits::check_mint(&env, &metadata.issuer, &new_owner)?;
+
+        let custodian = env.current_contract_address();
+        env.storage().persistent().remove(&key);
+        env.storage().persistent().remove(&claim_key);
+        let metadata = rebind_patient(&env, token_id, &custodian, &new_owner)?;
+        move_token(&env, &custodian, &new_owner, token_id, &metadata);
+
+        append_history(
+            &env,
+            token_id,
+            "claimed",
+            &new_owner,
+            &metadata.metadata_uri,
+        );
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("claimed")),
+            token_id,
+            (token_id, new_owner),
+        );
+        Ok(())
+    }
+
+    /// Check whether a token is still waiting to be claimed
+    pub fn is_claimable(env: Env, token_id: u64) -> bool {
+        env.storage()
+            .persistent()
+            .has(&PseudonymKey::PatientCommitment(token_id))
+    }
+}
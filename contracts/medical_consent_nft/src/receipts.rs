@@ -0,0 +1,76 @@
+use super::*;
+use soroban_sdk::{xdr::ToXdr, BytesN};
+
+// Storage keys for verification receipts
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReceiptKey {
+    ReceiptCounter,
+    Receipt(u64),
+}
+
+// Proof that a verifier checked a consent before using it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationReceipt {
+    pub verifier: Address,
+    pub token_id: u64,
+    pub ledger: u32,             // Ledger sequence the check ran in
+    pub result_hash: BytesN<32>, // sha256 of the `ValidityReport` XDR returned
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Check a consent like `validity` and keep a receipt of the check, so a
+    /// provider can later prove it validated the consent before accessing
+    /// data (registered verifier only). The receipt is kept whatever the
+    /// outcome. Returns the receipt's ID and the report it commits to.
+    pub fn verify_and_receipt(
+        env: Env,
+        verifier: Address,
+        token_id: u64,
+    ) -> Result<(u64, ValidityReport), ContractError> {
+        verifier.require_auth();
+        if !access_log::verifiers(&env).contains(&verifier) {
+            return Err(ContractError::NotAuthorized);
+        }
+        let report = Self::validity(env.clone(), token_id)?;
+
+        let receipt_id: u64 = env
+            .storage()
+            .instance()
+            .get(&ReceiptKey::ReceiptCounter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&ReceiptKey::ReceiptCounter, &(receipt_id + 1));
+
+        let result_hash = env.crypto().sha256(&report.clone().to_xdr(&env));
+        let receipt = VerificationReceipt {
+            verifier: verifier.clone(),
+            token_id,
+            ledger: env.ledger().sequence(),
+            result_hash: result_hash.clone(),
+        };
+        let key = ReceiptKey::Receipt(receipt_id);
+        env.storage().persistent().set(&key, &receipt);
+        ttl::bump_entry(&env, &key);
+        anchoring::anchor(&env, symbol_short!("receipt"), token_id, receipt);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("verify"), symbol_short!("receipt")),
+            token_id,
+            (token_id, receipt_id, verifier, result_hash),
+        );
+        Ok((receipt_id, report))
+    }
+
+    /// Get a verification receipt by ID
+    pub fn get_receipt(env: Env, receipt_id: u64) -> Option<VerificationReceipt> {
+        env.storage()
+            .persistent()
+            .get(&ReceiptKey::Receipt(receipt_id))
+    }
+}
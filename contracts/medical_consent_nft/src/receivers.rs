@@ -0,0 +1,108 @@
+use super::*;
+use soroban_sdk::contractclient;
+
+// Storage keys for transfer hooks on receiving contracts
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReceiverKey {
+    HookRequired,      // Whether contracts that haven't registered a hook are refused
+    Receiver(Address), // Contract that implements the hook
+}
+
+/// The hook a contract implements to accept or refuse consent tokens
+#[allow(dead_code)] // Only used through the generated client
+#[contractclient(name = "ConsentReceiverClient")]
+pub trait ConsentReceiverInterface {
+    fn on_consent_received(env: Env, token_id: u64, from: Address) -> bool;
+}
+
+fn hook_required(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&ReceiverKey::HookRequired)
+        .unwrap_or(false)
+}
+
+fn is_receiver(env: &Env, address: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&ReceiverKey::Receiver(address.clone()))
+}
+
+/// Contract addresses are the strkeys starting with `C`
+fn is_contract(address: &Address) -> bool {
+    let mut strkey = [0u8; 56];
+    address.to_string().copy_into_slice(&mut strkey);
+    strkey[0] == b'C'
+}
+
+/// Ask a registered receiving contract whether it accepts the token; a
+/// refusal or a failing hook blocks the transfer. Contracts that haven't
+/// registered are only refused while hooks are required. Accounts are never
+/// asked.
+pub(crate) fn check_receiver(
+    env: &Env,
+    token_id: u64,
+    from: &Address,
+    to: &Address,
+) -> Result<(), ContractError> {
+    let accepted = if is_receiver(env, to) {
+        matches!(
+            ConsentReceiverClient::new(env, to).try_on_consent_received(&token_id, from),
+            Ok(Ok(true))
+        )
+    } else {
+        !(hook_required(env) && is_contract(to))
+    };
+    if accepted {
+        return Ok(());
+    }
+    events::publish_token(
+        env,
+        (symbol_short!("transfer"), symbol_short!("refused")),
+        token_id,
+        (token_id, from.clone(), to.clone()),
+    );
+    Err(ContractError::RecipientNotAllowed)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Register a contract's `on_consent_received` hook, which is then asked
+    /// before every transfer to it (the contract authorizes)
+    pub fn register_consent_receiver(env: Env, receiver: Address) {
+        receiver.require_auth();
+        let key = ReceiverKey::Receiver(receiver.clone());
+        env.storage().persistent().set(&key, &true);
+        ttl::bump_entry(&env, &key);
+    }
+
+    /// Stop asking a contract before transfers to it (the contract authorizes)
+    pub fn unregister_consent_receiver(env: Env, receiver: Address) {
+        receiver.require_auth();
+        env.storage()
+            .persistent()
+            .remove(&ReceiverKey::Receiver(receiver));
+    }
+
+    /// Check whether a contract has registered an `on_consent_received` hook
+    pub fn is_consent_receiver(env: Env, address: Address) -> bool {
+        is_receiver(&env, &address)
+    }
+
+    /// Refuse transfers to contracts that haven't registered a hook, so
+    /// tokens can't be trapped in contracts unable to handle them (admin only)
+    pub fn set_receiver_hook_required(env: Env, required: bool) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&ReceiverKey::HookRequired, &required);
+        Ok(())
+    }
+
+    /// Check whether contracts must register a hook to receive tokens
+    pub fn is_receiver_hook_required(env: Env) -> bool {
+        hook_required(&env)
+    }
+}
@@ -0,0 +1,110 @@
+use super::*;
+use crate::institutions::{read_institution, token_institution};
+
+// Storage keys for cross-institution recognition agreements
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RecognitionKey {
+    Recognition(u64, u64), // Scopes agreed between two institutions (stored both ways)
+}
+
+fn agreed_scopes(env: &Env, a: u64, b: u64) -> u32 {
+    let key = RecognitionKey::Recognition(a, b);
+    let scopes = env.storage().persistent().get(&key).unwrap_or(0);
+    ttl::bump_entry(env, &key);
+    scopes
+}
+
+fn write_agreement(env: &Env, a: u64, b: u64, scopes: Option<u32>) {
+    for key in [
+        RecognitionKey::Recognition(a, b),
+        RecognitionKey::Recognition(b, a),
+    ] {
+        match scopes {
+            Some(scopes) => {
+                env.storage().persistent().set(&key, &scopes);
+                ttl::bump_entry(env, &key);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Agree that consents issued under either institution are accepted by
+    /// verifiers of the other for `scopes`. Both institution-admins must sign.
+    pub fn create_recognition(
+        env: Env,
+        inst_a: u64,
+        inst_b: u64,
+        scopes: u32,
+    ) -> Result<(), ContractError> {
+        if inst_a == inst_b || scopes == 0 {
+            return Err(ContractError::PolicyViolation);
+        }
+        read_institution(&env, inst_a)?.admin.require_auth();
+        read_institution(&env, inst_b)?.admin.require_auth();
+
+        write_agreement(&env, inst_a, inst_b, Some(scopes));
+
+        env.events().publish(
+            (symbol_short!("inst"), symbol_short!("recog")),
+            (inst_a, inst_b, scopes),
+        );
+        Ok(())
+    }
+
+    /// End a recognition agreement (either institution-admin)
+    pub fn remove_recognition(
+        env: Env,
+        caller: Address,
+        inst_a: u64,
+        inst_b: u64,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        if caller != read_institution(&env, inst_a)?.admin
+            && caller != read_institution(&env, inst_b)?.admin
+        {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        write_agreement(&env, inst_a, inst_b, None);
+
+        env.events().publish(
+            (symbol_short!("inst"), symbol_short!("unrecog")),
+            (inst_a, inst_b),
+        );
+        Ok(())
+    }
+
+    /// Get the scopes two institutions recognize each other's consents for
+    pub fn get_recognition(env: Env, inst_a: u64, inst_b: u64) -> u32 {
+        agreed_scopes(&env, inst_a, inst_b)
+    }
+
+    /// Scopes of a consent that verifiers of `verifier_institution` accept
+    /// (0 if the consent is inactive or not covered by an agreement)
+    pub fn get_recognized_scopes(env: Env, token_id: u64, verifier_institution: u64) -> u32 {
+        let Ok(metadata) = read_metadata(&env, token_id) else {
+            return 0;
+        };
+        if ensure_active(&env, token_id, &metadata).is_err() {
+            return 0;
+        }
+        let Some(issuing_institution) = token_institution(&env, token_id) else {
+            return 0;
+        };
+        let scopes = token_scopes(&env, token_id);
+        if issuing_institution == verifier_institution {
+            return scopes;
+        }
+        scopes & agreed_scopes(&env, issuing_institution, verifier_institution)
+    }
+
+    /// Check whether verifiers of `verifier_institution` accept a consent for any scope
+    pub fn is_recognized(env: Env, token_id: u64, verifier_institution: u64) -> bool {
+        Self::get_recognized_scopes(env, token_id, verifier_institution) != 0
+    }
+}
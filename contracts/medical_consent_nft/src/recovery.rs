@@ -0,0 +1,273 @@
+use super::*;
+
+/// Most contacts a recovery circle can have
+pub const MAX_RECOVERY_CONTACTS: u32 = 10;
+
+// Storage keys for social recovery
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RecoveryKey {
+    Circle(Address),  // Owner -> recovery circle
+    Pending(Address), // Owner -> recovery in progress
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecoveryCircle {
+    pub contacts: Vec<Address>,
+    pub threshold: u32, // Approvals needed to rotate the owner
+    pub window: u64,    // Seconds to collect approvals, during which the owner can cancel
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecoveryRequest {
+    pub new_owner: Address,
+    pub approvals: Vec<Address>,
+    pub started: u64,
+}
+
+fn read_circle(env: &Env, owner: &Address) -> Result<RecoveryCircle, ContractError> {
+    let key = RecoveryKey::Circle(owner.clone());
+    ttl::bump_entry(env, &key);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::RequestNotFound)
+}
+
+fn write_circle(env: &Env, owner: &Address, circle: &RecoveryCircle) {
+    let key = RecoveryKey::Circle(owner.clone());
+    env.storage().persistent().set(&key, circle);
+    ttl::bump_entry(env, &key);
+}
+
+fn read_request(env: &Env, owner: &Address) -> Result<RecoveryRequest, ContractError> {
+    let key = RecoveryKey::Pending(owner.clone());
+    ttl::bump_entry(env, &key);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::RequestNotFound)
+}
+
+fn write_request(env: &Env, owner: &Address, request: &RecoveryRequest) {
+    let key = RecoveryKey::Pending(owner.clone());
+    env.storage().persistent().set(&key, request);
+    ttl::bump_entry(env, &key);
+}
+
+/// Move every consent issued for, and every token held by, `old` to `new`
+/// through the same paths as claims and transfers, so every index follows
+fn rotate_owner(env: &Env, old: &Address, new: &Address) -> u32 {
+    let consents: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::PatientConsents(old.clone()))
+        .unwrap_or(Vec::new(env));
+    let owned: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::OwnerTokens(old.clone()))
+        .unwrap_or(Vec::new(env));
+
+    for token_id in consents.iter() {
+        let _ = rebind_patient(env, token_id, old, new);
+    }
+    for token_id in owned.iter() {
+        if let Ok(metadata) = read_metadata(env, token_id) {
+            move_token(env, old, new, token_id, &metadata);
+        }
+    }
+    joint::rotate_owner(env, old, new, &owned);
+    env.storage()
+        .persistent()
+        .remove(&DataKey::OwnerTokens(old.clone()));
+    env.storage()
+        .persistent()
+        .remove(&DataKey::PatientConsents(old.clone()));
+
+    let mut touched = owned;
+    for token_id in consents.iter() {
+        if !touched.contains(token_id) {
+            touched.push_back(token_id);
+        }
+    }
+    for token_id in touched.iter() {
+        if let Ok(metadata) = read_metadata(env, token_id) {
+            append_history(env, token_id, "recovered", new, &metadata.metadata_uri);
+        }
+        archive::commit_token(env, token_id);
+        ttl::bump_token(env, token_id);
+    }
+    touched.len()
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Nominate the contacts who can recover the owner's tokens if their key is lost
+    pub fn set_recovery_circle(
+        env: Env,
+        owner: Address,
+        contacts: Vec<Address>,
+        threshold: u32,
+        window: u64,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+        if contacts.len() > MAX_RECOVERY_CONTACTS {
+            return Err(ContractError::QuotaExceeded);
+        }
+        if threshold == 0 || threshold > contacts.len() || window == 0 || contacts.contains(&owner)
+        {
+            return Err(ContractError::PolicyViolation);
+        }
+        // A new circle invalidates approvals gathered under the old one
+        env.storage()
+            .persistent()
+            .remove(&RecoveryKey::Pending(owner.clone()));
+        write_circle(
+            &env,
+            &owner,
+            &RecoveryCircle {
+                contacts,
+                threshold,
+                window,
+            },
+        );
+
+        env.events().publish(
+            (symbol_short!("recovery"), symbol_short!("circle")),
+            (owner, threshold),
+        );
+        Ok(())
+    }
+
+    /// Start recovering an owner's tokens to a new address (contact only). The
+    /// starting contact's approval counts towards the threshold.
+    pub fn initiate_recovery(
+        env: Env,
+        contact: Address,
+        owner: Address,
+        new_owner: Address,
+    ) -> Result<(), ContractError> {
+        contact.require_auth();
+        let circle = read_circle(&env, &owner)?;
+        if !circle.contacts.contains(&contact) {
+            return Err(ContractError::NotAuthorized);
+        }
+        // The circle moves to the new address, so it mustn't have one already
+        if read_request(&env, &owner).is_ok()
+            || new_owner == owner
+            || read_circle(&env, &new_owner).is_ok()
+        {
+            return Err(ContractError::PolicyViolation);
+        }
+
+        let mut approvals = Vec::new(&env);
+        approvals.push_back(contact.clone());
+        write_request(
+            &env,
+            &owner,
+            &RecoveryRequest {
+                new_owner: new_owner.clone(),
+                approvals,
+                started: clock::now(&env),
+            },
+        );
+
+        env.events().publish(
+            (symbol_short!("recovery"), symbol_short!("started")),
+            (owner, new_owner, contact),
+        );
+        Ok(())
+    }
+
+    /// Approve a recovery in progress (contact only, within the window)
+    pub fn approve_recovery(
+        env: Env,
+        contact: Address,
+        owner: Address,
+    ) -> Result<(), ContractError> {
+        contact.require_auth();
+        let circle = read_circle(&env, &owner)?;
+        let mut request = read_request(&env, &owner)?;
+        if !circle.contacts.contains(&contact) {
+            return Err(ContractError::NotAuthorized);
+        }
+        if clock::now(&env) > request.started + circle.window {
+            return Err(ContractError::RequestExpired);
+        }
+        if !request.approvals.contains(&contact) {
+            request.approvals.push_back(contact.clone());
+            write_request(&env, &owner, &request);
+        }
+
+        env.events().publish(
+            (symbol_short!("recovery"), symbol_short!("approved")),
+            (owner, contact),
+        );
+        Ok(())
+    }
+
+    /// Cancel a recovery in progress (original owner only)
+    pub fn cancel_recovery(env: Env, owner: Address) -> Result<(), ContractError> {
+        owner.require_auth();
+        read_request(&env, &owner)?;
+        env.storage()
+            .persistent()
+            .remove(&RecoveryKey::Pending(owner.clone()));
+
+        env.events().publish(
+            (symbol_short!("recovery"), symbol_short!("cancelled")),
+            owner,
+        );
+        Ok(())
+    }
+
+    /// Rotate the owner once the window has passed with enough approvals.
+    /// Every token held by the owner and every consent issued for them moves
+    /// to the new address, along with the recovery circle; fails with
+    /// `PolicyViolation` if the new address already has a circle. Returns
+    /// the number of tokens moved.
+    pub fn execute_recovery(env: Env, owner: Address) -> Result<u32, ContractError> {
+        let circle = read_circle(&env, &owner)?;
+        let request = read_request(&env, &owner)?;
+        // Approvals that arrived after the window closed were already rejected
+        if request.approvals.len() < circle.threshold {
+            return Err(ContractError::PolicyViolation);
+        }
+        if clock::now(&env) <= request.started + circle.window {
+            return Err(ContractError::NotYetActive);
+        }
+        // Refuse rather than overwrite a circle the new owner set up since
+        if read_circle(&env, &request.new_owner).is_ok() {
+            return Err(ContractError::PolicyViolation);
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&RecoveryKey::Pending(owner.clone()));
+        env.storage()
+            .persistent()
+            .remove(&RecoveryKey::Circle(owner.clone()));
+        write_circle(&env, &request.new_owner, &circle);
+        let moved = rotate_owner(&env, &owner, &request.new_owner);
+
+        env.events().publish(
+            (symbol_short!("recovery"), symbol_short!("executed")),
+            (owner, request.new_owner, moved),
+        );
+        Ok(moved)
+    }
+
+    /// Get an owner's recovery circle
+    pub fn get_recovery_circle(env: Env, owner: Address) -> Option<RecoveryCircle> {
+        read_circle(&env, &owner).ok()
+    }
+
+    /// Get the recovery in progress for an owner
+    pub fn get_recovery_request(env: Env, owner: Address) -> Option<RecoveryRequest> {
+        read_request(&env, &owner).ok()
+    }
+}
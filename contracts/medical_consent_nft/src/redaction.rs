@@ -0,0 +1,59 @@
+use super::*;
+
+// Metadata as seen by a particular caller
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedactedMetadata {
+    pub metadata: ConsentMetadata, // With sensitive fields blanked if `redacted`
+    pub redacted: bool,
+}
+
+/// Callers that may see a token's issuer and storage pointers: the admin,
+/// auditors, the token's issuer and patient, and its current holder
+fn can_see_sensitive(
+    env: &Env,
+    token_id: u64,
+    metadata: &ConsentMetadata,
+    caller: &Address,
+) -> bool {
+    if *caller == metadata.issuer || *caller == metadata.patient {
+        return true;
+    }
+    let owner: Option<Address> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TokenOwner(token_id));
+    owner.as_ref() == Some(caller)
+        || read_admin(env).is_ok_and(|admin| admin == *caller)
+        || PatientConsentToken::is_auditor(env.clone(), caller.clone())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Get consent metadata with the issuer and metadata URI blanked unless `caller`
+    /// may see them, so public verifiers can check type and expiry without
+    /// learning storage pointers. The issuer is replaced by this contract's address.
+    pub fn get_metadata_redacted(
+        env: Env,
+        token_id: u64,
+        caller: Address,
+    ) -> Result<RedactedMetadata, ContractError> {
+        caller.require_auth();
+        attribution::record_read(&env, &caller)?;
+        let mut metadata = read_metadata(&env, token_id)?;
+        ttl::bump_token(&env, token_id);
+
+        if can_see_sensitive(&env, token_id, &metadata, &caller) {
+            return Ok(RedactedMetadata {
+                metadata,
+                redacted: false,
+            });
+        }
+        metadata.metadata_uri = String::from_str(&env, "");
+        metadata.issuer = env.current_contract_address();
+        Ok(RedactedMetadata {
+            metadata,
+            redacted: true,
+        })
+    }
+}
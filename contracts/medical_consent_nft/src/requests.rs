@@ -0,0 +1,193 @@
+use super::*;
+
+// Requests older than this can no longer be approved (seconds)
+pub(crate) const DEFAULT_REQUEST_WINDOW: u64 = 7 * 24 * 60 * 60;
+
+// Storage keys for patient-initiated consent requests
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RequestKey {
+    RequestCounter,
+    RequestWindow,
+    Request(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RequestStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Expired,
+}
+
+// A consent requested by a patient, awaiting the issuer's countersignature
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsentRequest {
+    pub patient: Address,
+    pub issuer: Address,
+    pub metadata_uri: String,
+    pub consent_type: String,
+    pub expiry_timestamp: u64,
+    pub requested_timestamp: u64,
+    pub status: RequestStatus,
+    pub token_id: Option<u64>, // Set once approved
+}
+
+pub(crate) fn request_window(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&RequestKey::RequestWindow)
+        .unwrap_or(DEFAULT_REQUEST_WINDOW)
+}
+
+pub(crate) fn write_request_window(env: &Env, window: u64) -> Result<(), ContractError> {
+    if window == 0 {
+        return Err(ContractError::PolicyViolation);
+    }
+    env.storage()
+        .instance()
+        .set(&RequestKey::RequestWindow, &window);
+    Ok(())
+}
+
+/// Read a request, reporting pending requests past the approval window as expired
+fn read_request(env: &Env, request_id: u64) -> Result<ConsentRequest, ContractError> {
+    let mut request: ConsentRequest = env
+        .storage()
+        .persistent()
+        .get(&RequestKey::Request(request_id))
+        .ok_or(ContractError::RequestNotFound)?;
+    if request.status == RequestStatus::Pending
+        && clock::now(env) > request.requested_timestamp + request_window(env)
+    {
+        request.status = RequestStatus::Expired;
+    }
+    Ok(request)
+}
+
+fn write_request(env: &Env, request_id: u64, request: &ConsentRequest) {
+    let key = RequestKey::Request(request_id);
+    env.storage().persistent().set(&key, request);
+    ttl::bump_entry(env, &key);
+}
+
+/// Load a request that can still be acted on by its issuer
+fn read_pending(env: &Env, request_id: u64) -> Result<ConsentRequest, ContractError> {
+    let request = read_request(env, request_id)?;
+    request.issuer.require_auth();
+    match request.status {
+        RequestStatus::Pending => Ok(request),
+        RequestStatus::Expired => Err(ContractError::RequestExpired),
+        _ => Err(ContractError::RequestNotPending),
+    }
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Patient-initiated consent: the patient asks an issuer to countersign a consent
+    pub fn request_consent(
+        env: Env,
+        patient: Address,
+        issuer: Address,
+        metadata_uri: String,
+        consent_type: String,
+        expiry_timestamp: u64,
+    ) -> Result<u64, ContractError> {
+        patient.require_auth();
+        if !Self::is_issuer(env.clone(), issuer.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+        validation::validate_uri(&metadata_uri)?;
+        validation::validate_expiry(&env, expiry_timestamp)?;
+        validation::validate_consent_type(&env, &consent_type)?;
+
+        let request_id: u64 = env
+            .storage()
+            .instance()
+            .get(&RequestKey::RequestCounter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&RequestKey::RequestCounter, &(request_id + 1));
+
+        let request = ConsentRequest {
+            patient: patient.clone(),
+            issuer: issuer.clone(),
+            metadata_uri,
+            consent_type,
+            expiry_timestamp,
+            requested_timestamp: clock::now(&env),
+            status: RequestStatus::Pending,
+            token_id: None,
+        };
+        write_request(&env, request_id, &request);
+
+        env.events().publish(
+            (symbol_short!("request"), symbol_short!("created")),
+            (request_id, patient, issuer),
+        );
+        Ok(request_id)
+    }
+
+    /// Countersign a pending request, minting the consent (issuer only)
+    pub fn approve_request(env: Env, request_id: u64) -> Result<u64, ContractError> {
+        let mut request = read_pending(&env, request_id)?;
+        // Issuer may have been removed since the request was made
+        if !Self::is_issuer(env.clone(), request.issuer.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        let token_id = mint_token(
+            &env,
+            &request.issuer,
+            &request.patient,
+            &request.metadata_uri,
+            &request.consent_type,
+            request.expiry_timestamp,
+        )?;
+
+        request.status = RequestStatus::Approved;
+        request.token_id = Some(token_id);
+        write_request(&env, request_id, &request);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("request"), symbol_short!("approved")),
+            token_id,
+            (request_id, token_id),
+        );
+        Ok(token_id)
+    }
+
+    /// Decline a pending request (issuer only)
+    pub fn reject_request(env: Env, request_id: u64) -> Result<(), ContractError> {
+        let mut request = read_pending(&env, request_id)?;
+        request.status = RequestStatus::Rejected;
+        write_request(&env, request_id, &request);
+
+        env.events().publish(
+            (symbol_short!("request"), symbol_short!("rejected")),
+            (request_id, request.issuer),
+        );
+        Ok(())
+    }
+
+    /// Get a consent request and its current state
+    pub fn get_request(env: Env, request_id: u64) -> Result<ConsentRequest, ContractError> {
+        read_request(&env, request_id)
+    }
+
+    /// Set how long a request stays approvable, in seconds (admin only)
+    pub fn set_request_window(env: Env, window: u64) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        write_request_window(&env, window)
+    }
+
+    /// Get how long a request stays approvable, in seconds
+    pub fn get_request_window(env: Env) -> u64 {
+        request_window(&env)
+    }
+}
@@ -0,0 +1,82 @@
+use super::*;
+use soroban_sdk::Symbol;
+
+/// Most regions a consent can be restricted to
+pub const MAX_REGIONS: u32 = 20;
+
+// Storage keys for data residency
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ResidencyKey {
+    Regions(u64), // Regions a consent's data may be accessed from (unset = anywhere)
+}
+
+fn regions(env: &Env, token_id: u64) -> Vec<Symbol> {
+    let key = ResidencyKey::Regions(token_id);
+    ttl::bump_entry(env, &key);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Restrict where a consent's data may be accessed from, as region codes
+    /// such as `KE` or `EU` (patient only). An empty list lifts the restriction.
+    pub fn set_residency(
+        env: Env,
+        token_id: u64,
+        regions: Vec<Symbol>,
+    ) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.patient.require_auth();
+        ensure_active(&env, token_id, &metadata)?;
+        if regions.len() > MAX_REGIONS {
+            return Err(ContractError::QuotaExceeded);
+        }
+
+        let key = ResidencyKey::Regions(token_id);
+        if regions.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &regions);
+            ttl::bump_entry(&env, &key);
+        }
+        append_history(
+            &env,
+            token_id,
+            "residency_set",
+            &metadata.patient,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("residency")),
+            token_id,
+            (token_id, regions),
+        );
+        Ok(())
+    }
+
+    /// Get the regions a consent is restricted to (empty = no restriction)
+    pub fn get_residency(env: Env, token_id: u64) -> Vec<Symbol> {
+        regions(&env, token_id)
+    }
+
+    /// Check whether a valid consent allows access from `region`, so verifiers
+    /// can reject cross-border requests
+    pub fn is_access_allowed_in(env: Env, token_id: u64, region: Symbol) -> bool {
+        let Ok(metadata) = read_metadata(&env, token_id) else {
+            return false;
+        };
+        if ensure_active(&env, token_id, &metadata).is_err() {
+            return false;
+        }
+        let allowed = regions(&env, token_id);
+        allowed.is_empty() || allowed.contains(&region)
+    }
+}
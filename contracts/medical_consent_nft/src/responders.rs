@@ -0,0 +1,207 @@
+use super::*;
+use crate::access_log::{access_count, AccessEntry, AccessLogKey};
+use soroban_sdk::{BytesN, Symbol};
+
+// Storage keys for the emergency responder registry
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ResponderKey {
+    Regulators,         // Addresses that manage the registry
+    Responder(Address), // Responder -> registration
+}
+
+// A responder allowed to break glass within its regions
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Responder {
+    pub institution: u64,     // Institution the responder works for
+    pub regions: Vec<Symbol>, // Regions it may respond in, as residency codes
+    pub expiry: u64,          // When the registration lapses (0 = never)
+    pub registered_by: Address,
+}
+
+fn regulators(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&ResponderKey::Regulators)
+        .unwrap_or(Vec::new(env))
+}
+
+fn require_regulator(env: &Env, regulator: &Address) -> Result<(), ContractError> {
+    regulator.require_auth();
+    if !regulators(env).contains(regulator) {
+        return Err(ContractError::NotAuthorized);
+    }
+    Ok(())
+}
+
+fn read_responder(env: &Env, responder: &Address) -> Option<Responder> {
+    env.storage()
+        .persistent()
+        .get(&ResponderKey::Responder(responder.clone()))
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Grant the regulator role, which manages the responder registry (admin only)
+    pub fn add_regulator(env: Env, regulator: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut list = regulators(&env);
+        if !list.contains(&regulator) {
+            list.push_back(regulator);
+            env.storage()
+                .instance()
+                .set(&ResponderKey::Regulators, &list);
+        }
+        Ok(())
+    }
+
+    /// Revoke the regulator role (admin only). Responders it registered stay.
+    pub fn remove_regulator(env: Env, regulator: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut list = regulators(&env);
+        if let Some(index) = list.first_index_of(&regulator) {
+            list.remove(index);
+            env.storage()
+                .instance()
+                .set(&ResponderKey::Regulators, &list);
+        }
+        Ok(())
+    }
+
+    /// Check if address holds the regulator role
+    pub fn is_regulator(env: Env, address: Address) -> bool {
+        regulators(&env).contains(&address)
+    }
+
+    /// Register an emergency responder from a known institution, or replace
+    /// its registration (regulator only). `regions` tags where it may break
+    /// glass; at most `MAX_REGIONS`, and at least one. Repeated regions are
+    /// stored once.
+    pub fn register_responder(
+        env: Env,
+        regulator: Address,
+        responder: Address,
+        institution: u64,
+        regions: Vec<Symbol>,
+        expiry: u64,
+    ) -> Result<(), ContractError> {
+        require_regulator(&env, &regulator)?;
+        institutions::read_institution(&env, institution)?;
+        if regions.is_empty() {
+            return Err(ContractError::PolicyViolation);
+        }
+        if regions.len() > residency::MAX_REGIONS {
+            return Err(ContractError::QuotaExceeded);
+        }
+        if clock::has_passed(&env, expiry) {
+            return Err(ContractError::PastTimestamp);
+        }
+        let mut unique = Vec::new(&env);
+        for region in regions.iter() {
+            if !unique.contains(&region) {
+                unique.push_back(region);
+            }
+        }
+
+        let key = ResponderKey::Responder(responder.clone());
+        env.storage().persistent().set(
+            &key,
+            &Responder {
+                institution,
+                regions: unique,
+                expiry,
+                registered_by: regulator.clone(),
+            },
+        );
+        ttl::bump_entry(&env, &key);
+
+        env.events().publish(
+            (symbol_short!("responder"), symbol_short!("added")),
+            (responder, institution, regulator),
+        );
+        Ok(())
+    }
+
+    /// Remove a responder from the registry (regulator only)
+    pub fn remove_responder(
+        env: Env,
+        regulator: Address,
+        responder: Address,
+    ) -> Result<(), ContractError> {
+        require_regulator(&env, &regulator)?;
+        let key = ResponderKey::Responder(responder.clone());
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().remove(&key);
+            env.events().publish(
+                (symbol_short!("responder"), symbol_short!("removed")),
+                (responder, regulator),
+            );
+        }
+        Ok(())
+    }
+
+    /// Get a responder's registration, including lapsed ones
+    pub fn get_responder(env: Env, responder: Address) -> Option<Responder> {
+        read_responder(&env, &responder)
+    }
+
+    /// Break glass: access a patient's consent in an emergency, bypassing the
+    /// patient's grants. The caller must be a registered, unexpired responder
+    /// tagged for `region`, and `region` must satisfy the consent's residency
+    /// restriction. It bypasses grants, not the consent's own state: a
+    /// revoked, suspended, expired or dormant consent fails as it would for
+    /// any other access. The access goes into the token's access log with the
+    /// responder as both verifier and accessor. Returns the entry's index.
+    pub fn break_glass_access(
+        env: Env,
+        responder: Address,
+        token_id: u64,
+        region: Symbol,
+        purpose_hash: BytesN<32>,
+    ) -> Result<u32, ContractError> {
+        responder.require_auth();
+        let registration = read_responder(&env, &responder).ok_or(ContractError::NotAuthorized)?;
+        if clock::has_passed(&env, registration.expiry) {
+            return Err(ContractError::NotAuthorized);
+        }
+        let metadata = read_metadata(&env, token_id)?;
+        ensure_active(&env, token_id, &metadata)?;
+        let residency = Self::get_residency(env.clone(), token_id);
+        if !registration.regions.contains(&region)
+            || !(residency.is_empty() || residency.contains(&region))
+        {
+            return Err(ContractError::OutOfRegion);
+        }
+
+        let index = access_count(&env, token_id);
+        let entry = AccessEntry {
+            verifier: responder.clone(),
+            accessor: responder.clone(),
+            purpose_hash,
+            timestamp: clock::now(&env),
+        };
+        let key = AccessLogKey::Access(token_id, index);
+        env.storage().persistent().set(&key, &entry);
+        let count_key = AccessLogKey::AccessCount(token_id);
+        env.storage().persistent().set(&count_key, &(index + 1));
+        ttl::bump_entry(&env, &key);
+        ttl::bump_entry(&env, &count_key);
+        ttl::bump_token(&env, token_id);
+        anchoring::anchor(
+            &env,
+            symbol_short!("brk_glass"),
+            token_id,
+            (token_id, entry, region.clone()),
+        );
+
+        events::publish_token(
+            &env,
+            (symbol_short!("access"), symbol_short!("brk_glass")),
+            token_id,
+            (token_id, responder, region),
+        );
+        Ok(index)
+    }
+}
@@ -0,0 +1,149 @@
+use super::*;
+
+// Storage keys for scheduled (future-dated) consents
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScheduleKey {
+    ScheduleCounter,
+    Scheduled(u64),
+}
+
+// A mint that becomes executable once activation_timestamp is reached
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScheduledConsent {
+    pub issuer: Address,
+    pub patient: Address,
+    pub metadata_uri: String,
+    pub consent_type: String,
+    pub expiry_timestamp: u64,     // 0 = no expiry
+    pub activation_timestamp: u64, // Earliest time the mint can be executed
+}
+
+fn read_scheduled(env: &Env, schedule_id: u64) -> Result<ScheduledConsent, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&ScheduleKey::Scheduled(schedule_id))
+        .ok_or(ContractError::ScheduleNotFound)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Schedule a consent to be minted at a future time (e.g. pre-admission
+    /// paperwork signed days before a procedure)
+    pub fn schedule_consent(
+        env: Env,
+        issuer: Address,
+        patient: Address,
+        metadata_uri: String,
+        consent_type: String,
+        expiry_timestamp: u64,
+        activation_timestamp: u64,
+    ) -> Result<u64, ContractError> {
+        issuer.require_auth();
+        if !Self::is_issuer(env.clone(), issuer.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        if activation_timestamp <= clock::now(&env) {
+            return Err(ContractError::PastTimestamp);
+        }
+        if expiry_timestamp != 0 && expiry_timestamp <= activation_timestamp {
+            return Err(ContractError::PastTimestamp);
+        }
+        validation::validate_uri(&metadata_uri)?;
+        validation::validate_consent_type(&env, &consent_type)?;
+
+        let schedule_id: u64 = env
+            .storage()
+            .instance()
+            .get(&ScheduleKey::ScheduleCounter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&ScheduleKey::ScheduleCounter, &(schedule_id + 1));
+
+        let scheduled = ScheduledConsent {
+            issuer,
+            patient: patient.clone(),
+            metadata_uri,
+            consent_type,
+            expiry_timestamp,
+            activation_timestamp,
+        };
+        let key = ScheduleKey::Scheduled(schedule_id);
+        env.storage().persistent().set(&key, &scheduled);
+        ttl::bump_entry(&env, &key);
+
+        env.events().publish(
+            (symbol_short!("consent"), symbol_short!("scheduled")),
+            (schedule_id, patient, activation_timestamp),
+        );
+        Ok(schedule_id)
+    }
+
+    /// Execute a scheduled mint once its activation time has been reached (callable by anyone)
+    pub fn execute_scheduled(env: Env, schedule_id: u64) -> Result<u64, ContractError> {
+        let scheduled = read_scheduled(&env, schedule_id)?;
+        if clock::now(&env) < scheduled.activation_timestamp {
+            return Err(ContractError::NotYetActive);
+        }
+
+        // Issuer may have been removed since scheduling
+        if !Self::is_issuer(env.clone(), scheduled.issuer.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&ScheduleKey::Scheduled(schedule_id));
+
+        let token_id = mint_token(
+            &env,
+            &scheduled.issuer,
+            &scheduled.patient,
+            &scheduled.metadata_uri,
+            &scheduled.consent_type,
+            scheduled.expiry_timestamp,
+        )?;
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("executed")),
+            token_id,
+            (schedule_id, token_id),
+        );
+        Ok(token_id)
+    }
+
+    /// Cancel a scheduled mint before it is executed (issuer or patient)
+    pub fn cancel_scheduled(
+        env: Env,
+        schedule_id: u64,
+        caller: Address,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        let scheduled = read_scheduled(&env, schedule_id)?;
+        if caller != scheduled.issuer && caller != scheduled.patient {
+            return Err(ContractError::NotAuthorized);
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&ScheduleKey::Scheduled(schedule_id));
+
+        env.events().publish(
+            (symbol_short!("consent"), symbol_short!("cancelled")),
+            (schedule_id, caller),
+        );
+        Ok(())
+    }
+
+    /// Get a pending scheduled mint
+    pub fn get_scheduled(env: Env, schedule_id: u64) -> Option<ScheduledConsent> {
+        env.storage()
+            .persistent()
+            .get(&ScheduleKey::Scheduled(schedule_id))
+    }
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Revoke one or more scopes of a consent while the rest stays valid (patient only)
+    pub fn revoke_scope(env: Env, token_id: u64, scope: u32) -> Result<(), ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.patient.require_auth();
+        ensure_active(&env, token_id, &metadata)?;
+
+        let old_scopes = token_scopes(&env, token_id);
+        if scope == 0 || old_scopes & scope != scope {
+            return Err(ContractError::ScopeNotGranted);
+        }
+        let new_scopes = old_scopes & !scope;
+        env.storage()
+            .persistent()
+            .set(&DataKey::TokenScopes(token_id), &new_scopes);
+
+        push_history(
+            &env,
+            token_id,
+            ConsentHistoryEntry {
+                action: String::from_str(&env, "scope_revoked"),
+                timestamp: clock::now(&env),
+                actor: metadata.patient.clone(),
+                metadata_uri: metadata.metadata_uri.clone(),
+                old_uri: metadata.metadata_uri.clone(),
+                new_uri: metadata.metadata_uri,
+                old_expiry: metadata.expiry_timestamp,
+                new_expiry: metadata.expiry_timestamp,
+                old_scopes,
+                new_scopes,
+                changed_fields: CHANGED_SCOPES,
+            },
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("consent"), symbol_short!("scope_rev")),
+            token_id,
+            (token_id, scope, new_scopes),
+        );
+        Ok(())
+    }
+
+    /// Check whether a consent is valid for every scope in `scope`
+    pub fn is_scope_valid(env: Env, token_id: u64, scope: u32) -> bool {
+        let Ok(metadata) = read_metadata(&env, token_id) else {
+            return false;
+        };
+        if scope == 0 || ensure_active(&env, token_id, &metadata).is_err() {
+            return false;
+        }
+        ttl::bump_token(&env, token_id);
+        token_scopes(&env, token_id) & scope == scope
+    }
+}
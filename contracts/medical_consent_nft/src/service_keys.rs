@@ -0,0 +1,223 @@
+use super::*;
+
+// Capability bits a service key can hold
+pub const KEY_CAN_MINT: u32 = 1 << 0;
+pub const KEY_CAN_REVOKE: u32 = 1 << 1;
+const ALL_KEY_CAPABILITIES: u32 = KEY_CAN_MINT | KEY_CAN_REVOKE;
+
+/// Most service keys a single issuer can register
+pub const MAX_KEYS_PER_ISSUER: u32 = 20;
+
+// Storage keys for issuers' service accounts
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ServiceKeyKey {
+    Key(Address),        // Service account -> its registration
+    IssuerKeys(Address), // Issuer -> its service accounts
+}
+
+// A service account (e.g. a clinic backend) acting for an issuer with a subset
+// of its powers
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServiceKey {
+    pub issuer: Address,
+    pub capabilities: u32, // KEY_CAN_* bits
+    pub enabled: bool,
+    pub created_at: u64,
+}
+
+fn read_key(env: &Env, key: &Address) -> Result<ServiceKey, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&ServiceKeyKey::Key(key.clone()))
+        .ok_or(ContractError::ServiceKeyNotFound)
+}
+
+fn write_key(env: &Env, key: &Address, service_key: &ServiceKey) {
+    let storage_key = ServiceKeyKey::Key(key.clone());
+    env.storage().persistent().set(&storage_key, service_key);
+    ttl::bump_entry(env, &storage_key);
+}
+
+fn issuer_keys(env: &Env, issuer: &Address) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&ServiceKeyKey::IssuerKeys(issuer.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_issuer_keys(env: &Env, issuer: &Address, keys: &Vec<Address>) {
+    let storage_key = ServiceKeyKey::IssuerKeys(issuer.clone());
+    env.storage().persistent().set(&storage_key, keys);
+    ttl::bump_entry(env, &storage_key);
+}
+
+/// Load a key registered by `issuer`, after the issuer has authorized
+fn read_own_key(env: &Env, issuer: &Address, key: &Address) -> Result<ServiceKey, ContractError> {
+    issuer.require_auth();
+    let service_key = read_key(env, key)?;
+    if service_key.issuer != *issuer {
+        return Err(ContractError::NotAuthorized);
+    }
+    Ok(service_key)
+}
+
+/// Authorize `key` for `capability` and return the issuer it acts for. Keys
+/// stop working as soon as they are disabled or their issuer is removed.
+fn authorize(env: &Env, key: &Address, capability: u32) -> Result<Address, ContractError> {
+    key.require_auth();
+    let service_key = read_key(env, key)?;
+    if !service_key.enabled
+        || service_key.capabilities & capability == 0
+        || !PatientConsentToken::is_issuer(env.clone(), service_key.issuer.clone())
+    {
+        return Err(ContractError::NotAuthorized);
+    }
+    Ok(service_key.issuer)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Register a service account that can act for an issuer with the given
+    /// `KEY_CAN_*` capabilities (issuer only, at most `MAX_KEYS_PER_ISSUER`)
+    pub fn add_service_key(
+        env: Env,
+        issuer: Address,
+        key: Address,
+        capabilities: u32,
+    ) -> Result<(), ContractError> {
+        issuer.require_auth();
+        if !Self::is_issuer(env.clone(), issuer.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+        if capabilities == 0 || capabilities & !ALL_KEY_CAPABILITIES != 0 || key == issuer {
+            return Err(ContractError::PolicyViolation);
+        }
+        // A key acts for exactly one issuer
+        if env
+            .storage()
+            .persistent()
+            .has(&ServiceKeyKey::Key(key.clone()))
+        {
+            return Err(ContractError::PolicyViolation);
+        }
+        let mut keys = issuer_keys(&env, &issuer);
+        if keys.len() >= MAX_KEYS_PER_ISSUER {
+            return Err(ContractError::QuotaExceeded);
+        }
+
+        write_key(
+            &env,
+            &key,
+            &ServiceKey {
+                issuer: issuer.clone(),
+                capabilities,
+                enabled: true,
+                created_at: clock::now(&env),
+            },
+        );
+        keys.push_back(key.clone());
+        set_issuer_keys(&env, &issuer, &keys);
+
+        env.events().publish(
+            (symbol_short!("svc_key"), symbol_short!("added")),
+            (issuer, key, capabilities),
+        );
+        Ok(())
+    }
+
+    /// Switch a service key off or back on without removing it (issuer only)
+    pub fn set_service_key_enabled(
+        env: Env,
+        issuer: Address,
+        key: Address,
+        enabled: bool,
+    ) -> Result<(), ContractError> {
+        let mut service_key = read_own_key(&env, &issuer, &key)?;
+        service_key.enabled = enabled;
+        write_key(&env, &key, &service_key);
+
+        env.events().publish(
+            (symbol_short!("svc_key"), symbol_short!("enabled")),
+            (issuer, key, enabled),
+        );
+        Ok(())
+    }
+
+    /// Remove a service key (issuer only)
+    pub fn remove_service_key(
+        env: Env,
+        issuer: Address,
+        key: Address,
+    ) -> Result<(), ContractError> {
+        read_own_key(&env, &issuer, &key)?;
+        env.storage()
+            .persistent()
+            .remove(&ServiceKeyKey::Key(key.clone()));
+        let mut keys = issuer_keys(&env, &issuer);
+        if let Some(index) = keys.first_index_of(&key) {
+            keys.remove(index);
+            set_issuer_keys(&env, &issuer, &keys);
+        }
+
+        env.events().publish(
+            (symbol_short!("svc_key"), symbol_short!("removed")),
+            (issuer, key),
+        );
+        Ok(())
+    }
+
+    /// Get a service key's registration
+    pub fn get_service_key(env: Env, key: Address) -> Option<ServiceKey> {
+        read_key(&env, &key).ok()
+    }
+
+    /// Get the service keys an issuer has registered
+    pub fn get_service_keys(env: Env, issuer: Address) -> Vec<Address> {
+        issuer_keys(&env, &issuer)
+    }
+
+    /// Mint a consent through a service key holding `KEY_CAN_MINT`. The token
+    /// is issued by the key's issuer, as if it had called `mint_consent`.
+    pub fn mint_consent_via_key(
+        env: Env,
+        key: Address,
+        patient: Address,
+        metadata_uri: String,
+        consent_type: String,
+        expiry_timestamp: u64,
+    ) -> Result<u64, ContractError> {
+        let issuer = authorize(&env, &key, KEY_CAN_MINT)?;
+        mint_token(
+            &env,
+            &issuer,
+            &patient,
+            &metadata_uri,
+            &consent_type,
+            expiry_timestamp,
+        )
+    }
+
+    /// Revoke a consent issued by the key's issuer through a service key
+    /// holding `KEY_CAN_REVOKE`. The audit trail names the key.
+    pub fn revoke_consent_via_key(
+        env: Env,
+        key: Address,
+        token_id: u64,
+    ) -> Result<(), ContractError> {
+        let issuer = authorize(&env, &key, KEY_CAN_REVOKE)?;
+        pause::ensure_issuer_not_paused(&env, &issuer)?;
+        let metadata = read_metadata(&env, token_id)?;
+        if metadata.issuer != issuer {
+            return Err(ContractError::NotAuthorized);
+        }
+        if Self::is_revoked(env.clone(), token_id) {
+            return Err(ContractError::ConsentRevoked);
+        }
+        mark_revoked(&env, token_id, &key, &metadata.metadata_uri);
+        activity::record_revocation(&env, &issuer);
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use super::*;
+use soroban_sdk::{IntoVal, Symbol, Val};
+
+/// Most subscribers a single action can have
+pub const MAX_SUBSCRIBERS: u32 = 20;
+
+// Consent actions integrators can subscribe to
+const ACTIONS: [&str; 4] = ["issued", "updated", "revoked", "transfer"];
+
+// Storage keys for event subscriptions
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SubscriptionKey {
+    Subscribers(Symbol), // Addresses subscribed to an action
+}
+
+fn subscribers(env: &Env, action: &Symbol) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&SubscriptionKey::Subscribers(action.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+fn is_known_action(env: &Env, action: &Symbol) -> bool {
+    ACTIONS
+        .iter()
+        .any(|known| Symbol::new(env, known) == *action)
+}
+
+/// Publish a `(consent, action, consent type)` event, plus a copy tagged with
+/// each subscriber's address as a fourth topic so indexers can filter for their
+/// institution only. The change is also written to the outbox, if one is
+/// configured.
+pub(crate) fn publish<D>(env: &Env, action: Symbol, token_id: u64, data: D)
+where
+    D: IntoVal<Env, Val> + Clone,
+{
+    outbox::notify(env, &action, token_id);
+    let consent_type = events::consent_type(env, token_id);
+    for subscriber in subscribers(env, &action).iter() {
+        env.events().publish(
+            (
+                symbol_short!("consent"),
+                action.clone(),
+                consent_type.clone(),
+                subscriber,
+            ),
+            data.clone(),
+        );
+    }
+    env.events()
+        .publish((symbol_short!("consent"), action, consent_type), data);
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Register for tagged copies of the given consent actions
+    pub fn subscribe(
+        env: Env,
+        subscriber: Address,
+        actions: Vec<Symbol>,
+    ) -> Result<(), ContractError> {
+        subscriber.require_auth();
+        for action in actions.iter() {
+            if !is_known_action(&env, &action) {
+                return Err(ContractError::UnknownAction);
+            }
+            let mut list = subscribers(&env, &action);
+            if list.contains(&subscriber) {
+                continue;
+            }
+            if list.len() >= MAX_SUBSCRIBERS {
+                return Err(ContractError::QuotaExceeded);
+            }
+            list.push_back(subscriber.clone());
+            env.storage()
+                .instance()
+                .set(&SubscriptionKey::Subscribers(action), &list);
+        }
+        Ok(())
+    }
+
+    /// Stop receiving tagged copies of the given consent actions
+    pub fn unsubscribe(
+        env: Env,
+        subscriber: Address,
+        actions: Vec<Symbol>,
+    ) -> Result<(), ContractError> {
+        subscriber.require_auth();
+        for action in actions.iter() {
+            let mut list = subscribers(&env, &action);
+            if let Some(index) = list.first_index_of(&subscriber) {
+                list.remove(index);
+                env.storage()
+                    .instance()
+                    .set(&SubscriptionKey::Subscribers(action), &list);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the addresses subscribed to an action
+    pub fn get_subscribers(env: Env, action: Symbol) -> Vec<Address> {
+        subscribers(&env, &action)
+    }
+}
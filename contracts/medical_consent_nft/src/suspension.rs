@@ -0,0 +1,178 @@
+use super::*;
+
+// Storage keys for cascading issuer suspensions
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SuspensionKey {
+    CascadeOnRemoval, // Whether removing or pausing an issuer suspends its consents
+    Cascade(Address), // Issuer -> position in its token index the pending cascade resumes at
+    Suspended(u64),   // Token -> issuer whose removal suspended it
+}
+
+// What one `suspend_by_issuer` call did
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CascadeProgress {
+    pub suspended: Vec<u64>,
+    pub done: bool, // No tokens left to scan; the cascade is finished
+}
+
+fn cascade_on_removal(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&SuspensionKey::CascadeOnRemoval)
+        .unwrap_or(false)
+}
+
+fn cascade_cursor(env: &Env, issuer: &Address) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get(&SuspensionKey::Cascade(issuer.clone()))
+}
+
+pub(crate) fn is_suspended(env: &Env, token_id: u64) -> bool {
+    let key = SuspensionKey::Suspended(token_id);
+    ttl::bump_entry(env, &key);
+    env.storage().persistent().has(&key)
+}
+
+/// Fails with `FrozenToken` while a consent is suspended pending review
+pub(crate) fn ensure_not_suspended(env: &Env, token_id: u64) -> Result<(), ContractError> {
+    if is_suspended(env, token_id) {
+        return Err(ContractError::FrozenToken);
+    }
+    Ok(())
+}
+
+/// Queue the suspension of an issuer's consents after it was removed or
+/// paused, if the policy asks for it. Keepers work through the queue with
+/// `suspend_by_issuer`.
+pub(crate) fn start_cascade(env: &Env, issuer: &Address) {
+    if !cascade_on_removal(env) || cascade_cursor(env, issuer).is_some() {
+        return;
+    }
+    let key = SuspensionKey::Cascade(issuer.clone());
+    env.storage().persistent().set(&key, &0u32);
+    ttl::bump_entry(env, &key);
+
+    env.events().publish(
+        (symbol_short!("issuer"), symbol_short!("cascade")),
+        issuer.clone(),
+    );
+}
+
+/// Drop an issuer's pending cascade once it is reinstated. Consents already
+/// suspended stay suspended until the admin reviews them.
+pub(crate) fn cancel_cascade(env: &Env, issuer: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&SuspensionKey::Cascade(issuer.clone()));
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Choose whether removing or pausing an issuer suspends every consent it
+    /// issued, pending admin review (admin only)
+    pub fn set_cascade_on_removal(env: Env, enabled: bool) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&SuspensionKey::CascadeOnRemoval, &enabled);
+        Ok(())
+    }
+
+    /// Check whether removing or pausing an issuer suspends its consents
+    pub fn get_cascade_on_removal(env: Env) -> bool {
+        cascade_on_removal(&env)
+    }
+
+    /// Get the position in the issuer's token index its pending cascade
+    /// resumes at (`None` = no cascade pending)
+    pub fn get_pending_cascade(env: Env, issuer: Address) -> Option<u32> {
+        cascade_cursor(&env, &issuer)
+    }
+
+    /// Keeper entrypoint: go through the next `limit` tokens (at most
+    /// `MAX_BATCH_SIZE`) an issuer with a pending cascade has minted and
+    /// suspend the active ones. Anyone may call it; call again until `done`.
+    pub fn suspend_by_issuer(
+        env: Env,
+        issuer: Address,
+        limit: u32,
+    ) -> Result<CascadeProgress, ContractError> {
+        if limit > batch::MAX_BATCH_SIZE {
+            return Err(ContractError::QuotaExceeded);
+        }
+        let start = cascade_cursor(&env, &issuer).ok_or(ContractError::PolicyViolation)?;
+        let token_count = issuer_index::issuer_token_count(&env, &issuer);
+        let end = start.saturating_add(limit).min(token_count);
+
+        let mut suspended = Vec::new(&env);
+        for token_id in issuer_index::issuer_tokens(&env, &issuer, start, end).iter() {
+            let Ok(metadata) = read_metadata(&env, token_id) else {
+                continue;
+            };
+            if Self::is_revoked(env.clone(), token_id) || is_suspended(&env, token_id) {
+                continue;
+            }
+            let key = SuspensionKey::Suspended(token_id);
+            env.storage().persistent().set(&key, &issuer);
+            ttl::bump_entry(&env, &key);
+            append_history(&env, token_id, "suspended", &issuer, &metadata.metadata_uri);
+            ttl::bump_token(&env, token_id);
+            events::publish_token(
+                &env,
+                (symbol_short!("consent"), symbol_short!("suspended")),
+                token_id,
+                (token_id, issuer.clone()),
+            );
+            suspended.push_back(token_id);
+        }
+
+        let key = SuspensionKey::Cascade(issuer.clone());
+        let done = end >= token_count;
+        if done {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &end);
+            ttl::bump_entry(&env, &key);
+        }
+        Ok(CascadeProgress { suspended, done })
+    }
+
+    /// Lift the suspension of consents the admin has reviewed (admin only).
+    /// Returns the tokens that were suspended and are now reinstated.
+    pub fn reinstate_consents(env: Env, token_ids: Vec<u64>) -> Result<Vec<u64>, ContractError> {
+        let admin = read_admin(&env)?;
+        admin.require_auth();
+        batch::ensure_batch_size(&token_ids)?;
+
+        let mut reinstated = Vec::new(&env);
+        for token_id in token_ids.iter() {
+            if !is_suspended(&env, token_id) {
+                continue;
+            }
+            env.storage()
+                .persistent()
+                .remove(&SuspensionKey::Suspended(token_id));
+            if let Ok(metadata) = read_metadata(&env, token_id) {
+                append_history(&env, token_id, "reinstated", &admin, &metadata.metadata_uri);
+                ttl::bump_token(&env, token_id);
+            }
+            events::publish_token(
+                &env,
+                (symbol_short!("consent"), symbol_short!("reinstate")),
+                token_id,
+                (token_id, admin.clone()),
+            );
+            reinstated.push_back(token_id);
+        }
+        Ok(reinstated)
+    }
+
+    /// Check whether a consent is suspended pending review
+    pub fn is_suspended(env: Env, token_id: u64) -> bool {
+        is_suspended(&env, token_id)
+    }
+}
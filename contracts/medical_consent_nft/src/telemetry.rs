@@ -0,0 +1,161 @@
+use super::*;
+use soroban_sdk::Map;
+
+/// Days of activity counters kept; older days are overwritten
+pub const ACTIVITY_DAYS: u32 = 30;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// Storage keys for error telemetry and daily activity
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TelemetryKey {
+    ErrorStats,   // Error code -> times seen
+    RevokedCount, // Tokens revoked so far
+    Day(u32),     // Ring buffer slot -> activity on the day last written to it
+}
+
+/// What a daily activity counter counts
+#[derive(Clone, Copy)]
+pub(crate) enum Activity {
+    Mint,
+    Revocation,
+    Grant,
+}
+
+// Activity on one UTC day
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DailyActivity {
+    pub day: u64, // Days since the Unix epoch
+    pub mints: u32,
+    pub revocations: u32,
+    pub grants: u32,
+}
+
+// Counters and gauges for monitoring, read in a single call
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsentMetrics {
+    pub tokens: u64,    // Tokens minted so far
+    pub active: u64,    // Tokens not revoked (expired ones included)
+    pub revoked: u64,   // Tokens revoked so far
+    pub issuers: u32,   // Registered issuers
+    pub ledger: u32,    // Ledger sequence the sample was taken at
+    pub timestamp: u64, // Ledger close time the sample was taken at
+}
+
+/// Count an error that was handled without failing the call.
+///
+/// The host rolls back every write of an invocation that returns an error, so
+/// counters can only be kept for errors a call recovers from (e.g. tokens
+/// skipped by `revoke_batch`). Failed invocations are visible in transaction
+/// results instead.
+pub(crate) fn record_error(env: &Env, error: ContractError) {
+    let mut stats = error_stats(env);
+    let code = error as u32;
+    stats.set(code, stats.get(code).unwrap_or(0).saturating_add(1));
+    env.storage()
+        .instance()
+        .set(&TelemetryKey::ErrorStats, &stats);
+}
+
+/// Count a revocation for the `revoked` metric
+pub(crate) fn record_revocation(env: &Env) {
+    env.storage()
+        .instance()
+        .set(&TelemetryKey::RevokedCount, &(revoked_count(env) + 1));
+    record_activity(env, Activity::Revocation);
+}
+
+fn day_activity(env: &Env, day: u64) -> DailyActivity {
+    let slot = (day % ACTIVITY_DAYS as u64) as u32;
+    env.storage()
+        .persistent()
+        .get(&TelemetryKey::Day(slot))
+        .filter(|activity: &DailyActivity| activity.day == day)
+        .unwrap_or(DailyActivity {
+            day,
+            mints: 0,
+            revocations: 0,
+            grants: 0,
+        })
+}
+
+/// Count an action against today's activity counters
+pub(crate) fn record_activity(env: &Env, activity: Activity) {
+    let day = clock::now(env) / SECONDS_PER_DAY;
+    let mut counters = day_activity(env, day);
+    let counter = match activity {
+        Activity::Mint => &mut counters.mints,
+        Activity::Revocation => &mut counters.revocations,
+        Activity::Grant => &mut counters.grants,
+    };
+    *counter = counter.saturating_add(1);
+    let key = TelemetryKey::Day((day % ACTIVITY_DAYS as u64) as u32);
+    env.storage().persistent().set(&key, &counters);
+    ttl::bump_entry(env, &key);
+}
+
+fn revoked_count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&TelemetryKey::RevokedCount)
+        .unwrap_or(0)
+}
+
+fn error_stats(env: &Env) -> Map<u32, u32> {
+    env.storage()
+        .instance()
+        .get(&TelemetryKey::ErrorStats)
+        .unwrap_or(Map::new(env))
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Get how often each error code was handled, keyed by code
+    pub fn get_error_stats(env: Env) -> Map<u32, u32> {
+        error_stats(&env)
+    }
+
+    /// Clear the error counters, e.g. after an incident is resolved (admin only)
+    pub fn reset_error_stats(env: Env) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        env.storage().instance().remove(&TelemetryKey::ErrorStats);
+        Ok(())
+    }
+
+    /// Get mint, revocation and grant counts for the last `days` days, oldest
+    /// first and ending today, so dashboards can chart activity without an
+    /// indexer. At most `ACTIVITY_DAYS` days are kept.
+    pub fn activity(env: Env, days: u32) -> Vec<DailyActivity> {
+        let today = clock::now(&env) / SECONDS_PER_DAY;
+        let days = days.min(ACTIVITY_DAYS).min(today as u32 + 1) as u64;
+        let mut result = Vec::new(&env);
+        for day in (today + 1 - days)..=today {
+            result.push_back(day_activity(&env, day));
+        }
+        result
+    }
+
+    /// Get the contract's counters and gauges in one call, so monitoring agents
+    /// can scrape a single read per interval. Reads only instance storage.
+    pub fn metrics(env: Env) -> ConsentMetrics {
+        let tokens: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenCounter)
+            .unwrap_or(0);
+        let issuers = allowlist::members(&env, &DataKey::Issuers);
+        let revoked = revoked_count(&env);
+        ConsentMetrics {
+            tokens,
+            active: tokens.saturating_sub(revoked),
+            revoked,
+            issuers: issuers.len(),
+            ledger: env.ledger().sequence(),
+            timestamp: clock::now(&env),
+        }
+    }
+}
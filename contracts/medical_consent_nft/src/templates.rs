@@ -0,0 +1,362 @@
+use super::*;
+use soroban_sdk::{Map, Symbol};
+
+/// Most fields a template can require
+pub const MAX_TEMPLATE_FIELDS: u32 = 10;
+
+/// Longest value a template field accepts, in bytes
+pub const MAX_FIELD_LEN: u32 = 64;
+
+// Storage keys for the consent template registry
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TemplateKey {
+    LegalReviewers,
+    LatestVersion(Symbol), // Template name -> newest version number
+    Version(Symbol, u32),  // Template name + version -> template
+    Approval(Symbol, u32), // Legal review sign-off for a version
+    TokenTemplate(u64),    // Template a token was minted from
+    TokenFields(u64),      // Field values a token was minted with
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TemplateStatus {
+    PendingReview,
+    Approved,
+    Deprecated,
+}
+
+/// What a template field's value must look like
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FieldFormat {
+    Text,    // Anything non-empty
+    Numeric, // ASCII digits only
+    Code,    // ASCII letters, digits, '-', '.' and '_' (e.g. "CPT-99213", "NCT01234567")
+}
+
+// A value every consent minted from a template must supply
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TemplateField {
+    pub name: Symbol,
+    pub format: FieldFormat,
+}
+
+// One version of a consent template
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsentTemplate {
+    pub metadata_uri: String, // Consent document for this version
+    pub consent_type: String,
+    pub duration: u64,              // Seconds from mint to expiry (0 = permanent)
+    pub fields: Vec<TemplateField>, // Values required at mint
+    pub author: Address,
+    pub status: TemplateStatus,
+    pub created: u64,
+}
+
+// Legal reviewer's sign-off on a template version
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TemplateApproval {
+    pub reviewer: Address,
+    pub timestamp: u64,
+}
+
+fn legal_reviewers(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&TemplateKey::LegalReviewers)
+        .unwrap_or(Vec::new(env))
+}
+
+fn require_reviewer(env: &Env, reviewer: &Address) -> Result<(), ContractError> {
+    reviewer.require_auth();
+    if !legal_reviewers(env).contains(reviewer) {
+        return Err(ContractError::NotAuthorized);
+    }
+    Ok(())
+}
+
+fn read_template(env: &Env, name: &Symbol, version: u32) -> Result<ConsentTemplate, ContractError> {
+    env.storage()
+        .persistent()
+        .get(&TemplateKey::Version(name.clone(), version))
+        .ok_or(ContractError::TemplateNotFound)
+}
+
+fn write_template(env: &Env, name: &Symbol, version: u32, template: &ConsentTemplate) {
+    let key = TemplateKey::Version(name.clone(), version);
+    env.storage().persistent().set(&key, template);
+    ttl::bump_entry(env, &key);
+}
+
+fn validate_field_value(value: &String, format: FieldFormat) -> bool {
+    let len = value.len();
+    if len == 0 || len > MAX_FIELD_LEN {
+        return false;
+    }
+    let mut buf = [0u8; MAX_FIELD_LEN as usize];
+    let bytes = &mut buf[..len as usize];
+    value.copy_into_slice(bytes);
+    match format {
+        FieldFormat::Text => true,
+        FieldFormat::Numeric => bytes.iter().all(u8::is_ascii_digit),
+        FieldFormat::Code => bytes
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_')),
+    }
+}
+
+/// Check that `values` has exactly the template's fields, each well formed
+fn validate_fields(
+    template: &ConsentTemplate,
+    values: &Map<Symbol, String>,
+) -> Result<(), ContractError> {
+    if values.len() != template.fields.len() {
+        return Err(ContractError::InvalidTemplateField);
+    }
+    for field in template.fields.iter() {
+        match values.get(field.name) {
+            Some(value) if validate_field_value(&value, field.format) => {}
+            _ => return Err(ContractError::InvalidTemplateField),
+        }
+    }
+    Ok(())
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Allow an address to approve template versions (admin only)
+    pub fn add_legal_reviewer(env: Env, reviewer: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut reviewers = legal_reviewers(&env);
+        if !reviewers.contains(&reviewer) {
+            reviewers.push_back(reviewer);
+            env.storage()
+                .instance()
+                .set(&TemplateKey::LegalReviewers, &reviewers);
+        }
+        Ok(())
+    }
+
+    /// Remove a legal reviewer (admin only). Past approvals stay valid.
+    pub fn remove_legal_reviewer(env: Env, reviewer: Address) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut reviewers = legal_reviewers(&env);
+        if let Some(index) = reviewers.first_index_of(&reviewer) {
+            reviewers.remove(index);
+            env.storage()
+                .instance()
+                .set(&TemplateKey::LegalReviewers, &reviewers);
+        }
+        Ok(())
+    }
+
+    /// Check if an address is a legal reviewer
+    pub fn is_legal_reviewer(env: Env, reviewer: Address) -> bool {
+        legal_reviewers(&env).contains(&reviewer)
+    }
+
+    /// Publish a new version of a template (issuer only). Versions start at 1
+    /// and can't be used for minting until a legal reviewer approves them.
+    /// Every consent minted from it must supply a value for each of `fields`.
+    pub fn publish_template(
+        env: Env,
+        author: Address,
+        name: Symbol,
+        metadata_uri: String,
+        consent_type: String,
+        duration: u64,
+        fields: Vec<TemplateField>,
+    ) -> Result<u32, ContractError> {
+        author.require_auth();
+        if !Self::is_issuer(env.clone(), author.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+        validation::validate_uri(&metadata_uri)?;
+        validation::validate_consent_type(&env, &consent_type)?;
+        if fields.len() > MAX_TEMPLATE_FIELDS {
+            return Err(ContractError::QuotaExceeded);
+        }
+        let mut names: Vec<Symbol> = Vec::new(&env);
+        for field in fields.iter() {
+            if names.contains(&field.name) {
+                return Err(ContractError::PolicyViolation);
+            }
+            names.push_back(field.name);
+        }
+
+        let version = Self::get_latest_template_version(env.clone(), name.clone()) + 1;
+        write_template(
+            &env,
+            &name,
+            version,
+            &ConsentTemplate {
+                metadata_uri,
+                consent_type,
+                duration,
+                fields,
+                author: author.clone(),
+                status: TemplateStatus::PendingReview,
+                created: clock::now(&env),
+            },
+        );
+        env.storage()
+            .persistent()
+            .set(&TemplateKey::LatestVersion(name.clone()), &version);
+
+        env.events().publish(
+            (symbol_short!("template"), symbol_short!("published")),
+            (name, version, author),
+        );
+        Ok(version)
+    }
+
+    /// Approve a template version for minting (legal reviewer only)
+    pub fn approve_template(
+        env: Env,
+        reviewer: Address,
+        name: Symbol,
+        version: u32,
+    ) -> Result<(), ContractError> {
+        require_reviewer(&env, &reviewer)?;
+        let mut template = read_template(&env, &name, version)?;
+        if template.status != TemplateStatus::PendingReview {
+            return Err(ContractError::PolicyViolation);
+        }
+
+        template.status = TemplateStatus::Approved;
+        write_template(&env, &name, version, &template);
+        let key = TemplateKey::Approval(name.clone(), version);
+        env.storage().persistent().set(
+            &key,
+            &TemplateApproval {
+                reviewer: reviewer.clone(),
+                timestamp: clock::now(&env),
+            },
+        );
+        ttl::bump_entry(&env, &key);
+
+        env.events().publish(
+            (symbol_short!("template"), symbol_short!("approved")),
+            (name, version, reviewer),
+        );
+        Ok(())
+    }
+
+    /// Retire a template version so no new consents are minted from it (legal
+    /// reviewer or admin). Consents already minted are unaffected.
+    pub fn deprecate_template(
+        env: Env,
+        caller: Address,
+        name: Symbol,
+        version: u32,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        if caller != read_admin(&env)? && !legal_reviewers(&env).contains(&caller) {
+            return Err(ContractError::NotAuthorized);
+        }
+        let mut template = read_template(&env, &name, version)?;
+        template.status = TemplateStatus::Deprecated;
+        write_template(&env, &name, version, &template);
+
+        env.events().publish(
+            (symbol_short!("template"), symbol_short!("deprecate")),
+            (name, version, caller),
+        );
+        Ok(())
+    }
+
+    /// Mint a consent from an approved template version (issuer only).
+    /// `fields` must hold a well-formed value for each of the template's
+    /// fields and nothing else; the values are stored with the token.
+    pub fn mint_from_template(
+        env: Env,
+        issuer: Address,
+        patient: Address,
+        name: Symbol,
+        version: u32,
+        fields: Map<Symbol, String>,
+    ) -> Result<u64, ContractError> {
+        issuer.require_auth();
+        if !Self::is_issuer(env.clone(), issuer.clone()) {
+            return Err(ContractError::NotAuthorized);
+        }
+        let template = read_template(&env, &name, version)?;
+        if template.status != TemplateStatus::Approved {
+            return Err(ContractError::TemplateNotApproved);
+        }
+        validate_fields(&template, &fields)?;
+
+        let expiry_timestamp = match template.duration {
+            0 => 0,
+            duration => clock::now(&env) + duration,
+        };
+        let token_id = mint_token(
+            &env,
+            &issuer,
+            &patient,
+            &template.metadata_uri,
+            &template.consent_type,
+            expiry_timestamp,
+        )?;
+        let key = TemplateKey::TokenTemplate(token_id);
+        env.storage().persistent().set(&key, &(name, version));
+        ttl::bump_entry(&env, &key);
+        if !fields.is_empty() {
+            let key = TemplateKey::TokenFields(token_id);
+            env.storage().persistent().set(&key, &fields);
+            ttl::bump_entry(&env, &key);
+        }
+        ttl::bump_token(&env, token_id);
+        Ok(token_id)
+    }
+
+    /// Get a template version
+    pub fn get_template(
+        env: Env,
+        name: Symbol,
+        version: u32,
+    ) -> Result<ConsentTemplate, ContractError> {
+        read_template(&env, &name, version)
+    }
+
+    /// Get the newest version number of a template (0 if never published)
+    pub fn get_latest_template_version(env: Env, name: Symbol) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&TemplateKey::LatestVersion(name))
+            .unwrap_or(0)
+    }
+
+    /// Get the legal review sign-off for a template version
+    pub fn get_template_approval(env: Env, name: Symbol, version: u32) -> Option<TemplateApproval> {
+        env.storage()
+            .persistent()
+            .get(&TemplateKey::Approval(name, version))
+    }
+
+    /// Get the field values a token was minted with (empty if none)
+    pub fn get_token_fields(env: Env, token_id: u64) -> Map<Symbol, String> {
+        env.storage()
+            .persistent()
+            .get(&TemplateKey::TokenFields(token_id))
+            .unwrap_or(Map::new(&env))
+    }
+
+    /// Get one field value a token was minted with
+    pub fn get_token_field(env: Env, token_id: u64, field: Symbol) -> Option<String> {
+        Self::get_token_fields(env, token_id).get(field)
+    }
+
+    /// Get the template name and version a token was minted from
+    pub fn get_token_template(env: Env, token_id: u64) -> Option<(Symbol, u32)> {
+        env.storage()
+            .persistent()
+            .get(&TemplateKey::TokenTemplate(token_id))
+    }
+}
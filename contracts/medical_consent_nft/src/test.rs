@@ -1,18 +1,19 @@
 #[cfg(test)]
 mod test {
-    use crate::{PatientConsentToken, PatientConsentTokenClient};
+    use crate::{InitConfig, PatientConsentToken, PatientConsentTokenClient};
     use soroban_sdk::{testutils::Address as _, Address, Env, String};
 
     #[test]
     fn test_initialize_and_add_issuer() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register_contract(None, PatientConsentToken);
         let client = PatientConsentTokenClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
         let issuer = Address::generate(&env);
 
-        client.initialize(&admin);
+        client.initialize(&InitConfig::new(&env, admin.clone()));
         client.add_issuer(&issuer);
 
         assert!(client.is_issuer(&issuer));
@@ -21,6 +22,7 @@ mod test {
     #[test]
     fn test_mint_consent() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register_contract(None, PatientConsentToken);
         let client = PatientConsentTokenClient::new(&env, &contract_id);
 
@@ -28,13 +30,13 @@ mod test {
         let issuer = Address::generate(&env);
         let patient = Address::generate(&env);
 
-        client.initialize(&admin);
+        client.initialize(&InitConfig::new(&env, admin.clone()));
         client.add_issuer(&issuer);
 
         let metadata_uri = String::from_str(&env, "ipfs://QmXxx...");
         let consent_type = String::from_str(&env, "treatment");
 
-        let token_id = client.mint_consent(&patient, &metadata_uri, &consent_type, &0);
+        let token_id = client.mint_consent(&issuer, &patient, &metadata_uri, &consent_type, &0);
 
         assert_eq!(token_id, 0);
         assert_eq!(client.owner_of(&token_id), patient);
@@ -44,6 +46,7 @@ mod test {
     #[test]
     fn test_revoke_consent() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register_contract(None, PatientConsentToken);
         let client = PatientConsentTokenClient::new(&env, &contract_id);
 
@@ -51,13 +54,13 @@ mod test {
         let issuer = Address::generate(&env);
         let patient = Address::generate(&env);
 
-        client.initialize(&admin);
+        client.initialize(&InitConfig::new(&env, admin.clone()));
         client.add_issuer(&issuer);
 
         let metadata_uri = String::from_str(&env, "ipfs://QmXxx...");
         let consent_type = String::from_str(&env, "research");
 
-        let token_id = client.mint_consent(&patient, &metadata_uri, &consent_type, &0);
+        let token_id = client.mint_consent(&issuer, &patient, &metadata_uri, &consent_type, &0);
         client.revoke_consent(&token_id);
 
         assert!(client.is_revoked(&token_id));
@@ -68,6 +71,7 @@ mod test {
     #[should_panic]
     fn test_transfer_revoked_fails() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register_contract(None, PatientConsentToken);
         let client = PatientConsentTokenClient::new(&env, &contract_id);
 
@@ -76,13 +80,13 @@ mod test {
         let patient = Address::generate(&env);
         let recipient = Address::generate(&env);
 
-        client.initialize(&admin);
+        client.initialize(&InitConfig::new(&env, admin.clone()));
         client.add_issuer(&issuer);
 
         let metadata_uri = String::from_str(&env, "ipfs://QmXxx...");
         let consent_type = String::from_str(&env, "treatment");
 
-        let token_id = client.mint_consent(&patient, &metadata_uri, &consent_type, &0);
+        let token_id = client.mint_consent(&issuer, &patient, &metadata_uri, &consent_type, &0);
         client.revoke_consent(&token_id);
         client.transfer(&patient, &recipient, &token_id);
     }
@@ -90,6 +94,7 @@ mod test {
     #[test]
     fn test_update_metadata() {
         let env = Env::default();
+        env.mock_all_auths();
         let contract_id = env.register_contract(None, PatientConsentToken);
         let client = PatientConsentTokenClient::new(&env, &contract_id);
 
@@ -97,13 +102,13 @@ mod test {
         let issuer = Address::generate(&env);
         let patient = Address::generate(&env);
 
-        client.initialize(&admin);
+        client.initialize(&InitConfig::new(&env, admin.clone()));
         client.add_issuer(&issuer);
 
         let metadata_uri = String::from_str(&env, "ipfs://QmXxx...");
         let consent_type = String::from_str(&env, "treatment");
 
-        let token_id = client.mint_consent(&patient, &metadata_uri, &consent_type, &0);
+        let token_id = client.mint_consent(&issuer, &patient, &metadata_uri, &consent_type, &0);
 
         let new_uri = String::from_str(&env, "ipfs://QmYyy...");
         client.update_consent(&token_id, &new_uri);
@@ -113,3 +118,4267 @@ mod test {
         assert_eq!(metadata.metadata_uri, new_uri);
     }
 }
+
+use crate::{
+    access_requests::MAX_PENDING_REQUESTS, anchoring::MAX_PENDING_ANCHORS, batch::MAX_BATCH_SIZE,
+    tickets::TICKET_LIFETIME, AccessLevel, ActivityThresholds, ClinicalCode, ConfigUpdate,
+    ConsentStatus, ContractError, DailyActivity, DataKey, DirectiveTarget, FieldFormat, FlagReason,
+    HistoryOverflow, HistoryPolicy, InitConfig, PatientConsentToken, PatientConsentTokenClient,
+    PatientPrefs, PosthumousStatus, RequestStatus, TemplateField, TemplateStatus, TtlPolicy,
+    ValidityReason, ACTIVITY_DAYS, ALL_SCOPES, CHANGED_EXPIRY, CHANGED_SCOPES, CHANGED_URI,
+    INTERFACE_MAJOR, INTERFACE_MINOR, ISSUER_PAGE_LEN, KEY_CAN_MINT, KEY_CAN_REVOKE, PAUSE_GRANTS,
+    PAUSE_MINT, PAUSE_TRANSFER, VIEW_ALL, VIEW_ISSUER, VIEW_STATUS, VIEW_TYPE,
+};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    vec, Address, Bytes, BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec,
+};
+use uzima_common::clock;
+
+fn setup(env: &Env) -> (PatientConsentTokenClient<'_>, Address, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, PatientConsentToken);
+    let client = PatientConsentTokenClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    let issuer = Address::generate(env);
+    client.initialize(&InitConfig::new(env, admin.clone()));
+    client.add_issuer(&issuer);
+    (client, admin, issuer)
+}
+
+fn mint(env: &Env, client: &PatientConsentTokenClient, issuer: &Address, patient: &Address) -> u64 {
+    client.mint_consent(
+        issuer,
+        patient,
+        &String::from_str(env, "ipfs://QmXxx..."),
+        &String::from_str(env, "treatment"),
+        &0,
+    )
+}
+
+#[test]
+fn test_access_ticket_single_use() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let er_doctor = Address::generate(&env);
+
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let ticket_id = client.issue_access_ticket(&token_id, &er_doctor, &1);
+    assert!(client.get_ticket(&ticket_id).is_some());
+
+    let ticket = client.redeem_ticket(&ticket_id);
+    assert_eq!(ticket.grantee, er_doctor);
+    assert_eq!(ticket.token_id, token_id);
+
+    // Burned on redemption
+    assert!(client.get_ticket(&ticket_id).is_none());
+    assert_eq!(
+        client.try_redeem_ticket(&ticket_id),
+        Err(Ok(ContractError::TicketNotFound))
+    );
+
+    let history = client.get_history(&token_id);
+    assert_eq!(history.len(), 3);
+    assert_eq!(
+        history.get(2).unwrap().action,
+        String::from_str(&env, "ticket_redeemed")
+    );
+}
+
+#[test]
+fn test_ticket_on_revoked_consent_fails() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let er_doctor = Address::generate(&env);
+
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let ticket_id = client.issue_access_ticket(&token_id, &er_doctor, &1);
+    client.revoke_consent(&token_id);
+
+    assert_eq!(
+        client.try_redeem_ticket(&ticket_id),
+        Err(Ok(ContractError::ConsentRevoked))
+    );
+    assert_eq!(
+        client.try_issue_access_ticket(&token_id, &er_doctor, &1),
+        Err(Ok(ContractError::ConsentRevoked))
+    );
+}
+
+#[test]
+fn test_ticket_scope_and_expiry() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let er_doctor = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    client.revoke_scope(&token_id, &0b100);
+
+    // The ticket can't cover nothing, or more than the consent does
+    assert_eq!(
+        client.try_issue_access_ticket(&token_id, &er_doctor, &0),
+        Err(Ok(ContractError::ScopeNotGranted))
+    );
+    assert_eq!(
+        client.try_issue_access_ticket(&token_id, &er_doctor, &0b100),
+        Err(Ok(ContractError::ScopeNotGranted))
+    );
+
+    clock::set(&env, 1_000);
+    let ticket_id = client.issue_access_ticket(&token_id, &er_doctor, &0b010);
+    assert_eq!(
+        client.get_ticket(&ticket_id).unwrap().expiry,
+        1_000 + TICKET_LIFETIME
+    );
+    clock::advance(&env, TICKET_LIFETIME + 1);
+    assert_eq!(
+        client.try_redeem_ticket(&ticket_id),
+        Err(Ok(ContractError::RequestExpired))
+    );
+}
+
+#[test]
+fn test_verification_payload_roundtrip() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let payload = client.get_verification_payload(&token_id);
+    assert_eq!(payload.len(), 86);
+    assert_eq!(payload.get(0), Some(1)); // format version
+    assert_eq!(payload.get(1), Some(0)); // valid
+    assert!(client.verify_payload(&payload));
+
+    // Tampering with any byte invalidates the payload
+    let mut tampered = payload.clone();
+    tampered.set(1, 2);
+    assert!(!client.verify_payload(&tampered));
+    assert!(!client.verify_payload(&payload.slice(0..54)));
+}
+
+#[test]
+fn test_verification_payload_stale_after_revoke() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+
+    let token_id = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmXxx..."),
+        &String::from_str(&env, "research"),
+        &1_000,
+    );
+    let payload = client.get_verification_payload(&token_id);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert!(!client.verify_payload(&payload));
+    assert_eq!(client.get_verification_payload(&token_id).get(1), Some(2)); // expired
+
+    client.revoke_consent(&token_id);
+    let revoked = client.get_verification_payload(&token_id);
+    assert_eq!(revoked.get(1), Some(1)); // revoked
+    assert!(client.verify_payload(&revoked));
+}
+
+#[test]
+fn test_expired_consent_error_codes() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+
+    let token_id = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmXxx..."),
+        &String::from_str(&env, "treatment"),
+        &1_000,
+    );
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    assert!(!client.is_valid(&token_id));
+    assert_eq!(
+        client.try_transfer(&patient, &doctor, &token_id),
+        Err(Ok(ContractError::ExpiredConsent))
+    );
+    assert_eq!(
+        client.try_update_consent(&token_id, &String::from_str(&env, "ipfs://new")),
+        Err(Ok(ContractError::ExpiredConsent))
+    );
+    assert_eq!(
+        client.try_issue_access_ticket(&token_id, &doctor, &1),
+        Err(Ok(ContractError::ExpiredConsent))
+    );
+}
+
+#[test]
+fn test_missing_token_and_uninitialized_error_codes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, PatientConsentToken);
+    let client = PatientConsentTokenClient::new(&env, &contract_id);
+    let issuer = Address::generate(&env);
+    let patient = Address::generate(&env);
+
+    assert_eq!(
+        client.try_add_issuer(&issuer),
+        Err(Ok(ContractError::NotInitialized))
+    );
+    assert_eq!(
+        client.try_remove_issuer(&issuer),
+        Err(Ok(ContractError::NotInitialized))
+    );
+
+    client.initialize(&InitConfig::new(&env, Address::generate(&env)));
+    assert_eq!(
+        client.try_owner_of(&7),
+        Err(Ok(ContractError::TokenNotFound))
+    );
+    assert_eq!(
+        client.try_get_metadata(&7),
+        Err(Ok(ContractError::TokenNotFound))
+    );
+    assert_eq!(
+        client.try_is_valid(&7),
+        Err(Ok(ContractError::TokenNotFound))
+    );
+    assert_eq!(
+        client.try_transfer(&patient, &issuer, &7),
+        Err(Ok(ContractError::TokenNotFound))
+    );
+    assert_eq!(
+        client.try_update_consent(&7, &String::from_str(&env, "ipfs://new")),
+        Err(Ok(ContractError::TokenNotFound))
+    );
+}
+
+#[test]
+fn test_mint_input_validation() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://QmXxx...");
+    let treatment = String::from_str(&env, "treatment");
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+
+    assert_eq!(
+        client.try_mint_consent(
+            &issuer,
+            &patient,
+            &String::from_str(&env, ""),
+            &treatment,
+            &0
+        ),
+        Err(Ok(ContractError::InvalidUri))
+    );
+    assert_eq!(
+        client.try_mint_consent(&issuer, &patient, &uri, &treatment, &10_000),
+        Err(Ok(ContractError::PastTimestamp))
+    );
+    assert_eq!(
+        client.try_mint_consent(
+            &issuer,
+            &patient,
+            &uri,
+            &String::from_str(&env, "cosmetic"),
+            &0
+        ),
+        Err(Ok(ContractError::UnknownConsentType))
+    );
+
+    // Admin-configured maximum duration
+    client.set_max_expiry_duration(&1_000);
+    assert_eq!(
+        client.try_mint_consent(&issuer, &patient, &uri, &treatment, &11_001),
+        Err(Ok(ContractError::ExpiryTooLong))
+    );
+    assert_eq!(
+        client.try_mint_consent(&issuer, &patient, &uri, &treatment, &0),
+        Err(Ok(ContractError::ExpiryTooLong))
+    );
+    let token_id = client.mint_consent(&issuer, &patient, &uri, &treatment, &11_000);
+
+    assert_eq!(
+        client.try_update_consent(&token_id, &String::from_str(&env, "")),
+        Err(Ok(ContractError::InvalidUri))
+    );
+}
+
+#[test]
+fn test_consent_type_registry() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://QmXxx...");
+    let genomics = String::from_str(&env, "genomics");
+
+    assert_eq!(client.get_consent_types().len(), 4);
+    client.add_consent_type(&genomics);
+    client.mint_consent(&issuer, &patient, &uri, &genomics, &0);
+
+    client.remove_consent_type(&genomics);
+    assert_eq!(
+        client.try_mint_consent(&issuer, &patient, &uri, &genomics, &0),
+        Err(Ok(ContractError::UnknownConsentType))
+    );
+}
+
+#[test]
+fn test_token_data_in_persistent_storage() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+
+    let token_id = mint(&env, &client, &issuer, &patient);
+    env.as_contract(&client.address, || {
+        let persistent = env.storage().persistent();
+        assert!(persistent.has(&DataKey::TokenOwner(token_id)));
+        assert!(persistent.has(&DataKey::TokenMetadata(token_id)));
+        assert!(persistent.has(&DataKey::ConsentHistory(token_id)));
+        assert!(persistent.has(&DataKey::OwnerTokens(patient.clone())));
+        assert!(!env.storage().instance().has(&DataKey::TokenOwner(token_id)));
+    });
+}
+
+#[test]
+fn test_ttl_policy() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+
+    let policy = client.get_ttl_policy();
+    assert!(policy.threshold <= policy.extend_to);
+
+    client.set_ttl_policy(&100, &1_000);
+    assert_eq!(
+        client.get_ttl_policy(),
+        TtlPolicy {
+            threshold: 100,
+            extend_to: 1_000
+        }
+    );
+
+    assert_eq!(
+        client.try_set_ttl_policy(&1_000, &100),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    assert_eq!(
+        client.try_set_ttl_policy(&100, &u32::MAX),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+}
+
+#[test]
+fn test_restore_token_against_commitment() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let before = client.get_commitment(&token_id);
+    client.transfer(&patient, &doctor, &token_id);
+    assert_ne!(client.get_commitment(&token_id), before);
+
+    let metadata = client.get_metadata(&token_id);
+
+    // Live entries are simply re-validated
+    client.restore_token(&token_id, &doctor, &metadata, &false);
+    assert_eq!(
+        client.try_restore_token(&token_id, &patient, &metadata, &false),
+        Err(Ok(ContractError::CommitmentMismatch))
+    );
+    assert_eq!(
+        client.try_restore_token(&token_id, &doctor, &metadata, &true),
+        Err(Ok(ContractError::CommitmentMismatch))
+    );
+
+    // Entries lost from the ledger are rebuilt from a matching record
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::TokenOwner(token_id));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::TokenMetadata(token_id));
+    });
+    assert_eq!(
+        client.try_owner_of(&token_id),
+        Err(Ok(ContractError::TokenNotFound))
+    );
+    client.restore_token(&token_id, &doctor, &metadata, &false);
+    assert_eq!(client.owner_of(&token_id), doctor);
+    assert_eq!(client.get_metadata(&token_id), metadata);
+    assert!(client.is_valid(&token_id));
+}
+
+#[test]
+fn test_localized_uris() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let canonical = client.get_metadata(&token_id).metadata_uri;
+    let swahili = String::from_str(&env, "ipfs://QmSw...");
+
+    // Falls back to the canonical document until a translation exists
+    assert_eq!(
+        client.token_uri_localized(&token_id, &symbol_short!("sw")),
+        canonical
+    );
+
+    client.set_localized_uri(&token_id, &symbol_short!("sw"), &swahili);
+    assert_eq!(
+        client.token_uri_localized(&token_id, &symbol_short!("sw")),
+        swahili
+    );
+    assert_eq!(
+        client.token_uri_localized(&token_id, &symbol_short!("fr")),
+        canonical
+    );
+    assert_eq!(client.get_token_languages(&token_id).len(), 1);
+
+    // Canonical/legal version is untouched
+    assert_eq!(client.get_metadata(&token_id).metadata_uri, canonical);
+    assert_eq!(client.get_metadata(&token_id).version, 1);
+
+    assert_eq!(
+        client.try_set_localized_uri(&token_id, &symbol_short!("fr"), &String::from_str(&env, "")),
+        Err(Ok(ContractError::InvalidUri))
+    );
+}
+
+#[test]
+fn test_scheduled_consent_lifecycle() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://QmPreAdmission");
+    let treatment = String::from_str(&env, "treatment");
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    assert_eq!(
+        client.try_schedule_consent(&issuer, &patient, &uri, &treatment, &0, &1_000),
+        Err(Ok(ContractError::PastTimestamp))
+    );
+
+    let schedule_id = client.schedule_consent(&issuer, &patient, &uri, &treatment, &0, &5_000);
+    assert!(client.get_scheduled(&schedule_id).is_some());
+    assert_eq!(
+        client.try_execute_scheduled(&schedule_id),
+        Err(Ok(ContractError::NotYetActive))
+    );
+    assert_eq!(client.tokens_of_owner(&patient).len(), 0);
+
+    env.ledger().with_mut(|li| li.timestamp = 5_000);
+    let token_id = client.execute_scheduled(&schedule_id);
+    assert_eq!(client.owner_of(&token_id), patient);
+    assert_eq!(client.get_metadata(&token_id).issuer, issuer);
+    assert_eq!(client.get_metadata(&token_id).issued_timestamp, 5_000);
+
+    // Executed schedules are consumed
+    assert!(client.get_scheduled(&schedule_id).is_none());
+    assert_eq!(
+        client.try_execute_scheduled(&schedule_id),
+        Err(Ok(ContractError::ScheduleNotFound))
+    );
+}
+
+#[test]
+fn test_cancel_scheduled_consent() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://QmPreAdmission");
+    let treatment = String::from_str(&env, "treatment");
+
+    let schedule_id = client.schedule_consent(&issuer, &patient, &uri, &treatment, &0, &5_000);
+    assert_eq!(
+        client.try_cancel_scheduled(&schedule_id, &stranger),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.cancel_scheduled(&schedule_id, &patient);
+
+    env.ledger().with_mut(|li| li.timestamp = 5_000);
+    assert_eq!(
+        client.try_execute_scheduled(&schedule_id),
+        Err(Ok(ContractError::ScheduleNotFound))
+    );
+}
+
+#[test]
+fn test_token_limits_enforced_at_mint() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let other_issuer = Address::generate(&env);
+    client.add_issuer(&other_issuer);
+    let patient = Address::generate(&env);
+
+    client.set_token_limits(&3, &2);
+    mint(&env, &client, &issuer, &patient);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    assert_eq!(
+        client.try_mint_consent(
+            &issuer,
+            &patient,
+            &String::from_str(&env, "ipfs://QmThird"),
+            &String::from_str(&env, "treatment"),
+            &0
+        ),
+        Err(Ok(ContractError::IssuerLimitReached))
+    );
+
+    // Another issuer still has room under the per-owner cap
+    mint(&env, &client, &other_issuer, &patient);
+    assert_eq!(
+        client.try_mint_consent(
+            &other_issuer,
+            &patient,
+            &String::from_str(&env, "ipfs://QmFourth"),
+            &String::from_str(&env, "treatment"),
+            &0
+        ),
+        Err(Ok(ContractError::OwnerLimitReached))
+    );
+
+    // Transferring a token away frees a slot for both limits
+    let proxy = Address::generate(&env);
+    client.transfer(&patient, &proxy, &token_id);
+    mint(&env, &client, &issuer, &patient);
+    assert_eq!(client.tokens_of_owner(&patient).len(), 3);
+}
+
+#[test]
+fn test_patient_requested_consent() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://QmSelfService");
+    let research = String::from_str(&env, "research");
+
+    let request_id = client.request_consent(&patient, &issuer, &uri, &research, &0);
+    assert_eq!(
+        client.get_request(&request_id).status,
+        RequestStatus::Pending
+    );
+    assert_eq!(client.tokens_of_owner(&patient).len(), 0);
+
+    let token_id = client.approve_request(&request_id);
+    let request = client.get_request(&request_id);
+    assert_eq!(request.status, RequestStatus::Approved);
+    assert_eq!(request.token_id, Some(token_id));
+    assert_eq!(client.owner_of(&token_id), patient);
+    assert_eq!(client.get_metadata(&token_id).issuer, issuer);
+
+    // Requests can only be acted on once
+    assert_eq!(
+        client.try_approve_request(&request_id),
+        Err(Ok(ContractError::RequestNotPending))
+    );
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_request_consent(&patient, &stranger, &uri, &research, &0),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_stale_and_rejected_requests() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://QmSelfService");
+    let treatment = String::from_str(&env, "treatment");
+
+    client.set_request_window(&3_600);
+    let stale_id = client.request_consent(&patient, &issuer, &uri, &treatment, &0);
+    let rejected_id = client.request_consent(&patient, &issuer, &uri, &treatment, &0);
+    client.reject_request(&rejected_id);
+    assert_eq!(
+        client.get_request(&rejected_id).status,
+        RequestStatus::Rejected
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 3_601);
+    assert_eq!(client.get_request(&stale_id).status, RequestStatus::Expired);
+    assert_eq!(
+        client.try_approve_request(&stale_id),
+        Err(Ok(ContractError::RequestExpired))
+    );
+    assert_eq!(client.tokens_of_owner(&patient).len(), 0);
+}
+
+#[test]
+fn test_transfer_whitelist() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let proxy = Address::generate(&env);
+    let family_member = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let first = mint(&env, &client, &issuer, &patient);
+    let second = mint(&env, &client, &issuer, &patient);
+
+    // Global whitelist of registered proxies
+    client.set_transfer_whitelist(&None, &vec![&env, proxy.clone()]);
+    assert_eq!(
+        client.try_transfer(&patient, &stranger, &first),
+        Err(Ok(ContractError::RecipientNotAllowed))
+    );
+
+    // A per-token whitelist overrides the global one
+    client.set_transfer_whitelist(&Some(second), &vec![&env, family_member.clone()]);
+    assert_eq!(
+        client.try_transfer(&patient, &proxy, &second),
+        Err(Ok(ContractError::RecipientNotAllowed))
+    );
+    client.transfer(&patient, &family_member, &second);
+    client.transfer(&patient, &proxy, &first);
+    assert_eq!(client.owner_of(&first), proxy);
+
+    // Clearing the global whitelist lifts the restriction
+    client.set_transfer_whitelist(&None, &vec![&env]);
+    assert_eq!(client.get_transfer_whitelist(&first), None);
+    client.transfer(&proxy, &stranger, &first);
+}
+
+#[test]
+fn test_auditor_history_export() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let auditor = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    for (timestamp, uri) in [
+        (100, "ipfs://QmV2"),
+        (200, "ipfs://QmV3"),
+        (300, "ipfs://QmV4"),
+    ] {
+        env.ledger().with_mut(|li| li.timestamp = timestamp);
+        client.update_consent(&token_id, &String::from_str(&env, uri));
+    }
+
+    assert_eq!(
+        client.try_export_history_range(&auditor, &token_id, &0, &u64::MAX, &0, &10),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.add_auditor(&auditor);
+    assert!(client.is_auditor(&auditor));
+
+    let window = client.export_history_range(&auditor, &token_id, &100, &300, &0, &10);
+    assert_eq!(window.len(), 3);
+    let page = client.export_history_range(&auditor, &token_id, &100, &300, &1, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().timestamp, 200);
+}
+
+#[test]
+fn test_auditor_revocation_export() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let auditor = Address::generate(&env);
+    client.add_auditor(&auditor);
+    let patient = Address::generate(&env);
+    mint(&env, &client, &issuer, &patient);
+    let revoked = mint(&env, &client, &issuer, &patient);
+    mint(&env, &client, &issuer, &patient);
+
+    env.ledger().with_mut(|li| li.timestamp = 500);
+    client.revoke_consent(&revoked);
+
+    let records = client.export_revocations(&auditor, &0, &10);
+    assert_eq!(records.len(), 1);
+    let record = records.get(0).unwrap();
+    assert_eq!(record.token_id, revoked);
+    assert_eq!(record.revoked_by, patient);
+    assert_eq!(record.timestamp, 500);
+    assert_eq!(client.export_revocations(&auditor, &2, &10).len(), 0);
+
+    client.remove_auditor(&auditor);
+    assert_eq!(
+        client.try_export_revocations(&auditor, &0, &10),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_get_statuses() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let valid = mint(&env, &client, &issuer, &patient);
+    let revoked = mint(&env, &client, &issuer, &patient);
+    let expiring = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmShortLived"),
+        &String::from_str(&env, "research"),
+        &1_000,
+    );
+    client.revoke_consent(&revoked);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    let views = client.get_statuses(&vec![&env, valid, revoked, expiring, 99]);
+    assert_eq!(views.len(), 4);
+    assert_eq!(views.get(0).unwrap().status, ConsentStatus::Valid);
+    assert_eq!(views.get(1).unwrap().status, ConsentStatus::Revoked);
+    assert_eq!(views.get(2).unwrap().status, ConsentStatus::Expired);
+    assert_eq!(views.get(2).unwrap().expiry_timestamp, 1_000);
+    assert_eq!(views.get(3).unwrap().status, ConsentStatus::NotFound);
+
+    let mut too_many = Vec::new(&env);
+    for token_id in 0..=MAX_BATCH_SIZE as u64 {
+        too_many.push_back(token_id);
+    }
+    assert_eq!(
+        client.try_get_statuses(&too_many),
+        Err(Ok(ContractError::QuotaExceeded))
+    );
+}
+
+#[test]
+fn test_revoke_batch() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let other_issuer = Address::generate(&env);
+    client.add_issuer(&other_issuer);
+    let patient = Address::generate(&env);
+    let first = mint(&env, &client, &issuer, &patient);
+    let second = mint(&env, &client, &issuer, &patient);
+    let foreign = mint(&env, &client, &other_issuer, &patient);
+    client.revoke_consent(&second);
+
+    let reason = String::from_str(&env, "breached form template v3");
+    let result = client.revoke_batch(&issuer, &vec![&env, first, second, foreign, 99], &reason);
+    assert_eq!(result.succeeded, vec![&env, first]);
+    assert_eq!(result.failed, vec![&env, second, foreign, 99]);
+    assert!(client.is_revoked(&first));
+    assert!(!client.is_revoked(&foreign));
+
+    // The admin can revoke regardless of issuer
+    let result = client.revoke_batch(&admin, &vec![&env, foreign], &reason);
+    assert_eq!(result.succeeded, vec![&env, foreign]);
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_revoke_batch(&stranger, &vec![&env, first], &reason),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_subscriber_tagged_events() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let clinic = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let issued = Symbol::new(&env, "issued");
+
+    assert_eq!(
+        client.try_subscribe(&clinic, &vec![&env, Symbol::new(&env, "minted")]),
+        Err(Ok(ContractError::UnknownAction))
+    );
+    client.subscribe(&clinic, &vec![&env, issued.clone()]);
+    assert_eq!(client.get_subscribers(&issued), vec![&env, clinic.clone()]);
+
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let treatment = String::from_str(&env, "treatment");
+    let tagged: Vec<Val> = (
+        symbol_short!("consent"),
+        issued.clone(),
+        treatment.clone(),
+        clinic.clone(),
+    )
+        .into_val(&env);
+    let tagged_count = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(_, topics, _)| *topics == tagged)
+        .count();
+    assert_eq!(tagged_count, 1);
+
+    // Actions the clinic didn't subscribe to aren't tagged
+    client.revoke_consent(&token_id);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics.len(), 3);
+
+    client.unsubscribe(&clinic, &vec![&env, issued.clone()]);
+    assert_eq!(client.get_subscribers(&issued).len(), 0);
+}
+
+#[test]
+fn test_legal_record() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let license_hash = BytesN::from_array(&env, &[1; 32]);
+    let name_hash = BytesN::from_array(&env, &[2; 32]);
+
+    assert_eq!(
+        client.try_get_legal_record(&token_id),
+        Err(Ok(ContractError::LegalRecordNotFound))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 42);
+    client.set_legal_record(&token_id, &license_hash, &3, &name_hash);
+    let record = client.get_legal_record(&token_id);
+    assert_eq!(record.license_hash, license_hash);
+    assert_eq!(record.terms_version, 3);
+    assert_eq!(record.signed_by_name_hash, name_hash);
+    assert_eq!(record.recorded_timestamp, 42);
+
+    // Records are write-once
+    assert_eq!(
+        client.try_set_legal_record(&token_id, &license_hash, &4, &name_hash),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+}
+
+#[test]
+fn test_dry_run_checks() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let proxy = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let treatment = String::from_str(&env, "treatment");
+
+    assert_eq!(
+        client.try_can_mint(&issuer, &patient, &treatment, &0),
+        Ok(Ok(()))
+    );
+    assert_eq!(
+        client.try_can_mint(&stranger, &patient, &treatment, &0),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_can_mint(&issuer, &patient, &String::from_str(&env, "cosmetic"), &0),
+        Err(Ok(ContractError::UnknownConsentType))
+    );
+    client.set_token_limits(&1, &0);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    assert_eq!(
+        client.try_can_mint(&issuer, &patient, &treatment, &0),
+        Err(Ok(ContractError::OwnerLimitReached))
+    );
+
+    client.set_transfer_whitelist(&None, &vec![&env, proxy.clone()]);
+    assert_eq!(
+        client.try_can_transfer(&patient, &proxy, &token_id),
+        Ok(Ok(()))
+    );
+    assert_eq!(
+        client.try_can_transfer(&patient, &stranger, &token_id),
+        Err(Ok(ContractError::RecipientNotAllowed))
+    );
+    assert_eq!(
+        client.try_can_transfer(&stranger, &proxy, &token_id),
+        Err(Ok(ContractError::NotTokenOwner))
+    );
+
+    // Dry runs don't change anything
+    assert_eq!(client.owner_of(&token_id), patient);
+}
+
+#[test]
+fn test_institution_namespacing() {
+    let env = Env::default();
+    let (client, _, global_issuer) = setup(&env);
+    let hospital_admin = Address::generate(&env);
+    let clinician = Address::generate(&env);
+    let patient = Address::generate(&env);
+
+    let hospital =
+        client.create_institution(&String::from_str(&env, "Nairobi General"), &hospital_admin);
+    client.add_institution_issuer(&hospital_admin, &hospital, &clinician);
+    assert!(client.is_issuer(&clinician));
+    assert_eq!(client.get_issuer_institution(&clinician), Some(hospital));
+
+    let token_id = mint(&env, &client, &clinician, &patient);
+    assert_eq!(client.get_token_institution(&token_id), Some(hospital));
+    let global_token = mint(&env, &client, &global_issuer, &patient);
+    assert_eq!(client.get_token_institution(&global_token), None);
+
+    // Institution policy: research only, quota of two tokens
+    client.set_institution_consent_types(
+        &hospital_admin,
+        &hospital,
+        &vec![&env, String::from_str(&env, "research")],
+    );
+    assert_eq!(
+        client.try_can_mint(
+            &clinician,
+            &patient,
+            &String::from_str(&env, "treatment"),
+            &0
+        ),
+        Err(Ok(ContractError::UnknownConsentType))
+    );
+    client.set_institution_quota(&hospital, &2);
+    client.mint_consent(
+        &clinician,
+        &patient,
+        &String::from_str(&env, "ipfs://QmStudy"),
+        &String::from_str(&env, "research"),
+        &0,
+    );
+    assert_eq!(client.get_institution(&hospital).tokens_issued, 2);
+    assert_eq!(
+        client.try_can_mint(
+            &clinician,
+            &patient,
+            &String::from_str(&env, "research"),
+            &0
+        ),
+        Err(Ok(ContractError::QuotaExceeded))
+    );
+
+    // Another institution's admin can't manage this one
+    let other_admin = Address::generate(&env);
+    client.create_institution(&String::from_str(&env, "Mombasa Clinic"), &other_admin);
+    assert_eq!(
+        client.try_add_institution_issuer(&other_admin, &hospital, &Address::generate(&env)),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+
+    client.remove_institution_issuer(&hospital_admin, &hospital, &clinician);
+    assert!(!client.is_issuer(&clinician));
+    assert_eq!(
+        client.try_get_institution(&7),
+        Err(Ok(ContractError::InstitutionNotFound))
+    );
+}
+
+#[test]
+fn test_institution_admin_cannot_take_over_global_issuers() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let hospital_admin = Address::generate(&env);
+    let hospital =
+        client.create_institution(&String::from_str(&env, "Nairobi General"), &hospital_admin);
+
+    // An issuer the contract admin added can't be adopted...
+    assert_eq!(
+        client.try_add_institution_issuer(&hospital_admin, &hospital, &issuer),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    // ...nor removed from the global list through the institution
+    assert_eq!(
+        client.try_remove_institution_issuer(&hospital_admin, &hospital, &issuer),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert!(client.is_issuer(&issuer));
+    assert_eq!(client.get_issuer_institution(&issuer), None);
+
+    // Issuers the institution added itself are removed from the global list
+    let clinician = Address::generate(&env);
+    client.add_institution_issuer(&hospital_admin, &hospital, &clinician);
+    client.add_institution_issuer(&hospital_admin, &hospital, &clinician);
+    client.remove_institution_issuer(&hospital_admin, &hospital, &clinician);
+    assert!(!client.is_issuer(&clinician));
+}
+
+#[test]
+fn test_institution_admin_cannot_restore_removed_issuer() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+    let hospital_admin = Address::generate(&env);
+    let hospital =
+        client.create_institution(&String::from_str(&env, "Nairobi General"), &hospital_admin);
+    let clinician = Address::generate(&env);
+    client.add_institution_issuer(&hospital_admin, &hospital, &clinician);
+
+    // The contract admin's removal also ends the institution binding...
+    client.remove_issuer(&clinician);
+    assert!(!client.is_issuer(&clinician));
+    assert_eq!(client.get_issuer_institution(&clinician), None);
+    // ...and the institution can't put the issuer back
+    assert_eq!(
+        client.try_add_institution_issuer(&hospital_admin, &hospital, &clinician),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert!(!client.is_issuer(&clinician));
+
+    // Once the contract admin reinstates it, it's a global issuer again
+    client.add_issuer(&clinician);
+    assert!(client.is_issuer(&clinician));
+    assert_eq!(
+        client.try_add_institution_issuer(&hospital_admin, &hospital, &clinician),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_cross_institution_recognition() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+    let admin_a = Address::generate(&env);
+    let admin_b = Address::generate(&env);
+    let admin_c = Address::generate(&env);
+    let inst_a = client.create_institution(&String::from_str(&env, "Hospital A"), &admin_a);
+    let inst_b = client.create_institution(&String::from_str(&env, "Hospital B"), &admin_b);
+    let inst_c = client.create_institution(&String::from_str(&env, "Hospital C"), &admin_c);
+    let clinician = Address::generate(&env);
+    client.add_institution_issuer(&admin_a, &inst_a, &clinician);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &clinician, &patient);
+
+    assert!(client.is_recognized(&token_id, &inst_a));
+    assert!(!client.is_recognized(&token_id, &inst_b));
+
+    client.create_recognition(&inst_a, &inst_b, &0b0101);
+    assert_eq!(client.get_recognition(&inst_b, &inst_a), 0b0101);
+    assert!(client.is_recognized(&token_id, &inst_b));
+    assert_eq!(client.get_recognized_scopes(&token_id, &inst_b), 0b0101);
+    assert!(!client.is_recognized(&token_id, &inst_c));
+
+    assert_eq!(
+        client.try_remove_recognition(&admin_c, &inst_a, &inst_b),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.remove_recognition(&admin_b, &inst_a, &inst_b);
+    assert!(!client.is_recognized(&token_id, &inst_b));
+
+    client.create_recognition(&inst_a, &inst_b, &ALL_SCOPES);
+    client.revoke_consent(&token_id);
+    assert!(!client.is_recognized(&token_id, &inst_b));
+}
+
+#[test]
+fn test_patient_preferences() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://QmPrefs");
+    let research = String::from_str(&env, "research");
+    let treatment = String::from_str(&env, "treatment");
+    let thirty_days = 30 * 24 * 60 * 60;
+
+    client.set_preferences(
+        &patient,
+        &PatientPrefs {
+            blocked_consent_types: vec![&env, research.clone()],
+            max_grant_duration: thirty_days,
+        },
+    );
+    assert_eq!(
+        client.try_mint_consent(&issuer, &patient, &uri, &research, &0),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+
+    // Permanent and over-long consents are shortened to the patient's maximum
+    clock::set(&env, 1_000);
+    let token_id = client.mint_consent(&issuer, &patient, &uri, &treatment, &0);
+    assert_eq!(
+        client.get_metadata(&token_id).expiry_timestamp,
+        1_000 + thirty_days
+    );
+    let short = client.mint_consent(&issuer, &patient, &uri, &treatment, &5_000);
+    assert_eq!(client.get_metadata(&short).expiry_timestamp, 5_000);
+
+    // Other patients are unaffected
+    let other = Address::generate(&env);
+    assert_eq!(client.get_preferences(&other), None);
+    client.mint_consent(&issuer, &other, &uri, &research, &0);
+}
+
+#[test]
+fn test_patient_preferences_bound_grants() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let researcher = Address::generate(&env);
+    let justification = BytesN::from_array(&env, &[7; 32]);
+    let uri = String::from_str(&env, "ipfs://QmPrefs");
+    let thirty_days = 30 * 24 * 60 * 60;
+    clock::set(&env, 1_000);
+    // Both consents predate the preferences
+    let research = client.mint_consent(
+        &issuer,
+        &patient,
+        &uri,
+        &String::from_str(&env, "research"),
+        &0,
+    );
+    let treatment = mint(&env, &client, &issuer, &patient);
+    let request_id = client.request_access(&research, &researcher, &1, &justification);
+    client.set_preferences(
+        &patient,
+        &PatientPrefs {
+            blocked_consent_types: vec![&env, String::from_str(&env, "research")],
+            max_grant_duration: thirty_days,
+        },
+    );
+
+    // Blocked types can't be granted on, directly or through a request
+    assert_eq!(
+        client.try_grant_access(&research, &patient, &doctor, &AccessLevel::Read, &0),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    assert_eq!(
+        client.try_approve_access_request(&request_id),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    assert_eq!(client.get_grant(&research, &researcher), None);
+
+    // Grants are capped at the patient's maximum duration
+    client.grant_access(&treatment, &patient, &doctor, &AccessLevel::Share, &0);
+    assert_eq!(
+        client.get_grant(&treatment, &doctor).unwrap().expiry,
+        1_000 + thirty_days
+    );
+    let request_id = client.request_access(&treatment, &researcher, &1, &justification);
+    client.approve_access_request(&request_id);
+    assert_eq!(
+        client.get_grant(&treatment, &researcher).unwrap().expiry,
+        1_000 + thirty_days
+    );
+
+    // Sub-grants stay within their grantor's capped grant
+    clock::advance(&env, 1_000);
+    let nurse = Address::generate(&env);
+    client.grant_access(&treatment, &doctor, &nurse, &AccessLevel::Read, &0);
+    assert_eq!(
+        client.get_grant(&treatment, &nurse).unwrap().expiry,
+        1_000 + thirty_days
+    );
+}
+
+#[test]
+fn test_pseudonymous_mint_and_claim() {
+    use soroban_sdk::xdr::ToXdr;
+
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let preimage = Bytes::from_slice(&env, b"salt:4f1c|national-id:12345678");
+    let commitment = env.crypto().sha256(&preimage);
+
+    let token_id = client.mint_pseudonymous(
+        &issuer,
+        &commitment,
+        &String::from_str(&env, "ipfs://QmPaperConsent"),
+        &String::from_str(&env, "treatment"),
+        &0,
+    );
+    assert!(client.is_claimable(&token_id));
+    assert_eq!(client.owner_of(&token_id), client.address);
+
+    let patient = Address::generate(&env);
+    assert_eq!(
+        client.try_claim(&token_id, &Bytes::from_slice(&env, b"wrong"), &patient),
+        Err(Ok(ContractError::CommitmentMismatch))
+    );
+
+    let mut bound = preimage.clone();
+    bound.append(&patient.clone().to_xdr(&env));
+    client.commit_claim(&token_id, &patient, &env.crypto().sha256(&bound));
+    clock::advance(&env, 5);
+    client.claim(&token_id, &preimage, &patient);
+    assert_eq!(client.owner_of(&token_id), patient);
+    assert_eq!(client.get_metadata(&token_id).patient, patient);
+    assert_eq!(client.tokens_of_owner(&patient), vec![&env, token_id]);
+    assert_eq!(client.tokens_of_owner(&client.address).len(), 0);
+    assert!(!client.is_claimable(&token_id));
+
+    // Claiming is one-time; the patient now controls the consent as usual
+    assert_eq!(
+        client.try_claim(&token_id, &preimage, &Address::generate(&env)),
+        Err(Ok(ContractError::NotClaimable))
+    );
+    client.revoke_consent(&token_id);
+}
+
+#[test]
+fn test_claimed_consent_keeps_its_inactivity_window() {
+    use soroban_sdk::xdr::ToXdr;
+
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let preimage = Bytes::from_slice(&env, b"salt:4f1c|national-id:12345678");
+    clock::set(&env, 1_000);
+    let token_id = client.mint_pseudonymous(
+        &issuer,
+        &env.crypto().sha256(&preimage),
+        &String::from_str(&env, "ipfs://QmPaperConsent"),
+        &String::from_str(&env, "treatment"),
+        &0,
+    );
+    client.set_inactivity_policy(&issuer, &token_id, &500);
+
+    let patient = Address::generate(&env);
+    let mut bound = preimage.clone();
+    bound.append(&patient.clone().to_xdr(&env));
+    client.commit_claim(&token_id, &patient, &env.crypto().sha256(&bound));
+    clock::advance(&env, 100);
+    client.claim(&token_id, &preimage, &patient);
+
+    // The patient has never sent a heartbeat, but claiming counts as one
+    assert_eq!(client.get_last_heartbeat(&patient), clock::now(&env));
+    assert_eq!(
+        client.try_auto_revoke_inactive(&token_id),
+        Err(Ok(ContractError::PatientActive))
+    );
+    clock::advance(&env, 501);
+    client.auto_revoke_inactive(&token_id);
+    assert!(client.is_revoked(&token_id));
+}
+
+#[test]
+fn test_pseudonymous_claim_cannot_be_front_run() {
+    use soroban_sdk::xdr::ToXdr;
+
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let preimage = Bytes::from_slice(&env, b"salt:4f1c|national-id:12345678");
+    let token_id = client.mint_pseudonymous(
+        &issuer,
+        &env.crypto().sha256(&preimage),
+        &String::from_str(&env, "ipfs://QmPaperConsent"),
+        &String::from_str(&env, "treatment"),
+        &0,
+    );
+    let claim_hash = |claimant: &Address| {
+        let mut bound = preimage.clone();
+        bound.append(&claimant.clone().to_xdr(&env));
+        env.crypto().sha256(&bound)
+    };
+    let patient = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let system = symbol_short!("ICD10");
+    let code = ClinicalCode {
+        system: system.clone(),
+        code: String::from_str(&env, "E11.9"),
+    };
+    client.register_code_system(&system);
+    client.tag_consent(&issuer, &token_id, &code);
+
+    client.commit_claim(&token_id, &patient, &claim_hash(&patient));
+    // The claim can't be revealed in the ledger it was committed in
+    assert_eq!(
+        client.try_claim(&token_id, &preimage, &patient),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    clock::advance(&env, 5);
+
+    // Once the preimage is public, an attacker can't use it without a
+    // commitment of its own made in an earlier ledger
+    assert_eq!(
+        client.try_claim(&token_id, &preimage, &attacker),
+        Err(Ok(ContractError::CommitmentMismatch))
+    );
+    client.commit_claim(&token_id, &attacker, &claim_hash(&attacker));
+    assert_eq!(
+        client.try_claim(&token_id, &preimage, &attacker),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    // Another claimant's commitment doesn't work for the attacker either
+    client.commit_claim(&token_id, &attacker, &claim_hash(&patient));
+    clock::advance(&env, 5);
+    assert_eq!(
+        client.try_claim(&token_id, &preimage, &attacker),
+        Err(Ok(ContractError::CommitmentMismatch))
+    );
+
+    client.claim(&token_id, &preimage, &patient);
+    assert_eq!(client.owner_of(&token_id), patient);
+    assert_eq!(
+        client.find_tokens_by_code(&patient, &code),
+        vec![&env, token_id]
+    );
+    assert!(client
+        .find_tokens_by_code(&client.address, &code)
+        .is_empty());
+    assert_eq!(
+        client.try_commit_claim(&token_id, &attacker, &claim_hash(&attacker)),
+        Err(Ok(ContractError::NotClaimable))
+    );
+}
+
+#[test]
+fn test_inactivity_auto_revoke() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    assert_eq!(
+        client.try_auto_revoke_inactive(&token_id),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.set_inactivity_policy(&issuer, &token_id, &500);
+    assert_eq!(client.get_inactivity_policy(&token_id), 500);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_400);
+    client.heartbeat(&patient);
+    env.ledger().with_mut(|li| li.timestamp = 1_800);
+    assert_eq!(
+        client.try_auto_revoke_inactive(&token_id),
+        Err(Ok(ContractError::PatientActive))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_901);
+    client.auto_revoke_inactive(&token_id);
+    assert!(client.is_revoked(&token_id));
+    let history = client.get_history(&token_id);
+    assert_eq!(history.last().unwrap().actor, client.address);
+}
+
+#[test]
+fn test_posthumous_release() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let oracle = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let executor = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    assert_eq!(
+        client.try_attest_death(&patient),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.set_death_oracle(&oracle);
+
+    client.designate_executor(&token_id, &executor);
+    assert_eq!(
+        client.get_posthumous_status(&token_id),
+        PosthumousStatus::Dormant
+    );
+    assert!(!client.is_valid(&token_id));
+    assert_eq!(client.executor_scopes(&token_id, &executor), 0);
+    assert_eq!(
+        client
+            .get_statuses(&vec![&env, token_id])
+            .get(0)
+            .unwrap()
+            .status,
+        ConsentStatus::Dormant
+    );
+
+    client.attest_death(&patient);
+    assert_eq!(
+        client.get_posthumous_status(&token_id),
+        PosthumousStatus::Released
+    );
+    assert!(client.is_valid(&token_id));
+    assert_eq!(client.executor_scopes(&token_id, &executor), ALL_SCOPES);
+    assert_eq!(
+        client.executor_scopes(&token_id, &Address::generate(&env)),
+        0
+    );
+}
+
+// Stand-in for the outbox contract that records what it receives
+#[contract]
+struct MockOutbox;
+
+#[contractimpl]
+impl MockOutbox {
+    pub fn push(env: Env, writer: Address, action: Symbol, subject_id: u64) -> u64 {
+        writer.require_auth();
+        let mut pushed: Vec<(Symbol, u64)> = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("pushed"))
+            .unwrap_or(Vec::new(&env));
+        pushed.push_back((action, subject_id));
+        env.storage()
+            .instance()
+            .set(&symbol_short!("pushed"), &pushed);
+        pushed.len() as u64 - 1
+    }
+
+    pub fn pushed(env: Env) -> Vec<(Symbol, u64)> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("pushed"))
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+#[test]
+fn test_state_changes_written_to_outbox() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let outbox_id = env.register_contract(None, MockOutbox);
+    let outbox = MockOutboxClient::new(&env, &outbox_id);
+    let patient = Address::generate(&env);
+
+    mint(&env, &client, &issuer, &patient);
+    client.set_outbox(&outbox_id);
+    assert_eq!(client.get_outbox(), Some(outbox_id.clone()));
+
+    let token_id = mint(&env, &client, &issuer, &patient);
+    client.update_consent(&token_id, &String::from_str(&env, "ipfs://QmV2"));
+    client.revoke_consent(&token_id);
+    assert_eq!(
+        outbox.pushed(),
+        vec![
+            &env,
+            (symbol_short!("issued"), token_id),
+            (symbol_short!("updated"), token_id),
+            (symbol_short!("revoked"), token_id),
+        ]
+    );
+
+    // An outbox that rejects writes doesn't block consent operations
+    client.set_outbox(&Address::generate(&env));
+    mint(&env, &client, &issuer, &patient);
+    client.clear_outbox();
+    assert_eq!(client.get_outbox(), None);
+}
+
+#[test]
+fn test_history_records_metadata_diff() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let old_uri = client.get_metadata(&token_id).metadata_uri;
+    let new_uri = String::from_str(&env, "ipfs://QmAmended");
+
+    client.update_consent(&token_id, &new_uri);
+
+    let history = client.get_history(&token_id);
+    let issued = history.get(0).unwrap();
+    assert_eq!(issued.changed_fields, 0);
+    assert_eq!(issued.new_uri, old_uri);
+
+    let updated = history.get(1).unwrap();
+    assert_eq!(updated.changed_fields, CHANGED_URI);
+    assert_eq!(updated.old_uri, old_uri);
+    assert_eq!(updated.new_uri, new_uri);
+    assert_eq!(updated.metadata_uri, new_uri);
+    assert_eq!(updated.old_expiry, updated.new_expiry);
+}
+
+#[test]
+fn test_per_scope_revocation() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let lab_results = 1 << 0;
+    let imaging = 1 << 1;
+
+    assert!(client.is_scope_valid(&token_id, &(lab_results | imaging)));
+    client.revoke_scope(&token_id, &imaging);
+    assert!(client.is_scope_valid(&token_id, &lab_results));
+    assert!(!client.is_scope_valid(&token_id, &imaging));
+    assert!(!client.is_scope_valid(&token_id, &(lab_results | imaging)));
+    assert!(client.is_valid(&token_id));
+    assert_eq!(client.get_scopes(&token_id), ALL_SCOPES & !imaging);
+
+    let entry = client.get_history(&token_id).last().unwrap();
+    assert_eq!(entry.changed_fields, CHANGED_SCOPES);
+    assert_eq!(entry.old_scopes, ALL_SCOPES);
+    assert_eq!(entry.new_scopes, ALL_SCOPES & !imaging);
+
+    assert_eq!(
+        client.try_revoke_scope(&token_id, &imaging),
+        Err(Ok(ContractError::ScopeNotGranted))
+    );
+    client.revoke_consent(&token_id);
+    assert!(!client.is_scope_valid(&token_id, &lab_results));
+}
+
+#[test]
+fn test_render_token_json() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let expiring = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmStudy"),
+        &String::from_str(&env, "research"),
+        &1_700_000_000,
+    );
+
+    assert_eq!(
+        client.render_token_json(&token_id),
+        String::from_str(
+            &env,
+            "{\"name\":\"Consent #0\",\"type\":\"treatment\",\"status\":\"valid\",\"expiry\":0}"
+        )
+    );
+    client.revoke_consent(&expiring);
+    assert_eq!(
+        client.render_token_json(&expiring),
+        String::from_str(
+            &env,
+            "{\"name\":\"Consent #1\",\"type\":\"research\",\"status\":\"revoked\",\"expiry\":1700000000}"
+        )
+    );
+}
+
+#[test]
+fn test_render_token_json_escapes_control_characters() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let consent_type = String::from_str(&env, "line\none \"two\"\t");
+    client.add_consent_type(&consent_type);
+    let token_id = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmStudy"),
+        &consent_type,
+        &u64::MAX,
+    );
+
+    assert_eq!(
+        client.render_token_json(&token_id),
+        String::from_str(
+            &env,
+            "{\"name\":\"Consent #0\",\"type\":\"line\\u000aone \\\"two\\\"\\u0009\",\"status\":\"valid\",\"expiry\":18446744073709551615}"
+        )
+    );
+
+    // A maximum-length type made only of control characters still fits
+    let worst = String::from_bytes(&env, &[0x01; 64]);
+    client.add_consent_type(&worst);
+    let token_id = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmStudy"),
+        &worst,
+        &u64::MAX,
+    );
+    // Fixed fields, a one-digit id, a 20-digit expiry and `valid`
+    assert_eq!(
+        client.render_token_json(&token_id).len(),
+        52 + 1 + 20 + 5 + 6 * 64
+    );
+}
+
+#[test]
+fn test_metadata_redaction() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let full = client.get_metadata(&token_id);
+
+    let public = client.get_metadata_redacted(&token_id, &verifier);
+    assert!(public.redacted);
+    assert_eq!(public.metadata.metadata_uri, String::from_str(&env, ""));
+    assert_eq!(public.metadata.issuer, client.address);
+    assert_eq!(public.metadata.consent_type, full.consent_type);
+    assert_eq!(public.metadata.expiry_timestamp, full.expiry_timestamp);
+
+    for caller in [patient, issuer, admin] {
+        let view = client.get_metadata_redacted(&token_id, &caller);
+        assert!(!view.redacted);
+        assert_eq!(view.metadata, full);
+    }
+
+    client.add_auditor(&verifier);
+    assert!(!client.get_metadata_redacted(&token_id, &verifier).redacted);
+}
+
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum MockTeamError {
+    TeamNotFound = 2,
+}
+
+// Stand-in for the care_teams contract with directly settable membership
+#[contract]
+struct MockCareTeams;
+
+#[contractimpl]
+impl MockCareTeams {
+    pub fn set_team(env: Env, team_id: u64, patient: Address) {
+        env.storage().instance().set(&team_id, &patient);
+    }
+
+    pub fn set_member(env: Env, team_id: u64, member: Address, active: bool) {
+        env.storage().instance().set(&(team_id, member), &active);
+    }
+
+    pub fn is_member(env: Env, team_id: u64, member: Address) -> bool {
+        env.storage()
+            .instance()
+            .get(&(team_id, member))
+            .unwrap_or(false)
+    }
+
+    pub fn get_team_patient(env: Env, team_id: u64) -> Result<Address, MockTeamError> {
+        env.storage()
+            .instance()
+            .get(&team_id)
+            .ok_or(MockTeamError::TeamNotFound)
+    }
+}
+
+#[test]
+fn test_care_team_grant_follows_membership() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let teams_id = env.register_contract(None, MockCareTeams);
+    let teams = MockCareTeamsClient::new(&env, &teams_id);
+    let patient = Address::generate(&env);
+    let nurse = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    assert_eq!(
+        client.try_grant_to_care_team(&token_id, &0),
+        Err(Ok(ContractError::NotInitialized))
+    );
+    client.set_care_team_registry(&teams_id);
+    assert_eq!(
+        client.try_grant_to_care_team(&token_id, &0),
+        Err(Ok(ContractError::CareTeamNotFound))
+    );
+    // A team caring for someone else can't be granted the consent
+    teams.set_team(&1, &Address::generate(&env));
+    assert_eq!(
+        client.try_grant_to_care_team(&token_id, &1),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+
+    teams.set_team(&0, &patient);
+    client.grant_to_care_team(&token_id, &0);
+    assert_eq!(client.get_token_care_team(&token_id), Some(0));
+    assert!(!client.has_care_team_access(&token_id, &nurse));
+
+    // Membership changes apply without touching the consent
+    teams.set_member(&0, &nurse, &true);
+    assert!(client.has_care_team_access(&token_id, &nurse));
+    teams.set_member(&0, &nurse, &false);
+    assert!(!client.has_care_team_access(&token_id, &nurse));
+
+    teams.set_member(&0, &nurse, &true);
+    client.revoke_care_team_grant(&token_id);
+    assert!(!client.has_care_team_access(&token_id, &nurse));
+    assert_eq!(
+        client.try_revoke_care_team_grant(&token_id),
+        Err(Ok(ContractError::CareTeamNotFound))
+    );
+}
+
+#[test]
+fn test_link_addresses() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let primary = Address::generate(&env);
+    let old_wallet = Address::generate(&env);
+    let first = mint(&env, &client, &issuer, &primary);
+    let second = mint(&env, &client, &issuer, &old_wallet);
+
+    client.link_addresses(&primary, &old_wallet);
+    assert_eq!(
+        client.get_primary_address(&old_wallet),
+        Some(primary.clone())
+    );
+    assert_eq!(client.tokens_of_linked(&primary), vec![&env, first, second]);
+    assert_eq!(
+        client.consents_of_linked(&primary),
+        vec![&env, first, second]
+    );
+    assert_eq!(
+        client.try_link_addresses(&Address::generate(&env), &old_wallet),
+        Err(Ok(ContractError::AlreadyLinked))
+    );
+    // Links don't chain
+    assert_eq!(
+        client.try_link_addresses(&old_wallet, &Address::generate(&env)),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+
+    assert_eq!(
+        client.try_unlink_addresses(&Address::generate(&env), &old_wallet),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.unlink_addresses(&admin, &old_wallet);
+    assert_eq!(client.tokens_of_linked(&primary), vec![&env, first]);
+    assert_eq!(client.get_primary_address(&old_wallet), None);
+
+    let log = client.get_link_log(&primary);
+    assert_eq!(log.len(), 2);
+    assert!(log.get(0).unwrap().linked);
+    assert!(!log.get(1).unwrap().linked);
+    assert_eq!(log.get(1).unwrap().actor, admin);
+}
+
+#[test]
+fn test_social_recovery() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let owner = Address::generate(&env);
+    let new_key = Address::generate(&env);
+    let contacts = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let token_id = mint(&env, &client, &issuer, &owner);
+    client.set_recovery_circle(
+        &owner,
+        &vec![
+            &env,
+            contacts[0].clone(),
+            contacts[1].clone(),
+            contacts[2].clone(),
+        ],
+        &2,
+        &1_000,
+    );
+
+    // The original key can cancel while the window is open
+    client.initiate_recovery(&contacts[0], &owner, &new_key);
+    client.cancel_recovery(&owner);
+    assert_eq!(client.get_recovery_request(&owner), None);
+
+    client.initiate_recovery(&contacts[0], &owner, &new_key);
+    assert_eq!(
+        client.try_execute_recovery(&owner),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    client.approve_recovery(&contacts[1], &owner);
+    assert_eq!(
+        client.try_execute_recovery(&owner),
+        Err(Ok(ContractError::NotYetActive))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    assert_eq!(
+        client.try_approve_recovery(&contacts[2], &owner),
+        Err(Ok(ContractError::RequestExpired))
+    );
+    assert_eq!(client.execute_recovery(&owner), 1);
+    assert_eq!(client.owner_of(&token_id), new_key);
+    assert_eq!(client.get_metadata(&token_id).patient, new_key);
+    assert_eq!(client.tokens_of_owner(&owner).len(), 0);
+    assert!(client.get_recovery_circle(&new_key).is_some());
+
+    // The recovered key controls the consent from now on
+    client.revoke_consent(&token_id);
+    assert!(client.is_revoked(&token_id));
+}
+
+#[test]
+fn test_recovery_moves_every_index() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let owner = Address::generate(&env);
+    let new_key = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let contact = Address::generate(&env);
+    let treatment = String::from_str(&env, "treatment");
+    let system = symbol_short!("ICD10");
+    let code = ClinicalCode {
+        system: system.clone(),
+        code: String::from_str(&env, "E11.9"),
+    };
+    client.register_code_system(&system);
+    client.set_token_limits(&0, &1);
+    let token_id = mint(&env, &client, &issuer, &owner);
+    client.grant_access(&token_id, &owner, &doctor, &AccessLevel::Read, &0);
+    client.tag_consent(&issuer, &token_id, &code);
+    client.set_recovery_circle(&owner, &vec![&env, contact.clone()], &1, &100);
+
+    client.initiate_recovery(&contact, &owner, &new_key);
+    clock::advance(&env, 101);
+    assert_eq!(client.execute_recovery(&owner), 1);
+
+    assert!(client.has_active_consent(&new_key, &treatment));
+    assert!(!client.has_active_consent(&owner, &treatment));
+    assert_eq!(
+        client.effective_access(&new_key, &doctor, &1),
+        Some(token_id)
+    );
+    assert_eq!(client.effective_access(&owner, &doctor, &1), None);
+    assert_eq!(
+        client.find_tokens_by_code(&new_key, &code),
+        vec![&env, token_id]
+    );
+    assert!(client.find_tokens_by_code(&owner, &code).is_empty());
+    // Per-issuer limits follow the token to the new key
+    assert_eq!(
+        client.try_mint_consent(
+            &issuer,
+            &new_key,
+            &String::from_str(&env, "ipfs://QmXxx..."),
+            &treatment,
+            &0,
+        ),
+        Err(Ok(ContractError::IssuerLimitReached))
+    );
+    mint(&env, &client, &issuer, &owner);
+}
+
+#[test]
+fn test_recovered_patient_keeps_inactivity_bound_consents() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let owner = Address::generate(&env);
+    let new_key = Address::generate(&env);
+    let contact = Address::generate(&env);
+    clock::set(&env, 1_000);
+    let token_id = mint(&env, &client, &issuer, &owner);
+    client.set_inactivity_policy(&owner, &token_id, &500);
+    client.set_recovery_circle(&owner, &vec![&env, contact.clone()], &1, &100);
+    client.initiate_recovery(&contact, &owner, &new_key);
+
+    clock::advance(&env, 101);
+    client.execute_recovery(&owner);
+    // The new key starts with a fresh heartbeat instead of none at all
+    assert_eq!(client.get_last_heartbeat(&new_key), clock::now(&env));
+    assert_eq!(
+        client.try_auto_revoke_inactive(&token_id),
+        Err(Ok(ContractError::PatientActive))
+    );
+
+    clock::advance(&env, 501);
+    client.auto_revoke_inactive(&token_id);
+    assert!(client.is_revoked(&token_id));
+}
+
+#[test]
+fn test_recovery_keeps_new_owners_circle() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let owner = Address::generate(&env);
+    let new_key = Address::generate(&env);
+    let contact = Address::generate(&env);
+    let other_contact = Address::generate(&env);
+    mint(&env, &client, &issuer, &owner);
+    client.set_recovery_circle(&owner, &vec![&env, contact.clone()], &1, &100);
+    client.set_recovery_circle(&new_key, &vec![&env, other_contact.clone()], &1, &100);
+
+    // Recovering to an address with its own circle would overwrite it
+    assert_eq!(
+        client.try_initiate_recovery(&contact, &owner, &new_key),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+
+    let fresh_key = Address::generate(&env);
+    client.initiate_recovery(&contact, &owner, &fresh_key);
+    client.set_recovery_circle(&fresh_key, &vec![&env, other_contact.clone()], &1, &100);
+    clock::advance(&env, 101);
+    assert_eq!(
+        client.try_execute_recovery(&owner),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    assert_eq!(
+        client.get_recovery_circle(&fresh_key).unwrap().contacts,
+        vec![&env, other_contact]
+    );
+    assert_eq!(client.tokens_of_owner(&owner).len(), 1);
+}
+
+#[test]
+fn test_residency_restricts_access_region() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    // Unrestricted consents can be accessed anywhere
+    assert!(client.is_access_allowed_in(&token_id, &symbol_short!("US")));
+
+    client.set_residency(
+        &token_id,
+        &vec![&env, symbol_short!("KE"), symbol_short!("UG")],
+    );
+    assert!(client.is_access_allowed_in(&token_id, &symbol_short!("KE")));
+    assert!(!client.is_access_allowed_in(&token_id, &symbol_short!("US")));
+
+    client.set_residency(&token_id, &Vec::new(&env));
+    assert_eq!(client.get_residency(&token_id).len(), 0);
+    assert!(client.is_access_allowed_in(&token_id, &symbol_short!("US")));
+
+    client.revoke_consent(&token_id);
+    assert!(!client.is_access_allowed_in(&token_id, &symbol_short!("KE")));
+}
+
+#[test]
+fn test_template_needs_legal_approval() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let reviewer = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let name = symbol_short!("research");
+    client.add_legal_reviewer(&reviewer);
+
+    let v1 = client.publish_template(
+        &issuer,
+        &name,
+        &String::from_str(&env, "ipfs://QmTemplateV1"),
+        &String::from_str(&env, "research"),
+        &1_000,
+        &Vec::new(&env),
+    );
+    assert_eq!(v1, 1);
+    assert_eq!(
+        client.try_mint_from_template(&issuer, &patient, &name, &v1, &Map::new(&env)),
+        Err(Ok(ContractError::TemplateNotApproved))
+    );
+    // Issuers can't approve their own templates
+    assert_eq!(
+        client.try_approve_template(&issuer, &name, &v1),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+
+    client.approve_template(&reviewer, &name, &v1);
+    assert_eq!(
+        client.get_template_approval(&name, &v1).unwrap().reviewer,
+        reviewer
+    );
+    let token_id = client.mint_from_template(&issuer, &patient, &name, &v1, &Map::new(&env));
+    let metadata = client.get_metadata(&token_id);
+    assert_eq!(
+        metadata.metadata_uri,
+        String::from_str(&env, "ipfs://QmTemplateV1")
+    );
+    assert_eq!(metadata.expiry_timestamp, env.ledger().timestamp() + 1_000);
+    assert_eq!(
+        client.get_token_template(&token_id),
+        Some((name.clone(), v1))
+    );
+
+    // A new version replaces the old one once approved
+    let v2 = client.publish_template(
+        &issuer,
+        &name,
+        &String::from_str(&env, "ipfs://QmTemplateV2"),
+        &String::from_str(&env, "research"),
+        &0,
+        &Vec::new(&env),
+    );
+    client.approve_template(&reviewer, &name, &v2);
+    client.deprecate_template(&admin, &name, &v1);
+    assert_eq!(
+        client.get_template(&name, &v1).status,
+        TemplateStatus::Deprecated
+    );
+    assert_eq!(
+        client.try_mint_from_template(&issuer, &patient, &name, &v1, &Map::new(&env)),
+        Err(Ok(ContractError::TemplateNotApproved))
+    );
+    assert!(client.is_valid(&token_id));
+    assert_eq!(client.get_latest_template_version(&name), 2);
+    client.mint_from_template(&issuer, &patient, &name, &v2, &Map::new(&env));
+}
+
+#[test]
+fn test_error_stats_count_handled_errors() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let other_issuer = Address::generate(&env);
+    client.add_issuer(&other_issuer);
+    let patient = Address::generate(&env);
+    let own = mint(&env, &client, &issuer, &patient);
+    let foreign = mint(&env, &client, &other_issuer, &patient);
+    let reason = String::from_str(&env, "form recalled");
+
+    client.revoke_batch(&issuer, &vec![&env, own, foreign, 99], &reason);
+    client.revoke_batch(&issuer, &vec![&env, own], &reason);
+
+    let stats = client.get_error_stats();
+    assert_eq!(stats.get(ContractError::NotAuthorized as u32), Some(1));
+    assert_eq!(stats.get(ContractError::TokenNotFound as u32), Some(1));
+    assert_eq!(stats.get(ContractError::ConsentRevoked as u32), Some(1));
+
+    client.reset_error_stats();
+    assert_eq!(client.get_error_stats().len(), 0);
+}
+
+#[test]
+fn test_attributed_reads_are_capped() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let scraper = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    // Off by default: reads are anonymous and uncounted
+    client.get_metadata(&token_id);
+    client.get_metadata_as(&scraper, &token_id);
+    assert_eq!(client.get_read_count(&scraper), 0);
+
+    client.set_read_policy(&true, &2);
+    assert_eq!(
+        client.try_get_metadata(&token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_get_history(&token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.get_metadata_as(&scraper, &token_id);
+    assert_eq!(client.get_history_as(&scraper, &token_id).len(), 1);
+    assert_eq!(client.get_read_count(&scraper), 2);
+    assert_eq!(
+        client.try_get_metadata_redacted(&token_id, &scraper),
+        Err(Ok(ContractError::QuotaExceeded))
+    );
+
+    // The allowance resets the next day
+    env.ledger().with_mut(|li| li.timestamp = 86_400);
+    assert_eq!(client.get_read_count(&scraper), 0);
+    client.get_metadata_as(&scraper, &token_id);
+}
+
+#[test]
+fn test_duration_policy_per_type() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let research = String::from_str(&env, "research");
+    let uri = String::from_str(&env, "ipfs://QmResearch");
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.set_duration_policy(&research, &100, &10_000);
+
+    assert_eq!(
+        client.try_mint_consent(&issuer, &patient, &uri, &research, &1_050),
+        Err(Ok(ContractError::ExpiryTooShort))
+    );
+    assert_eq!(
+        client.try_mint_consent(&issuer, &patient, &uri, &research, &20_000),
+        Err(Ok(ContractError::ExpiryTooLong))
+    );
+    // Permanent consents exceed any maximum
+    assert_eq!(
+        client.try_mint_consent(&issuer, &patient, &uri, &research, &0),
+        Err(Ok(ContractError::ExpiryTooLong))
+    );
+    let token_id = client.mint_consent(&issuer, &patient, &uri, &research, &5_000);
+    // Other types are unaffected
+    mint(&env, &client, &issuer, &patient);
+
+    env.ledger().with_mut(|li| li.timestamp = 4_000);
+    assert_eq!(
+        client.try_renew_consent(&token_id, &20_000),
+        Err(Ok(ContractError::ExpiryTooLong))
+    );
+    client.renew_consent(&token_id, &12_000);
+    assert_eq!(client.get_metadata(&token_id).expiry_timestamp, 12_000);
+    let history = client.get_history(&token_id);
+    let entry = history.get(history.len() - 1).unwrap();
+    assert_eq!(entry.old_expiry, 5_000);
+    assert_eq!(entry.changed_fields, CHANGED_EXPIRY);
+}
+
+#[test]
+fn test_access_log() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let purpose = BytesN::from_array(&env, &[7; 32]);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    assert_eq!(
+        client.try_log_access(&verifier, &token_id, &doctor, &purpose),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.add_verifier(&verifier);
+    for _ in 0..3 {
+        client.log_access(&verifier, &token_id, &doctor, &purpose);
+    }
+    assert_eq!(client.get_access_count(&token_id), 3);
+
+    let page = client.get_access_log(&token_id, &1, &5);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap().accessor, doctor);
+    assert_eq!(page.get(0).unwrap().purpose_hash, purpose);
+
+    // Revoked consents can't be used, so access against them isn't logged
+    client.revoke_consent(&token_id);
+    assert_eq!(
+        client.try_log_access(&verifier, &token_id, &doctor, &purpose),
+        Err(Ok(ContractError::ConsentRevoked))
+    );
+}
+
+#[test]
+fn test_grant_access_levels() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let nurse = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    client.grant_access(&token_id, &patient, &doctor, &AccessLevel::Write, &0);
+    assert!(client.check_access(&token_id, &doctor, &AccessLevel::Read));
+    assert!(client.check_access(&token_id, &doctor, &AccessLevel::Write));
+    assert!(!client.check_access(&token_id, &doctor, &AccessLevel::Share));
+    assert!(!client.check_access(&token_id, &nurse, &AccessLevel::Read));
+
+    // Only Share-level grantees can sub-grant
+    assert_eq!(
+        client.try_grant_access(&token_id, &doctor, &nurse, &AccessLevel::Read, &0),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+
+    client.revoke_access(&token_id, &patient, &doctor);
+    assert!(!client.check_access(&token_id, &doctor, &AccessLevel::Read));
+    assert_eq!(client.get_grantees(&token_id).len(), 0);
+}
+
+#[test]
+fn test_share_grant_bounds_sub_grants() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let clinic = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    client.grant_access(&token_id, &patient, &clinic, &AccessLevel::Share, &5_000);
+    assert_eq!(
+        client.try_grant_access(&token_id, &clinic, &doctor, &AccessLevel::Read, &6_000),
+        Err(Ok(ContractError::ExpiryTooLong))
+    );
+    client.grant_access(&token_id, &clinic, &doctor, &AccessLevel::Write, &0);
+
+    let grant = client.get_grant(&token_id, &doctor).unwrap();
+    assert_eq!(grant.granted_by, clinic);
+    assert_eq!(grant.depth, 1);
+    assert_eq!(grant.expiry, 5_000);
+    assert!(client.check_access(&token_id, &doctor, &AccessLevel::Write));
+
+    // Sub-grants lapse with the grant they came from
+    client.revoke_access(&token_id, &patient, &clinic);
+    assert!(!client.check_access(&token_id, &doctor, &AccessLevel::Read));
+}
+
+#[test]
+fn test_delegation_depth_and_chain() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let clinic = Address::generate(&env);
+    let department = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    client.set_max_delegation_depth(&1);
+    client.grant_access(&token_id, &patient, &clinic, &AccessLevel::Share, &0);
+    client.grant_access(&token_id, &clinic, &department, &AccessLevel::Share, &0);
+    assert_eq!(
+        client.try_grant_access(&token_id, &department, &doctor, &AccessLevel::Read, &0),
+        Err(Ok(ContractError::DelegationTooDeep))
+    );
+
+    client.set_max_delegation_depth(&2);
+    client.grant_access(&token_id, &department, &doctor, &AccessLevel::Read, &0);
+    let chain = client.get_grant_chain(&token_id, &doctor);
+    assert_eq!(chain.len(), 3);
+    assert_eq!(chain.get(0).unwrap().granted_by, patient);
+    assert_eq!(chain.get(1).unwrap().granted_by, clinic);
+    assert_eq!(chain.get(2).unwrap().granted_by, department);
+
+    // Lowering the limit retires deeper grants that already exist
+    client.set_max_delegation_depth(&1);
+    assert!(!client.check_access(&token_id, &doctor, &AccessLevel::Read));
+    assert!(client.check_access(&token_id, &department, &AccessLevel::Read));
+}
+
+#[test]
+fn test_metrics() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let first = mint(&env, &client, &issuer, &patient);
+    mint(&env, &client, &issuer, &patient);
+    mint(&env, &client, &issuer, &patient);
+    client.revoke_consent(&first);
+
+    let metrics = client.metrics();
+    assert_eq!(metrics.tokens, 3);
+    assert_eq!(metrics.revoked, 1);
+    assert_eq!(metrics.active, 2);
+    assert_eq!(metrics.issuers, 1);
+}
+
+#[test]
+fn test_verify_invariants() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let kept = mint(&env, &client, &issuer, &patient);
+    let moved = mint(&env, &client, &issuer, &patient);
+    client.transfer(&patient, &doctor, &moved);
+    client.revoke_consent(&kept);
+    assert_eq!(client.verify_invariants().len(), 0);
+
+    // Drop the moved token from its owner's index behind the contract's back
+    env.as_contract(&client.address, || {
+        env.storage().persistent().set(
+            &DataKey::OwnerTokens(doctor.clone()),
+            &Vec::<u64>::new(&env),
+        );
+    });
+    let violations = client.verify_invariants();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations.get(0).unwrap().token_id, moved);
+    assert_eq!(
+        violations.get(0).unwrap().invariant,
+        Symbol::new(&env, "owner_idx")
+    );
+}
+
+#[test]
+fn test_versioned_document_hashes() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let original = BytesN::from_array(&env, &[1; 32]);
+    let amended = BytesN::from_array(&env, &[2; 32]);
+
+    client.record_initial_document_hash(&token_id, &original);
+    client.set_require_document_hash(&true);
+    let new_uri = String::from_str(&env, "ipfs://QmAmended");
+    assert_eq!(
+        client.try_update_consent(&token_id, &new_uri),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    assert_eq!(
+        client.update_consent_with_hash(&token_id, &new_uri, &amended),
+        2
+    );
+
+    // Both the historical and the current document verify
+    assert!(client.verify_version_document(&token_id, &1, &original));
+    assert!(client.verify_version_document(&token_id, &2, &amended));
+    assert!(!client.verify_version_document(&token_id, &1, &amended));
+    assert_eq!(
+        client.try_record_initial_document_hash(&token_id, &amended),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+}
+
+#[test]
+fn test_validity_report_lists_every_reason() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmXxx..."),
+        &String::from_str(&env, "treatment"),
+        &1_000,
+    );
+
+    let report = client.validity(&token_id);
+    assert!(report.valid);
+    assert!(report.reasons.is_empty());
+    assert_eq!(report.remaining_seconds, 1_000);
+    assert_eq!(report.version, 1);
+
+    client.revoke_consent(&token_id);
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    let report = client.validity(&token_id);
+    assert!(!report.valid);
+    assert_eq!(
+        report.reasons,
+        vec![&env, ValidityReason::Revoked, ValidityReason::Expired]
+    );
+    assert_eq!(report.remaining_seconds, 0);
+    assert!(!client.is_valid(&token_id));
+
+    let open_ended = mint(&env, &client, &issuer, &patient);
+    assert_eq!(client.validity(&open_ended).remaining_seconds, u64::MAX);
+    assert_eq!(
+        client.try_validity(&99),
+        Err(Ok(ContractError::TokenNotFound))
+    );
+}
+
+#[test]
+fn test_clock_skew_tolerance_extends_expiry_checks() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmXxx..."),
+        &String::from_str(&env, "treatment"),
+        &1_000,
+    );
+    assert_eq!(
+        client.try_set_clock_skew_tolerance(&301),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    client.set_clock_skew_tolerance(&60);
+
+    // Still usable within the tolerance past expiry
+    env.ledger().with_mut(|li| li.timestamp = 1_030);
+    let report = client.validity(&token_id);
+    assert!(report.valid);
+    assert_eq!(report.remaining_seconds, 30);
+    assert_eq!(report.skew_tolerance, 60);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_060);
+    assert!(!client.is_valid(&token_id));
+    assert_eq!(
+        client.try_update_consent(&token_id, &String::from_str(&env, "ipfs://new")),
+        Err(Ok(ContractError::ExpiredConsent))
+    );
+}
+
+#[test]
+fn test_access_request_workflow() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let researcher = Address::generate(&env);
+    let insurer = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let justification = BytesN::from_array(&env, &[7; 32]);
+
+    client.revoke_scope(&token_id, &0b10);
+    assert_eq!(
+        client.try_request_access(&token_id, &researcher, &0b11, &justification),
+        Err(Ok(ContractError::ScopeNotGranted))
+    );
+    assert_eq!(
+        client.try_request_access(&token_id, &patient, &0b01, &justification),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+
+    let approved = client.request_access(&token_id, &researcher, &0b01, &justification);
+    let denied = client.request_access(&token_id, &insurer, &0b01, &justification);
+    assert_eq!(
+        client.get_pending_access_requests(&token_id),
+        vec![&env, approved, denied]
+    );
+
+    client.approve_access_request(&approved);
+    assert!(client.check_access(&token_id, &researcher, &AccessLevel::Read));
+    assert!(!client.check_access(&token_id, &researcher, &AccessLevel::Write));
+    client.deny_access_request(&denied);
+    assert!(!client.check_access(&token_id, &insurer, &AccessLevel::Read));
+    assert_eq!(
+        client.get_access_request(&denied).status,
+        RequestStatus::Rejected
+    );
+    assert_eq!(
+        client.try_approve_access_request(&denied),
+        Err(Ok(ContractError::RequestNotPending))
+    );
+    assert!(client.get_pending_access_requests(&token_id).is_empty());
+
+    // Unanswered requests lapse after the request window
+    let stale = client.request_access(&token_id, &insurer, &0b01, &justification);
+    let window = client.get_request_window();
+    env.ledger().with_mut(|li| li.timestamp += window + 1);
+    assert_eq!(
+        client.get_access_request(&stale).status,
+        RequestStatus::Expired
+    );
+    assert!(client.get_pending_access_requests(&token_id).is_empty());
+    assert_eq!(
+        client.try_approve_access_request(&stale),
+        Err(Ok(ContractError::RequestExpired))
+    );
+}
+
+#[test]
+fn test_batch_access_request_answers() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let other_patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let other_token = mint(&env, &client, &issuer, &other_patient);
+    let justification = BytesN::from_array(&env, &[7; 32]);
+    let consumers: [Address; 4] = core::array::from_fn(|_| Address::generate(&env));
+    let mut ids = [0u64; 4];
+    for (id, consumer) in ids.iter_mut().zip(consumers.iter()) {
+        *id = client.request_access(&token_id, consumer, &1, &justification);
+    }
+    let foreign = client.request_access(&other_token, &consumers[0], &1, &justification);
+
+    client.deny_access_request(&ids[1]);
+    let result =
+        client.approve_access_requests(&patient, &vec![&env, ids[0], ids[1], ids[2], foreign, 99]);
+    assert_eq!(result.succeeded, vec![&env, ids[0], ids[2]]);
+    assert_eq!(result.failed, vec![&env, ids[1], foreign, 99]);
+    assert!(client.check_access(&token_id, &consumers[0], &AccessLevel::Read));
+    assert!(!client.check_access(&other_token, &consumers[0], &AccessLevel::Read));
+
+    let result = client.deny_access_requests(&patient, &vec![&env, ids[3], ids[0]]);
+    assert_eq!(result.succeeded, vec![&env, ids[3]]);
+    assert_eq!(result.failed, vec![&env, ids[0]]);
+    assert_eq!(
+        client.get_access_request(&ids[3]).status,
+        RequestStatus::Rejected
+    );
+    assert!(client.get_pending_access_requests(&token_id).is_empty());
+    assert_eq!(
+        client.get_access_request(&foreign).status,
+        RequestStatus::Pending
+    );
+}
+
+#[test]
+fn test_access_requests_are_capped_and_scoped() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let researcher = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let justification = BytesN::from_array(&env, &[7; 32]);
+
+    // Approval only grants the scopes asked for
+    let request_id = client.request_access(&token_id, &researcher, &0b01, &justification);
+    client.approve_access_request(&request_id);
+    assert_eq!(
+        client.get_grant(&token_id, &researcher).unwrap().scope,
+        0b01
+    );
+    assert_eq!(
+        client.effective_access(&patient, &researcher, &0b01),
+        Some(token_id)
+    );
+    assert_eq!(client.effective_access(&patient, &researcher, &0b11), None);
+
+    // Filling the list takes more than the test's budget, which all calls share
+    env.budget().reset_unlimited();
+    for _ in 0..MAX_PENDING_REQUESTS {
+        client.request_access(&token_id, &Address::generate(&env), &1, &justification);
+    }
+    assert_eq!(
+        client.try_request_access(&token_id, &researcher, &1, &justification),
+        Err(Ok(ContractError::QuotaExceeded))
+    );
+
+    // Expired requests make room again
+    clock::advance(&env, client.get_request_window() + 1);
+    let request_id = client.request_access(&token_id, &researcher, &1, &justification);
+    assert_eq!(
+        client.get_pending_access_requests(&token_id),
+        vec![&env, request_id]
+    );
+}
+
+#[test]
+fn test_standing_directives_auto_approve_requests() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let clinic_backend = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let justification = BytesN::from_array(&env, &[7; 32]);
+    let clinic = client.create_institution(&String::from_str(&env, "Clinic"), &admin);
+    client.add_institution_issuer(&admin, &clinic, &clinic_backend);
+
+    assert_eq!(
+        client.try_add_directive(
+            &patient,
+            &DirectiveTarget::Institution(clinic),
+            &0b01,
+            &String::from_str(&env, "astrology"),
+        ),
+        Err(Ok(ContractError::UnknownConsentType))
+    );
+    let directive_id = client.add_directive(
+        &patient,
+        &DirectiveTarget::Institution(clinic),
+        &0b01,
+        &String::from_str(&env, "treatment"),
+    );
+    assert_eq!(client.get_directives(&patient), vec![&env, directive_id]);
+
+    // Matching requests are granted at once, with the directive as provenance
+    let request_id = client.request_access(&token_id, &clinic_backend, &0b01, &justification);
+    assert_eq!(
+        client.get_access_request(&request_id).status,
+        RequestStatus::Approved
+    );
+    assert!(client.check_access(&token_id, &clinic_backend, &AccessLevel::Read));
+    assert_eq!(
+        client
+            .get_grant(&token_id, &clinic_backend)
+            .unwrap()
+            .directive,
+        Some(directive_id)
+    );
+
+    // Wider scopes and other requesters still wait for the patient
+    let wider = client.request_access(&token_id, &clinic_backend, &0b11, &justification);
+    let other = client.request_access(&token_id, &stranger, &0b01, &justification);
+    assert_eq!(
+        client.get_pending_access_requests(&token_id),
+        vec![&env, wider, other]
+    );
+
+    client.revoke_directive(&directive_id);
+    assert!(client.get_directive(&directive_id).is_none());
+    let after = client.request_access(&token_id, &clinic_backend, &0b01, &justification);
+    assert_eq!(
+        client.get_access_request(&after).status,
+        RequestStatus::Pending
+    );
+    assert_eq!(
+        client.try_revoke_directive(&directive_id),
+        Err(Ok(ContractError::DirectiveNotFound))
+    );
+
+    for _ in 0..10 {
+        client.add_directive(
+            &patient,
+            &DirectiveTarget::Requester(stranger.clone()),
+            &0b01,
+            &String::from_str(&env, ""),
+        );
+    }
+    assert_eq!(
+        client.try_add_directive(
+            &patient,
+            &DirectiveTarget::Requester(stranger.clone()),
+            &0b01,
+            &String::from_str(&env, ""),
+        ),
+        Err(Ok(ContractError::QuotaExceeded))
+    );
+}
+
+#[test]
+fn test_service_keys_act_for_issuer() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let backend = Address::generate(&env);
+    let uri = String::from_str(&env, "ipfs://QmXxx...");
+    let consent_type = String::from_str(&env, "treatment");
+
+    assert_eq!(
+        client.try_add_service_key(&issuer, &backend, &8),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    client.add_service_key(&issuer, &backend, &KEY_CAN_MINT);
+    assert_eq!(
+        client.get_service_keys(&issuer),
+        vec![&env, backend.clone()]
+    );
+
+    // Tokens minted through the key are the issuer's
+    let token_id = client.mint_consent_via_key(&backend, &patient, &uri, &consent_type, &0);
+    assert_eq!(client.get_metadata(&token_id).issuer, issuer);
+    assert_eq!(
+        client.try_revoke_consent_via_key(&backend, &token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+
+    // A compromised key is switched off without touching the issuer
+    client.set_service_key_enabled(&issuer, &backend, &false);
+    assert_eq!(
+        client.try_mint_consent_via_key(&backend, &patient, &uri, &consent_type, &0),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert!(client.is_issuer(&issuer));
+    mint(&env, &client, &issuer, &patient);
+
+    let ops = Address::generate(&env);
+    client.add_service_key(&issuer, &ops, &(KEY_CAN_MINT | KEY_CAN_REVOKE));
+    client.revoke_consent_via_key(&ops, &token_id);
+    assert!(client.is_revoked(&token_id));
+    let history = client.get_history(&token_id);
+    assert_eq!(history.get(history.len() - 1).unwrap().actor, ops);
+
+    client.remove_service_key(&issuer, &backend);
+    assert!(client.get_service_key(&backend).is_none());
+    assert_eq!(
+        client.try_mint_consent_via_key(&backend, &patient, &uri, &consent_type, &0),
+        Err(Ok(ContractError::ServiceKeyNotFound))
+    );
+    client.remove_issuer(&issuer);
+    assert_eq!(
+        client.try_mint_consent_via_key(&ops, &patient, &uri, &consent_type, &0),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_config_object_and_reconfigure() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, PatientConsentToken);
+    let client = PatientConsentTokenClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let issuer = Address::generate(&env);
+
+    let mut config = InitConfig::new(&env, admin.clone());
+    config.issuers = vec![&env, issuer.clone()];
+    config.consent_types = vec![&env, String::from_str(&env, "research")];
+    config.max_delegation_depth = 1;
+    client.initialize(&config);
+    assert_eq!(client.get_config(), config);
+    assert!(client.is_issuer(&issuer));
+    assert_eq!(
+        client.try_initialize(&config),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+
+    client.reconfigure(&ConfigUpdate {
+        request_window: Some(3_600),
+        clock_skew_tolerance: Some(30),
+        ..ConfigUpdate::new(&env)
+    });
+    assert_eq!(client.get_request_window(), 3_600);
+    assert_eq!(client.get_clock_skew_tolerance(), 30);
+    assert_eq!(client.get_max_delegation_depth(), 1);
+
+    // An invalid setting rejects the whole update
+    assert_eq!(
+        client.try_reconfigure(&ConfigUpdate {
+            max_expiry_duration: Some(86_400),
+            request_window: Some(0),
+            ..ConfigUpdate::new(&env)
+        }),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    assert_eq!(client.get_max_expiry_duration(), 0);
+}
+
+#[test]
+fn test_feature_flags_gate_grants() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let grants = Symbol::new(&env, "grants");
+
+    // Grants were live before flags existed, so they start on; others start off
+    assert!(client.is_feature_enabled(&grants));
+    assert!(!client.is_feature_enabled(&Symbol::new(&env, "disputes")));
+    assert!(client.get_features().is_empty());
+
+    client.grant_access(&token_id, &patient, &doctor, &AccessLevel::Read, &0);
+    client.set_feature(&grants, &false);
+    assert!(!client.is_feature_enabled(&grants));
+    assert_eq!(
+        client.try_grant_access(&token_id, &patient, &issuer, &AccessLevel::Read, &0),
+        Err(Ok(ContractError::FeatureDisabled))
+    );
+    assert_eq!(
+        client.try_request_access(&token_id, &issuer, &1, &BytesN::from_array(&env, &[0; 32])),
+        Err(Ok(ContractError::FeatureDisabled))
+    );
+    // Grants already made stay in force
+    assert!(client.check_access(&token_id, &doctor, &AccessLevel::Read));
+
+    client.set_feature(&grants, &true);
+    client.grant_access(&token_id, &patient, &issuer, &AccessLevel::Read, &0);
+}
+
+#[test]
+fn test_template_fields_validated_at_mint() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let reviewer = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let name = symbol_short!("trial");
+    let study = symbol_short!("study_id");
+    let visits = symbol_short!("visits");
+    client.add_legal_reviewer(&reviewer);
+
+    let fields = vec![
+        &env,
+        TemplateField {
+            name: study.clone(),
+            format: FieldFormat::Code,
+        },
+        TemplateField {
+            name: visits.clone(),
+            format: FieldFormat::Numeric,
+        },
+    ];
+    let version = client.publish_template(
+        &issuer,
+        &name,
+        &String::from_str(&env, "ipfs://QmTrial"),
+        &String::from_str(&env, "research"),
+        &0,
+        &fields,
+    );
+    client.approve_template(&reviewer, &name, &version);
+    assert_eq!(client.get_template(&name, &version).fields, fields);
+
+    let mint = |values: &[(&Symbol, &str)]| {
+        let mut map = Map::new(&env);
+        for (field, value) in values {
+            map.set((*field).clone(), String::from_str(&env, value));
+        }
+        client.try_mint_from_template(&issuer, &patient, &name, &version, &map)
+    };
+    // Missing, malformed and unexpected fields are all rejected
+    let invalid = Err(Ok(ContractError::InvalidTemplateField));
+    assert_eq!(mint(&[(&study, "NCT01234567")]), invalid);
+    assert_eq!(mint(&[(&study, "NCT 0123"), (&visits, "4")]), invalid);
+    assert_eq!(mint(&[(&study, "NCT01234567"), (&visits, "four")]), invalid);
+    assert_eq!(mint(&[(&study, ""), (&visits, "4")]), invalid);
+    assert_eq!(
+        mint(&[
+            (&study, "NCT01234567"),
+            (&visits, "4"),
+            (&symbol_short!("extra"), "x")
+        ]),
+        invalid
+    );
+
+    let token_id = mint(&[(&study, "NCT01234567"), (&visits, "4")])
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        client.get_token_field(&token_id, &study),
+        Some(String::from_str(&env, "NCT01234567"))
+    );
+    assert_eq!(client.get_token_fields(&token_id).len(), 2);
+
+    // Field names must be unique
+    let twice = vec![&env, fields.get(0).unwrap(), fields.get(0).unwrap()];
+    assert_eq!(
+        client.try_publish_template(
+            &issuer,
+            &name,
+            &String::from_str(&env, "ipfs://QmTrial"),
+            &String::from_str(&env, "research"),
+            &0,
+            &twice,
+        ),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+}
+
+#[test]
+fn test_code_tags_follow_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let caregiver = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let diabetes = ClinicalCode {
+        system: symbol_short!("ICD10"),
+        code: String::from_str(&env, "E11.9"),
+    };
+
+    assert_eq!(
+        client.try_tag_consent(&issuer, &token_id, &diabetes),
+        Err(Ok(ContractError::UnknownCodeSystem))
+    );
+    client.register_code_system(&symbol_short!("ICD10"));
+    assert_eq!(
+        client.try_tag_consent(&caregiver, &token_id, &diabetes),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.tag_consent(&issuer, &token_id, &diabetes);
+    client.tag_consent(&patient, &token_id, &diabetes);
+    assert_eq!(client.get_consent_codes(&token_id).len(), 1);
+    assert_eq!(
+        client.find_tokens_by_code(&patient, &diabetes),
+        vec![&env, token_id]
+    );
+
+    // The index moves with the token
+    client.transfer(&patient, &caregiver, &token_id);
+    assert!(client.find_tokens_by_code(&patient, &diabetes).is_empty());
+    assert_eq!(
+        client.find_tokens_by_code(&caregiver, &diabetes),
+        vec![&env, token_id]
+    );
+
+    client.untag_consent(&caregiver, &token_id, &diabetes);
+    assert!(client.get_consent_codes(&token_id).is_empty());
+    assert!(client.find_tokens_by_code(&caregiver, &diabetes).is_empty());
+}
+
+#[test]
+fn test_effective_access_finds_covering_consent() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let first = mint(&env, &client, &issuer, &patient);
+    let second = mint(&env, &client, &issuer, &patient);
+    assert_eq!(client.effective_access(&patient, &doctor, &1), None);
+
+    client.grant_access(&first, &patient, &doctor, &AccessLevel::Read, &0);
+    client.grant_access(&second, &patient, &doctor, &AccessLevel::Read, &0);
+    assert_eq!(client.effective_access(&patient, &doctor, &1), Some(first));
+
+    // A consent that no longer covers the scope is skipped
+    client.revoke_scope(&first, &1);
+    assert_eq!(client.effective_access(&patient, &doctor, &1), Some(second));
+    assert_eq!(client.effective_access(&patient, &doctor, &2), Some(first));
+
+    client.revoke_access(&second, &patient, &doctor);
+    assert_eq!(client.effective_access(&patient, &doctor, &1), None);
+    client.revoke_consent(&first);
+    assert_eq!(client.effective_access(&patient, &doctor, &2), None);
+}
+
+#[test]
+fn test_active_consent_index_by_type() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let other = Address::generate(&env);
+    let treatment = String::from_str(&env, "treatment");
+    let research = String::from_str(&env, "research");
+    assert!(!client.has_active_consent(&patient, &treatment));
+
+    let expiring = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmXxx..."),
+        &treatment,
+        &(env.ledger().timestamp() + 100),
+    );
+    let lasting = mint(&env, &client, &issuer, &patient);
+    assert!(client.has_active_consent(&patient, &treatment));
+    assert!(!client.has_active_consent(&patient, &research));
+    assert_eq!(
+        client.get_active_consents(&patient, &treatment),
+        vec![&env, expiring, lasting]
+    );
+
+    // Expired tokens stop counting and can be pruned by anyone
+    env.ledger().with_mut(|ledger| ledger.timestamp += 200);
+    assert_eq!(
+        client.get_active_consents(&patient, &treatment),
+        vec![&env, lasting]
+    );
+    assert_eq!(client.prune_active_consents(&patient, &treatment), 1);
+
+    // Transfers move the entry, revocations remove it
+    client.transfer(&patient, &other, &lasting);
+    assert!(!client.has_active_consent(&patient, &treatment));
+    assert!(client.has_active_consent(&other, &treatment));
+    client.revoke_consent(&lasting);
+    assert!(!client.has_active_consent(&other, &treatment));
+    assert_eq!(client.prune_active_consents(&other, &treatment), 0);
+}
+
+#[test]
+fn test_compliance_report() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let purpose = BytesN::from_array(&env, &[7; 32]);
+    client.add_verifier(&verifier);
+
+    let hashed = mint(&env, &client, &issuer, &patient);
+    client.record_initial_document_hash(&hashed, &BytesN::from_array(&env, &[1; 32]));
+    let open = mint(&env, &client, &issuer, &patient);
+    client.grant_access(&open, &patient, &doctor, &AccessLevel::Read, &0);
+    let expiring = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmXxx..."),
+        &String::from_str(&env, "research"),
+        &(env.ledger().timestamp() + 100),
+    );
+    let emergency = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmXxx..."),
+        &String::from_str(&env, "emergency"),
+        &0,
+    );
+    client.log_access(&verifier, &emergency, &doctor, &purpose);
+    env.ledger()
+        .with_mut(|ledger| ledger.timestamp += 3 * 86_400);
+    client.log_access(&verifier, &emergency, &doctor, &purpose);
+    client.revoke_consent(&emergency);
+
+    let report = client.compliance_report(&admin, &patient, &1);
+    assert_eq!(report.tokens_checked, 4);
+    assert_eq!(report.missing_document_hash, vec![&env, open]);
+    assert_eq!(report.expired_unrevoked, vec![&env, expiring]);
+    assert_eq!(report.open_ended_grants.len(), 1);
+    assert_eq!(report.open_ended_grants.get(0).unwrap().grantee, doctor);
+    // Only the access inside the one-day window counts
+    assert_eq!(report.emergency_accesses, 1);
+    assert_eq!(
+        client
+            .compliance_report(&admin, &patient, &7)
+            .emergency_accesses,
+        2
+    );
+
+    assert_eq!(
+        client.try_compliance_report(&doctor, &patient, &1),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_mint_burst_flags_issuer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+
+    // Off until the admin sets thresholds
+    mint(&env, &client, &issuer, &patient);
+    assert_eq!(client.get_issuer_activity(&issuer).mints, 0);
+
+    client.set_activity_thresholds(&ActivityThresholds {
+        window: 3_600,
+        max_mints: 2,
+        max_revocations: 0,
+        max_failures: 0,
+    });
+    mint(&env, &client, &issuer, &patient);
+    mint(&env, &client, &issuer, &patient);
+    assert_eq!(client.get_issuer_flag(&issuer), None);
+    // Counters start over once the window has passed
+    env.ledger().with_mut(|ledger| ledger.timestamp += 3_600);
+    mint(&env, &client, &issuer, &patient);
+    mint(&env, &client, &issuer, &patient);
+    assert_eq!(client.get_issuer_activity(&issuer).mints, 2);
+
+    // The mint that goes over the limit succeeds; the next ones wait for review
+    mint(&env, &client, &issuer, &patient);
+    assert_eq!(
+        client.get_issuer_flag(&issuer).unwrap().reason,
+        FlagReason::MintBurst
+    );
+    assert_eq!(
+        client.try_mint_consent(
+            &issuer,
+            &patient,
+            &String::from_str(&env, "ipfs://QmXxx..."),
+            &String::from_str(&env, "treatment"),
+            &0,
+        ),
+        Err(Ok(ContractError::IssuerFlagged))
+    );
+
+    client.clear_issuer_flag(&issuer);
+    assert_eq!(client.get_issuer_activity(&issuer).mints, 0);
+    mint(&env, &client, &issuer, &patient);
+}
+
+#[test]
+fn test_failed_revocations_flag_issuer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin, issuer) = setup(&env);
+    let other_issuer = Address::generate(&env);
+    let patient = Address::generate(&env);
+    client.add_issuer(&other_issuer);
+    let foreign = mint(&env, &client, &other_issuer, &patient);
+    let own = mint(&env, &client, &issuer, &patient);
+    client.set_activity_thresholds(&ActivityThresholds {
+        window: 3_600,
+        max_mints: 0,
+        max_revocations: 0,
+        max_failures: 1,
+    });
+
+    let reason = String::from_str(&env, "cleanup");
+    client.revoke_batch(&issuer, &vec![&env, foreign, foreign, own], &reason);
+    assert_eq!(client.get_issuer_activity(&issuer).failures, 2);
+    assert_eq!(
+        client.get_issuer_flag(&issuer).unwrap().reason,
+        FlagReason::RepeatedFailures
+    );
+    assert!(client.get_issuer_flag(&other_issuer).is_none());
+}
+
+#[test]
+fn test_break_glass_requires_registered_responder_in_region() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let regulator = Address::generate(&env);
+    let responder = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let institution =
+        client.create_institution(&String::from_str(&env, "Nairobi General"), &regulator);
+    let kenya = vec![&env, symbol_short!("KE")];
+    let purpose = BytesN::from_array(&env, &[9; 32]);
+
+    // Only regulators manage the registry
+    assert_eq!(
+        client.try_register_responder(&regulator, &responder, &institution, &kenya, &0),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.add_regulator(&regulator);
+    assert!(client.is_regulator(&regulator));
+    assert_eq!(
+        client.try_register_responder(&regulator, &responder, &99, &kenya, &0),
+        Err(Ok(ContractError::InstitutionNotFound))
+    );
+
+    // Unregistered responders can't break glass
+    assert_eq!(
+        client.try_break_glass_access(&responder, &token_id, &symbol_short!("KE"), &purpose),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+
+    client.register_responder(&regulator, &responder, &institution, &kenya, &0);
+    assert_eq!(client.get_responder(&responder).unwrap().regions, kenya);
+
+    // The responder must be tagged for the region it reports
+    assert_eq!(
+        client.try_break_glass_access(&responder, &token_id, &symbol_short!("UG"), &purpose),
+        Err(Ok(ContractError::OutOfRegion))
+    );
+    // ...and the patient's residency must allow it
+    client.set_residency(&token_id, &vec![&env, symbol_short!("EU")]);
+    assert_eq!(
+        client.try_break_glass_access(&responder, &token_id, &symbol_short!("KE"), &purpose),
+        Err(Ok(ContractError::OutOfRegion))
+    );
+    client.set_residency(
+        &token_id,
+        &vec![&env, symbol_short!("EU"), symbol_short!("KE")],
+    );
+
+    let index = client.break_glass_access(&responder, &token_id, &symbol_short!("KE"), &purpose);
+    let entry = client.get_access_log(&token_id, &index, &1).get(0).unwrap();
+    assert_eq!(entry.accessor, responder);
+    assert_eq!(entry.purpose_hash, purpose);
+    // Break-glass accesses are anchored for the audit trail, once one is set
+    let anchor = client.get_pending_anchors(&0, &0).get(0).unwrap();
+    assert_eq!(anchor.kind, symbol_short!("brk_glass"));
+    let audit_log_id = env.register_contract(None, MockAuditLog);
+    client.set_audit_log(&audit_log_id);
+    assert_eq!(client.flush_anchors(&1), 1);
+    let (kind, anchored_id, _) = MockAuditLogClient::new(&env, &audit_log_id)
+        .anchored()
+        .get(0)
+        .unwrap();
+    assert_eq!(kind, symbol_short!("brk_glass"));
+    assert_eq!(anchored_id, token_id);
+
+    client.remove_responder(&regulator, &responder);
+    assert!(client.get_responder(&responder).is_none());
+    assert_eq!(
+        client.try_break_glass_access(&responder, &token_id, &symbol_short!("KE"), &purpose),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_lapsed_responder_cannot_break_glass() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let regulator = Address::generate(&env);
+    let responder = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    let institution =
+        client.create_institution(&String::from_str(&env, "Nairobi General"), &regulator);
+    let kenya = vec![&env, symbol_short!("KE")];
+    let purpose = BytesN::from_array(&env, &[9; 32]);
+    client.add_regulator(&regulator);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert_eq!(
+        client.try_register_responder(&regulator, &responder, &institution, &kenya, &1_000),
+        Err(Ok(ContractError::PastTimestamp))
+    );
+    client.register_responder(&regulator, &responder, &institution, &kenya, &2_000);
+    client.break_glass_access(&responder, &token_id, &symbol_short!("KE"), &purpose);
+
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    assert_eq!(
+        client.try_break_glass_access(&responder, &token_id, &symbol_short!("KE"), &purpose),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_break_glass_respects_revoked_and_suspended_consents() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let regulator = Address::generate(&env);
+    let responder = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let revoked = mint(&env, &client, &issuer, &patient);
+    let suspended = mint(&env, &client, &issuer, &patient);
+    let institution =
+        client.create_institution(&String::from_str(&env, "Nairobi General"), &regulator);
+    let purpose = BytesN::from_array(&env, &[9; 32]);
+    client.add_regulator(&regulator);
+
+    // Repeated regions are stored once
+    client.register_responder(
+        &regulator,
+        &responder,
+        &institution,
+        &vec![&env, symbol_short!("KE"), symbol_short!("KE")],
+        &0,
+    );
+    assert_eq!(
+        client.get_responder(&responder).unwrap().regions,
+        vec![&env, symbol_short!("KE")]
+    );
+
+    client.revoke_consent(&revoked);
+    assert_eq!(
+        client.try_break_glass_access(&responder, &revoked, &symbol_short!("KE"), &purpose),
+        Err(Ok(ContractError::ConsentRevoked))
+    );
+
+    client.set_cascade_on_removal(&true);
+    client.remove_issuer(&issuer);
+    client.suspend_by_issuer(&issuer, &10);
+    assert!(client.is_suspended(&suspended));
+    assert_eq!(
+        client.try_break_glass_access(&responder, &suspended, &symbol_short!("KE"), &purpose),
+        Err(Ok(ContractError::FrozenToken))
+    );
+    assert_eq!(client.get_access_count(&suspended), 0);
+}
+
+#[test]
+fn test_scoped_pause_only_blocks_paused_capabilities() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    assert_eq!(client.get_paused(), 0);
+
+    client.set_paused(&PAUSE_MINT);
+    assert_eq!(
+        client.try_mint_consent(
+            &issuer,
+            &patient,
+            &String::from_str(&env, "ipfs://QmPaused"),
+            &String::from_str(&env, "treatment"),
+            &0,
+        ),
+        Err(Ok(ContractError::ContractPaused))
+    );
+    // Grants and transfers carry on while minting is paused
+    client.grant_access(&token_id, &patient, &doctor, &AccessLevel::Read, &0);
+    client.transfer(&patient, &recipient, &token_id);
+
+    client.set_paused(&(PAUSE_TRANSFER | PAUSE_GRANTS));
+    assert_eq!(
+        client.try_transfer(&recipient, &patient, &token_id),
+        Err(Ok(ContractError::ContractPaused))
+    );
+    assert_eq!(
+        client.try_grant_access(&token_id, &patient, &issuer, &AccessLevel::Read, &0),
+        Err(Ok(ContractError::ContractPaused))
+    );
+    mint(&env, &client, &issuer, &patient);
+
+    assert_eq!(
+        client.try_set_paused(&8),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    client.set_paused(&0);
+    client.transfer(&recipient, &patient, &token_id);
+}
+
+#[test]
+fn test_paused_issuer_is_held_alone() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let other_issuer = Address::generate(&env);
+    let patient = Address::generate(&env);
+    client.add_issuer(&other_issuer);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    client.pause_issuer(&issuer);
+    assert!(client.is_issuer_paused(&issuer));
+    assert_eq!(
+        client.try_mint_consent(
+            &issuer,
+            &patient,
+            &String::from_str(&env, "ipfs://QmPaused"),
+            &String::from_str(&env, "treatment"),
+            &0,
+        ),
+        Err(Ok(ContractError::ContractPaused))
+    );
+    assert_eq!(
+        client.try_revoke_batch(
+            &issuer,
+            &vec![&env, token_id],
+            &String::from_str(&env, "incident")
+        ),
+        Err(Ok(ContractError::ContractPaused))
+    );
+    mint(&env, &client, &other_issuer, &patient);
+
+    client.unpause_issuer(&issuer);
+    assert!(!client.is_issuer_paused(&issuer));
+    mint(&env, &client, &issuer, &patient);
+}
+
+#[test]
+fn test_capabilities_track_enabled_features() {
+    let env = Env::default();
+    let (client, _admin, _issuer) = setup(&env);
+    let grants = Symbol::new(&env, "grants");
+    let disputes = Symbol::new(&env, "disputes");
+
+    let capabilities = client.capabilities();
+    assert_eq!(capabilities.get(0), Some(Symbol::new(&env, "consent_v1")));
+    assert!(capabilities.contains(Symbol::new(&env, "templates")));
+    assert!(capabilities.contains(&grants));
+    assert!(!capabilities.contains(&disputes));
+
+    client.set_feature(&grants, &false);
+    client.set_feature(&disputes, &true);
+    let capabilities = client.capabilities();
+    assert!(!capabilities.contains(&grants));
+    assert!(capabilities.contains(&disputes));
+}
+
+#[test]
+fn test_interface_version_matches_capabilities() {
+    let env = Env::default();
+    let (client, _admin, _issuer) = setup(&env);
+    let version = client.interface_version();
+    assert_eq!(version.major, INTERFACE_MAJOR);
+    assert_eq!(version.minor, INTERFACE_MINOR);
+    // The advertised interface symbol names the same major
+    assert_eq!(
+        client.capabilities().get(0),
+        Some(Symbol::new(&env, "consent_v1"))
+    );
+    assert_eq!(INTERFACE_MAJOR, 1);
+}
+
+#[test]
+fn test_estimate_mint_footprint_scales_with_history() {
+    let env = Env::default();
+    let (client, _admin, _issuer) = setup(&env);
+
+    let base = client.estimate_mint_footprint(&1, &0);
+    assert_eq!(base.token_entries, 5);
+    assert_eq!(base.index_entries, 4);
+    assert!(base.index_bytes > 0);
+    assert_eq!(base.extend_to, client.get_ttl_policy().extend_to);
+    // An empty history still holds the "issued" entry
+    assert_eq!(client.estimate_mint_footprint(&0, &0), base);
+
+    let longer = client.estimate_mint_footprint(&11, &0);
+    let per_entry = (longer.token_bytes - base.token_bytes) / 10;
+    assert_eq!(longer.token_bytes, base.token_bytes + 10 * per_entry);
+    assert!(per_entry > 100);
+
+    let scoped = client.estimate_mint_footprint(&1, &3);
+    assert_eq!(scoped.token_entries, 6);
+    assert!(scoped.token_bytes > base.token_bytes);
+}
+
+#[test]
+fn test_history_cap_seals_oldest_entries() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    client.set_history_policy(&HistoryPolicy {
+        max_len: 4,
+        overflow: HistoryOverflow::Seal,
+    });
+
+    for uri in ["ipfs://v2", "ipfs://v3", "ipfs://v4"] {
+        client.update_consent(&token_id, &String::from_str(&env, uri));
+    }
+    let full = client.get_history(&token_id);
+    assert_eq!(full.len(), 4);
+    assert_eq!(client.get_sealed_history(&token_id).entries, 0);
+
+    // The fifth entry takes the history over the cap: the oldest three are
+    // sealed and the newest two stay readable
+    client.update_consent(&token_id, &String::from_str(&env, "ipfs://v5"));
+    let kept = client.get_history(&token_id);
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept.get(0), full.get(3));
+    assert_eq!(client.get_sealed_history(&token_id).entries, 3);
+
+    let sealed = full.slice(0..3);
+    assert!(client.verify_sealed_history(&token_id, &vec![&env, sealed.clone()]));
+    assert!(!client.verify_sealed_history(&token_id, &vec![&env, full.slice(1..4)]));
+    assert!(!client.verify_sealed_history(&token_id, &vec![&env, sealed.slice(0..2)]));
+}
+
+#[test]
+fn test_history_cap_can_reject_updates() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    client.set_history_policy(&HistoryPolicy {
+        max_len: 2,
+        overflow: HistoryOverflow::Reject,
+    });
+
+    client.update_consent(&token_id, &String::from_str(&env, "ipfs://v2"));
+    assert_eq!(
+        client.try_update_consent(&token_id, &String::from_str(&env, "ipfs://v3")),
+        Err(Ok(ContractError::QuotaExceeded))
+    );
+    // Revocation is still recorded past the cap
+    client.revoke_consent(&token_id);
+    assert_eq!(client.get_history(&token_id).len(), 3);
+}
+
+#[test]
+fn test_joint_owners_must_meet_threshold() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let parent = Address::generate(&env);
+    let other_parent = Address::generate(&env);
+    let clinic = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &parent);
+    let parents = vec![&env, parent.clone(), other_parent.clone()];
+
+    // The primary owner must stay an owner, and the threshold must be reachable
+    assert_eq!(
+        client.try_set_joint_owners(
+            &vec![&env, parent.clone()],
+            &token_id,
+            &vec![&env, other_parent.clone()],
+            &1
+        ),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    assert_eq!(
+        client.try_set_joint_owners(&vec![&env, parent.clone()], &token_id, &parents, &3),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    client.set_joint_owners(&vec![&env, parent.clone()], &token_id, &parents, &2);
+    assert_eq!(client.owners_of(&token_id), parents);
+    assert_eq!(client.get_joint_threshold(&token_id), 2);
+    assert_eq!(
+        client.get_co_owned_tokens(&other_parent),
+        vec![&env, token_id]
+    );
+
+    // One parent alone can no longer transfer, revoke or change the owners
+    assert_eq!(
+        client.try_transfer(&parent, &clinic, &token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_revoke_consent(&token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_joint_transfer(&vec![&env, other_parent.clone()], &clinic, &token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_joint_revoke(&vec![&env, clinic.clone(), parent.clone()], &token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+
+    client.joint_transfer(&parents, &clinic, &token_id);
+    assert_eq!(client.owner_of(&token_id), clinic);
+    assert_eq!(client.owners_of(&token_id), vec![&env, clinic.clone()]);
+    assert!(client.get_co_owned_tokens(&other_parent).is_empty());
+    assert_eq!(client.tokens_of_owner(&clinic), vec![&env, token_id]);
+}
+
+#[test]
+fn test_joint_revoke_needs_threshold_of_owners() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let parent = Address::generate(&env);
+    let other_parent = Address::generate(&env);
+    let guardian = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &parent);
+    let owners = vec![&env, parent.clone(), other_parent.clone(), guardian.clone()];
+    client.set_joint_owners(&vec![&env, parent.clone()], &token_id, &owners, &2);
+
+    // Repeating an approver doesn't count twice
+    assert_eq!(
+        client.try_joint_revoke(&vec![&env, guardian.clone(), guardian.clone()], &token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.joint_revoke(
+        &vec![&env, guardian.clone(), other_parent.clone()],
+        &token_id,
+    );
+    assert!(client.is_revoked(&token_id));
+}
+
+#[test]
+fn test_joint_update_needs_threshold_of_owners() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let parent = Address::generate(&env);
+    let other_parent = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &parent);
+    let parents = vec![&env, parent.clone(), other_parent.clone()];
+    client.set_joint_owners(&vec![&env, parent.clone()], &token_id, &parents, &2);
+    let new_uri = String::from_str(&env, "ipfs://QmUpdatedByBothParents");
+    let content_hash = BytesN::from_array(&env, &[7; 32]);
+
+    // The primary owner alone can't update the metadata any more
+    assert_eq!(
+        client.try_update_consent(&token_id, &new_uri),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_update_consent_with_hash(&token_id, &new_uri, &content_hash),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    assert_eq!(
+        client.try_joint_update_consent(&vec![&env, parent.clone()], &token_id, &new_uri, &None),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+
+    let version =
+        client.joint_update_consent(&parents, &token_id, &new_uri, &Some(content_hash.clone()));
+    assert_eq!(version, 2);
+    assert_eq!(client.get_metadata(&token_id).metadata_uri, new_uri);
+    assert_eq!(client.get_document_hash(&token_id, &2), Some(content_hash));
+}
+
+#[test]
+fn test_emancipate_hands_token_to_patient() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let child = Address::generate(&env);
+    let parent = Address::generate(&env);
+    let other_parent = Address::generate(&env);
+    let oracle = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &child);
+    let parents = vec![&env, parent.clone(), other_parent.clone()];
+    client.transfer(&child, &parent, &token_id);
+    client.set_joint_owners(&vec![&env, parent.clone()], &token_id, &parents, &2);
+
+    // Only the oracle or the admin may attest majority
+    assert_eq!(
+        client.try_emancipate(&oracle, &token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.set_majority_oracle(&oracle);
+    client.emancipate(&oracle, &token_id);
+
+    assert_eq!(client.owner_of(&token_id), child);
+    assert_eq!(client.owners_of(&token_id), vec![&env, child.clone()]);
+    assert_eq!(client.tokens_of_owner(&child), vec![&env, token_id]);
+    assert!(client.tokens_of_owner(&parent).is_empty());
+    assert!(client.get_co_owned_tokens(&other_parent).is_empty());
+    let history = client.get_history(&token_id);
+    assert_eq!(
+        history.get(history.len() - 1).unwrap().action,
+        String::from_str(&env, "emancipated")
+    );
+
+    // The patient now acts alone; a solely held token can't be emancipated again
+    client.transfer(&child, &parent, &token_id);
+    assert_eq!(
+        client.try_emancipate(&admin, &token_id),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+}
+
+#[contract]
+struct MockReceiver;
+
+#[contractimpl]
+impl MockReceiver {
+    pub fn set_accepting(env: Env, accepting: bool) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("accept"), &accepting);
+    }
+
+    pub fn on_consent_received(env: Env, _token_id: u64, _from: Address) -> bool {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("accept"))
+            .unwrap_or(false)
+    }
+}
+
+#[test]
+fn test_receiving_contract_can_refuse_transfer() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let receiver_id = env.register_contract(None, MockReceiver);
+    let receiver = MockReceiverClient::new(&env, &receiver_id);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    client.register_consent_receiver(&receiver_id);
+    assert!(client.is_consent_receiver(&receiver_id));
+
+    assert_eq!(
+        client.try_transfer(&patient, &receiver_id, &token_id),
+        Err(Ok(ContractError::RecipientNotAllowed))
+    );
+    assert_eq!(client.owner_of(&token_id), patient);
+
+    receiver.set_accepting(&true);
+    client.transfer(&patient, &receiver_id, &token_id);
+    assert_eq!(client.owner_of(&token_id), receiver_id);
+}
+
+#[test]
+fn test_receiver_hook_can_be_required() {
+    let env = Env::default();
+    let (client, _admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let no_hook = env.register_contract(None, MockCareTeams);
+    let receiver_id = env.register_contract(None, MockReceiver);
+    MockReceiverClient::new(&env, &receiver_id).set_accepting(&true);
+    client.register_consent_receiver(&receiver_id);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    // Contracts without a registered hook receive tokens unless the admin
+    // requires one
+    client.transfer(&patient, &no_hook, &token_id);
+    client.transfer(&no_hook, &patient, &token_id);
+
+    client.set_receiver_hook_required(&true);
+    assert!(client.is_receiver_hook_required());
+    assert_eq!(
+        client.try_transfer(&patient, &no_hook, &token_id),
+        Err(Ok(ContractError::RecipientNotAllowed))
+    );
+    client.transfer(&patient, &receiver_id, &token_id);
+}
+
+#[contract]
+struct MockIdentityRegistry;
+
+#[contractimpl]
+impl MockIdentityRegistry {
+    pub fn attest(env: Env, subject: Address, claim_hash: BytesN<32>) {
+        env.storage().instance().set(&(subject, claim_hash), &true);
+    }
+
+    pub fn is_attested(env: Env, subject: Address, claim_hash: BytesN<32>) -> bool {
+        env.storage()
+            .instance()
+            .get(&(subject, claim_hash))
+            .unwrap_or(false)
+    }
+}
+
+#[test]
+fn test_custodial_transfers_only_reach_attested_custodians() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let registry_id = env.register_contract(None, MockIdentityRegistry);
+    let registry = MockIdentityRegistryClient::new(&env, &registry_id);
+    let custodian_claim = BytesN::from_array(&env, &[1; 32]);
+    let proxy_claim = BytesN::from_array(&env, &[2; 32]);
+    let patient = Address::generate(&env);
+    let custodian = Address::generate(&env);
+    let proxy = Address::generate(&env);
+    let collector = Address::generate(&env);
+    registry.attest(&custodian, &custodian_claim);
+    registry.attest(&proxy, &proxy_claim);
+    // An unrelated attestation doesn't make an address a custodian
+    registry.attest(&collector, &BytesN::from_array(&env, &[3; 32]));
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    client.set_custodial_transfers(
+        &registry_id,
+        &vec![&env, custodian_claim.clone(), proxy_claim],
+    );
+    assert_eq!(
+        client.try_transfer(&patient, &collector, &token_id),
+        Err(Ok(ContractError::RecipientNotAllowed))
+    );
+    assert_eq!(
+        client.try_can_transfer(&patient, &collector, &token_id),
+        Err(Ok(ContractError::RecipientNotAllowed))
+    );
+    client.transfer(&patient, &custodian, &token_id);
+    client.transfer(&custodian, &proxy, &token_id);
+    assert_eq!(client.owner_of(&token_id), proxy);
+
+    // Whitelists still narrow the custodians further
+    client.set_transfer_whitelist(&None, &vec![&env, collector.clone()]);
+    assert_eq!(
+        client.try_transfer(&proxy, &custodian, &token_id),
+        Err(Ok(ContractError::RecipientNotAllowed))
+    );
+    client.set_transfer_whitelist(&None, &vec![&env]);
+
+    // No claims turns the mode off
+    client.set_custodial_transfers(&registry_id, &vec![&env]);
+    assert_eq!(client.get_custodial_policy(), None);
+    client.transfer(&proxy, &collector, &token_id);
+}
+
+#[test]
+fn test_consent_view_shows_only_masked_fields_until_expiry() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    assert_eq!(
+        client.try_create_view(&token_id, &0, &2_000),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    assert_eq!(
+        client.try_create_view(&token_id, &(VIEW_ALL + 1), &2_000),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+    assert_eq!(
+        client.try_create_view(&token_id, &VIEW_TYPE, &1_000),
+        Err(Ok(ContractError::PastTimestamp))
+    );
+
+    let view_id = client.create_view(&token_id, &(VIEW_TYPE | VIEW_STATUS), &2_000);
+    let data = client.read_view(&view_id).unwrap();
+    assert_eq!(data.consent_type, String::from_str(&env, "treatment"));
+    assert!(data.valid);
+    assert_eq!(data.issued_timestamp, 0);
+    assert_eq!(data.issuer, client.address);
+
+    // The view follows the consent's status
+    client.revoke_consent(&token_id);
+    assert!(!client.read_view(&view_id).unwrap().valid);
+
+    // Expired views no longer resolve
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    assert_eq!(client.read_view(&view_id), None);
+}
+
+#[test]
+fn test_patient_can_withdraw_a_view() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    let view_id = client.create_view(&token_id, &VIEW_ISSUER, &u64::MAX);
+    let data = client.read_view(&view_id).unwrap();
+    assert_eq!(data.issuer, issuer);
+    assert_eq!(data.consent_type, String::from_str(&env, ""));
+    assert!(!data.valid);
+    client.revoke_view(&view_id);
+    assert_eq!(client.read_view(&view_id), None);
+    assert_eq!(
+        client.try_revoke_view(&view_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_rebase_uris_after_storage_migration() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let other_issuer = Address::generate(&env);
+    client.add_issuer(&other_issuer);
+    let patient = Address::generate(&env);
+    let first = mint(&env, &client, &issuer, &patient);
+    let revoked = mint(&env, &client, &issuer, &patient);
+    let foreign = mint(&env, &client, &other_issuer, &patient);
+    let elsewhere = mint(&env, &client, &issuer, &patient);
+    client.update_consent(&elsewhere, &String::from_str(&env, "s3://bucket/consent"));
+    client.revoke_consent(&revoked);
+
+    let old_prefix = String::from_str(&env, "ipfs://");
+    let new_prefix = String::from_str(&env, "https://gateway.example/ipfs/");
+    let result = client.rebase_uris(
+        &issuer,
+        &vec![&env, first, revoked, foreign, elsewhere, 99],
+        &old_prefix,
+        &new_prefix,
+    );
+    assert_eq!(result.rebased, vec![&env, first, revoked]);
+    assert_eq!(result.skipped, vec![&env, foreign, elsewhere, 99]);
+
+    let metadata = client.get_metadata(&revoked);
+    assert_eq!(
+        metadata.metadata_uri,
+        String::from_str(&env, "https://gateway.example/ipfs/QmXxx...")
+    );
+    assert_eq!(metadata.version, 2);
+    let entry = client.get_history(&first).last().unwrap();
+    assert_eq!(entry.action, String::from_str(&env, "rebased"));
+    assert_eq!(entry.actor, issuer);
+
+    // The admin can rebase regardless of issuer
+    let result = client.rebase_uris(&admin, &vec![&env, foreign], &old_prefix, &new_prefix);
+    assert_eq!(result.rebased, vec![&env, foreign]);
+
+    assert_eq!(
+        client.try_rebase_uris(
+            &admin,
+            &vec![&env, first],
+            &String::from_str(&env, ""),
+            &new_prefix
+        ),
+        Err(Ok(ContractError::InvalidUri))
+    );
+    assert_eq!(
+        client.try_rebase_uris(
+            &Address::generate(&env),
+            &vec![&env, first],
+            &old_prefix,
+            &new_prefix
+        ),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_rebase_uris_skips_tokens_that_cant_be_updated() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let full = mint(&env, &client, &issuer, &patient);
+    let roomy = mint(&env, &client, &issuer, &patient);
+    client.set_history_policy(&HistoryPolicy {
+        max_len: 2,
+        overflow: HistoryOverflow::Reject,
+    });
+    client.update_consent(&full, &String::from_str(&env, "ipfs://QmFull"));
+
+    let old_prefix = String::from_str(&env, "ipfs://");
+    let new_prefix = String::from_str(&env, "https://gateway.example/ipfs/");
+    let result = client.rebase_uris(&admin, &vec![&env, full, roomy], &old_prefix, &new_prefix);
+    assert_eq!(result.rebased, vec![&env, roomy]);
+    assert_eq!(result.skipped, vec![&env, full]);
+    assert_eq!(client.get_history(&full).len(), 2);
+
+    // Rebases carry no document hash
+    let other = mint(&env, &client, &issuer, &patient);
+    client.set_require_document_hash(&true);
+    let result = client.rebase_uris(&admin, &vec![&env, other], &old_prefix, &new_prefix);
+    assert!(result.rebased.is_empty());
+    assert_eq!(result.skipped, vec![&env, other]);
+    assert_eq!(client.get_metadata(&other).version, 1);
+}
+
+#[test]
+fn test_daily_activity_counters() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    mint(&env, &client, &issuer, &patient);
+    mint(&env, &client, &issuer, &patient);
+
+    env.ledger().with_mut(|li| li.timestamp = 86_400 + 3_600);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    client.grant_access(&token_id, &patient, &doctor, &AccessLevel::Read, &0);
+    client.revoke_consent(&token_id);
+
+    // Only days since the epoch exist, oldest first
+    assert_eq!(
+        client.activity(&7),
+        vec![
+            &env,
+            DailyActivity {
+                day: 0,
+                mints: 2,
+                revocations: 0,
+                grants: 0
+            },
+            DailyActivity {
+                day: 1,
+                mints: 1,
+                revocations: 1,
+                grants: 1
+            },
+        ]
+    );
+    assert_eq!(client.activity(&1).get(0).unwrap().day, 1);
+    assert_eq!(client.activity(&0).len(), 0);
+
+    // Day 31 reuses day 1's slot, which no longer counts
+    env.ledger().with_mut(|li| li.timestamp = 31 * 86_400);
+    assert_eq!(client.activity(&100).len(), ACTIVITY_DAYS);
+    mint(&env, &client, &issuer, &patient);
+    let today = client.activity(&1).get(0).unwrap();
+    assert_eq!((today.day, today.mints, today.revocations), (31, 1, 0));
+    assert!(client
+        .activity(&ACTIVITY_DAYS)
+        .iter()
+        .all(|day| day.day == 31 || day.mints == 0));
+}
+
+#[test]
+fn test_consent_events_are_routed_by_consent_type() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let doctor = Address::generate(&env);
+    let research = String::from_str(&env, "research");
+    let token_id = client.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmResearch"),
+        &research,
+        &0,
+    );
+    client.grant_access(&token_id, &patient, &doctor, &AccessLevel::Read, &0);
+
+    let research_topics = |action: Symbol, category: Symbol| -> Vec<Val> {
+        (category, action, research.clone()).into_val(&env)
+    };
+    let events = env.events().all();
+    let (_, topics, _) = events.last().unwrap();
+    assert_eq!(
+        topics,
+        research_topics(symbol_short!("granted"), symbol_short!("access"))
+    );
+    assert!(events.iter().any(|(_, topics, _)| topics
+        == research_topics(symbol_short!("issued"), symbol_short!("consent"))));
+
+    // Events that aren't about one consent keep just category and action
+    client.set_paused(&0);
+    let (_, topics, _) = env.events().all().last().unwrap();
+    assert_eq!(topics.len(), 2);
+}
+
+#[test]
+fn test_removing_issuer_cascades_suspension_until_reinstated() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let other_issuer = Address::generate(&env);
+    client.add_issuer(&other_issuer);
+    let patient = Address::generate(&env);
+    let first = mint(&env, &client, &issuer, &patient);
+    let foreign = mint(&env, &client, &other_issuer, &patient);
+    let revoked = mint(&env, &client, &issuer, &patient);
+    let last = mint(&env, &client, &issuer, &patient);
+    client.revoke_consent(&revoked);
+
+    // Without the policy removal leaves consents alone
+    client.remove_issuer(&other_issuer);
+    assert_eq!(client.get_pending_cascade(&other_issuer), None);
+    assert_eq!(
+        client.try_suspend_by_issuer(&other_issuer, &10),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+
+    client.set_cascade_on_removal(&true);
+    client.remove_issuer(&issuer);
+    assert_eq!(client.get_pending_cascade(&issuer), Some(0));
+
+    // Keepers page through the tokens
+    let progress = client.suspend_by_issuer(&issuer, &2);
+    assert_eq!(progress.suspended, vec![&env, first]);
+    assert!(!progress.done);
+    let progress = client.suspend_by_issuer(&issuer, &2);
+    assert_eq!(progress.suspended, vec![&env, last]);
+    assert!(progress.done);
+    assert_eq!(client.get_pending_cascade(&issuer), None);
+
+    assert!(client.is_suspended(&first));
+    assert!(!client.is_suspended(&foreign));
+    assert!(!client.is_suspended(&revoked));
+    let report = client.validity(&first);
+    assert!(!report.valid);
+    assert_eq!(report.reasons, vec![&env, ValidityReason::Suspended]);
+    assert_eq!(
+        client
+            .get_statuses(&vec![&env, first])
+            .get(0)
+            .unwrap()
+            .status,
+        ConsentStatus::Suspended
+    );
+    assert_eq!(
+        client.try_create_view(&first, &VIEW_TYPE, &u64::MAX),
+        Err(Ok(ContractError::FrozenToken))
+    );
+
+    // The admin reinstates consents after review
+    assert_eq!(
+        client.reinstate_consents(&vec![&env, first, foreign]),
+        vec![&env, first]
+    );
+    assert!(client.is_valid(&first));
+    assert!(!client.is_valid(&last));
+    assert_eq!(
+        client.get_history(&first).last().unwrap().action,
+        String::from_str(&env, "reinstated")
+    );
+}
+
+#[test]
+fn test_unpausing_issuer_cancels_pending_cascade() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    client.set_cascade_on_removal(&true);
+
+    client.pause_issuer(&issuer);
+    assert_eq!(client.get_pending_cascade(&issuer), Some(0));
+    client.unpause_issuer(&issuer);
+    assert_eq!(client.get_pending_cascade(&issuer), None);
+    assert!(client.is_valid(&token_id));
+}
+
+#[test]
+fn test_tokens_by_issuer_pages_across_index_entries() {
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let other_issuer = Address::generate(&env);
+    client.add_issuer(&other_issuer);
+    let patient = Address::generate(&env);
+    let first = mint(&env, &client, &issuer, &patient);
+    let foreign = mint(&env, &client, &other_issuer, &patient);
+    assert_eq!(client.tokens_by_issuer(&issuer, &0, &0), vec![&env, first]);
+    assert_eq!(
+        client.tokens_by_issuer(&other_issuer, &0, &0),
+        vec![&env, foreign]
+    );
+
+    // Fill the issuer's index past its first page entry without minting
+    let mut indexed = vec![&env, first];
+    env.as_contract(&client.address, || {
+        for token_id in 1_000..1_000 + ISSUER_PAGE_LEN as u64 {
+            crate::issuer_index::record_mint(&env, &issuer, token_id);
+            indexed.push_back(token_id);
+        }
+    });
+    assert_eq!(client.issuer_token_count(&issuer), ISSUER_PAGE_LEN + 1);
+    assert_eq!(client.tokens_by_issuer(&issuer, &0, &0).len(), 100);
+    // A page spanning two index entries
+    let start = ISSUER_PAGE_LEN - 2;
+    assert_eq!(
+        client.tokens_by_issuer(&issuer, &start, &10),
+        indexed.slice(start..)
+    );
+    assert_eq!(client.tokens_by_issuer(&issuer, &500, &10).len(), 0);
+}
+
+#[test]
+fn test_verify_and_receipt() {
+    use soroban_sdk::xdr::ToXdr;
+
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+
+    assert_eq!(
+        client.try_verify_and_receipt(&verifier, &token_id),
+        Err(Ok(ContractError::NotAuthorized))
+    );
+    client.add_verifier(&verifier);
+
+    env.ledger().with_mut(|li| li.sequence_number = 42);
+    let (receipt_id, report) = client.verify_and_receipt(&verifier, &token_id);
+    assert!(report.valid);
+    let receipt = client.get_receipt(&receipt_id).unwrap();
+    assert_eq!(receipt.verifier, verifier);
+    assert_eq!(receipt.token_id, token_id);
+    assert_eq!(receipt.ledger, 42);
+    assert_eq!(
+        receipt.result_hash,
+        env.crypto().sha256(&report.to_xdr(&env))
+    );
+
+    // Failed checks get receipts too, committing to the negative result
+    client.revoke_consent(&token_id);
+    let (second_id, report) = client.verify_and_receipt(&verifier, &token_id);
+    assert_eq!(second_id, receipt_id + 1);
+    assert!(!report.valid);
+    assert_eq!(
+        client.get_receipt(&second_id).unwrap().result_hash,
+        env.crypto().sha256(&report.to_xdr(&env))
+    );
+    assert_eq!(client.get_receipt(&99), None);
+}
+
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum MockAuditError {
+    Paused = 1,
+}
+
+// Stand-in for the audit_log contract that can be paused
+#[contract]
+struct MockAuditLog;
+
+#[contractimpl]
+impl MockAuditLog {
+    pub fn set_paused(env: Env, paused: bool) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("paused"), &paused);
+    }
+
+    pub fn anchor(
+        env: Env,
+        writer: Address,
+        kind: Symbol,
+        subject_id: u64,
+        record_hash: BytesN<32>,
+    ) -> Result<u64, MockAuditError> {
+        writer.require_auth();
+        if env
+            .storage()
+            .instance()
+            .get(&symbol_short!("paused"))
+            .unwrap_or(false)
+        {
+            return Err(MockAuditError::Paused);
+        }
+        let mut anchored: Vec<(Symbol, u64, BytesN<32>)> = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("anchored"))
+            .unwrap_or(Vec::new(&env));
+        anchored.push_back((kind, subject_id, record_hash));
+        env.storage()
+            .instance()
+            .set(&symbol_short!("anchored"), &anchored);
+        Ok(anchored.len() as u64 - 1)
+    }
+
+    pub fn anchored(env: Env) -> Vec<(Symbol, u64, BytesN<32>)> {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("anchored"))
+            .unwrap_or(Vec::new(&env))
+    }
+}
+
+#[test]
+fn test_anchors_kept_locally_until_audit_log_accepts_them() {
+    use soroban_sdk::xdr::ToXdr;
+
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let audit_log_id = env.register_contract(None, MockAuditLog);
+    let audit_log = MockAuditLogClient::new(&env, &audit_log_id);
+    let patient = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    client.add_verifier(&verifier);
+
+    // Without an audit log, anchors are kept locally
+    let (first, _) = client.verify_and_receipt(&verifier, &token_id);
+    assert_eq!(client.pending_anchor_count(), 1);
+    let pending = client.get_pending_anchors(&0, &0).get(0).unwrap();
+    assert_eq!(pending.kind, symbol_short!("receipt"));
+    assert_eq!(pending.token_id, token_id);
+    assert_eq!(
+        pending.record_hash,
+        env.crypto()
+            .sha256(&client.get_receipt(&first).unwrap().to_xdr(&env))
+    );
+
+    // A paused audit log doesn't block verification either
+    client.set_audit_log(&audit_log_id);
+    assert_eq!(client.get_audit_log(), Some(audit_log_id.clone()));
+    audit_log.set_paused(&true);
+    client.verify_and_receipt(&verifier, &token_id);
+    assert_eq!(client.pending_anchor_count(), 2);
+    assert_eq!(client.flush_anchors(&10), 0);
+    assert_eq!(
+        client.try_flush_anchors(&(MAX_BATCH_SIZE + 1)),
+        Err(Ok(ContractError::QuotaExceeded))
+    );
+
+    // Once it's back, the backlog is delivered oldest first
+    audit_log.set_paused(&false);
+    assert_eq!(client.flush_anchors(&1), 1);
+    assert_eq!(client.pending_anchor_count(), 1);
+    assert_eq!(client.flush_anchors(&10), 1);
+    assert_eq!(client.pending_anchor_count(), 0);
+    assert_eq!(audit_log.anchored().get(0).unwrap().2, pending.record_hash);
+
+    // With an empty backlog, anchors go straight to the audit log
+    client.verify_and_receipt(&verifier, &token_id);
+    assert_eq!(client.pending_anchor_count(), 0);
+    assert_eq!(audit_log.anchored().len(), 3);
+
+    client.clear_audit_log();
+    assert_eq!(client.get_audit_log(), None);
+}
+
+#[test]
+fn test_local_anchor_queue_is_bounded() {
+    use soroban_sdk::xdr::ToXdr;
+
+    let env = Env::default();
+    let (client, _, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    client.add_verifier(&verifier);
+
+    // Filling the queue takes more than the test's budget, which all calls share
+    env.budget().reset_unlimited();
+    for _ in 0..MAX_PENDING_ANCHORS {
+        client.verify_and_receipt(&verifier, &token_id);
+    }
+    assert_eq!(client.pending_anchor_count(), MAX_PENDING_ANCHORS);
+    assert_eq!(client.dropped_anchor_count(), 0);
+
+    // Past the cap the oldest anchor makes room for the newest
+    let (newest, _) = client.verify_and_receipt(&verifier, &token_id);
+    assert_eq!(client.pending_anchor_count(), MAX_PENDING_ANCHORS);
+    assert_eq!(client.dropped_anchor_count(), 1);
+    let last = client
+        .get_pending_anchors(&(MAX_PENDING_ANCHORS - 1), &1)
+        .get(0)
+        .unwrap();
+    assert_eq!(
+        last.record_hash,
+        env.crypto()
+            .sha256(&client.get_receipt(&newest).unwrap().to_xdr(&env))
+    );
+
+    // Setting an audit log lets one flush drain the whole queue
+    let audit_log_id = env.register_contract(None, MockAuditLog);
+    client.set_audit_log(&audit_log_id);
+    assert_eq!(client.flush_anchors(&MAX_BATCH_SIZE), MAX_PENDING_ANCHORS);
+    assert_eq!(client.pending_anchor_count(), 0);
+    assert_eq!(
+        MockAuditLogClient::new(&env, &audit_log_id)
+            .anchored()
+            .len(),
+        MAX_PENDING_ANCHORS
+    );
+}
+
+#[test]
+fn test_cooling_off_period_delays_activation() {
+    let env = Env::default();
+    let (client, admin, issuer) = setup(&env);
+    let patient = Address::generate(&env);
+    let verifier = Address::generate(&env);
+    let purpose = BytesN::from_array(&env, &[3; 32]);
+    let treatment = String::from_str(&env, "treatment");
+    client.add_verifier(&verifier);
+    clock::set(&env, 1_000);
+
+    client.set_cooling_off_period(&treatment, &3_600);
+    assert_eq!(client.get_cooling_off_period(&treatment), 3_600);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    assert_eq!(client.get_activation_time(&token_id), 4_600);
+
+    // Verifiers see the pending state rather than a revocation
+    let report = client.validity(&token_id);
+    assert!(!report.valid);
+    assert_eq!(
+        report.reasons,
+        vec![&env, ValidityReason::PendingActivation]
+    );
+    assert_eq!(
+        client
+            .get_statuses(&vec![&env, token_id])
+            .get(0)
+            .unwrap()
+            .status,
+        ConsentStatus::PendingActivation
+    );
+    assert_eq!(client.get_verification_payload(&token_id).get(1), Some(5));
+    assert_eq!(
+        client.try_log_access(&verifier, &token_id, &verifier, &purpose),
+        Err(Ok(ContractError::NotYetActive))
+    );
+
+    // Cancelling during the period voids the consent without counting as a revocation
+    client.cancel_pending_consent(&token_id);
+    assert!(client.is_revoked(&token_id));
+    assert_eq!(client.metrics().revoked, 0);
+    assert!(client.export_revocations(&admin, &0, &10).is_empty());
+    let history = client.get_history(&token_id);
+    assert_eq!(
+        history.get(history.len() - 1).unwrap().action,
+        String::from_str(&env, "cancelled")
+    );
+    assert_eq!(
+        client.try_cancel_pending_consent(&token_id),
+        Err(Ok(ContractError::ConsentRevoked))
+    );
+
+    // Once the period elapses the consent is active and can only be revoked
+    let token_id = mint(&env, &client, &issuer, &patient);
+    clock::advance(&env, 3_600);
+    assert!(client.is_valid(&token_id));
+    assert_eq!(
+        client.try_cancel_pending_consent(&token_id),
+        Err(Ok(ContractError::PolicyViolation))
+    );
+
+    // Consents minted after the period is removed activate at once
+    client.set_cooling_off_period(&treatment, &0);
+    let token_id = mint(&env, &client, &issuer, &patient);
+    assert_eq!(client.get_activation_time(&token_id), 0);
+    assert!(client.is_valid(&token_id));
+}
+
+#[test]
+fn test_find_active_consent_for_institution() {
+    let env = Env::default();
+    let (client, _, global_issuer) = setup(&env);
+    let hospital_admin = Address::generate(&env);
+    let clinician = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let treatment = String::from_str(&env, "treatment");
+    let hospital =
+        client.create_institution(&String::from_str(&env, "Nairobi General"), &hospital_admin);
+    let clinic =
+        client.create_institution(&String::from_str(&env, "Mombasa Clinic"), &hospital_admin);
+    client.add_institution_issuer(&hospital_admin, &hospital, &clinician);
+
+    // Consents from outside the institution don't cover it
+    mint(&env, &client, &global_issuer, &patient);
+    assert_eq!(
+        client.find_active_consent(&patient, &treatment, &hospital),
+        None
+    );
+
+    let token_id = mint(&env, &client, &clinician, &patient);
+    assert_eq!(
+        client.find_active_consent(&patient, &treatment, &hospital),
+        Some(token_id)
+    );
+    assert_eq!(
+        client.find_active_consent(&patient, &treatment, &clinic),
+        None
+    );
+    assert_eq!(
+        client.find_active_consent(&patient, &String::from_str(&env, "research"), &hospital),
+        None
+    );
+
+    client.revoke_consent(&token_id);
+    assert_eq!(
+        client.find_active_consent(&patient, &treatment, &hospital),
+        None
+    );
+}
@@ -0,0 +1,135 @@
+use super::*;
+
+/// How long a ticket can be redeemed for after it is issued
+pub const TICKET_LIFETIME: u64 = 24 * 60 * 60;
+
+// Storage keys for one-time access tickets
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TicketKey {
+    TicketCounter,
+    Ticket(u64),
+}
+
+// Single-use access ticket - burned on redemption
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccessTicket {
+    pub token_id: u64,
+    pub grantee: Address,      // Only this address can redeem the ticket
+    pub scope: u32,            // Scope bitmap the ticket grants access to
+    pub issued_by: Address,    // Patient who issued the ticket
+    pub issued_timestamp: u64, // When the ticket was issued
+    pub expiry: u64,           // Last moment the ticket can be redeemed
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Issue a single-use access ticket against a consent (e.g. for a referral
+    /// or ER visit). `scope` must be non-empty and covered by the consent; the
+    /// ticket can be redeemed for `TICKET_LIFETIME` seconds.
+    pub fn issue_access_ticket(
+        env: Env,
+        token_id: u64,
+        grantee: Address,
+        scope: u32,
+    ) -> Result<u64, ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+
+        // Patient controls who may see their data, same as revoke
+        metadata.patient.require_auth();
+        ensure_active(&env, token_id, &metadata)?;
+        if scope == 0 || token_scopes(&env, token_id) & scope != scope {
+            return Err(ContractError::ScopeNotGranted);
+        }
+
+        let ticket_id: u64 = env
+            .storage()
+            .instance()
+            .get(&TicketKey::TicketCounter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&TicketKey::TicketCounter, &(ticket_id + 1));
+
+        let ticket = AccessTicket {
+            token_id,
+            grantee: grantee.clone(),
+            scope,
+            issued_by: metadata.patient.clone(),
+            issued_timestamp: clock::now(&env),
+            expiry: clock::now(&env).saturating_add(TICKET_LIFETIME),
+        };
+        env.storage()
+            .persistent()
+            .set(&TicketKey::Ticket(ticket_id), &ticket);
+        ttl::bump_entry(&env, &TicketKey::Ticket(ticket_id));
+
+        append_history(
+            &env,
+            token_id,
+            "ticket_issued",
+            &metadata.patient,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("ticket"), symbol_short!("issued")),
+            token_id,
+            (ticket_id, token_id, grantee, scope),
+        );
+
+        Ok(ticket_id)
+    }
+
+    /// Redeem a ticket - the grantee uses it once, after which it is burned.
+    /// Fails with `RequestExpired` once the ticket's lifetime has passed.
+    pub fn redeem_ticket(env: Env, ticket_id: u64) -> Result<AccessTicket, ContractError> {
+        let ticket: AccessTicket = env
+            .storage()
+            .persistent()
+            .get(&TicketKey::Ticket(ticket_id))
+            .ok_or(ContractError::TicketNotFound)?;
+
+        ticket.grantee.require_auth();
+        if clock::has_passed(&env, ticket.expiry) {
+            return Err(ContractError::RequestExpired);
+        }
+
+        let metadata = read_metadata(&env, ticket.token_id)?;
+        ensure_active(&env, ticket.token_id, &metadata)?;
+
+        // Burn the ticket so it cannot be used again
+        env.storage()
+            .persistent()
+            .remove(&TicketKey::Ticket(ticket_id));
+
+        append_history(
+            &env,
+            ticket.token_id,
+            "ticket_redeemed",
+            &ticket.grantee,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, ticket.token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("ticket"), symbol_short!("redeemed")),
+            ticket.token_id,
+            (ticket_id, ticket.token_id, ticket.grantee.clone()),
+        );
+
+        Ok(ticket)
+    }
+
+    /// Get an outstanding (unredeemed) ticket
+    pub fn get_ticket(env: Env, ticket_id: u64) -> Option<AccessTicket> {
+        env.storage()
+            .persistent()
+            .get(&TicketKey::Ticket(ticket_id))
+    }
+}
@@ -0,0 +1,161 @@
+use super::*;
+use soroban_sdk::{contractclient, BytesN};
+
+// Storage keys for transfer recipient whitelists
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TransferKey {
+    GlobalWhitelist,     // Applies to tokens without their own whitelist
+    TokenWhitelist(u64), // Overrides the global whitelist for one token
+    Custodial,           // Restricts every recipient to attested custodians
+}
+
+// Custodial transfer mode: recipients must hold one of `claims` in the
+// identity registry, e.g. the custodian or healthcare proxy attestation
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CustodialPolicy {
+    pub registry: Address, // identity_registry contract
+    pub claims: Vec<BytesN<32>>,
+}
+
+/// The part of the identity_registry contract's interface this contract reads
+#[allow(dead_code)] // Only used through the generated client
+#[contractclient(name = "IdentityRegistryClient")]
+pub trait IdentityRegistryInterface {
+    fn is_attested(env: Env, subject: Address, claim_hash: BytesN<32>) -> bool;
+}
+
+fn custodial_policy(env: &Env) -> Option<CustodialPolicy> {
+    env.storage().instance().get(&TransferKey::Custodial)
+}
+
+fn is_custodian(env: &Env, policy: &CustodialPolicy, address: &Address) -> bool {
+    let registry = IdentityRegistryClient::new(env, &policy.registry);
+    policy
+        .claims
+        .iter()
+        .any(|claim| matches!(registry.try_is_attested(address, &claim), Ok(Ok(true))))
+}
+
+fn whitelist_for(env: &Env, token_id: u64) -> Option<Vec<Address>> {
+    let key = TransferKey::TokenWhitelist(token_id);
+    ttl::bump_entry(env, &key);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .or_else(|| env.storage().instance().get(&TransferKey::GlobalWhitelist))
+}
+
+pub(crate) fn recipient_allowed(env: &Env, token_id: u64, to: &Address) -> bool {
+    if let Some(policy) = custodial_policy(env) {
+        if !is_custodian(env, &policy, to) {
+            return false;
+        }
+    }
+    match whitelist_for(env, token_id) {
+        Some(allowed) => allowed.contains(to),
+        None => true,
+    }
+}
+
+/// Fails if `to` is not an allowed recipient for the token. Blocked attempts
+/// emit an event, which shows up in the failed transaction's diagnostic events.
+pub(crate) fn check_recipient(
+    env: &Env,
+    token_id: u64,
+    from: &Address,
+    to: &Address,
+) -> Result<(), ContractError> {
+    if recipient_allowed(env, token_id, to) {
+        return Ok(());
+    }
+    events::publish_token(
+        env,
+        (symbol_short!("transfer"), symbol_short!("blocked")),
+        token_id,
+        (token_id, from.clone(), to.clone()),
+    );
+    Err(ContractError::RecipientNotAllowed)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Restrict transfer recipients to a whitelist (e.g. registered healthcare proxies).
+    /// With `token_id` the patient sets a whitelist for one token, otherwise the admin
+    /// sets the global one. An empty list removes the restriction.
+    pub fn set_transfer_whitelist(
+        env: Env,
+        token_id: Option<u64>,
+        recipients: Vec<Address>,
+    ) -> Result<(), ContractError> {
+        match token_id {
+            Some(token_id) => {
+                let metadata = read_metadata(&env, token_id)?;
+                metadata.patient.require_auth();
+
+                let key = TransferKey::TokenWhitelist(token_id);
+                if recipients.is_empty() {
+                    env.storage().persistent().remove(&key);
+                } else {
+                    env.storage().persistent().set(&key, &recipients);
+                    ttl::bump_entry(&env, &key);
+                }
+                ttl::bump_token(&env, token_id);
+            }
+            None => {
+                read_admin(&env)?.require_auth();
+                let key = TransferKey::GlobalWhitelist;
+                if recipients.is_empty() {
+                    env.storage().instance().remove(&key);
+                } else {
+                    env.storage().instance().set(&key, &recipients);
+                }
+            }
+        }
+
+        env.events().publish(
+            (symbol_short!("transfer"), symbol_short!("whitelist")),
+            (token_id, recipients.len()),
+        );
+        Ok(())
+    }
+
+    /// Get the whitelist that applies to a token (`None` = unrestricted)
+    pub fn get_transfer_whitelist(env: Env, token_id: u64) -> Option<Vec<Address>> {
+        whitelist_for(&env, token_id)
+    }
+
+    /// Only allow transfers to addresses the identity registry attests as
+    /// custodians or proxies, i.e. holding one of `claims`, so consents can't
+    /// be passed around between arbitrary accounts (admin only). Whitelists
+    /// still apply on top. An empty `claims` list turns the mode off.
+    pub fn set_custodial_transfers(
+        env: Env,
+        registry: Address,
+        claims: Vec<BytesN<32>>,
+    ) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let enabled = !claims.is_empty();
+        if enabled {
+            env.storage().instance().set(
+                &TransferKey::Custodial,
+                &CustodialPolicy { registry, claims },
+            );
+        } else {
+            env.storage().instance().remove(&TransferKey::Custodial);
+        }
+
+        env.events().publish(
+            (symbol_short!("transfer"), symbol_short!("custodial")),
+            enabled,
+        );
+        Ok(())
+    }
+
+    /// Get the custodial transfer mode (`None` = any recipient)
+    pub fn get_custodial_policy(env: Env) -> Option<CustodialPolicy> {
+        custodial_policy(&env)
+    }
+}
@@ -0,0 +1,97 @@
+use super::*;
+use soroban_sdk::{IntoVal, Val};
+
+// Roughly one day of ledgers at ~5s close time
+pub(crate) const DAY_IN_LEDGERS: u32 = 17_280;
+
+// Storage keys for ledger TTL management
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TtlKey {
+    TtlPolicy,
+}
+
+/// How far token entries are extended whenever they are used
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlPolicy {
+    pub threshold: u32, // Only extend once remaining TTL drops below this (ledgers)
+    pub extend_to: u32, // New TTL after an extension (ledgers)
+}
+
+/// Policy used until the admin configures another
+pub(crate) const DEFAULT_TTL_POLICY: TtlPolicy = TtlPolicy {
+    threshold: 7 * DAY_IN_LEDGERS,
+    extend_to: 30 * DAY_IN_LEDGERS,
+};
+
+pub(crate) fn ttl_policy(env: &Env) -> TtlPolicy {
+    env.storage()
+        .instance()
+        .get(&TtlKey::TtlPolicy)
+        .unwrap_or(DEFAULT_TTL_POLICY)
+}
+
+pub(crate) fn write_policy(env: &Env, policy: &TtlPolicy) -> Result<(), ContractError> {
+    // The network caps how far ahead rent can be paid
+    if policy.threshold > policy.extend_to || policy.extend_to > env.storage().max_ttl() {
+        return Err(ContractError::PolicyViolation);
+    }
+    env.storage().instance().set(&TtlKey::TtlPolicy, policy);
+    Ok(())
+}
+
+fn extend_if_present<K>(env: &Env, key: &K, policy: &TtlPolicy)
+where
+    K: IntoVal<Env, Val>,
+{
+    if env.storage().persistent().has(key) {
+        env.storage()
+            .persistent()
+            .extend_ttl(key, policy.threshold, policy.extend_to);
+    }
+}
+
+/// Extend the TTL of a token's core entries, plus the contract instance, so
+/// consents in active use never get archived. Modules extend the per-token
+/// entries they own with `bump_entry` when they touch them.
+pub(crate) fn bump_token(env: &Env, token_id: u64) {
+    let policy = ttl_policy(env);
+    extend_if_present(env, &DataKey::TokenOwner(token_id), &policy);
+    extend_if_present(env, &DataKey::TokenMetadata(token_id), &policy);
+    extend_if_present(env, &DataKey::TokenRevoked(token_id), &policy);
+    extend_if_present(env, &DataKey::ConsentHistory(token_id), &policy);
+    extend_if_present(env, &DataKey::TokenScopes(token_id), &policy);
+    env.storage()
+        .instance()
+        .extend_ttl(policy.threshold, policy.extend_to);
+}
+
+/// Extend the TTL of a single persistent entry (e.g. an address index)
+pub(crate) fn bump_entry<K>(env: &Env, key: &K)
+where
+    K: IntoVal<Env, Val>,
+{
+    extend_if_present(env, key, &ttl_policy(env));
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Configure how far token entries are extended on access (admin only)
+    pub fn set_ttl_policy(env: Env, threshold: u32, extend_to: u32) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        write_policy(
+            &env,
+            &TtlPolicy {
+                threshold,
+                extend_to,
+            },
+        )
+    }
+
+    /// Get the current TTL extension policy
+    pub fn get_ttl_policy(env: Env) -> TtlPolicy {
+        ttl_policy(&env)
+    }
+}
@@ -0,0 +1,118 @@
+use super::*;
+
+// Storage keys for mint-time validation settings
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValidationKey {
+    MaxExpiryDuration, // Max seconds between mint and expiry (0 = unlimited)
+    ConsentTypes,      // Consent types accepted at mint
+}
+
+/// Consent types accepted by a freshly initialized contract
+const DEFAULT_CONSENT_TYPES: [&str; 4] = ["treatment", "research", "emergency", "data_sharing"];
+
+pub(crate) fn default_consent_types(env: &Env) -> Vec<String> {
+    let mut types = Vec::new(env);
+    for consent_type in DEFAULT_CONSENT_TYPES {
+        types.push_back(String::from_str(env, consent_type));
+    }
+    types
+}
+
+pub(crate) fn write_consent_types(env: &Env, types: &Vec<String>) {
+    env.storage()
+        .instance()
+        .set(&ValidationKey::ConsentTypes, types);
+}
+
+pub(crate) fn write_max_expiry_duration(env: &Env, max_duration: u64) {
+    env.storage()
+        .instance()
+        .set(&ValidationKey::MaxExpiryDuration, &max_duration);
+}
+
+pub(crate) fn validate_uri(metadata_uri: &String) -> Result<(), ContractError> {
+    if metadata_uri.len() == 0 {
+        return Err(ContractError::InvalidUri);
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_expiry(env: &Env, expiry_timestamp: u64) -> Result<(), ContractError> {
+    let now = clock::now(env);
+    if expiry_timestamp != 0 && expiry_timestamp <= now {
+        return Err(ContractError::PastTimestamp);
+    }
+
+    let max_duration: u64 = env
+        .storage()
+        .instance()
+        .get(&ValidationKey::MaxExpiryDuration)
+        .unwrap_or(0);
+    // With a cap configured, permanent consents are not allowed either
+    if max_duration != 0 && (expiry_timestamp == 0 || expiry_timestamp - now > max_duration) {
+        return Err(ContractError::ExpiryTooLong);
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_consent_type(env: &Env, consent_type: &String) -> Result<(), ContractError> {
+    if consent_types(env).contains(consent_type) {
+        Ok(())
+    } else {
+        Err(ContractError::UnknownConsentType)
+    }
+}
+
+pub(crate) fn consent_types(env: &Env) -> Vec<String> {
+    env.storage()
+        .instance()
+        .get(&ValidationKey::ConsentTypes)
+        .unwrap_or(Vec::new(env))
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set the maximum consent duration accepted at mint (0 = unlimited)
+    pub fn set_max_expiry_duration(env: Env, max_duration: u64) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        write_max_expiry_duration(&env, max_duration);
+        Ok(())
+    }
+
+    /// Get the maximum consent duration accepted at mint (0 = unlimited)
+    pub fn get_max_expiry_duration(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&ValidationKey::MaxExpiryDuration)
+            .unwrap_or(0)
+    }
+
+    /// Allow a new consent type to be minted
+    pub fn add_consent_type(env: Env, consent_type: String) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut types = consent_types(&env);
+        if !types.contains(&consent_type) {
+            types.push_back(consent_type);
+            write_consent_types(&env, &types);
+        }
+        Ok(())
+    }
+
+    /// Stop accepting a consent type for new mints (existing tokens are unaffected)
+    pub fn remove_consent_type(env: Env, consent_type: String) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        let mut types = consent_types(&env);
+        if let Some(index) = types.first_index_of(&consent_type) {
+            types.remove(index);
+            write_consent_types(&env, &types);
+        }
+        Ok(())
+    }
+
+    /// Get the consent types accepted at mint
+    pub fn get_consent_types(env: Env) -> Vec<String> {
+        consent_types(&env)
+    }
+}
@@ -0,0 +1,133 @@
+use super::*;
+
+/// Largest clock skew tolerance the admin can configure, in seconds
+pub const MAX_CLOCK_SKEW: u64 = 300;
+
+// Storage keys for validity checks
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValidityKey {
+    ClockSkew, // Seconds of leeway on not-before and expiry comparisons
+}
+
+/// Why a consent can't currently be used
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValidityReason {
+    Revoked,
+    Expired,
+    NotYetActive,      // Issued with a timestamp still in the future
+    Dormant,           // Posthumous release waiting for an attested death
+    Suspended,         // Issuer removed or paused; awaiting admin review
+    PendingActivation, // Still in its type's cooling-off period
+}
+
+// Everything a verifier needs to know about whether a consent can be used
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidityReport {
+    pub valid: bool,
+    pub reasons: Vec<ValidityReason>, // Every failed check, empty when valid
+    pub remaining_seconds: u64,       // Until expiry plus tolerance; u64::MAX = no expiry
+    pub version: u32,                 // Metadata version the report applies to
+    pub skew_tolerance: u64,          // Clock skew tolerance the checks were made with
+}
+
+pub(crate) fn skew_tolerance(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&ValidityKey::ClockSkew)
+        .unwrap_or(0)
+}
+
+pub(crate) fn write_skew_tolerance(env: &Env, seconds: u64) -> Result<(), ContractError> {
+    if seconds > MAX_CLOCK_SKEW {
+        return Err(ContractError::PolicyViolation);
+    }
+    env.storage()
+        .instance()
+        .set(&ValidityKey::ClockSkew, &seconds);
+    Ok(())
+}
+
+/// Seconds a consent can still be used, counting the skew tolerance past its
+/// expiry (`u64::MAX` when it never expires)
+fn remaining(env: &Env, expiry_timestamp: u64) -> u64 {
+    if expiry_timestamp == 0 {
+        return u64::MAX;
+    }
+    clock::seconds_until(env, expiry_timestamp.saturating_add(skew_tolerance(env)))
+}
+
+/// Whether a consent is past its expiry, once the skew tolerance has elapsed too
+pub(crate) fn is_expired(env: &Env, expiry_timestamp: u64) -> bool {
+    remaining(env, expiry_timestamp) == 0
+}
+
+fn report(env: &Env, token_id: u64, metadata: &ConsentMetadata) -> ValidityReport {
+    let now = clock::now(env);
+    let mut reasons = Vec::new(env);
+    let revoked: bool = env
+        .storage()
+        .persistent()
+        .get(&DataKey::TokenRevoked(token_id))
+        .unwrap_or(false);
+    if revoked {
+        reasons.push_back(ValidityReason::Revoked);
+    }
+    let remaining_seconds = remaining(env, metadata.expiry_timestamp);
+    if remaining_seconds == 0 {
+        reasons.push_back(ValidityReason::Expired);
+    }
+    let skew_tolerance = skew_tolerance(env);
+    if metadata.issued_timestamp > now.saturating_add(skew_tolerance) {
+        reasons.push_back(ValidityReason::NotYetActive);
+    }
+    if posthumous::ensure_not_dormant(env, token_id, &metadata.patient).is_err() {
+        reasons.push_back(ValidityReason::Dormant);
+    }
+    if suspension::is_suspended(env, token_id) {
+        reasons.push_back(ValidityReason::Suspended);
+    }
+    if cooling_off::is_pending(env, token_id) {
+        reasons.push_back(ValidityReason::PendingActivation);
+    }
+    ValidityReport {
+        valid: reasons.is_empty(),
+        reasons,
+        remaining_seconds,
+        version: metadata.version,
+        skew_tolerance,
+    }
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Set how many seconds of clock skew to tolerate when comparing the ledger
+    /// time with a consent's issue and expiry times, so consents at the edge
+    /// don't flap between valid and invalid across verifiers (admin only, at
+    /// most `MAX_CLOCK_SKEW`)
+    pub fn set_clock_skew_tolerance(env: Env, seconds: u64) -> Result<(), ContractError> {
+        read_admin(&env)?.require_auth();
+        write_skew_tolerance(&env, seconds)
+    }
+
+    /// Get the clock skew tolerance in seconds
+    pub fn get_clock_skew_tolerance(env: Env) -> u64 {
+        skew_tolerance(&env)
+    }
+
+    /// Explain whether a consent can be used: every reason it can't, how long
+    /// until it expires, and which metadata version was checked
+    pub fn validity(env: Env, token_id: u64) -> Result<ValidityReport, ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        ttl::bump_token(&env, token_id);
+        Ok(report(&env, token_id, &metadata))
+    }
+
+    /// Check if consent is valid. Kept for existing callers; `validity` says why not.
+    pub fn is_valid(env: Env, token_id: u64) -> Result<bool, ContractError> {
+        Ok(Self::validity(env, token_id)?.valid)
+    }
+}
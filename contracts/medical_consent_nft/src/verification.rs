@@ -0,0 +1,80 @@
+use super::*;
+use soroban_sdk::{xdr::ToXdr, Bytes};
+
+/// Current verification payload format version
+pub const PAYLOAD_VERSION: u8 = 1;
+
+// Status byte values carried in the payload
+pub const STATUS_VALID: u8 = 0;
+pub const STATUS_REVOKED: u8 = 1;
+pub const STATUS_EXPIRED: u8 = 2;
+pub const STATUS_DORMANT: u8 = 3;
+pub const STATUS_SUSPENDED: u8 = 4;
+pub const STATUS_PENDING: u8 = 5;
+
+// version(1) | status(1) | token_id(8) | expiry(8) | scopes(4) | sha256(issuer)(32)
+const BODY_LEN: u32 = 54;
+// body | sha256(contract address || body)(32)
+const PAYLOAD_LEN: u32 = BODY_LEN + 32;
+
+fn status_byte(status: ConsentStatus) -> u8 {
+    match status {
+        ConsentStatus::Valid => STATUS_VALID,
+        ConsentStatus::Expired => STATUS_EXPIRED,
+        ConsentStatus::Dormant => STATUS_DORMANT,
+        ConsentStatus::Suspended => STATUS_SUSPENDED,
+        ConsentStatus::PendingActivation => STATUS_PENDING,
+        // Payloads are only built for existing tokens
+        ConsentStatus::Revoked | ConsentStatus::NotFound => STATUS_REVOKED,
+    }
+}
+
+fn build_payload(env: &Env, token_id: u64) -> Result<Bytes, ContractError> {
+    let metadata = read_metadata(env, token_id)?;
+
+    let status = status_byte(batch::consent_status(env, token_id, &metadata));
+
+    let mut body = Bytes::new(env);
+    body.push_back(PAYLOAD_VERSION);
+    body.push_back(status);
+    body.extend_from_array(&token_id.to_be_bytes());
+    body.extend_from_array(&metadata.expiry_timestamp.to_be_bytes());
+    body.extend_from_array(&token_scopes(env, token_id).to_be_bytes());
+    let issuer_hash = env.crypto().sha256(&metadata.issuer.to_xdr(env));
+    body.extend_from_array(&issuer_hash.to_array());
+
+    // Bind the payload to this deployment so it can't be replayed against another
+    let mut preimage = env.current_contract_address().to_xdr(env);
+    preimage.append(&body);
+    let digest = env.crypto().sha256(&preimage);
+
+    let mut payload = body;
+    payload.extend_from_array(&digest.to_array());
+    Ok(payload)
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Get a compact, versioned status payload for a consent (suitable for QR codes)
+    pub fn get_verification_payload(env: Env, token_id: u64) -> Result<Bytes, ContractError> {
+        let payload = build_payload(&env, token_id)?;
+        ttl::bump_token(&env, token_id);
+        Ok(payload)
+    }
+
+    /// Re-validate a payload on-chain: true only if it is well-formed, untampered
+    /// and still matches the consent's current status
+    pub fn verify_payload(env: Env, payload: Bytes) -> bool {
+        if payload.len() != PAYLOAD_LEN || payload.get(0) != Some(PAYLOAD_VERSION) {
+            return false;
+        }
+
+        let mut token_id = [0u8; 8];
+        payload.slice(2..10).copy_into_slice(&mut token_id);
+
+        match build_payload(&env, u64::from_be_bytes(token_id)) {
+            Ok(expected) => expected == payload,
+            Err(_) => false,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use super::*;
+
+/// The consent type
+pub const VIEW_TYPE: u32 = 1;
+/// When the consent expires
+pub const VIEW_EXPIRY: u32 = 2;
+/// Whether the consent is currently valid
+pub const VIEW_STATUS: u32 = 4;
+/// Who issued the consent
+pub const VIEW_ISSUER: u32 = 8;
+/// When the consent was issued
+pub const VIEW_ISSUED_AT: u32 = 16;
+/// Every field above
+pub const VIEW_ALL: u32 = VIEW_TYPE | VIEW_EXPIRY | VIEW_STATUS | VIEW_ISSUER | VIEW_ISSUED_AT;
+
+// Storage keys for read-only consent views
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ViewKey {
+    ViewCounter,
+    View(u64),
+}
+
+// A shareable, expiring handle on some fields of a consent
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsentView {
+    pub token_id: u64,
+    pub fields: u32, // Bitmask of `VIEW_*` fields the view shows
+    pub expiry: u64, // When the view stops resolving
+    pub created_by: Address,
+}
+
+// What a view shows. Fields outside `fields` are blanked: empty, 0 or
+// `false`, and this contract's address for the issuer.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConsentViewData {
+    pub token_id: u64,
+    pub fields: u32,
+    pub consent_type: String,
+    pub expiry_timestamp: u64,
+    pub valid: bool,
+    pub issuer: Address,
+    pub issued_timestamp: u64,
+}
+
+fn stored_view(env: &Env, view_id: u64) -> Option<ConsentView> {
+    env.storage().persistent().get(&ViewKey::View(view_id))
+}
+
+#[contractimpl]
+impl PatientConsentToken {
+    /// Create a read-only view of a consent showing only `fields_mask` (a mask
+    /// of `VIEW_*` bits) until `expiry`, so the patient can share limited
+    /// consent information, e.g. with an employer, without granting access to
+    /// any data (patient only). Returns the view's ID, which is the handle to
+    /// share.
+    pub fn create_view(
+        env: Env,
+        token_id: u64,
+        fields_mask: u32,
+        expiry: u64,
+    ) -> Result<u64, ContractError> {
+        let metadata = read_metadata(&env, token_id)?;
+        metadata.patient.require_auth();
+        ensure_active(&env, token_id, &metadata)?;
+        if fields_mask == 0 || fields_mask & !VIEW_ALL != 0 {
+            return Err(ContractError::PolicyViolation);
+        }
+        if expiry <= clock::now(&env) {
+            return Err(ContractError::PastTimestamp);
+        }
+
+        let view_id: u64 = env
+            .storage()
+            .instance()
+            .get(&ViewKey::ViewCounter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&ViewKey::ViewCounter, &(view_id + 1));
+
+        let key = ViewKey::View(view_id);
+        env.storage().persistent().set(
+            &key,
+            &ConsentView {
+                token_id,
+                fields: fields_mask,
+                expiry,
+                created_by: metadata.patient.clone(),
+            },
+        );
+        ttl::bump_entry(&env, &key);
+
+        append_history(
+            &env,
+            token_id,
+            "view_created",
+            &metadata.patient,
+            &metadata.metadata_uri,
+        );
+        ttl::bump_token(&env, token_id);
+
+        events::publish_token(
+            &env,
+            (symbol_short!("view"), symbol_short!("created")),
+            token_id,
+            (token_id, view_id, fields_mask, expiry),
+        );
+        Ok(view_id)
+    }
+
+    /// Withdraw a view before it expires (patient only)
+    pub fn revoke_view(env: Env, view_id: u64) -> Result<(), ContractError> {
+        let view = stored_view(&env, view_id).ok_or(ContractError::NotAuthorized)?;
+        view.created_by.require_auth();
+        env.storage().persistent().remove(&ViewKey::View(view_id));
+
+        events::publish_token(
+            &env,
+            (symbol_short!("view"), symbol_short!("revoked")),
+            view.token_id,
+            (view.token_id, view_id),
+        );
+        Ok(())
+    }
+
+    /// Read the fields a view shows. Anyone holding the view's ID may read
+    /// it; `None` once it has expired or been withdrawn.
+    pub fn read_view(env: Env, view_id: u64) -> Option<ConsentViewData> {
+        let view = stored_view(&env, view_id)?;
+        if view.expiry <= clock::now(&env) {
+            return None;
+        }
+        let mut metadata = read_metadata(&env, view.token_id).ok()?;
+        let shows = |field: u32| view.fields & field != 0;
+        if !shows(VIEW_TYPE) {
+            metadata.consent_type = String::from_str(&env, "");
+        }
+        if !shows(VIEW_ISSUER) {
+            metadata.issuer = env.current_contract_address();
+        }
+
+        Some(ConsentViewData {
+            token_id: view.token_id,
+            fields: view.fields,
+            consent_type: metadata.consent_type,
+            expiry_timestamp: if shows(VIEW_EXPIRY) {
+                metadata.expiry_timestamp
+            } else {
+                0
+            },
+            valid: shows(VIEW_STATUS)
+                && Self::is_valid(env.clone(), view.token_id).unwrap_or(false),
+            issuer: metadata.issuer,
+            issued_timestamp: if shows(VIEW_ISSUED_AT) {
+                metadata.issued_timestamp
+            } else {
+                0
+            },
+        })
+    }
+}
@@ -3,7 +3,7 @@
 #[cfg(test)]
 mod test;
 
-use soroban_sdk::{contract, contractimpl, contracttype, vec, Address, Env, Map, String, Symbol, Vec, panic_with_error, contracterror};
+use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, vec, Address, Env, Map, String, Symbol, Vec, panic_with_error, contracterror};
 
 #[derive(Clone)]
 #[contracttype]
@@ -35,10 +35,34 @@ pub struct MedicalRecord {
     pub treatment_type: String,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ExecutorCondition {
+    Death,
+    Incapacity,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ExecutorScope {
+    NonConfidential,
+    All,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ExecutorDesignation {
+    pub executor: Address,
+    pub scope: ExecutorScope,
+    pub conditions: Vec<ExecutorCondition>, // Any one of these, once attested, activates the executor
+    pub designated_at: u64,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
     RecordCount,
+    Attested(Address), // Owner -> conditions the oracle attested, with when
 }
 
 const USERS: Symbol = Symbol::short("USERS");
@@ -50,6 +74,12 @@ const PAUSED: Symbol = Symbol::short("PAUSED");
 const PROPOSALS: Symbol = Symbol::short("PROPOSALS");
 const APPROVAL_THRESHOLD: u32 = 2;
 const TIMELOCK_SECS: u64 = 86_400; // 24 hours timelock
+// Executor designation storage
+const EXECUTORS: Symbol = symbol_short!("EXECUTORS");
+const ORACLE: Symbol = symbol_short!("ORACLE");
+const EXECUTOR_TIMELOCK_SECS: u64 = 7 * 86_400; // 7 days between attestation and executor access
+// Data residency storage
+const RESIDENCY: Symbol = symbol_short!("RESIDENCY");
 
 #[derive(Clone)]
 #[contracttype]
@@ -76,6 +106,11 @@ pub enum Error {
     ProposalAlreadyExecuted = 6,
     TimelockNotElasped = 7,
     NotEnoughApproval = 8,
+    DesignationNotFound = 9,
+    InvalidDesignation = 10,
+    OracleNotSet = 11,
+    RecordNotFound = 12,
+    AttestationNotFound = 13,
 }
 
 #[contract]
@@ -291,10 +326,12 @@ impl MedicalRecordsContract {
             // 2. Caller is the patient
             // 3. Caller is the doctor who created the record
             // 4. Caller is any doctor and record is not confidential
+            // 5. Caller is the patient's executor, activated and within scope
             if Self::has_role(&env, &caller, &Role::Admin)
                 || caller == record.patient_id
                 || caller == record.doctor_id
-                || (Self::has_role(&env, &caller, &Role::Doctor) && !record.is_confidential) {
+                || (Self::has_role(&env, &caller, &Role::Doctor) && !record.is_confidential)
+                || Self::executor_can_access(&env, &caller, &record) {
                 Some(record)
             } else {
                 panic!("Unauthorized access to medical record");
@@ -343,7 +380,8 @@ impl MedicalRecordsContract {
                 if Self::has_role(&env, &caller, &Role::Admin)
                     || caller == record.patient_id
                     || caller == record.doctor_id
-                    || (Self::has_role(&env, &caller, &Role::Doctor) && !record.is_confidential) {
+                    || (Self::has_role(&env, &caller, &Role::Doctor) && !record.is_confidential)
+                    || Self::executor_can_access(&env, &caller, &record) {
                     let tuple = (record_id, record);
                     history.push_back(tuple);
                 }
@@ -476,4 +514,187 @@ impl MedicalRecordsContract {
         // env.events().publish((Symbol::short("RecoveryExecuted"),), (caller.clone(), proposal_id, ts));
         Ok(true)
     }
+
+    // ------------------ Executor designation (oracle + timelock) ------------------
+
+    /// Internal function to get the conditions attested for an owner, with when they were attested
+    fn attested_conditions(env: &Env, owner: &Address) -> Map<ExecutorCondition, u64> {
+        env.storage().persistent().get(&DataKey::Attested(owner.clone())).unwrap_or(Map::new(env))
+    }
+
+    /// Internal function to get the time a patient's executor gains access, if a condition was attested.
+    /// The timelock runs from the later of the earliest attestation and the designation, so naming an
+    /// executor after an old attestation still comes with the full waiting period.
+    fn executor_active_from(env: &Env, owner: &Address, designation: &ExecutorDesignation) -> Option<u64> {
+        let attested = Self::attested_conditions(env, owner);
+        designation
+            .conditions
+            .iter()
+            .filter_map(|condition| attested.get(condition))
+            .min()
+            .map(|attested_at| attested_at.max(designation.designated_at) + EXECUTOR_TIMELOCK_SECS)
+    }
+
+    /// Internal function to check if caller is an active executor for the record's patient
+    fn executor_can_access(env: &Env, caller: &Address, record: &MedicalRecord) -> bool {
+        let executors: Map<Address, ExecutorDesignation> = env.storage().persistent().get(&EXECUTORS).unwrap_or(Map::new(env));
+        let designation = match executors.get(record.patient_id.clone()) {
+            Some(designation) => designation,
+            None => return false,
+        };
+        if designation.executor != *caller
+            || (designation.scope == ExecutorScope::NonConfidential && record.is_confidential) {
+            return false;
+        }
+        match Self::executor_active_from(env, &record.patient_id, &designation) {
+            Some(active_from) => env.ledger().timestamp() >= active_from,
+            None => false,
+        }
+    }
+
+    /// Set the oracle trusted to attest death or incapacity - only admins
+    pub fn set_condition_oracle(env: Env, caller: Address, oracle: Address) -> Result<bool, Error> {
+        caller.require_auth();
+        if !Self::has_role(&env, &caller, &Role::Admin) {
+            return Err(Error::NotAuthorized)
+        }
+        env.storage().persistent().set(&ORACLE, &oracle);
+        Ok(true)
+    }
+
+    /// Name an executor who gains access to the owner's records once one of the
+    /// conditions is attested and the timelock has passed
+    pub fn designate_executor(
+        env: Env,
+        owner: Address,
+        executor: Address,
+        scope: ExecutorScope,
+        conditions: Vec<ExecutorCondition>,
+    ) -> Result<bool, Error> {
+        owner.require_auth();
+        if conditions.is_empty() || executor == owner {
+            return Err(Error::InvalidDesignation)
+        }
+
+        let designation = ExecutorDesignation {
+            executor: executor.clone(),
+            scope,
+            conditions,
+            designated_at: env.ledger().timestamp(),
+        };
+        let mut executors: Map<Address, ExecutorDesignation> = env.storage().persistent().get(&EXECUTORS).unwrap_or(Map::new(&env));
+        executors.set(owner.clone(), designation);
+        env.storage().persistent().set(&EXECUTORS, &executors);
+
+        env.events().publish((Symbol::new(&env, "ExecutorDesignated"),), (owner, executor));
+        Ok(true)
+    }
+
+    /// Revoke an executor designation - the owner can do this at any time, including during the timelock
+    pub fn revoke_executor(env: Env, owner: Address) -> Result<bool, Error> {
+        owner.require_auth();
+        let mut executors: Map<Address, ExecutorDesignation> = env.storage().persistent().get(&EXECUTORS).unwrap_or(Map::new(&env));
+        if executors.remove(owner.clone()).is_none() {
+            return Err(Error::DesignationNotFound)
+        }
+        env.storage().persistent().set(&EXECUTORS, &executors);
+
+        env.events().publish((Symbol::new(&env, "ExecutorRevoked"),), owner);
+        Ok(true)
+    }
+
+    /// Record that a condition applies to the owner - only the condition oracle
+    pub fn attest_condition(env: Env, oracle: Address, owner: Address, condition: ExecutorCondition) -> Result<bool, Error> {
+        oracle.require_auth();
+        let expected: Address = env.storage().persistent().get(&ORACLE).ok_or(Error::OracleNotSet)?;
+        if oracle != expected {
+            return Err(Error::NotAuthorized)
+        }
+
+        let mut attested = Self::attested_conditions(&env, &owner);
+        // Keep the first attestation so repeats can't restart the timelock
+        if !attested.contains_key(condition) {
+            attested.set(condition, env.ledger().timestamp());
+            env.storage().persistent().set(&DataKey::Attested(owner.clone()), &attested);
+        }
+
+        env.events().publish((Symbol::new(&env, "ConditionAttested"),), (owner, condition));
+        Ok(true)
+    }
+
+    /// Withdraw an incapacity attestation once the owner has recovered - the condition oracle or the owner.
+    /// Death attestations can't be withdrawn.
+    pub fn clear_incapacity(env: Env, caller: Address, owner: Address) -> Result<bool, Error> {
+        caller.require_auth();
+        let oracle: Option<Address> = env.storage().persistent().get(&ORACLE);
+        if caller != owner && oracle != Some(caller.clone()) {
+            return Err(Error::NotAuthorized)
+        }
+
+        let mut attested = Self::attested_conditions(&env, &owner);
+        if attested.remove(ExecutorCondition::Incapacity).is_none() {
+            return Err(Error::AttestationNotFound)
+        }
+        let key = DataKey::Attested(owner.clone());
+        if attested.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &attested);
+        }
+
+        env.events().publish((Symbol::new(&env, "IncapacityCleared"),), (owner, caller));
+        Ok(true)
+    }
+
+    /// Get the owner's executor designation
+    pub fn get_executor(env: Env, owner: Address) -> Option<ExecutorDesignation> {
+        let executors: Map<Address, ExecutorDesignation> = env.storage().persistent().get(&EXECUTORS).unwrap_or(Map::new(&env));
+        executors.get(owner)
+    }
+
+    /// Get the time the owner's executor gains access, once a condition has been attested
+    pub fn get_executor_active_from(env: Env, owner: Address) -> Option<u64> {
+        let designation = Self::get_executor(env.clone(), owner.clone())?;
+        Self::executor_active_from(&env, &owner, &designation)
+    }
+
+    // ------------------ Data residency ------------------
+
+    /// Restrict where a record may be accessed from, as region codes such as `KE` or `EU`.
+    /// Only the patient or the doctor who created the record; an empty list lifts the restriction.
+    pub fn set_record_residency(env: Env, caller: Address, record_id: u64, regions: Vec<Symbol>) -> Result<bool, Error> {
+        caller.require_auth();
+        let records: Map<u64, MedicalRecord> = env.storage().persistent().get(&RECORDS).unwrap_or(Map::new(&env));
+        let record = records.get(record_id).ok_or(Error::RecordNotFound)?;
+        if caller != record.patient_id && caller != record.doctor_id {
+            return Err(Error::NotAuthorized)
+        }
+
+        let mut residency: Map<u64, Vec<Symbol>> = env.storage().persistent().get(&RESIDENCY).unwrap_or(Map::new(&env));
+        if regions.is_empty() {
+            residency.remove(record_id);
+        } else {
+            residency.set(record_id, regions.clone());
+        }
+        env.storage().persistent().set(&RESIDENCY, &residency);
+
+        env.events().publish((Symbol::new(&env, "ResidencySet"),), (record_id, regions));
+        Ok(true)
+    }
+
+    /// Get the regions a record is restricted to (empty = no restriction)
+    pub fn get_record_residency(env: Env, record_id: u64) -> Vec<Symbol> {
+        let residency: Map<u64, Vec<Symbol>> = env.storage().persistent().get(&RESIDENCY).unwrap_or(Map::new(&env));
+        residency.get(record_id).unwrap_or(Vec::new(&env))
+    }
+
+    /// Check whether a record may be accessed from `region`, so cross-border requests can be rejected
+    pub fn is_record_access_allowed_in(env: Env, record_id: u64, region: Symbol) -> bool {
+        let records: Map<u64, MedicalRecord> = env.storage().persistent().get(&RECORDS).unwrap_or(Map::new(&env));
+        if !records.contains_key(record_id) {
+            return false;
+        }
+        let allowed = Self::get_record_residency(env, record_id);
+        allowed.is_empty() || allowed.contains(&region)
+    }
 }
@@ -561,4 +561,110 @@ fn test_get_history_pagination_and_access() {
 
     let empty_page = client.mock_all_auths().get_history(&patient, &patient, &3u32, &1u32);
     assert_eq!(empty_page.len(), 0);
-}
\ No newline at end of file
+}
+#[test]
+fn test_executor_access_after_attestation_and_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, admin) = create_contract(&env);
+    let doctor = Address::generate(&env);
+    let patient = Address::generate(&env);
+    let executor = Address::generate(&env);
+    let oracle = Address::generate(&env);
+    client.manage_user(&admin, &doctor, &Role::Doctor);
+    client.set_condition_oracle(&admin, &oracle);
+
+    let tags = vec![&env, String::from_str(&env, "cardio")];
+    let open_id = client.add_record(&doctor, &patient, &String::from_str(&env, "Arrhythmia"), &String::from_str(&env, "Beta blockers"), &false, &tags, &String::from_str(&env, "Modern"), &String::from_str(&env, "Medication"));
+    client.add_record(&doctor, &patient, &String::from_str(&env, "Depression"), &String::from_str(&env, "Therapy"), &true, &tags, &String::from_str(&env, "Modern"), &String::from_str(&env, "Counselling"));
+
+    client.designate_executor(&patient, &executor, &ExecutorScope::NonConfidential, &vec![&env, ExecutorCondition::Incapacity]);
+    assert_eq!(client.get_history(&executor, &patient, &0, &10).len(), 0);
+
+    // Attesting a condition the designation doesn't cover changes nothing
+    client.attest_condition(&oracle, &patient, &ExecutorCondition::Death);
+    assert_eq!(client.get_executor_active_from(&patient), None);
+
+    client.attest_condition(&oracle, &patient, &ExecutorCondition::Incapacity);
+    let active_from = env.ledger().timestamp() + EXECUTOR_TIMELOCK_SECS;
+    assert_eq!(client.get_executor_active_from(&patient), Some(active_from));
+    assert_eq!(client.get_history(&executor, &patient, &0, &10).len(), 0);
+
+    env.ledger().with_mut(|l| {
+        l.timestamp = active_from;
+    });
+    let history = client.get_history(&executor, &patient, &0, &10);
+    assert_eq!(history.len(), 1);
+    // The confidential record stays out of scope
+    assert_eq!(history.get(0).unwrap().0, open_id);
+    assert!(client.get_record(&executor, &open_id).is_some());
+
+    // Only the configured oracle can attest
+    let result = client.try_attest_condition(&executor, &patient, &ExecutorCondition::Death);
+    assert_eq!(result, Err(Ok(Error::NotAuthorized)));
+
+    client.revoke_executor(&patient);
+    assert_eq!(client.get_history(&executor, &patient, &0, &10).len(), 0);
+    assert_eq!(client.try_revoke_executor(&patient), Err(Ok(Error::DesignationNotFound)));
+}
+
+#[test]
+fn test_executor_timelock_runs_from_designation_and_incapacity_can_be_cleared() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, admin) = create_contract(&env);
+    let patient = Address::generate(&env);
+    let executor = Address::generate(&env);
+    let oracle = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    client.set_condition_oracle(&admin, &oracle);
+
+    // An old attestation doesn't let a later designation skip the timelock
+    client.attest_condition(&oracle, &patient, &ExecutorCondition::Incapacity);
+    env.ledger().with_mut(|l| {
+        l.timestamp += 30 * 86_400;
+    });
+    client.designate_executor(&patient, &executor, &ExecutorScope::All, &vec![&env, ExecutorCondition::Incapacity]);
+    let active_from = env.ledger().timestamp() + EXECUTOR_TIMELOCK_SECS;
+    assert_eq!(client.get_executor_active_from(&patient), Some(active_from));
+
+    // Only the oracle or the owner can withdraw it
+    assert_eq!(client.try_clear_incapacity(&stranger, &patient), Err(Ok(Error::NotAuthorized)));
+    client.clear_incapacity(&patient, &patient);
+    assert_eq!(client.get_executor_active_from(&patient), None);
+    assert_eq!(client.try_clear_incapacity(&oracle, &patient), Err(Ok(Error::AttestationNotFound)));
+
+    // Death attestations stay when incapacity is cleared
+    client.attest_condition(&oracle, &patient, &ExecutorCondition::Incapacity);
+    client.attest_condition(&oracle, &patient, &ExecutorCondition::Death);
+    client.clear_incapacity(&oracle, &patient);
+    client.designate_executor(&patient, &executor, &ExecutorScope::All, &vec![&env, ExecutorCondition::Death]);
+    assert_eq!(client.get_executor_active_from(&patient), Some(env.ledger().timestamp() + EXECUTOR_TIMELOCK_SECS));
+}
+
+#[test]
+fn test_record_residency() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, admin) = create_contract(&env);
+    let doctor = Address::generate(&env);
+    let patient = Address::generate(&env);
+    client.manage_user(&admin, &doctor, &Role::Doctor);
+    let tags = vec![&env, String::from_str(&env, "lab")];
+    let record_id = client.add_record(&doctor, &patient, &String::from_str(&env, "Anemia"), &String::from_str(&env, "Iron"), &false, &tags, &String::from_str(&env, "Modern"), &String::from_str(&env, "Supplement"));
+
+    assert!(client.is_record_access_allowed_in(&record_id, &symbol_short!("US")));
+    client.set_record_residency(&patient, &record_id, &vec![&env, symbol_short!("KE")]);
+    assert!(client.is_record_access_allowed_in(&record_id, &symbol_short!("KE")));
+    assert!(!client.is_record_access_allowed_in(&record_id, &symbol_short!("US")));
+
+    let outsider = Address::generate(&env);
+    let result = client.try_set_record_residency(&outsider, &record_id, &Vec::new(&env));
+    assert_eq!(result, Err(Ok(Error::NotAuthorized)));
+    let result = client.try_set_record_residency(&patient, &99, &Vec::new(&env));
+    assert_eq!(result, Err(Ok(Error::RecordNotFound)));
+    assert!(!client.is_record_access_allowed_in(&99, &symbol_short!("KE")));
+}
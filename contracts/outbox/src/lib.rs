@@ -0,0 +1,204 @@
+#![no_std]
+
+#[cfg(test)]
+mod test;
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Env, Symbol, Vec,
+};
+use uzima_common::{admin, allowlist, clock, page};
+
+/// Largest page returned by `pull`
+pub const MAX_PULL: u32 = 100;
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    Reader,   // Off-chain service that acknowledges messages
+    Writers,  // Contracts allowed to push messages
+    Capacity, // Number of slots in the ring buffer
+    NextSeq,  // Sequence id of the next message
+    AckedSeq, // Every message below this has been acknowledged
+    Slot(u32),
+}
+
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    NotAuthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    InvalidCapacity = 4,
+    InvalidSequence = 5,
+}
+
+// Canonical state-change message
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Message {
+    pub seq: u64,
+    pub source: Address, // Contract that pushed the message
+    pub action: Symbol,  // e.g. "issued", "revoked"
+    pub subject_id: u64, // Id of the changed record (e.g. a token id)
+    pub timestamp: u64,
+}
+
+// Cursor positions of the buffer
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutboxState {
+    pub capacity: u32,
+    pub next_seq: u64,   // Sequence id the next message will get
+    pub acked_seq: u64,  // Every message below this has been acknowledged
+    pub oldest_seq: u64, // Oldest message still in the buffer
+}
+
+fn read_admin(env: &Env) -> Result<Address, Error> {
+    admin::read(env, &DataKey::Admin).ok_or(Error::NotInitialized)
+}
+
+fn read_u64(env: &Env, key: &DataKey) -> u64 {
+    env.storage().instance().get(key).unwrap_or(0)
+}
+
+fn writers(env: &Env) -> Vec<Address> {
+    allowlist::members(env, &DataKey::Writers)
+}
+
+fn state(env: &Env) -> Result<OutboxState, Error> {
+    let capacity: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::Capacity)
+        .ok_or(Error::NotInitialized)?;
+    let next_seq = read_u64(env, &DataKey::NextSeq);
+    Ok(OutboxState {
+        capacity,
+        next_seq,
+        acked_seq: read_u64(env, &DataKey::AckedSeq),
+        oldest_seq: next_seq.saturating_sub(capacity as u64),
+    })
+}
+
+#[contract]
+pub struct OutboxContract;
+
+#[contractimpl]
+impl OutboxContract {
+    /// Initialize the outbox with an admin and the number of buffered messages
+    pub fn initialize(env: Env, admin: Address, capacity: u32) -> Result<(), Error> {
+        if !admin::init(&env, &DataKey::Admin, &admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        if capacity == 0 {
+            return Err(Error::InvalidCapacity);
+        }
+
+        env.storage().instance().set(&DataKey::Capacity, &capacity);
+        env.storage().instance().set(&DataKey::NextSeq, &0u64);
+        env.storage().instance().set(&DataKey::AckedSeq, &0u64);
+        Ok(())
+    }
+
+    /// Allow a contract to push messages (admin only)
+    pub fn add_writer(env: Env, writer: Address) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        allowlist::add(&env, &DataKey::Writers, &writer);
+        Ok(())
+    }
+
+    /// Stop a contract from pushing messages (admin only)
+    pub fn remove_writer(env: Env, writer: Address) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        allowlist::remove(&env, &DataKey::Writers, &writer);
+        Ok(())
+    }
+
+    /// Set the service allowed to acknowledge messages (admin only)
+    pub fn set_reader(env: Env, reader: Address) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        env.storage().instance().set(&DataKey::Reader, &reader);
+        Ok(())
+    }
+
+    /// Append a message. Once the buffer is full the oldest message is
+    /// overwritten, acknowledged or not; readers detect the gap via `oldest_seq`.
+    pub fn push(env: Env, writer: Address, action: Symbol, subject_id: u64) -> Result<u64, Error> {
+        if !allowlist::require_member(&env, &DataKey::Writers, &writer) {
+            return Err(Error::NotAuthorized);
+        }
+        let state = state(&env)?;
+
+        let seq = state.next_seq;
+        let message = Message {
+            seq,
+            source: writer,
+            action,
+            subject_id,
+            timestamp: clock::now(&env),
+        };
+        let slot = (seq % state.capacity as u64) as u32;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Slot(slot), &message);
+        env.storage().instance().set(&DataKey::NextSeq, &(seq + 1));
+
+        env.events()
+            .publish((symbol_short!("outbox"), symbol_short!("push")), seq);
+        Ok(seq)
+    }
+
+    /// Read up to `limit` messages starting at `from_seq` (messages that were
+    /// already overwritten are skipped)
+    pub fn pull(env: Env, from_seq: u64, limit: u32) -> Result<Vec<Message>, Error> {
+        let state = state(&env)?;
+        let limit = page::limit(limit, MAX_PULL);
+
+        let mut messages = Vec::new(&env);
+        let mut seq = from_seq.max(state.oldest_seq);
+        while seq < state.next_seq && messages.len() < limit {
+            let slot = (seq % state.capacity as u64) as u32;
+            if let Some(message) = env
+                .storage()
+                .persistent()
+                .get::<_, Message>(&DataKey::Slot(slot))
+            {
+                messages.push_back(message);
+            }
+            seq += 1;
+        }
+        Ok(messages)
+    }
+
+    /// Acknowledge every message up to and including `seq` (reader only)
+    pub fn ack(env: Env, seq: u64) -> Result<(), Error> {
+        let reader: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Reader)
+            .ok_or(Error::NotAuthorized)?;
+        reader.require_auth();
+
+        let state = state(&env)?;
+        if seq >= state.next_seq {
+            return Err(Error::InvalidSequence);
+        }
+        if seq + 1 > state.acked_seq {
+            env.storage().instance().set(&DataKey::AckedSeq, &(seq + 1));
+        }
+        Ok(())
+    }
+
+    /// Get the buffer's cursor positions
+    pub fn get_state(env: Env) -> Result<OutboxState, Error> {
+        state(&env)
+    }
+
+    /// Get the addresses allowed to push messages
+    pub fn get_writers(env: Env) -> Vec<Address> {
+        writers(&env)
+    }
+}
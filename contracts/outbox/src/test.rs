@@ -0,0 +1,69 @@
+#![cfg(test)]
+
+use crate::{Error, OutboxContract, OutboxContractClient};
+use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env};
+
+fn setup(env: &Env, capacity: u32) -> (OutboxContractClient<'_>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, OutboxContract);
+    let client = OutboxContractClient::new(env, &contract_id);
+    let admin = Address::generate(env);
+    client.initialize(&admin, &capacity);
+
+    let writer = Address::generate(env);
+    client.add_writer(&writer);
+    client.set_reader(&Address::generate(env));
+    (client, writer)
+}
+
+#[test]
+fn test_push_pull_ack() {
+    let env = Env::default();
+    let (client, writer) = setup(&env, 8);
+
+    assert_eq!(client.push(&writer, &symbol_short!("issued"), &0), 0);
+    assert_eq!(client.push(&writer, &symbol_short!("revoked"), &0), 1);
+    assert_eq!(client.push(&writer, &symbol_short!("issued"), &1), 2);
+
+    let messages = client.pull(&0, &10);
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages.get(1).unwrap().action, symbol_short!("revoked"));
+    assert_eq!(messages.get(2).unwrap().subject_id, 1);
+    assert_eq!(messages.get(0).unwrap().source, writer);
+
+    client.ack(&1);
+    let state = client.get_state();
+    assert_eq!(state.acked_seq, 2);
+    assert_eq!(state.next_seq, 3);
+
+    // Acks never move backwards and can't run ahead of the buffer
+    client.ack(&0);
+    assert_eq!(client.get_state().acked_seq, 2);
+    assert_eq!(client.try_ack(&3), Err(Ok(Error::InvalidSequence)));
+
+    let unknown = Address::generate(&env);
+    assert_eq!(
+        client.try_push(&unknown, &symbol_short!("issued"), &9),
+        Err(Ok(Error::NotAuthorized))
+    );
+}
+
+#[test]
+fn test_ring_buffer_overwrites_oldest() {
+    let env = Env::default();
+    let (client, writer) = setup(&env, 3);
+
+    for subject_id in 0..5 {
+        client.push(&writer, &symbol_short!("updated"), &subject_id);
+    }
+
+    let state = client.get_state();
+    assert_eq!(state.oldest_seq, 2);
+    let messages = client.pull(&0, &10);
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages.get(0).unwrap().seq, 2);
+    assert_eq!(messages.get(2).unwrap().subject_id, 4);
+
+    assert_eq!(client.pull(&4, &10).len(), 1);
+    assert_eq!(client.pull(&3, &1).len(), 1);
+}
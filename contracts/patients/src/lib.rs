@@ -0,0 +1,292 @@
+#![no_std]
+
+#[cfg(test)]
+mod test;
+
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, BytesN, Env,
+    String, Vec,
+};
+use uzima_common::{admin, allowlist, clock};
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    Registrars, // Institutions allowed to register patients
+    PatientCounter,
+    Patient(u64),
+    HashIndex(BytesN<32>), // Demographics hash -> patient id
+    AddressIndex(Address), // Linked address -> patient id
+}
+
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    NotAuthorized = 1,
+    AlreadyInitialized = 2,
+    NotInitialized = 3,
+    PatientNotFound = 4,
+    DuplicatePatient = 5,
+    AddressAlreadyLinked = 6,
+    PatientMerged = 7,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PatientStatus {
+    Active,
+    Inactive,
+    Deceased,
+    Merged, // Folded into another record, see `merged_into`
+}
+
+// Minimal on-chain patient record; demographics stay off-chain
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatientRecord {
+    pub demographics_hash: BytesN<32>, // Hash of normalized demographics, used for duplicate detection
+    pub institution: Address,          // Registrar that created the record
+    pub status: PatientStatus,
+    pub addresses: Vec<Address>, // Addresses acting for the patient; the first one authorizes changes
+    pub dids: Vec<String>,       // Linked decentralized identifiers
+    pub merged_into: Option<u64>,
+    pub registered: u64,
+}
+
+fn read_admin(env: &Env) -> Result<Address, Error> {
+    admin::read(env, &DataKey::Admin).ok_or(Error::NotInitialized)
+}
+
+fn require_registrar(env: &Env, registrar: &Address) -> Result<(), Error> {
+    if !allowlist::require_member(env, &DataKey::Registrars, registrar) {
+        return Err(Error::NotAuthorized);
+    }
+    Ok(())
+}
+
+fn read_patient(env: &Env, patient_id: u64) -> Result<PatientRecord, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Patient(patient_id))
+        .ok_or(Error::PatientNotFound)
+}
+
+fn write_patient(env: &Env, patient_id: u64, record: &PatientRecord) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Patient(patient_id), record);
+}
+
+/// Follow merges to the record that is still in use
+fn canonical_id(env: &Env, patient_id: u64) -> Result<u64, Error> {
+    let mut id = patient_id;
+    loop {
+        match read_patient(env, id)?.merged_into {
+            Some(next) => id = next,
+            None => return Ok(id),
+        }
+    }
+}
+
+#[contract]
+pub struct PatientsContract;
+
+#[contractimpl]
+impl PatientsContract {
+    /// Initialize the registry with an admin
+    pub fn initialize(env: Env, admin: Address) -> Result<(), Error> {
+        if !admin::init(&env, &DataKey::Admin, &admin) {
+            return Err(Error::AlreadyInitialized);
+        }
+        Ok(())
+    }
+
+    /// Allow an institution to register patients (admin only)
+    pub fn add_registrar(env: Env, registrar: Address) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        allowlist::add(&env, &DataKey::Registrars, &registrar);
+        Ok(())
+    }
+
+    /// Stop an institution registering patients (admin only)
+    pub fn remove_registrar(env: Env, registrar: Address) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        allowlist::remove(&env, &DataKey::Registrars, &registrar);
+        Ok(())
+    }
+
+    /// Register a patient. A demographics hash that is already on file is
+    /// rejected so the same person isn't registered twice.
+    pub fn register_patient(
+        env: Env,
+        registrar: Address,
+        patient: Address,
+        demographics_hash: BytesN<32>,
+    ) -> Result<u64, Error> {
+        require_registrar(&env, &registrar)?;
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::HashIndex(demographics_hash.clone()))
+        {
+            return Err(Error::DuplicatePatient);
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::AddressIndex(patient.clone()))
+        {
+            return Err(Error::AddressAlreadyLinked);
+        }
+
+        let patient_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::PatientCounter)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::PatientCounter, &(patient_id + 1));
+
+        let mut addresses = Vec::new(&env);
+        addresses.push_back(patient.clone());
+        write_patient(
+            &env,
+            patient_id,
+            &PatientRecord {
+                demographics_hash: demographics_hash.clone(),
+                institution: registrar.clone(),
+                status: PatientStatus::Active,
+                addresses,
+                dids: Vec::new(&env),
+                merged_into: None,
+                registered: clock::now(&env),
+            },
+        );
+        env.storage()
+            .persistent()
+            .set(&DataKey::HashIndex(demographics_hash), &patient_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::AddressIndex(patient.clone()), &patient_id);
+
+        env.events().publish(
+            (symbol_short!("patient"), symbol_short!("register")),
+            (patient_id, patient, registrar),
+        );
+        Ok(patient_id)
+    }
+
+    /// Link a decentralized identifier to a record (patient's primary address authorizes)
+    pub fn link_did(env: Env, patient_id: u64, did: String) -> Result<(), Error> {
+        let mut record = read_patient(&env, patient_id)?;
+        if record.status == PatientStatus::Merged {
+            return Err(Error::PatientMerged);
+        }
+        record.addresses.get(0).unwrap().require_auth();
+        if !record.dids.contains(&did) {
+            record.dids.push_back(did.clone());
+            write_patient(&env, patient_id, &record);
+        }
+        env.events().publish(
+            (symbol_short!("patient"), symbol_short!("did")),
+            (patient_id, did),
+        );
+        Ok(())
+    }
+
+    /// Update a record's status, e.g. to `Deceased` (registrar only).
+    /// Records can only become `Merged` through `merge_patients`.
+    pub fn set_status(
+        env: Env,
+        registrar: Address,
+        patient_id: u64,
+        status: PatientStatus,
+    ) -> Result<(), Error> {
+        require_registrar(&env, &registrar)?;
+        let mut record = read_patient(&env, patient_id)?;
+        if record.status == PatientStatus::Merged || status == PatientStatus::Merged {
+            return Err(Error::PatientMerged);
+        }
+        record.status = status;
+        write_patient(&env, patient_id, &record);
+        env.events().publish(
+            (symbol_short!("patient"), symbol_short!("status")),
+            (patient_id, status),
+        );
+        Ok(())
+    }
+
+    /// Fold a duplicate record into the surviving one (admin only). The
+    /// duplicate's addresses and DIDs move to the survivor and lookups on the
+    /// duplicate resolve to it from then on.
+    pub fn merge_patients(env: Env, survivor_id: u64, duplicate_id: u64) -> Result<(), Error> {
+        read_admin(&env)?.require_auth();
+        let mut survivor = read_patient(&env, survivor_id)?;
+        let mut duplicate = read_patient(&env, duplicate_id)?;
+        if survivor_id == duplicate_id
+            || survivor.status == PatientStatus::Merged
+            || duplicate.status == PatientStatus::Merged
+        {
+            return Err(Error::PatientMerged);
+        }
+
+        for address in duplicate.addresses.iter() {
+            env.storage()
+                .persistent()
+                .set(&DataKey::AddressIndex(address.clone()), &survivor_id);
+            survivor.addresses.push_back(address);
+        }
+        for did in duplicate.dids.iter() {
+            if !survivor.dids.contains(&did) {
+                survivor.dids.push_back(did);
+            }
+        }
+        duplicate.addresses = Vec::new(&env);
+        duplicate.dids = Vec::new(&env);
+        duplicate.status = PatientStatus::Merged;
+        duplicate.merged_into = Some(survivor_id);
+        write_patient(&env, survivor_id, &survivor);
+        write_patient(&env, duplicate_id, &duplicate);
+
+        env.events().publish(
+            (symbol_short!("patient"), symbol_short!("merged")),
+            (duplicate_id, survivor_id),
+        );
+        Ok(())
+    }
+
+    /// Get a record as stored (merged records point at their survivor)
+    pub fn get_patient(env: Env, patient_id: u64) -> Result<PatientRecord, Error> {
+        read_patient(&env, patient_id)
+    }
+
+    /// Resolve a patient id through any merges to the record in use
+    pub fn resolve_patient(env: Env, patient_id: u64) -> Result<u64, Error> {
+        canonical_id(&env, patient_id)
+    }
+
+    /// Find the record registered with a demographics hash
+    pub fn find_by_hash(env: Env, demographics_hash: BytesN<32>) -> Option<u64> {
+        let patient_id = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HashIndex(demographics_hash))?;
+        canonical_id(&env, patient_id).ok()
+    }
+
+    /// Find the record an address acts for
+    pub fn find_by_address(env: Env, address: Address) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AddressIndex(address))
+    }
+
+    /// Whether a record exists and is active, for contracts that reference patients by id
+    pub fn is_active(env: Env, patient_id: u64) -> bool {
+        read_patient(&env, patient_id).is_ok_and(|record| record.status == PatientStatus::Active)
+    }
+}
@@ -0,0 +1,74 @@
+#![cfg(test)]
+
+use crate::{Error, PatientStatus, PatientsContract, PatientsContractClient};
+use soroban_sdk::{testutils::Address as _, Address, BytesN, Env, String};
+
+fn setup(env: &Env) -> (PatientsContractClient<'_>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, PatientsContract);
+    let client = PatientsContractClient::new(env, &contract_id);
+    client.initialize(&Address::generate(env));
+    let registrar = Address::generate(env);
+    client.add_registrar(&registrar);
+    (client, registrar)
+}
+
+#[test]
+fn test_register_rejects_duplicates() {
+    let env = Env::default();
+    let (client, registrar) = setup(&env);
+    let patient = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[1; 32]);
+
+    let patient_id = client.register_patient(&registrar, &patient, &hash);
+    assert_eq!(client.find_by_hash(&hash), Some(patient_id));
+    assert_eq!(client.find_by_address(&patient), Some(patient_id));
+    assert!(client.is_active(&patient_id));
+
+    assert_eq!(
+        client.try_register_patient(&registrar, &Address::generate(&env), &hash),
+        Err(Ok(Error::DuplicatePatient))
+    );
+    assert_eq!(
+        client.try_register_patient(&registrar, &patient, &BytesN::from_array(&env, &[2; 32])),
+        Err(Ok(Error::AddressAlreadyLinked))
+    );
+    assert_eq!(
+        client.try_register_patient(
+            &Address::generate(&env),
+            &Address::generate(&env),
+            &BytesN::from_array(&env, &[3; 32])
+        ),
+        Err(Ok(Error::NotAuthorized))
+    );
+
+    client.set_status(&registrar, &patient_id, &PatientStatus::Deceased);
+    assert!(!client.is_active(&patient_id));
+}
+
+#[test]
+fn test_merge_patients() {
+    let env = Env::default();
+    let (client, registrar) = setup(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[2; 32]);
+    let survivor = client.register_patient(&registrar, &first, &BytesN::from_array(&env, &[1; 32]));
+    let duplicate = client.register_patient(&registrar, &second, &hash);
+    client.link_did(&duplicate, &String::from_str(&env, "did:stellar:abc"));
+
+    client.merge_patients(&survivor, &duplicate);
+    let record = client.get_patient(&survivor);
+    assert_eq!(record.addresses.len(), 2);
+    assert_eq!(record.dids.len(), 1);
+    assert_eq!(client.get_patient(&duplicate).merged_into, Some(survivor));
+
+    // Every lookup on the duplicate now lands on the survivor
+    assert_eq!(client.find_by_address(&second), Some(survivor));
+    assert_eq!(client.find_by_hash(&hash), Some(survivor));
+    assert_eq!(client.resolve_patient(&duplicate), survivor);
+    assert_eq!(
+        client.try_merge_patients(&survivor, &duplicate),
+        Err(Ok(Error::PatientMerged))
+    );
+}
@@ -0,0 +1,419 @@
+use super::*;
+use soroban_sdk::{contracterror, token, Vec};
+
+// Storage keys for capitated payment distribution
+
+#[contracttype]
+#[derive(Clone)]
+pub enum CapKey {
+    Admin,
+    Token,
+    Providers,
+    CarryOver,                 // Funds rolled into the next period that closes
+    LastClosed,                // Latest period closed; earlier ones can't reopen
+    Period(u32),               // Period id, e.g. 202610 for October 2026
+    Attribution(u32, Address), // Members attributed to a provider for a period
+    Dispute(u32, Address),
+    Payout(u32, Address),
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum CapitationError {
+    PeriodClosed = 1,        // The period, or a later one, has already closed
+    AttributionOverflow = 2, // Attributed members no longer fit in a u32
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CapitationPeriod {
+    pub pool: i128,         // Funded for this period
+    pub carried_in: i128,   // Carry-over added when the period closed
+    pub total_members: u32, // Sum of all attributions
+    pub closed: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum PayoutStatus {
+    Paid,
+    Held,      // Provider disputed the period, funds wait for resolution
+    Forfeited, // Dispute resolved against the provider, funds carried over
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Payout {
+    pub amount: i128,
+    pub members: u32,
+    pub status: PayoutStatus,
+}
+
+fn admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&CapKey::Admin)
+        .unwrap_or_else(|| panic!("Capitation not initialized"))
+}
+
+fn token_client(env: &Env) -> token::Client<'_> {
+    let token: Address = env.storage().instance().get(&CapKey::Token).unwrap();
+    token::Client::new(env, &token)
+}
+
+fn providers(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&CapKey::Providers)
+        .unwrap_or(Vec::new(env))
+}
+
+fn carry_over(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&CapKey::CarryOver)
+        .unwrap_or(0)
+}
+
+fn last_closed(env: &Env) -> Option<u32> {
+    env.storage().instance().get(&CapKey::LastClosed)
+}
+
+fn open_period(env: &Env, period: u32) -> Result<CapitationPeriod, CapitationError> {
+    // Periods close in order, so carry-over always moves forward
+    if last_closed(env).is_some_and(|last| period <= last) {
+        return Err(CapitationError::PeriodClosed);
+    }
+    let state: CapitationPeriod = env
+        .storage()
+        .persistent()
+        .get(&CapKey::Period(period))
+        .unwrap_or(CapitationPeriod {
+            pool: 0,
+            carried_in: 0,
+            total_members: 0,
+            closed: false,
+        });
+    if state.closed {
+        return Err(CapitationError::PeriodClosed);
+    }
+    Ok(state)
+}
+
+fn attribution(env: &Env, period: u32, provider: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&CapKey::Attribution(period, provider.clone()))
+        .unwrap_or(0)
+}
+
+#[contractimpl]
+impl PaymentRouter {
+    /// Set up capitation with an admin and the token payments are made in
+    pub fn init_capitation(env: Env, admin: Address, token: Address) {
+        if env.storage().instance().has(&CapKey::Admin) {
+            panic!("Capitation already initialized");
+        }
+        admin.require_auth();
+        env.storage().instance().set(&CapKey::Admin, &admin);
+        env.storage().instance().set(&CapKey::Token, &token);
+    }
+
+    /// Enroll a provider in the capitation pool (admin only)
+    pub fn enroll_provider(env: Env, provider: Address) {
+        admin(&env).require_auth();
+        let mut list = providers(&env);
+        if !list.contains(&provider) {
+            list.push_back(provider);
+            env.storage().instance().set(&CapKey::Providers, &list);
+        }
+    }
+
+    /// Record how many members are attributed to a provider for a period (admin only)
+    pub fn set_attribution(
+        env: Env,
+        period: u32,
+        provider: Address,
+        members: u32,
+    ) -> Result<(), CapitationError> {
+        admin(&env).require_auth();
+        if !providers(&env).contains(&provider) {
+            panic!("Provider not enrolled");
+        }
+        let mut state = open_period(&env, period)?;
+        let previous = attribution(&env, period, &provider);
+        state.total_members = state
+            .total_members
+            .checked_sub(previous)
+            .and_then(|total| total.checked_add(members))
+            .ok_or(CapitationError::AttributionOverflow)?;
+
+        env.storage()
+            .persistent()
+            .set(&CapKey::Attribution(period, provider), &members);
+        env.storage()
+            .persistent()
+            .set(&CapKey::Period(period), &state);
+        Ok(())
+    }
+
+    /// Pay into a period's pool
+    pub fn fund_period(
+        env: Env,
+        funder: Address,
+        period: u32,
+        amount: i128,
+    ) -> Result<(), CapitationError> {
+        funder.require_auth();
+        if amount <= 0 {
+            panic!("Invalid amount");
+        }
+        let mut state = open_period(&env, period)?;
+        token_client(&env).transfer(&funder, &env.current_contract_address(), &amount);
+        state.pool += amount;
+        env.storage()
+            .persistent()
+            .set(&CapKey::Period(period), &state);
+        Ok(())
+    }
+
+    /// Hold a provider's share of a period until the admin resolves the dispute
+    pub fn open_dispute(env: Env, provider: Address, period: u32) -> Result<(), CapitationError> {
+        provider.require_auth();
+        open_period(&env, period)?;
+        if attribution(&env, period, &provider) == 0 {
+            panic!("No attribution");
+        }
+        env.storage()
+            .persistent()
+            .set(&CapKey::Dispute(period, provider), &true);
+        Ok(())
+    }
+
+    /// Close a period: the pool plus any carry-over is split across providers by
+    /// attributed members. Disputed shares are held; rounding remainders (or the
+    /// whole pool when nobody is attributed) carry over to the next period.
+    /// Periods close in order: once one closes, earlier ones can't be funded,
+    /// attributed or closed.
+    pub fn close_period(env: Env, period: u32) -> Result<i128, CapitationError> {
+        admin(&env).require_auth();
+        let mut state = open_period(&env, period)?;
+        state.carried_in = carry_over(&env);
+        let available = state.pool + state.carried_in;
+
+        let token = token_client(&env);
+        let mut distributed: i128 = 0;
+        if state.total_members > 0 {
+            for provider in providers(&env).iter() {
+                let members = attribution(&env, period, &provider);
+                if members == 0 {
+                    continue;
+                }
+                let amount = available * members as i128 / state.total_members as i128;
+                let disputed = env
+                    .storage()
+                    .persistent()
+                    .has(&CapKey::Dispute(period, provider.clone()));
+                let status = if disputed {
+                    PayoutStatus::Held
+                } else {
+                    token.transfer(&env.current_contract_address(), &provider, &amount);
+                    PayoutStatus::Paid
+                };
+                env.storage().persistent().set(
+                    &CapKey::Payout(period, provider),
+                    &Payout {
+                        amount,
+                        members,
+                        status,
+                    },
+                );
+                distributed += amount;
+            }
+        }
+
+        let remainder = available - distributed;
+        env.storage().instance().set(&CapKey::CarryOver, &remainder);
+        env.storage().instance().set(&CapKey::LastClosed, &period);
+        state.closed = true;
+        env.storage()
+            .persistent()
+            .set(&CapKey::Period(period), &state);
+
+        env.events().publish(
+            (symbol_short!("cap_close"), period),
+            (distributed, remainder),
+        );
+        Ok(remainder)
+    }
+
+    /// Resolve a held payout: release it to the provider or carry it over (admin only)
+    pub fn resolve_dispute(env: Env, period: u32, provider: Address, release: bool) {
+        admin(&env).require_auth();
+        let key = CapKey::Payout(period, provider.clone());
+        let mut payout: Payout = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("No payout"));
+        if payout.status != PayoutStatus::Held {
+            panic!("Payout not held");
+        }
+
+        if release {
+            token_client(&env).transfer(&env.current_contract_address(), &provider, &payout.amount);
+            payout.status = PayoutStatus::Paid;
+        } else {
+            let carried = carry_over(&env) + payout.amount;
+            env.storage().instance().set(&CapKey::CarryOver, &carried);
+            payout.status = PayoutStatus::Forfeited;
+        }
+        env.storage().persistent().set(&key, &payout);
+        env.storage()
+            .persistent()
+            .remove(&CapKey::Dispute(period, provider));
+    }
+
+    pub fn get_period(env: Env, period: u32) -> Option<CapitationPeriod> {
+        env.storage().persistent().get(&CapKey::Period(period))
+    }
+
+    pub fn get_payout(env: Env, period: u32, provider: Address) -> Option<Payout> {
+        env.storage()
+            .persistent()
+            .get(&CapKey::Payout(period, provider))
+    }
+
+    pub fn get_carry_over(env: Env) -> i128 {
+        carry_over(&env)
+    }
+}
+
+#[cfg(all(test, feature = "testutils"))]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    fn setup(
+        env: &Env,
+    ) -> (
+        PaymentRouterClient<'_>,
+        token::StellarAssetClient<'_>,
+        token::Client<'_>,
+    ) {
+        env.mock_all_auths();
+        let cid = env.register_contract(None, PaymentRouter);
+        let client = PaymentRouterClient::new(env, &cid);
+        let token_id = env.register_stellar_asset_contract(Address::generate(env));
+        client.init_capitation(&Address::generate(env), &token_id);
+        (
+            client,
+            token::StellarAssetClient::new(env, &token_id),
+            token::Client::new(env, &token_id),
+        )
+    }
+
+    #[test]
+    fn test_capitation_distribution() {
+        let env = Env::default();
+        let (client, minter, token) = setup(&env);
+        let payer = Address::generate(&env);
+        let clinic_a = Address::generate(&env);
+        let clinic_b = Address::generate(&env);
+        minter.mint(&payer, &10_000);
+        client.enroll_provider(&clinic_a);
+        client.enroll_provider(&clinic_b);
+
+        client.set_attribution(&202610, &clinic_a, &200);
+        client.set_attribution(&202610, &clinic_b, &100);
+        client.fund_period(&payer, &202610, &1_000);
+
+        // 1000 split 2:1 leaves one unit of rounding to carry over
+        assert_eq!(client.close_period(&202610), 1);
+        assert_eq!(token.balance(&clinic_a), 666);
+        assert_eq!(token.balance(&clinic_b), 333);
+
+        // Carry-over is added to the next period's pool
+        client.set_attribution(&202611, &clinic_a, &1);
+        client.fund_period(&payer, &202611, &99);
+        client.close_period(&202611);
+        assert_eq!(client.get_period(&202611).unwrap().carried_in, 1);
+        assert_eq!(token.balance(&clinic_a), 766);
+    }
+
+    #[test]
+    fn test_capitation_dispute_hold() {
+        let env = Env::default();
+        let (client, minter, token) = setup(&env);
+        let payer = Address::generate(&env);
+        let clinic_a = Address::generate(&env);
+        let clinic_b = Address::generate(&env);
+        minter.mint(&payer, &10_000);
+        client.enroll_provider(&clinic_a);
+        client.enroll_provider(&clinic_b);
+        client.set_attribution(&202610, &clinic_a, &1);
+        client.set_attribution(&202610, &clinic_b, &1);
+        client.fund_period(&payer, &202610, &1_000);
+
+        client.open_dispute(&clinic_b, &202610);
+        client.close_period(&202610);
+        assert_eq!(token.balance(&clinic_a), 500);
+        assert_eq!(token.balance(&clinic_b), 0);
+        assert_eq!(
+            client.get_payout(&202610, &clinic_b).unwrap().status,
+            PayoutStatus::Held
+        );
+
+        client.resolve_dispute(&202610, &clinic_b, &false);
+        assert_eq!(client.get_carry_over(), 500);
+        assert_eq!(
+            client.get_payout(&202610, &clinic_b).unwrap().status,
+            PayoutStatus::Forfeited
+        );
+    }
+
+    #[test]
+    fn test_capitation_attribution_overflow() {
+        let env = Env::default();
+        let (client, _, _) = setup(&env);
+        let clinic_a = Address::generate(&env);
+        let clinic_b = Address::generate(&env);
+        client.enroll_provider(&clinic_a);
+        client.enroll_provider(&clinic_b);
+
+        client.set_attribution(&202610, &clinic_a, &u32::MAX);
+        // Replacing an attribution doesn't count it twice
+        client.set_attribution(&202610, &clinic_a, &u32::MAX);
+        assert_eq!(
+            client.try_set_attribution(&202610, &clinic_b, &1),
+            Err(Ok(CapitationError::AttributionOverflow))
+        );
+        client.set_attribution(&202610, &clinic_a, &1);
+        client.set_attribution(&202610, &clinic_b, &1);
+        assert_eq!(client.get_period(&202610).unwrap().total_members, 2);
+    }
+
+    #[test]
+    fn test_capitation_periods_close_in_order() {
+        let env = Env::default();
+        let (client, minter, _) = setup(&env);
+        let payer = Address::generate(&env);
+        minter.mint(&payer, &10_000);
+        client.fund_period(&payer, &202610, &100);
+        client.fund_period(&payer, &202611, &100);
+
+        // Closing November first rules out closing October afterwards, so
+        // November's carry-over can't land in an earlier period
+        assert_eq!(client.close_period(&202611), 100);
+        assert_eq!(
+            client.try_close_period(&202610),
+            Err(Ok(CapitationError::PeriodClosed))
+        );
+        assert_eq!(
+            client.try_fund_period(&payer, &202610, &100),
+            Err(Ok(CapitationError::PeriodClosed))
+        );
+        assert_eq!(client.get_carry_over(), 100);
+    }
+}
@@ -2,6 +2,10 @@
 
 use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, Env, Symbol};
 
+mod capitation;
+
+pub use capitation::{CapitationError, CapitationPeriod, Payout, PayoutStatus};
+
 #[derive(Clone)]
 #[contracttype]
 pub struct RouterFeeConfig {
@@ -8,5 +8,6 @@ mod vesting;
 #[cfg(test)]
 mod test;
 
-pub use contract::TokenSaleContract;
-pub use vesting::VestingContract;
+pub use contract::{TokenSaleContract, TokenSaleContractClient};
+pub use types::{Contribution, SaleConfig, SalePhase, VestingSchedule};
+pub use vesting::{VestingContract, VestingContractClient};
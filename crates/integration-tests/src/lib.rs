@@ -0,0 +1,3 @@
+//! Cross-contract flows exercised against the Uzima contracts deployed into a
+//! single `Env`. Everything lives in `tests/`: `tests/common` deploys the
+//! stack and each other file holds a group of flows.
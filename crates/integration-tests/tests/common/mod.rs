@@ -0,0 +1,69 @@
+//! Deploys every contract the flows use into a single `Env`
+
+// Each test binary only uses part of the stack
+#![allow(dead_code)]
+
+use soroban_sdk::{testutils::Address as _, Address, Env};
+use uzima_sdk::{
+    care_teams::{CareTeamsContract, CareTeamsContractClient},
+    consent::{InitConfig, PatientConsentToken, PatientConsentTokenClient},
+    escrow::{EscrowContract, EscrowContractClient},
+    records::{MedicalRecordsContract, MedicalRecordsContractClient, Role},
+};
+
+/// Platform fee the escrow charges in the flows, in basis points
+pub const PLATFORM_FEE_BPS: u32 = 250;
+
+/// The deployed contracts and the accounts that run them
+pub struct Stack<'a> {
+    pub consent: PatientConsentTokenClient<'a>,
+    pub records: MedicalRecordsContractClient<'a>,
+    pub escrow: EscrowContractClient<'a>,
+    pub care_teams: CareTeamsContractClient<'a>,
+    pub admin: Address,
+    pub hospital: Address, // Consent issuer
+    pub doctor: Address,
+    pub fee_receiver: Address,
+}
+
+impl<'a> Stack<'a> {
+    /// Deploy and initialize every contract. Auths are mocked; flows check
+    /// who authorized what through `env.auths()`.
+    pub fn deploy(env: &'a Env) -> Self {
+        env.mock_all_auths();
+        let admin = Address::generate(env);
+        let hospital = Address::generate(env);
+        let doctor = Address::generate(env);
+        let fee_receiver = Address::generate(env);
+
+        let consent =
+            PatientConsentTokenClient::new(env, &env.register_contract(None, PatientConsentToken));
+        consent.initialize(&InitConfig::new(env, admin.clone()));
+        consent.add_issuer(&hospital);
+
+        let records = MedicalRecordsContractClient::new(
+            env,
+            &env.register_contract(None, MedicalRecordsContract),
+        );
+        records.initialize(&admin);
+        records.manage_user(&admin, &doctor, &Role::Doctor);
+
+        let escrow = EscrowContractClient::new(env, &env.register_contract(None, EscrowContract));
+        escrow.set_fee_config(&fee_receiver, &PLATFORM_FEE_BPS);
+
+        let care_teams =
+            CareTeamsContractClient::new(env, &env.register_contract(None, CareTeamsContract));
+        consent.set_care_team_registry(&care_teams.address);
+
+        Stack {
+            consent,
+            records,
+            escrow,
+            care_teams,
+            admin,
+            hospital,
+            doctor,
+            fee_receiver,
+        }
+    }
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use common::{Stack, PLATFORM_FEE_BPS};
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, AuthorizedFunction},
+    vec, Address, Env, String, Symbol,
+};
+use uzima_sdk::consent::AccessLevel;
+
+/// Whether `address` authorized a top-level call of `function` on `contract`
+/// in the last invocation
+fn authorized(env: &Env, address: &Address, contract: &Address, function: &str) -> bool {
+    env.auths().iter().any(|(signer, invocation)| {
+        signer == address
+            && matches!(
+                &invocation.function,
+                AuthorizedFunction::Contract((id, name, _))
+                    if id == contract && *name == Symbol::new(env, function)
+            )
+    })
+}
+
+#[test]
+fn test_consent_record_claim_settlement() {
+    let env = Env::default();
+    let stack = Stack::deploy(&env);
+    let patient = Address::generate(&env);
+    let insurer = Address::generate(&env);
+
+    // Consent minted by the hospital, then the patient lets the doctor write
+    let token_id = stack.consent.mint_consent(
+        &stack.hospital,
+        &patient,
+        &String::from_str(&env, "ipfs://QmConsent"),
+        &String::from_str(&env, "treatment"),
+        &0,
+    );
+    assert!(authorized(
+        &env,
+        &stack.hospital,
+        &stack.consent.address,
+        "mint_consent"
+    ));
+    stack
+        .consent
+        .grant_access(&token_id, &patient, &stack.doctor, &AccessLevel::Write, &0);
+    assert!(authorized(
+        &env,
+        &patient,
+        &stack.consent.address,
+        "grant_access"
+    ));
+    assert!(stack
+        .consent
+        .check_access(&token_id, &stack.doctor, &AccessLevel::Write));
+
+    // Record anchored by the doctor the consent was granted to
+    let record_id = stack.records.add_record(
+        &stack.doctor,
+        &patient,
+        &String::from_str(&env, "Hypertension"),
+        &String::from_str(&env, "ACE inhibitor"),
+        &false,
+        &vec![&env, String::from_str(&env, "cardio")],
+        &String::from_str(&env, "Modern"),
+        &String::from_str(&env, "medication"),
+    );
+    assert!(authorized(
+        &env,
+        &stack.doctor,
+        &stack.records.address,
+        "add_record"
+    ));
+    let record = stack.records.get_record(&patient, &record_id).unwrap();
+    assert_eq!(record.doctor_id, stack.doctor);
+
+    // Claim filed against the record: the insurer escrows the payment
+    let amount: i128 = 10_000;
+    let token = env.register_stellar_asset_contract(stack.admin.clone());
+    stack
+        .escrow
+        .create_escrow(&record_id, &insurer, &stack.doctor, &amount, &token);
+    stack.escrow.approve_release(&record_id, &insurer);
+    stack.escrow.approve_release(&record_id, &stack.admin);
+
+    // Payment settled, less the platform fee
+    assert!(stack.escrow.release_escrow(&record_id));
+    let fee = amount * PLATFORM_FEE_BPS as i128 / 10_000;
+    assert_eq!(stack.escrow.get_credit(&stack.doctor), amount - fee);
+    assert_eq!(stack.escrow.get_credit(&stack.fee_receiver), fee);
+    assert_eq!(stack.escrow.withdraw(&token, &stack.doctor), amount - fee);
+    assert_eq!(stack.escrow.get_credit(&stack.doctor), 0);
+
+    // Once the consent is revoked the doctor can't act on it any more
+    stack.consent.revoke_consent(&token_id);
+    assert!(!stack
+        .consent
+        .check_access(&token_id, &stack.doctor, &AccessLevel::Read));
+    assert!(stack.consent.verify_invariants().is_empty());
+}
+
+#[test]
+fn test_care_team_access_follows_registry() {
+    let env = Env::default();
+    let stack = Stack::deploy(&env);
+    let patient = Address::generate(&env);
+    let nurse = Address::generate(&env);
+
+    let token_id = stack.consent.mint_consent(
+        &stack.hospital,
+        &patient,
+        &String::from_str(&env, "ipfs://QmConsent"),
+        &String::from_str(&env, "treatment"),
+        &0,
+    );
+    let team_id = stack.care_teams.create_team(&stack.doctor, &patient);
+    stack.care_teams.approve_team(&team_id);
+    assert!(authorized(
+        &env,
+        &patient,
+        &stack.care_teams.address,
+        "approve_team"
+    ));
+
+    // The consent contract resolves the team in the care_teams contract
+    stack.consent.grant_to_care_team(&token_id, &team_id);
+    stack
+        .care_teams
+        .propose_member(&team_id, &nurse, &symbol_short!("nurse"));
+    assert!(!stack.consent.has_care_team_access(&token_id, &nurse));
+    stack.care_teams.approve_member(&team_id, &nurse);
+    assert!(stack.consent.has_care_team_access(&token_id, &nurse));
+
+    // Membership changes take effect without touching the consent
+    stack.care_teams.remove_member(&patient, &team_id, &nurse);
+    assert!(authorized(
+        &env,
+        &patient,
+        &stack.care_teams.address,
+        "remove_member"
+    ));
+    assert!(!stack.consent.has_care_team_access(&token_id, &nurse));
+
+    // A team belonging to another patient can't be granted
+    let other_team = stack
+        .care_teams
+        .create_team(&stack.doctor, &Address::generate(&env));
+    assert_eq!(
+        stack.consent.try_grant_to_care_team(&token_id, &other_team),
+        Err(Ok(uzima_sdk::consent::ContractError::NotAuthorized))
+    );
+    assert!(stack.consent.verify_invariants().is_empty());
+}
@@ -0,0 +1,132 @@
+//! Golden-state tests: run a canonical scenario, serialize the contract's
+//! storage and compare it with the snapshot checked in under `tests/golden/`.
+//! A storage-layout change (a new key, or an entry moved from instance to
+//! persistent storage) fails here even when every behavioral test passes.
+//!
+//! After an intended layout change, regenerate the snapshots with
+//! `UPDATE_GOLDEN=1 cargo test -p integration-tests --test golden` and review
+//! the diff.
+
+mod common;
+
+use common::Stack;
+use serde_json::{json, Value};
+use soroban_sdk::{
+    symbol_short,
+    testutils::Address as _,
+    vec,
+    xdr::{ContractDataDurability, LedgerEntryData, ScAddress},
+    Address, Env, String,
+};
+use std::{fs, path::PathBuf};
+use uzima_sdk::consent::AccessLevel;
+
+/// Every storage entry of `contract`, sorted so the output is stable
+fn contract_state(env: &Env, contract: &Address) -> Value {
+    let contract = ScAddress::try_from(contract).unwrap();
+    let mut entries: Vec<Value> = env
+        .to_ledger_snapshot()
+        .ledger_entries
+        .into_iter()
+        .filter_map(|(_, (entry, _))| match entry.data {
+            LedgerEntryData::ContractData(data) if data.contract == contract => Some(json!({
+                "durability": match data.durability {
+                    ContractDataDurability::Persistent => "persistent",
+                    ContractDataDurability::Temporary => "temporary",
+                },
+                "key": data.key,
+                "val": data.val,
+            })),
+            _ => None,
+        })
+        .collect();
+    entries.sort_by_key(|entry| entry["key"].to_string());
+    Value::Array(entries)
+}
+
+fn assert_golden(name: &str, state: Value) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.json"));
+    let actual = serde_json::to_string_pretty(&state).unwrap() + "\n";
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing {}, run with UPDATE_GOLDEN=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "storage of {name} no longer matches {}; if the change is intended, \
+         rerun with UPDATE_GOLDEN=1 and review the diff",
+        path.display()
+    );
+}
+
+#[test]
+fn test_consent_storage_golden() {
+    let env = Env::default();
+    let stack = Stack::deploy(&env);
+    let patient = Address::generate(&env);
+    let mint = |uri: &str| {
+        stack.consent.mint_consent(
+            &stack.hospital,
+            &patient,
+            &String::from_str(&env, uri),
+            &String::from_str(&env, "treatment"),
+            &0,
+        )
+    };
+
+    let kept = mint("ipfs://QmKept");
+    let revoked = mint("ipfs://QmRevoked");
+    stack
+        .consent
+        .update_consent(&kept, &String::from_str(&env, "ipfs://QmKeptV2"));
+    stack
+        .consent
+        .grant_access(&kept, &patient, &stack.doctor, &AccessLevel::Read, &0);
+    stack.consent.revoke_consent(&revoked);
+
+    assert_golden(
+        "consent_lifecycle",
+        contract_state(&env, &stack.consent.address),
+    );
+}
+
+#[test]
+fn test_records_storage_golden() {
+    let env = Env::default();
+    let stack = Stack::deploy(&env);
+    let patient = Address::generate(&env);
+
+    stack.records.add_record(
+        &stack.doctor,
+        &patient,
+        &String::from_str(&env, "Malaria"),
+        &String::from_str(&env, "Artemisinin"),
+        &true,
+        &vec![&env, String::from_str(&env, "fever")],
+        &String::from_str(&env, "Herbal"),
+        &String::from_str(&env, "infusion"),
+    );
+    let team_id = stack.care_teams.create_team(&stack.doctor, &patient);
+    stack.care_teams.approve_team(&team_id);
+    stack
+        .care_teams
+        .propose_member(&team_id, &Address::generate(&env), &symbol_short!("nurse"));
+
+    assert_golden(
+        "records_basic",
+        contract_state(&env, &stack.records.address),
+    );
+    assert_golden(
+        "care_team_basic",
+        contract_state(&env, &stack.care_teams.address),
+    );
+}
@@ -0,0 +1,170 @@
+use uzima_sdk::Network;
+
+pub const USAGE: &str = "\
+usage: uzima-admin [options] <command> [args]
+
+commands:
+  initialize <admin>                 initialize the consent contract
+  add-issuer <issuer>                allow an address to mint consents
+  remove-issuer <issuer>             stop an address minting consents
+  pause <caller> | unpause <caller>  pause or resume the records contract
+  mint-test <issuer> <patient> [n]   mint n (default 1) test consents
+  dump <token_id>                    print a token's owner, metadata, status and history
+  export-history <token_id> [file]   write a token's history as JSON
+  help                               show this message
+
+options (environment variable in brackets):
+  --network <name>     local, testnet, futurenet or mainnet [UZIMA_NETWORK, default testnet]
+  --source <identity>  soroban identity that signs [SOROBAN_IDENTITY, default \"default\"]
+  --consent-id <id>    consent contract id [UZIMA_CONSENT_ID]
+  --records-id <id>    records contract id [UZIMA_RECORDS_ID]
+  --dry-run            print the soroban commands instead of running them
+  --confirm-mainnet    required for commands that write to mainnet";
+
+/// Where and as whom commands run
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Profile {
+    pub network: Network,
+    pub source: String,
+    pub consent_id: Option<String>,
+    pub records_id: Option<String>,
+    pub dry_run: bool,
+    pub confirm_mainnet: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    Help,
+    Initialize {
+        admin: String,
+    },
+    AddIssuer {
+        issuer: String,
+    },
+    RemoveIssuer {
+        issuer: String,
+    },
+    Pause {
+        caller: String,
+    },
+    Unpause {
+        caller: String,
+    },
+    MintTest {
+        issuer: String,
+        patient: String,
+        count: u32,
+    },
+    Dump {
+        token_id: u64,
+    },
+    ExportHistory {
+        token_id: u64,
+        out: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Invocation {
+    pub profile: Profile,
+    pub command: Command,
+}
+
+/// Parse the command line. `env` looks up environment variables, which
+/// supply defaults for options that aren't given.
+pub fn parse(argv: &[String], env: impl Fn(&str) -> Option<String>) -> Result<Invocation, String> {
+    let mut network = env("UZIMA_NETWORK");
+    let mut source = env("SOROBAN_IDENTITY");
+    let mut consent_id = env("UZIMA_CONSENT_ID");
+    let mut records_id = env("UZIMA_RECORDS_ID");
+    let mut dry_run = false;
+    let mut confirm_mainnet = false;
+    let mut positional = Vec::new();
+
+    let mut args = argv.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{arg} needs a value"))
+        };
+        match arg.as_str() {
+            "--network" => network = Some(value()?),
+            "--source" => source = Some(value()?),
+            "--consent-id" => consent_id = Some(value()?),
+            "--records-id" => records_id = Some(value()?),
+            "--dry-run" => dry_run = true,
+            "--confirm-mainnet" => confirm_mainnet = true,
+            "-h" | "--help" => positional.insert(0, "help".to_string()),
+            option if option.starts_with("--") => return Err(format!("unknown option {option}")),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let network_name = network.unwrap_or_else(|| "testnet".to_string());
+    let network = Network::from_name(&network_name)
+        .ok_or_else(|| format!("unknown network {network_name}"))?;
+    let profile = Profile {
+        network,
+        source: source.unwrap_or_else(|| "default".to_string()),
+        consent_id,
+        records_id,
+        dry_run,
+        confirm_mainnet,
+    };
+    let command = parse_command(&positional)?;
+    Ok(Invocation { profile, command })
+}
+
+fn parse_command(positional: &[String]) -> Result<Command, String> {
+    let Some((name, rest)) = positional.split_first() else {
+        return Ok(Command::Help);
+    };
+    let arg = |index: usize, what: &str| {
+        rest.get(index)
+            .cloned()
+            .ok_or_else(|| format!("{name} needs <{what}>"))
+    };
+    let token_id = || {
+        arg(0, "token_id")?
+            .parse::<u64>()
+            .map_err(|_| "token_id must be a number".to_string())
+    };
+    let command = match name.as_str() {
+        "help" => Command::Help,
+        "initialize" => Command::Initialize {
+            admin: arg(0, "admin")?,
+        },
+        "add-issuer" => Command::AddIssuer {
+            issuer: arg(0, "issuer")?,
+        },
+        "remove-issuer" => Command::RemoveIssuer {
+            issuer: arg(0, "issuer")?,
+        },
+        "pause" => Command::Pause {
+            caller: arg(0, "caller")?,
+        },
+        "unpause" => Command::Unpause {
+            caller: arg(0, "caller")?,
+        },
+        "mint-test" => Command::MintTest {
+            issuer: arg(0, "issuer")?,
+            patient: arg(1, "patient")?,
+            count: match rest.get(2) {
+                Some(count) => count
+                    .parse()
+                    .map_err(|_| "count must be a number".to_string())?,
+                None => 1,
+            },
+        },
+        "dump" => Command::Dump {
+            token_id: token_id()?,
+        },
+        "export-history" => Command::ExportHistory {
+            token_id: token_id()?,
+            out: rest.get(1).cloned(),
+        },
+        other => return Err(format!("unknown command {other}")),
+    };
+    Ok(command)
+}
@@ -0,0 +1,253 @@
+use crate::args::{Command, Invocation, Profile, USAGE};
+use serde_json::{json, Value};
+use std::process;
+use uzima_sdk::Network;
+
+/// Metadata URI given to consents minted by `mint-test`
+pub const TEST_CONSENT_URI: &str = "ipfs://uzima-admin-test-consent";
+
+/// One contract function call
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Call {
+    pub contract_id: String,
+    pub function: &'static str,
+    pub args: Vec<(&'static str, String)>,
+    pub writes: bool,
+}
+
+impl Call {
+    /// Arguments for `soroban` that perform this call
+    pub fn argv(&self, profile: &Profile) -> Vec<String> {
+        let mut argv: Vec<String> = vec![
+            "contract".into(),
+            "invoke".into(),
+            "--id".into(),
+            self.contract_id.clone(),
+            "--source".into(),
+            profile.source.clone(),
+            "--rpc-url".into(),
+            profile.network.rpc_url().into(),
+            "--network-passphrase".into(),
+            profile.network.passphrase().into(),
+            "--".into(),
+            self.function.into(),
+        ];
+        for (name, value) in &self.args {
+            argv.push(format!("--{name}"));
+            argv.push(value.clone());
+        }
+        argv
+    }
+}
+
+fn contract(id: &Option<String>, option: &str) -> Result<String, String> {
+    id.clone()
+        .ok_or_else(|| format!("no contract id, pass {option} or set its environment variable"))
+}
+
+/// The consent contract's `InitConfig` for `admin`, with the same defaults as
+/// `InitConfig::new`. Change other settings afterwards with `reconfigure`.
+fn default_config(admin: &str) -> String {
+    json!({
+        "admin": admin,
+        "issuers": [],
+        "consent_types": ["treatment", "research", "emergency", "data_sharing"],
+        "max_expiry_duration": 0,
+        "max_delegation_depth": 3,
+        "request_window": 7 * 24 * 60 * 60,
+        "clock_skew_tolerance": 0,
+        "ttl_policy": { "threshold": 7 * 17_280, "extend_to": 30 * 17_280 },
+        "features": {},
+    })
+    .to_string()
+}
+
+/// The calls a command makes, in order
+pub fn calls(invocation: &Invocation) -> Result<Vec<Call>, String> {
+    let profile = &invocation.profile;
+    let consent = || contract(&profile.consent_id, "--consent-id");
+    let records = || contract(&profile.records_id, "--records-id");
+    let call = |contract_id: String, function, args: Vec<(&'static str, String)>, writes| Call {
+        contract_id,
+        function,
+        args,
+        writes,
+    };
+
+    let calls = match &invocation.command {
+        Command::Help => Vec::new(),
+        Command::Initialize { admin } => {
+            vec![call(
+                consent()?,
+                "initialize",
+                vec![("config", default_config(admin))],
+                true,
+            )]
+        }
+        Command::AddIssuer { issuer } => {
+            vec![call(
+                consent()?,
+                "add_issuer",
+                vec![("issuer", issuer.clone())],
+                true,
+            )]
+        }
+        Command::RemoveIssuer { issuer } => vec![call(
+            consent()?,
+            "remove_issuer",
+            vec![("issuer", issuer.clone())],
+            true,
+        )],
+        Command::Pause { caller } => {
+            vec![call(
+                records()?,
+                "pause",
+                vec![("caller", caller.clone())],
+                true,
+            )]
+        }
+        Command::Unpause { caller } => {
+            vec![call(
+                records()?,
+                "unpause",
+                vec![("caller", caller.clone())],
+                true,
+            )]
+        }
+        Command::MintTest {
+            issuer,
+            patient,
+            count,
+        } => {
+            let id = consent()?;
+            (0..*count)
+                .map(|_| {
+                    call(
+                        id.clone(),
+                        "mint_consent",
+                        vec![
+                            ("issuer", issuer.clone()),
+                            ("patient", patient.clone()),
+                            ("metadata_uri", TEST_CONSENT_URI.into()),
+                            ("consent_type", "treatment".into()),
+                            ("expiry_timestamp", "0".into()),
+                        ],
+                        true,
+                    )
+                })
+                .collect()
+        }
+        Command::Dump { token_id } => {
+            let id = consent()?;
+            ["owner_of", "get_metadata", "is_revoked", "get_history"]
+                .into_iter()
+                .map(|function| {
+                    call(
+                        id.clone(),
+                        function,
+                        vec![("token_id", token_id.to_string())],
+                        false,
+                    )
+                })
+                .collect()
+        }
+        Command::ExportHistory { token_id, .. } => vec![call(
+            consent()?,
+            "get_history",
+            vec![("token_id", token_id.to_string())],
+            false,
+        )],
+    };
+    Ok(calls)
+}
+
+fn execute(call: &Call, profile: &Profile) -> Result<Value, String> {
+    let output = process::Command::new("soroban")
+        .args(call.argv(profile))
+        .output()
+        .map_err(|error| format!("couldn't run soroban: {error}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            call.function,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // soroban prints return values as JSON; keep anything else as text
+    Ok(serde_json::from_str(&stdout).unwrap_or(Value::String(stdout)))
+}
+
+/// Run a command and return what to print
+pub fn run(invocation: &Invocation) -> Result<String, String> {
+    let profile = &invocation.profile;
+    if invocation.command == Command::Help {
+        return Ok(USAGE.to_string());
+    }
+    let calls = calls(invocation)?;
+    if profile.network == Network::Mainnet
+        && !profile.confirm_mainnet
+        && calls.iter().any(|call| call.writes)
+    {
+        return Err("this command writes to mainnet, pass --confirm-mainnet".into());
+    }
+    if profile.dry_run {
+        let lines: Vec<String> = calls
+            .iter()
+            .map(|call| format!("soroban {}", shell_words(&call.argv(profile))))
+            .collect();
+        return Ok(lines.join("\n"));
+    }
+
+    let results = calls
+        .iter()
+        .map(|call| execute(call, profile))
+        .collect::<Result<Vec<Value>, String>>()?;
+    let output = match &invocation.command {
+        Command::Dump { token_id } => {
+            let dump = json!({
+                "token_id": token_id,
+                "owner": results[0],
+                "metadata": results[1],
+                "revoked": results[2],
+                "history": results[3],
+            });
+            serde_json::to_string_pretty(&dump).map_err(|error| error.to_string())?
+        }
+        Command::ExportHistory { out, .. } => {
+            let history =
+                serde_json::to_string_pretty(&results[0]).map_err(|error| error.to_string())?;
+            match out {
+                Some(path) => {
+                    std::fs::write(path, &history)
+                        .map_err(|error| format!("couldn't write {path}: {error}"))?;
+                    format!("wrote history to {path}")
+                }
+                None => history,
+            }
+        }
+        _ => results
+            .iter()
+            .map(|result| match result {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    Ok(output)
+}
+
+/// Quote arguments that contain spaces, for printing
+fn shell_words(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| {
+            if arg.contains(' ') || arg.contains(';') {
+                format!("\"{arg}\"")
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,33 @@
+//! `uzima-admin`: command-line administration for deployed Uzima contracts.
+//!
+//! Each command is translated into one or more `soroban contract invoke`
+//! calls against the network profile picked with `--network`, so signing and
+//! key management stay with the `soroban` CLI.
+
+mod args;
+mod invoke;
+#[cfg(test)]
+mod test;
+
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    let invocation = match args::parse(&argv, |key| std::env::var(key).ok()) {
+        Ok(invocation) => invocation,
+        Err(error) => {
+            eprintln!("error: {error}\n\n{}", args::USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match invoke::run(&invocation) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,88 @@
+#![cfg(test)]
+
+use crate::{
+    args::{parse, Command},
+    invoke::{calls, run},
+};
+use uzima_sdk::Network;
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_profile_from_options_and_environment() {
+    let env = |key: &str| match key {
+        "UZIMA_CONSENT_ID" => Some("CCONSENT".to_string()),
+        "UZIMA_NETWORK" => Some("local".to_string()),
+        _ => None,
+    };
+    let invocation = parse(
+        &argv(&["--network", "testnet", "add-issuer", "GISSUER"]),
+        env,
+    )
+    .unwrap();
+    assert_eq!(invocation.profile.network, Network::Testnet);
+    assert_eq!(invocation.profile.source, "default");
+    assert_eq!(
+        invocation.command,
+        Command::AddIssuer {
+            issuer: "GISSUER".to_string()
+        }
+    );
+
+    let calls = calls(&invocation).unwrap();
+    let argv = calls[0].argv(&invocation.profile);
+    assert_eq!(argv[3], "CCONSENT");
+    assert_eq!(argv[7], Network::Testnet.rpc_url());
+    assert_eq!(&argv[11..], ["add_issuer", "--issuer", "GISSUER"]);
+}
+
+#[test]
+fn test_mainnet_writes_need_confirmation() {
+    let env = |_: &str| None;
+    let mint = argv(&[
+        "--network",
+        "mainnet",
+        "--consent-id",
+        "CCONSENT",
+        "--dry-run",
+        "mint-test",
+        "GISSUER",
+        "GPATIENT",
+        "2",
+    ]);
+    let invocation = parse(&mint, env).unwrap();
+    assert!(run(&invocation).unwrap_err().contains("--confirm-mainnet"));
+
+    let mut confirmed = mint.clone();
+    confirmed.push("--confirm-mainnet".to_string());
+    let output = run(&parse(&confirmed, env).unwrap()).unwrap();
+    assert_eq!(output.lines().count(), 2);
+    assert!(output.contains("mint_consent --issuer GISSUER --patient GPATIENT"));
+
+    // Reads don't need confirmation
+    let dump = argv(&[
+        "--network",
+        "mainnet",
+        "--consent-id",
+        "C",
+        "--dry-run",
+        "dump",
+        "7",
+    ]);
+    assert_eq!(run(&parse(&dump, env).unwrap()).unwrap().lines().count(), 4);
+}
+
+#[test]
+fn test_initialize_sends_default_config() {
+    let env = |key: &str| (key == "UZIMA_CONSENT_ID").then(|| "CCONSENT".to_string());
+    let invocation = parse(&argv(&["initialize", "GADMIN"]), env).unwrap();
+    let calls = calls(&invocation).unwrap();
+    let argv = calls[0].argv(&invocation.profile);
+    assert_eq!(&argv[11..13], ["initialize", "--config"]);
+    let config: serde_json::Value = serde_json::from_str(&argv[13]).unwrap();
+    assert_eq!(config["admin"], "GADMIN");
+    assert_eq!(config["max_delegation_depth"], 3);
+    assert_eq!(config["consent_types"].as_array().unwrap().len(), 4);
+}
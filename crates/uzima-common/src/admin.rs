@@ -0,0 +1,26 @@
+//! A single admin address in instance storage
+
+use soroban_sdk::{Address, Env, IntoVal, Val};
+
+/// Store `admin` under `key` once it has authorized. Returns `false`, storing
+/// nothing, if an admin is already set.
+pub fn init<K: IntoVal<Env, Val>>(env: &Env, key: &K, admin: &Address) -> bool {
+    if env.storage().instance().has(key) {
+        return false;
+    }
+    admin.require_auth();
+    env.storage().instance().set(key, admin);
+    true
+}
+
+/// The admin stored under `key`, if the contract was initialized
+pub fn read<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> Option<Address> {
+    env.storage().instance().get(key)
+}
+
+/// Require the admin's authorization and return it, if the contract was initialized
+pub fn require<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> Option<Address> {
+    let admin = read(env, key)?;
+    admin.require_auth();
+    Some(admin)
+}
@@ -0,0 +1,41 @@
+//! A set of addresses allowed to do something (issuers, registrars, writers...)
+//! kept as one list in instance storage
+
+use soroban_sdk::{Address, Env, IntoVal, Val, Vec};
+
+/// Every address on the list under `key`, in the order they were added
+pub fn members<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> Vec<Address> {
+    env.storage().instance().get(key).unwrap_or(Vec::new(env))
+}
+
+pub fn contains<K: IntoVal<Env, Val>>(env: &Env, key: &K, address: &Address) -> bool {
+    members(env, key).contains(address)
+}
+
+/// Add `address` to the list. Returns `false` if it was already on it.
+pub fn add<K: IntoVal<Env, Val>>(env: &Env, key: &K, address: &Address) -> bool {
+    let mut list = members(env, key);
+    if list.contains(address) {
+        return false;
+    }
+    list.push_back(address.clone());
+    env.storage().instance().set(key, &list);
+    true
+}
+
+/// Take `address` off the list. Returns `false` if it wasn't on it.
+pub fn remove<K: IntoVal<Env, Val>>(env: &Env, key: &K, address: &Address) -> bool {
+    let mut list = members(env, key);
+    let Some(index) = list.first_index_of(address) else {
+        return false;
+    };
+    list.remove(index);
+    env.storage().instance().set(key, &list);
+    true
+}
+
+/// Require `address` to authorize and to be on the list under `key`
+pub fn require_member<K: IntoVal<Env, Val>>(env: &Env, key: &K, address: &Address) -> bool {
+    address.require_auth();
+    contains(env, key, address)
+}
@@ -0,0 +1,51 @@
+//! Ledger time behind one seam, so expiry, cooling-off and grace-period logic
+//! reads the same clock in every contract and tests can move it uniformly
+
+use soroban_sdk::Env;
+
+/// Seconds between ledger closes assumed when time travel closes ledgers
+pub const LEDGER_CLOSE_SECONDS: u64 = 5;
+
+/// The current ledger time, in seconds since the Unix epoch
+pub fn now(env: &Env) -> u64 {
+    env.ledger().timestamp()
+}
+
+/// Whether `timestamp` has been reached. 0 means "never" and is never reached.
+pub fn has_passed(env: &Env, timestamp: u64) -> bool {
+    timestamp != 0 && now(env) >= timestamp
+}
+
+/// Seconds left until `timestamp`, 0 once it has been reached
+pub fn seconds_until(env: &Env, timestamp: u64) -> u64 {
+    timestamp.saturating_sub(now(env))
+}
+
+/// Jump the ledger clock to `timestamp` without closing ledgers
+#[cfg(any(test, feature = "testutils"))]
+pub fn set(env: &Env, timestamp: u64) {
+    use soroban_sdk::testutils::Ledger;
+    env.ledger().with_mut(|ledger| ledger.timestamp = timestamp);
+}
+
+/// Move the ledger clock forward by `seconds`, closing a ledger every
+/// `LEDGER_CLOSE_SECONDS` so sequence-based logic moves along with it
+#[cfg(any(test, feature = "testutils"))]
+pub fn advance(env: &Env, seconds: u64) {
+    use soroban_sdk::testutils::Ledger;
+    env.ledger().with_mut(|ledger| {
+        ledger.timestamp = ledger.timestamp.saturating_add(seconds);
+        ledger.sequence_number = ledger
+            .sequence_number
+            .saturating_add((seconds / LEDGER_CLOSE_SECONDS) as u32);
+    });
+}
+
+/// Move the ledger clock to just past `timestamp`, e.g. an expiry
+#[cfg(any(test, feature = "testutils"))]
+pub fn advance_past(env: &Env, timestamp: u64) {
+    let target = timestamp.saturating_add(1);
+    if target > now(env) {
+        advance(env, target - now(env));
+    }
+}
@@ -0,0 +1,29 @@
+//! A bounded, append-only trail of entries (audit logs, status changes...)
+//! kept as one list in persistent storage
+
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val, Vec};
+
+/// Every entry on the trail under `key`, oldest first
+pub fn entries<K, T>(env: &Env, key: &K) -> Vec<T>
+where
+    K: IntoVal<Env, Val>,
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    env.storage().persistent().get(key).unwrap_or(Vec::new(env))
+}
+
+/// Append `entry`, dropping the oldest entries past `max_len` (0 = unbounded).
+/// Returns the trail's new length.
+pub fn append<K, T>(env: &Env, key: &K, entry: T, max_len: u32) -> u32
+where
+    K: IntoVal<Env, Val>,
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    let mut trail: Vec<T> = entries(env, key);
+    trail.push_back(entry);
+    if max_len > 0 && trail.len() > max_len {
+        trail = trail.slice(trail.len() - max_len..);
+    }
+    env.storage().persistent().set(key, &trail);
+    trail.len()
+}
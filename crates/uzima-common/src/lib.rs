@@ -0,0 +1,17 @@
+#![no_std]
+
+//! Storage and ledger-time patterns shared by the Uzima contracts.
+//!
+//! Every helper takes the storage key from the calling contract's own
+//! `DataKey`, so a contract can adopt them without moving any data, and
+//! reports failures as `bool`/`Option` for the contract to map onto its own
+//! error enum.
+
+pub mod admin;
+pub mod allowlist;
+pub mod clock;
+pub mod history;
+pub mod page;
+pub mod pause;
+#[cfg(test)]
+mod test;
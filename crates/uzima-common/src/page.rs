@@ -0,0 +1,24 @@
+//! Paging through lists and sequence ranges
+
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val, Vec};
+
+/// The page size to use for a requested `limit`: `max` when `limit` is 0 or
+/// above it
+pub fn limit(limit: u32, max: u32) -> u32 {
+    if limit == 0 || limit > max {
+        max
+    } else {
+        limit
+    }
+}
+
+/// Up to `limit` items of `items` starting at index `offset`
+pub fn slice<T>(env: &Env, items: &Vec<T>, offset: u32, limit: u32) -> Vec<T>
+where
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    if offset >= items.len() {
+        return Vec::new(env);
+    }
+    items.slice(offset..items.len().min(offset.saturating_add(limit)))
+}
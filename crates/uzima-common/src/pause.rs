@@ -0,0 +1,31 @@
+//! An emergency stop flag, or a mask of paused capabilities, in instance storage
+
+use soroban_sdk::{Env, IntoVal, Val};
+
+pub fn is_paused<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    env.storage().instance().get(key).unwrap_or(false)
+}
+
+/// Set the flag. Returns `false` if it already had that value.
+pub fn set_paused<K: IntoVal<Env, Val>>(env: &Env, key: &K, paused: bool) -> bool {
+    if is_paused(env, key) == paused {
+        return false;
+    }
+    env.storage().instance().set(key, &paused);
+    true
+}
+
+/// The paused capabilities under `key`, for contracts that pause some
+/// operations and not others. Each bit is one capability the contract defines.
+pub fn paused_mask<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> u32 {
+    env.storage().instance().get(key).unwrap_or(0)
+}
+
+/// Set the paused capabilities. Returns `false` if they were already `mask`.
+pub fn set_paused_mask<K: IntoVal<Env, Val>>(env: &Env, key: &K, mask: u32) -> bool {
+    if paused_mask(env, key) == mask {
+        return false;
+    }
+    env.storage().instance().set(key, &mask);
+    true
+}
@@ -0,0 +1,103 @@
+#![cfg(test)]
+
+use crate::{admin, allowlist, clock, history, page, pause};
+use soroban_sdk::{contract, contracttype, testutils::Address as _, vec, Address, Env, Vec};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum DataKey {
+    Admin,
+    Writers,
+    Paused,
+    PausedMask,
+    Trail(u64),
+}
+
+// Storage only exists inside a contract
+#[contract]
+struct Host;
+
+#[test]
+fn test_admin_and_allowlist() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let host = env.register_contract(None, Host);
+    let admin = Address::generate(&env);
+    let writer = Address::generate(&env);
+
+    env.as_contract(&host, || {
+        assert_eq!(admin::read(&env, &DataKey::Admin), None);
+        assert!(admin::init(&env, &DataKey::Admin, &admin));
+        assert!(!admin::init(&env, &DataKey::Admin, &writer));
+    });
+    // Each call authorizes in its own frame
+    env.as_contract(&host, || {
+        assert_eq!(admin::require(&env, &DataKey::Admin), Some(admin.clone()));
+    });
+    env.as_contract(&host, || {
+        assert!(allowlist::add(&env, &DataKey::Writers, &writer));
+        assert!(!allowlist::add(&env, &DataKey::Writers, &writer));
+        assert!(allowlist::require_member(&env, &DataKey::Writers, &writer));
+        assert!(!allowlist::contains(&env, &DataKey::Writers, &admin));
+        assert!(allowlist::remove(&env, &DataKey::Writers, &writer));
+        assert!(!allowlist::remove(&env, &DataKey::Writers, &writer));
+        assert!(allowlist::members(&env, &DataKey::Writers).is_empty());
+    });
+}
+
+#[test]
+fn test_pause_history_and_paging() {
+    let env = Env::default();
+    let host = env.register_contract(None, Host);
+
+    env.as_contract(&host, || {
+        assert!(!pause::is_paused(&env, &DataKey::Paused));
+        assert!(pause::set_paused(&env, &DataKey::Paused, true));
+        assert!(!pause::set_paused(&env, &DataKey::Paused, true));
+        assert!(pause::is_paused(&env, &DataKey::Paused));
+        assert_eq!(pause::paused_mask(&env, &DataKey::PausedMask), 0);
+        assert!(pause::set_paused_mask(&env, &DataKey::PausedMask, 0b101));
+        assert!(!pause::set_paused_mask(&env, &DataKey::PausedMask, 0b101));
+        assert_eq!(pause::paused_mask(&env, &DataKey::PausedMask), 0b101);
+
+        for entry in 0..5u64 {
+            history::append(&env, &DataKey::Trail(1), entry, 3);
+        }
+        let trail: Vec<u64> = history::entries(&env, &DataKey::Trail(1));
+        assert_eq!(trail, vec![&env, 2, 3, 4]);
+        assert!(history::entries::<_, u64>(&env, &DataKey::Trail(2)).is_empty());
+
+        assert_eq!(page::limit(0, 50), 50);
+        assert_eq!(page::limit(80, 50), 50);
+        assert_eq!(page::limit(10, 50), 10);
+        assert_eq!(page::slice(&env, &trail, 1, 10), vec![&env, 3, 4]);
+        assert!(page::slice(&env, &trail, 3, 10).is_empty());
+    });
+}
+
+#[test]
+fn test_clock_time_travel() {
+    let env = Env::default();
+    clock::set(&env, 1_000);
+    assert_eq!(clock::now(&env), 1_000);
+    assert!(clock::has_passed(&env, 1_000));
+    assert!(!clock::has_passed(&env, 1_001));
+    assert!(!clock::has_passed(&env, 0));
+    assert_eq!(clock::seconds_until(&env, 1_060), 60);
+    assert_eq!(clock::seconds_until(&env, 900), 0);
+
+    let sequence = env.ledger().sequence();
+    clock::advance(&env, 60);
+    assert_eq!(clock::now(&env), 1_060);
+    assert_eq!(
+        env.ledger().sequence(),
+        sequence + (60 / clock::LEDGER_CLOSE_SECONDS) as u32
+    );
+
+    clock::advance_past(&env, 2_000);
+    assert!(clock::has_passed(&env, 2_000));
+    assert_eq!(clock::now(&env), 2_001);
+    // Never moves backwards
+    clock::advance_past(&env, 1_500);
+    assert_eq!(clock::now(&env), 2_001);
+}
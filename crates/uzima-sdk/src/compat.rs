@@ -0,0 +1,30 @@
+use crate::consent::{InterfaceVersion, PatientConsentTokenClient, INTERFACE_MAJOR};
+
+/// A deployment whose interface this SDK wasn't built against
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IncompatibleInterface {
+    /// The major version this SDK calls
+    pub expected_major: u32,
+    /// What the deployment reported, or `None` if it predates
+    /// `interface_version` or the call failed
+    pub found: Option<InterfaceVersion>,
+}
+
+/// Check that a consent deployment implements the major interface version
+/// this SDK was built against, before making calls that could otherwise
+/// silently decode wrongly after an upgrade. Newer minors are accepted.
+pub fn check_consent_interface(
+    client: &PatientConsentTokenClient,
+) -> Result<InterfaceVersion, IncompatibleInterface> {
+    let found = match client.try_interface_version() {
+        Ok(Ok(version)) => Some(version),
+        _ => None,
+    };
+    match found {
+        Some(version) if version.major == INTERFACE_MAJOR => Ok(version),
+        _ => Err(IncompatibleInterface {
+            expected_major: INTERFACE_MAJOR,
+            found,
+        }),
+    }
+}
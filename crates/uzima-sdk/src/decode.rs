@@ -0,0 +1,32 @@
+use soroban_sdk::InvokeError;
+
+/// Why a `try_` call through a generated client failed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CallError<E, A = InvokeError> {
+    /// The contract returned one of its own error codes
+    Contract(E),
+    /// The contract panicked, or returned a code its error enum doesn't know
+    Aborted(A),
+    /// The call succeeded but its return value didn't decode
+    Decode,
+}
+
+/// Flatten the nested result of a generated `try_` call.
+///
+/// ```ignore
+/// match decode(client.try_check_access(&token_id, &doctor, &AccessLevel::Read)) {
+///     Ok(allowed) => ...,
+///     Err(CallError::Contract(error)) => ...,
+///     Err(_) => ...,
+/// }
+/// ```
+pub fn decode<T, C, E, A>(
+    result: Result<Result<T, C>, Result<E, A>>,
+) -> Result<T, CallError<E, A>> {
+    match result {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(_)) => Err(CallError::Decode),
+        Err(Ok(error)) => Err(CallError::Contract(error)),
+        Err(Err(error)) => Err(CallError::Aborted(error)),
+    }
+}
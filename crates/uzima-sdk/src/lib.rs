@@ -0,0 +1,90 @@
+#![no_std]
+
+//! Typed clients and shared types for the Uzima contracts.
+//!
+//! Each contract crate is re-exported under a short module name, so its
+//! generated client, `contracttype` definitions and error enum can be used
+//! without copying them. [`Deployment`] builds clients from a network and
+//! contract ids, [`decode`] turns `try_` call results into one error type,
+//! and [`check_consent_interface`] refuses deployments of another major
+//! interface version.
+
+mod compat;
+mod decode;
+mod network;
+#[cfg(test)]
+mod test;
+
+pub use compat::{check_consent_interface, IncompatibleInterface};
+pub use decode::{decode, CallError};
+pub use network::{Deployment, Network};
+
+/// Patient consent tokens (`medical_consent_nft`)
+pub mod consent {
+    pub use medical_consent_nft::*;
+}
+
+/// Medical records (`medical_records`)
+pub mod records {
+    pub use medical_records::*;
+}
+
+/// Patient registry (`patients`)
+pub mod patients {
+    pub use ::patients::*;
+}
+
+/// Care teams (`care_teams`)
+pub mod care_teams {
+    pub use ::care_teams::*;
+}
+
+/// Identity hashes and attestations (`identity_registry`)
+pub mod identity {
+    pub use identity_registry::*;
+}
+
+/// Patient notification inbox (`inbox`)
+pub mod inbox {
+    pub use ::inbox::*;
+}
+
+/// Change feed for off-chain services (`outbox`)
+pub mod outbox {
+    pub use ::outbox::*;
+}
+
+/// Payment splitting and capitation (`payment_router`)
+pub mod payments {
+    pub use payment_router::*;
+}
+
+/// Order escrow (`escrow`)
+pub mod escrow {
+    pub use ::escrow::*;
+}
+
+/// SUT governance token (`sut_token`)
+pub mod token {
+    pub use sut_token::*;
+}
+
+/// SUT token sale and vesting (`token_sale`)
+pub mod token_sale {
+    pub use ::token_sale::*;
+}
+
+/// Treasury multisig (`treasury_controller`)
+pub mod treasury {
+    pub use treasury_controller::*;
+}
+
+/// Governance proposals and voting (`governor`)
+pub mod governor {
+    pub use ::governor::*;
+}
+
+/// Delayed execution of governance actions (`timelock`)
+pub mod timelock {
+    pub use ::timelock::*;
+}
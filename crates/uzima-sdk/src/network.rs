@@ -0,0 +1,155 @@
+use crate::{
+    care_teams::CareTeamsContractClient, check_consent_interface,
+    consent::PatientConsentTokenClient, escrow::EscrowContractClient, governor::GovernorClient,
+    identity::IdentityRegistryContractClient, inbox::InboxContractClient,
+    outbox::OutboxContractClient, patients::PatientsContractClient, payments::PaymentRouterClient,
+    records::MedicalRecordsContractClient, timelock::TimelockClient, token::SutTokenClient,
+    token_sale::TokenSaleContractClient, treasury::TreasuryControllerClient, IncompatibleInterface,
+};
+use soroban_sdk::{Address, Bytes, Env, String};
+
+/// Networks the contracts are deployed to, matching `scripts/deploy.sh`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Network {
+    Local,
+    Testnet,
+    Futurenet,
+    Mainnet,
+}
+
+impl Network {
+    /// Look a network up by the name `scripts/deploy.sh` uses for it
+    pub fn from_name(name: &str) -> Option<Network> {
+        match name {
+            "local" => Some(Network::Local),
+            "testnet" => Some(Network::Testnet),
+            "futurenet" => Some(Network::Futurenet),
+            "mainnet" => Some(Network::Mainnet),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Network::Local => "local",
+            Network::Testnet => "testnet",
+            Network::Futurenet => "futurenet",
+            Network::Mainnet => "mainnet",
+        }
+    }
+
+    pub fn passphrase(&self) -> &'static str {
+        match self {
+            Network::Local => "Standalone Network ; February 2017",
+            Network::Testnet => "Test SDF Network ; September 2015",
+            Network::Futurenet => "Test SDF Future Network ; October 2022",
+            Network::Mainnet => "Public Global Stellar Network ; September 2015",
+        }
+    }
+
+    pub fn rpc_url(&self) -> &'static str {
+        match self {
+            Network::Local => "http://localhost:8000/soroban/rpc",
+            Network::Testnet => "https://soroban-testnet.stellar.org:443",
+            Network::Futurenet => "https://rpc-futurenet.stellar.org:443",
+            Network::Mainnet => "https://soroban-rpc.stellar.org:443",
+        }
+    }
+
+    /// Check whether `env` runs on this network (its network id is the
+    /// SHA-256 of the passphrase)
+    pub fn matches(&self, env: &Env) -> bool {
+        let passphrase = Bytes::from_slice(env, self.passphrase().as_bytes());
+        env.crypto().sha256(&passphrase) == env.ledger().network_id()
+    }
+}
+
+/// Builds clients for contracts deployed on one network
+pub struct Deployment<'a> {
+    env: &'a Env,
+    network: Network,
+}
+
+impl<'a> Deployment<'a> {
+    /// Returns `None` if `env` doesn't run on `network`, so clients are never
+    /// pointed at ids from another network by mistake
+    pub fn new(env: &'a Env, network: Network) -> Option<Self> {
+        network.matches(env).then_some(Deployment { env, network })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Parse a contract id in strkey form (`C...`)
+    pub fn address(&self, contract_id: &str) -> Address {
+        Address::from_string(&String::from_str(self.env, contract_id))
+    }
+
+    pub fn consent(&self, contract_id: &str) -> PatientConsentTokenClient<'a> {
+        PatientConsentTokenClient::new(self.env, &self.address(contract_id))
+    }
+
+    /// Like `consent`, but fails if the deployment's major interface version
+    /// isn't the one this SDK was built against
+    pub fn consent_checked(
+        &self,
+        contract_id: &str,
+    ) -> Result<PatientConsentTokenClient<'a>, IncompatibleInterface> {
+        let client = self.consent(contract_id);
+        check_consent_interface(&client)?;
+        Ok(client)
+    }
+
+    pub fn records(&self, contract_id: &str) -> MedicalRecordsContractClient<'a> {
+        MedicalRecordsContractClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn patients(&self, contract_id: &str) -> PatientsContractClient<'a> {
+        PatientsContractClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn care_teams(&self, contract_id: &str) -> CareTeamsContractClient<'a> {
+        CareTeamsContractClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn identity(&self, contract_id: &str) -> IdentityRegistryContractClient<'a> {
+        IdentityRegistryContractClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn inbox(&self, contract_id: &str) -> InboxContractClient<'a> {
+        InboxContractClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn outbox(&self, contract_id: &str) -> OutboxContractClient<'a> {
+        OutboxContractClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn payments(&self, contract_id: &str) -> PaymentRouterClient<'a> {
+        PaymentRouterClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn escrow(&self, contract_id: &str) -> EscrowContractClient<'a> {
+        EscrowContractClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn token(&self, contract_id: &str) -> SutTokenClient<'a> {
+        SutTokenClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn token_sale(&self, contract_id: &str) -> TokenSaleContractClient<'a> {
+        TokenSaleContractClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn treasury(&self, contract_id: &str) -> TreasuryControllerClient<'a> {
+        TreasuryControllerClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn governor(&self, contract_id: &str) -> GovernorClient<'a> {
+        GovernorClient::new(self.env, &self.address(contract_id))
+    }
+
+    pub fn timelock(&self, contract_id: &str) -> TimelockClient<'a> {
+        TimelockClient::new(self.env, &self.address(contract_id))
+    }
+}
@@ -0,0 +1,118 @@
+#![cfg(test)]
+
+use crate::{
+    check_consent_interface,
+    consent::{
+        AccessLevel, InitConfig, InterfaceVersion, PatientConsentToken, PatientConsentTokenClient,
+        INTERFACE_MAJOR,
+    },
+    decode,
+    inbox::{Error as InboxError, InboxContract},
+    CallError, Deployment, IncompatibleInterface, Network,
+};
+use soroban_sdk::{
+    contract, contractimpl,
+    testutils::{Address as _, Ledger},
+    Address, Bytes, Env, String,
+};
+
+fn on_network(env: &Env, network: Network) {
+    let passphrase = Bytes::from_slice(env, network.passphrase().as_bytes());
+    let network_id = env.crypto().sha256(&passphrase).to_array();
+    env.ledger()
+        .with_mut(|ledger| ledger.network_id = network_id);
+}
+
+#[test]
+fn test_deployment_checks_network() {
+    let env = Env::default();
+    env.mock_all_auths();
+    on_network(&env, Network::Testnet);
+    assert!(Deployment::new(&env, Network::Mainnet).is_none());
+    let deployment = Deployment::new(&env, Network::Testnet).unwrap();
+
+    let contract_id = env.register_contract(None, PatientConsentToken);
+    // Contract ids are 56-character strkeys
+    let mut strkey = [0u8; 56];
+    contract_id.to_string().copy_into_slice(&mut strkey);
+    let consent = deployment.consent(core::str::from_utf8(&strkey).unwrap());
+    assert_eq!(consent.address, contract_id);
+
+    let admin = Address::generate(&env);
+    let issuer = Address::generate(&env);
+    let patient = Address::generate(&env);
+    consent.initialize(&InitConfig::new(&env, admin.clone()));
+    consent.add_issuer(&issuer);
+    let token_id = consent.mint_consent(
+        &issuer,
+        &patient,
+        &String::from_str(&env, "ipfs://QmXxx..."),
+        &String::from_str(&env, "treatment"),
+        &0,
+    );
+    assert!(consent.check_access(&token_id, &patient, &AccessLevel::Share));
+}
+
+#[test]
+fn test_decode_contract_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register_contract(None, InboxContract);
+    let inbox = crate::inbox::InboxContractClient::new(&env, &contract_id);
+    let patient = Address::generate(&env);
+
+    assert_eq!(
+        decode(inbox.try_ack(&patient, &0)),
+        Err(CallError::Contract(InboxError::NotificationNotFound))
+    );
+    inbox.initialize(&Address::generate(&env));
+    assert_eq!(decode(inbox.try_get_state(&patient)).unwrap().unread, 0);
+}
+
+// A consent deployment from a future major version
+#[contract]
+struct NextMajorConsent;
+
+#[contractimpl]
+impl NextMajorConsent {
+    pub fn interface_version(_env: Env) -> InterfaceVersion {
+        InterfaceVersion {
+            major: INTERFACE_MAJOR + 1,
+            minor: 0,
+        }
+    }
+}
+
+#[test]
+fn test_consent_interface_major_must_match() {
+    let env = Env::default();
+    let current =
+        PatientConsentTokenClient::new(&env, &env.register_contract(None, PatientConsentToken));
+    assert_eq!(
+        check_consent_interface(&current).unwrap().major,
+        INTERFACE_MAJOR
+    );
+
+    let next = PatientConsentTokenClient::new(&env, &env.register_contract(None, NextMajorConsent));
+    assert_eq!(
+        check_consent_interface(&next),
+        Err(IncompatibleInterface {
+            expected_major: INTERFACE_MAJOR,
+            found: Some(InterfaceVersion {
+                major: INTERFACE_MAJOR + 1,
+                minor: 0,
+            }),
+        })
+    );
+
+    // Deployments from before interface_version existed are refused too
+    let unversioned =
+        PatientConsentTokenClient::new(&env, &env.register_contract(None, InboxContract));
+    assert_eq!(
+        check_consent_interface(&unversioned),
+        Err(IncompatibleInterface {
+            expected_major: INTERFACE_MAJOR,
+            found: None,
+        })
+    );
+}